@@ -0,0 +1,284 @@
+use crate::config::{Config, Profile, home_dir};
+use crate::report::{self, outln};
+use crate::shortcuts::win_quote;
+use std::env;
+use std::fs;
+use std::path::{self, Path, PathBuf};
+use std::process::Command;
+
+const BNDB_EXTENSION: &str = "bndb";
+const BNDB_MIME_TYPE: &str = "application/x-binaryninja-database";
+const MIME_PACKAGE_FILE: &str = "bn-loader.xml";
+
+// Binary formats offered as "Open with" targets (never made the default on Windows)
+const WINDOWS_BINARY_EXTENSIONS: &[&str] = &["exe", "dll", "sys"];
+const LINUX_BINARY_MIME_TYPES: &[&str] = &[
+    "application/x-executable",
+    "application/x-sharedlib",
+    "application/x-object",
+    "application/vnd.microsoft.portable-executable",
+];
+
+pub(crate) struct AssociateOptions<'a> {
+    pub profile: &'a str,
+    pub binaries: bool,
+    pub config_path: &'a Path,
+}
+
+pub(crate) fn run_associate(config: &Config, options: &AssociateOptions) -> Result<(), String> {
+    let profile = config
+        .profiles
+        .get(options.profile)
+        .ok_or_else(|| format!("Profile '{}' not found", options.profile))?;
+
+    let loader_exe =
+        env::current_exe().map_err(|e| format!("Failed to locate bn-loader executable: {e}"))?;
+    let config_path = path::absolute(options.config_path)
+        .map_err(|e| format!("Failed to resolve config path: {e}"))?;
+
+    let target = Target {
+        name: options.profile,
+        profile,
+        loader_exe: &loader_exe,
+        config_path: &config_path,
+    };
+
+    outln!("Associating files with profile '{}'...", options.profile);
+
+    if cfg!(windows) {
+        associate_windows(&target, options.binaries)?;
+    } else if cfg!(target_os = "linux") {
+        associate_linux(&target, options.binaries)?;
+    } else {
+        return Err("File associations are only supported on Windows and Linux".to_string());
+    }
+
+    outln!(
+        "\nDouble-clicking a .{BNDB_EXTENSION} file now opens it with profile '{}'.",
+        options.profile
+    );
+    Ok(())
+}
+
+struct Target<'a> {
+    name: &'a str,
+    profile: &'a Profile,
+    loader_exe: &'a Path,
+    config_path: &'a Path,
+}
+
+fn associate_windows(target: &Target, binaries: bool) -> Result<(), String> {
+    let prog_id = format!("bn-loader.{}", target.name);
+    let classes = r"HKCU\Software\Classes";
+    let command = format!(
+        "{} --config {} {} \"%1\"",
+        win_quote(&target.loader_exe.to_string_lossy()),
+        win_quote(&target.config_path.to_string_lossy()),
+        win_quote(target.name)
+    );
+    let icon = target
+        .profile
+        .icon
+        .clone()
+        .unwrap_or_else(|| target.profile.install_dir.join(&target.profile.executable));
+
+    reg_set(
+        &format!(r"{classes}\{prog_id}"),
+        &format!("Binary Ninja ({})", target.name),
+    )?;
+    reg_set(
+        &format!(r"{classes}\{prog_id}\DefaultIcon"),
+        &icon.to_string_lossy(),
+    )?;
+    reg_set(
+        &format!(r"{classes}\{prog_id}\shell\open\command"),
+        &command,
+    )?;
+    reg_set(&format!(r"{classes}\.{BNDB_EXTENSION}"), &prog_id)?;
+    outln!("  Registered: .{BNDB_EXTENSION} -> {prog_id}");
+
+    if binaries {
+        for ext in WINDOWS_BINARY_EXTENSIONS {
+            reg_run(&[
+                "add",
+                &format!(r"{classes}\.{ext}\OpenWithProgids"),
+                "/v",
+                &prog_id,
+                "/t",
+                "REG_NONE",
+                "/f",
+            ])?;
+            outln!("  Added to 'Open with': .{ext}");
+        }
+    }
+
+    outln!("  Note: a per-user choice made in Explorer's 'Open with' dialog takes precedence.");
+    Ok(())
+}
+
+fn reg_set(key: &str, value: &str) -> Result<(), String> {
+    reg_run(&["add", key, "/ve", "/d", value, "/f"])
+}
+
+fn reg_run(args: &[&str]) -> Result<(), String> {
+    let output = Command::new("reg")
+        .args(args)
+        .output()
+        .map_err(|e| format!("Failed to run reg.exe: {e}"))?;
+    if !output.status.success() {
+        return Err(format!(
+            "reg.exe failed: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+    Ok(())
+}
+
+fn associate_linux(target: &Target, binaries: bool) -> Result<(), String> {
+    let data_dir = xdg_data_home()?;
+    let apps_dir = data_dir.join("applications");
+    let mime_dir = data_dir.join("mime");
+    let packages_dir = mime_dir.join("packages");
+
+    // Teach the shared MIME database about .bndb files
+    fs::create_dir_all(&packages_dir)
+        .map_err(|e| format!("Failed to create {}: {e}", packages_dir.display()))?;
+    let package_path = packages_dir.join(MIME_PACKAGE_FILE);
+    fs::write(&package_path, mime_package())
+        .map_err(|e| format!("Failed to write {}: {e}", package_path.display()))?;
+    report::changed(&package_path);
+    run_optional("update-mime-database", &[mime_dir.as_os_str()]);
+    outln!("  Registered MIME type: {BNDB_MIME_TYPE}");
+
+    let mut mime_types = vec![BNDB_MIME_TYPE];
+    if binaries {
+        mime_types.extend_from_slice(LINUX_BINARY_MIME_TYPES);
+    }
+
+    fs::create_dir_all(&apps_dir)
+        .map_err(|e| format!("Failed to create {}: {e}", apps_dir.display()))?;
+    let desktop_name = format!("bn-loader-{}.desktop", target.name);
+    let desktop_path = apps_dir.join(&desktop_name);
+    fs::write(&desktop_path, desktop_entry(target, &mime_types))
+        .map_err(|e| format!("Failed to write {}: {e}", desktop_path.display()))?;
+    report::changed(&desktop_path);
+    run_optional("update-desktop-database", &[apps_dir.as_os_str()]);
+    outln!("  Created: {}", desktop_path.display());
+
+    let status = Command::new("xdg-mime")
+        .arg("default")
+        .arg(&desktop_name)
+        .args(&mime_types)
+        .status()
+        .map_err(|e| format!("Failed to run xdg-mime: {e}"))?;
+    if !status.success() {
+        return Err(format!("xdg-mime exited with {status}"));
+    }
+    for mime in &mime_types {
+        outln!("  Default for: {mime}");
+    }
+
+    Ok(())
+}
+
+fn xdg_data_home() -> Result<PathBuf, String> {
+    if let Ok(dir) = env::var("XDG_DATA_HOME")
+        && !dir.is_empty()
+    {
+        return Ok(PathBuf::from(dir));
+    }
+    home_dir()
+        .map(|home| home.join(".local").join("share"))
+        .ok_or_else(|| "Could not determine home directory".to_string())
+}
+
+fn mime_package() -> String {
+    format!(
+        r#"<?xml version="1.0" encoding="UTF-8"?>
+<mime-info xmlns="http://www.freedesktop.org/standards/shared-mime-info">
+  <mime-type type="{BNDB_MIME_TYPE}">
+    <comment>Binary Ninja database</comment>
+    <glob pattern="*.{BNDB_EXTENSION}"/>
+  </mime-type>
+</mime-info>
+"#
+    )
+}
+
+fn desktop_entry(target: &Target, mime_types: &[&str]) -> String {
+    let exec = [
+        desktop_quote(&target.loader_exe.to_string_lossy()),
+        "--config".to_string(),
+        desktop_quote(&target.config_path.to_string_lossy()),
+        desktop_quote(target.name),
+        "%f".to_string(),
+    ]
+    .join(" ");
+
+    let mut entry = format!(
+        "[Desktop Entry]\n\
+         Type=Application\n\
+         Name=Binary Ninja ({name})\n\
+         Comment=Open with the '{name}' bn-loader profile\n\
+         Exec={exec}\n\
+         Terminal=false\n\
+         Categories=Development;\n\
+         MimeType={mimes};\n",
+        name = target.name,
+        mimes = mime_types.join(";"),
+    );
+    if let Some(icon) = &target.profile.icon {
+        entry.push_str(&format!("Icon={}\n", icon.display()));
+    }
+    entry
+}
+
+/// Quote an argument for a desktop entry `Exec` key
+fn desktop_quote(arg: &str) -> String {
+    let mut quoted = String::from("\"");
+    for c in arg.chars() {
+        match c {
+            '"' | '`' | '$' | '\\' => {
+                quoted.push('\\');
+                quoted.push(c);
+            }
+            '%' => quoted.push_str("%%"),
+            _ => quoted.push(c),
+        }
+    }
+    quoted.push('"');
+    quoted
+}
+
+/// Run a cache-refresh helper that may not be installed
+fn run_optional(program: &str, args: &[&std::ffi::OsStr]) {
+    if let Err(e) = Command::new(program).args(args).status() {
+        report::warn(&format!("Failed to run {program}: {e}"));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn desktop_quote_escapes_exec_characters() {
+        assert_eq!(desktop_quote("my profile"), "\"my profile\"");
+        assert_eq!(desktop_quote(r#"a"b`c$d\e%f"#), r#""a\"b\`c\$d\\e%%f""#);
+    }
+
+    #[test]
+    fn desktop_entry_quotes_the_profile_name() {
+        let profile = Profile::default();
+        let target = Target {
+            name: "reverse engineering 🦀",
+            profile: &profile,
+            loader_exe: Path::new("/usr/bin/bn-loader"),
+            config_path: Path::new("/home/me/my config.toml"),
+        };
+        let entry = desktop_entry(&target, &[BNDB_MIME_TYPE]);
+        assert!(entry.contains(
+            "Exec=\"/usr/bin/bn-loader\" --config \"/home/me/my config.toml\" \"reverse engineering 🦀\" %f\n"
+        ));
+    }
+}
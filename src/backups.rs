@@ -0,0 +1,741 @@
+use crate::colors::paint;
+use crate::config::Config;
+use crate::copy::{copy_file, copy_item, copy_link, read_link};
+use crate::history::format_age;
+use crate::items::{HANDLERS, ItemHandler};
+use crate::progress::Progress;
+use crate::report::{self, dataln, outln};
+use crate::repositories::{format_size, tree_size};
+use crate::safety::guard_config_dir;
+use crate::sync::{confirm, remove_path};
+use dialoguer::Select;
+use std::fs;
+use std::io::{self, IsTerminal};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use termcolor::Color;
+
+pub(crate) const BACKUP_PREFIX: &str = ".bn-loader-backup-";
+
+pub(crate) struct RestoreOptions<'a> {
+    pub profile: &'a str,
+    /// Timestamp of the backup to restore; prompts when not given
+    pub timestamp: Option<u64>,
+    pub latest: bool,
+    /// Only restore these items
+    pub only: Vec<&'a str>,
+    pub dry_run: bool,
+    pub yes: bool,
+    pub allow_suspicious_dirs: bool,
+}
+
+/// Put the items from one of a profile's sync backups back into its config
+/// dir. What is there now is backed up first, so a restore can be undone too.
+pub(crate) fn run_restore(config: &Config, options: &RestoreOptions) -> Result<(), String> {
+    let name = options.profile;
+    let profile = config
+        .profiles
+        .get(name)
+        .ok_or_else(|| format!("Profile '{name}' not found."))?;
+
+    let store = BackupStore::new(
+        config.global.backup_dir.as_deref(),
+        name,
+        &profile.config_dir,
+    );
+    let backups = store.list()?;
+    if backups.is_empty() {
+        return Err(format!("Profile '{name}' has no sync backups."));
+    }
+    let now = now_secs();
+
+    let (backup_dir, timestamp) = if let Some(timestamp) = options.timestamp {
+        backups
+            .iter()
+            .find(|(_, ts)| *ts == timestamp)
+            .ok_or_else(|| {
+                format!(
+                    "No backup {timestamp} for '{name}'. Available backups:\n  {}",
+                    describe_backups(&backups, now).join("\n  ")
+                )
+            })?
+    } else if options.latest {
+        &backups[0]
+    } else {
+        pick_backup(name, &backups, now)?
+    };
+
+    let known: Vec<&str> = HANDLERS.iter().map(|h| h.name()).collect();
+    if let Some(unknown) = options.only.iter().find(|item| !known.contains(item)) {
+        return Err(format!(
+            "Unknown sync item '{unknown}' (expected one of: {})",
+            known.join(", ")
+        ));
+    }
+    let in_backup: Vec<&'static dyn ItemHandler> = HANDLERS
+        .iter()
+        .copied()
+        .filter(|item| backup_dir.join(item.name()).exists())
+        .collect();
+    if let Some(missing) = options
+        .only
+        .iter()
+        .find(|only| !in_backup.iter().any(|item| item.name() == **only))
+    {
+        let names: Vec<&str> = in_backup.iter().map(|item| item.name()).collect();
+        return Err(format!(
+            "Backup {timestamp} has no '{missing}' (it contains: {})",
+            names.join(", ")
+        ));
+    }
+    let items: Vec<&dyn ItemHandler> = in_backup
+        .into_iter()
+        .filter(|item| options.only.is_empty() || options.only.contains(&item.name()))
+        .collect();
+
+    outln!("Restore Plan:");
+    outln!("  Profile: {name} ({})", profile.config_dir.display());
+    outln!(
+        "  Backup: {timestamp} ({})",
+        format_age(now.saturating_sub(*timestamp))
+    );
+    outln!("  Items:");
+    for item in &items {
+        outln!("    {:<18} {}", item.name(), item.describe());
+    }
+
+    if options.dry_run {
+        outln!("\n{}", paint("[Dry run] No changes made.", Color::Yellow));
+        return Ok(());
+    }
+
+    guard_config_dir(name, &profile.config_dir, options.allow_suspicious_dirs)?;
+
+    if !options.yes && !confirm("\nProceed?")? {
+        outln!("Aborted.");
+        return Ok(());
+    }
+
+    outln!("\nRestoring '{name}'...");
+    if let Some(current) = store.create(&items)? {
+        outln!("  Current state backed up: {}", current.display());
+        report::changed(&current);
+    }
+    for item in &items {
+        restore_item(Some(backup_dir), &profile.config_dir, item.name())?;
+        outln!("  {} {}", paint("Restored:", Color::Green), item.name());
+    }
+
+    outln!("\n{}", paint("Restore complete.", Color::Green));
+    Ok(())
+}
+
+/// Put an item back the way a backup has it. Without a backup, or when the
+/// backup doesn't have the item, the config dir didn't have it either, so it
+/// is removed.
+pub(crate) fn restore_item(
+    backup_dir: Option<&Path>,
+    config_dir: &Path,
+    name: &str,
+) -> Result<(), String> {
+    let target = config_dir.join(name);
+    if fs::symlink_metadata(&target).is_ok() {
+        remove_path(&target)?;
+    }
+    if let Some(source) = backup_dir
+        .map(|dir| dir.join(name))
+        .filter(|source| source.exists())
+    {
+        copy_item(&source, &target).map_err(|e| format!("Failed to restore {name}: {e}"))?;
+    }
+    report::changed(&target);
+    Ok(())
+}
+
+/// A config dir, the profiles that use it and all their backups
+struct BackupLocation {
+    names: Vec<String>,
+    config_dir: PathBuf,
+    backups: Vec<(PathBuf, u64)>,
+}
+
+/// Every config dir, each once (profiles can share one)
+fn backup_locations(config: &Config, profile: Option<&str>) -> Result<Vec<BackupLocation>, String> {
+    if let Some(name) = profile
+        && !config.profiles.contains_key(name)
+    {
+        return Err(format!("Profile '{name}' not found."));
+    }
+
+    let mut names: Vec<&String> = config.profiles.keys().collect();
+    names.sort_unstable();
+    let mut locations: Vec<BackupLocation> = Vec::new();
+    for name in names {
+        if profile.is_some_and(|p| p != name) {
+            continue;
+        }
+        let dir = &config.profiles[name].config_dir;
+        let backups = BackupStore::new(config.global.backup_dir.as_deref(), name, dir).list()?;
+        match locations.iter_mut().find(|l| l.config_dir == *dir) {
+            Some(location) => {
+                location.names.push(name.clone());
+                for backup in backups {
+                    if !location.backups.contains(&backup) {
+                        location.backups.push(backup);
+                    }
+                }
+                location.backups.sort_by_key(|b| std::cmp::Reverse(b.1));
+            }
+            None => locations.push(BackupLocation {
+                names: vec![name.clone()],
+                config_dir: dir.clone(),
+                backups,
+            }),
+        }
+    }
+    Ok(locations)
+}
+
+/// Print every sync backup with its age and size
+pub(crate) fn print_backups(config: &Config, profile: Option<&str>) -> Result<(), String> {
+    let now = now_secs();
+    let mut count = 0;
+    let mut total = 0;
+
+    for location in backup_locations(config, profile)? {
+        let backups = &location.backups;
+        if backups.is_empty() {
+            continue;
+        }
+        dataln!(
+            "{} ({}):",
+            paint(&location.names.join(", "), Color::Cyan),
+            location.config_dir.display()
+        );
+        for (line, (path, _)) in describe_backups(backups, now).iter().zip(backups) {
+            let size = tree_size(path)?;
+            dataln!("  {:>10}  {line}", format_size(size));
+            count += 1;
+            total += size;
+        }
+    }
+
+    if count == 0 {
+        dataln!("No sync backups.");
+    } else {
+        dataln!(
+            "\n{count} backup{}, {} in total",
+            if count == 1 { "" } else { "s" },
+            format_size(total)
+        );
+    }
+    Ok(())
+}
+
+/// A profile's sync backups as `backups list` describes them, newest first
+#[cfg(feature = "tui")]
+pub(crate) fn describe_profile_backups(config: &Config, name: &str) -> Result<Vec<String>, String> {
+    let backups: Vec<(PathBuf, u64)> = backup_locations(config, Some(name))?
+        .into_iter()
+        .flat_map(|location| location.backups)
+        .collect();
+    Ok(describe_backups(&backups, now_secs()))
+}
+
+pub(crate) struct PruneOptions<'a> {
+    /// Only prune this profile's backups
+    pub profile: Option<&'a str>,
+    /// Keep at least this many of the newest backups per config dir
+    pub keep: Option<usize>,
+    /// Only remove backups older than this
+    pub older_than: Option<Duration>,
+    pub dry_run: bool,
+    pub yes: bool,
+}
+
+/// Remove sync backups by count and/or age across profiles
+pub(crate) fn run_prune(config: &Config, options: &PruneOptions) -> Result<(), String> {
+    let now = now_secs();
+
+    let mut doomed = Vec::new();
+    for location in backup_locations(config, options.profile)? {
+        doomed.extend(prunable(
+            &location.backups,
+            options.keep,
+            options.older_than,
+            now,
+        ));
+    }
+
+    if doomed.is_empty() {
+        outln!("No backups to prune.");
+        return Ok(());
+    }
+
+    outln!("Backups to remove:");
+    let mut total = 0;
+    for path in &doomed {
+        let size = tree_size(path)?;
+        total += size;
+        outln!("  {:>10}  {}", format_size(size), path.display());
+    }
+    outln!("  Total: {}", format_size(total));
+
+    if options.dry_run {
+        outln!("\n{}", paint("[Dry run] No changes made.", Color::Yellow));
+        return Ok(());
+    }
+    if !options.yes && !confirm("\nProceed?")? {
+        outln!("Aborted.");
+        return Ok(());
+    }
+
+    outln!();
+    remove_backups(&doomed);
+    Ok(())
+}
+
+/// Parse an age such as `30d`, `12h`, `2w` or `90m`
+pub(crate) fn parse_age(value: &str) -> Result<Duration, String> {
+    let split = value
+        .find(|c: char| !c.is_ascii_digit())
+        .ok_or_else(|| format!("Missing unit in '{value}' (use m, h, d or w)"))?;
+    let (number, unit) = value.split_at(split);
+    let number: u64 = number
+        .parse()
+        .map_err(|_| format!("Invalid age '{value}'"))?;
+    let unit_secs = match unit {
+        "m" => 60,
+        "h" => 60 * 60,
+        "d" => 24 * 60 * 60,
+        "w" => 7 * 24 * 60 * 60,
+        _ => {
+            return Err(format!(
+                "Unknown unit '{unit}' in '{value}' (use m, h, d or w)"
+            ));
+        }
+    };
+    number
+        .checked_mul(unit_secs)
+        .map(Duration::from_secs)
+        .ok_or_else(|| format!("Age '{value}' is too large"))
+}
+
+fn pick_backup<'a>(
+    name: &str,
+    backups: &'a [(PathBuf, u64)],
+    now: u64,
+) -> Result<&'a (PathBuf, u64), String> {
+    let labels = describe_backups(backups, now);
+    if !io::stdin().is_terminal() || !io::stderr().is_terminal() {
+        return Err(format!(
+            "Pass a backup timestamp or --latest. Available backups for '{name}':\n  {}",
+            labels.join("\n  ")
+        ));
+    }
+
+    let selection = Select::new()
+        .with_prompt(format!("Backup of '{name}' to restore"))
+        .items(&labels)
+        .default(0)
+        .interact_opt()
+        .map_err(|e| format!("Failed to read selection: {e}"))?;
+
+    selection
+        .map(|idx| &backups[idx])
+        .ok_or_else(|| "No backup selected.".to_string())
+}
+
+/// One line per backup: timestamp, age and the items it holds
+fn describe_backups(backups: &[(PathBuf, u64)], now: u64) -> Vec<String> {
+    backups
+        .iter()
+        .map(|(path, ts)| {
+            let items: Vec<&str> = HANDLERS
+                .iter()
+                .map(|item| item.name())
+                .filter(|item| path.join(item).exists())
+                .collect();
+            format!(
+                "{ts} ({}): {}",
+                format_age(now.saturating_sub(*ts)),
+                items.join(", ")
+            )
+        })
+        .collect()
+}
+
+fn now_secs() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+/// Where one profile's sync backups are kept: `<backup_dir>/<profile>/<timestamp>`
+/// when `global.backup_dir` is set, `.bn-loader-backup-<timestamp>` inside the
+/// config dir otherwise. Backups left in the config dir from before
+/// `backup_dir` was set are still found.
+pub(crate) struct BackupStore<'a> {
+    /// The profile's config dir, which backups are taken of
+    config_dir: &'a Path,
+    central_dir: Option<PathBuf>,
+}
+
+impl<'a> BackupStore<'a> {
+    pub(crate) fn new(backup_dir: Option<&Path>, name: &str, config_dir: &'a Path) -> Self {
+        Self {
+            config_dir,
+            central_dir: backup_dir.map(|dir| dir.join(name)),
+        }
+    }
+
+    /// Back up the items the config dir has, returning the new backup
+    pub(crate) fn create(&self, items: &[&dyn ItemHandler]) -> Result<Option<PathBuf>, String> {
+        let items_to_backup: Vec<&str> = items
+            .iter()
+            .filter(|item| item.identify(self.config_dir))
+            .map(|item| item.name())
+            .collect();
+
+        if items_to_backup.is_empty() {
+            return Ok(None);
+        }
+
+        let mut timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_err(|e| format!("System clock error: {e}"))?
+            .as_secs();
+        // A second backup within the same second (a restore right after a
+        // sync) must not write into the first
+        let backup_dir = loop {
+            let dir = match &self.central_dir {
+                Some(dir) => dir.join(timestamp.to_string()),
+                None => self.config_dir.join(format!("{BACKUP_PREFIX}{timestamp}")),
+            };
+            if fs::symlink_metadata(&dir).is_err() {
+                break dir;
+            }
+            timestamp += 1;
+        };
+
+        // Files unchanged since the newest backup share its copies
+        let previous = self.list()?.into_iter().next().map(|(path, _)| path);
+
+        fs::create_dir_all(&backup_dir)
+            .map_err(|e| format!("Failed to create backup directory: {e}"))?;
+
+        let mut links = HardLinks::default();
+        for item in items_to_backup {
+            let src = self.config_dir.join(item);
+            if fs::symlink_metadata(&src).is_ok_and(|m| m.file_type().is_symlink()) {
+                copy_item(&src, &backup_dir.join(item))
+                    .map_err(|e| format!("Failed to backup {item}: {e}"))?;
+                continue;
+            }
+            let mut progress = Progress::for_tree(item, &src, item, &|_, _| false);
+            backup_tree(
+                &src,
+                &backup_dir.join(item),
+                previous.as_ref().map(|dir| dir.join(item)).as_deref(),
+                &mut links,
+                &mut progress,
+            )
+            .map_err(|e| format!("Failed to backup {item}: {e}"))?;
+        }
+        if links.linked > 0 {
+            outln!(
+                "  Hard-linked {} unchanged file(s) to the previous backup ({} saved)",
+                links.linked,
+                format_size(links.bytes)
+            );
+        }
+
+        Ok(Some(backup_dir))
+    }
+
+    /// Backups with their timestamps, newest first
+    pub(crate) fn list(&self) -> Result<Vec<(PathBuf, u64)>, String> {
+        let mut backups = list_dir_backups(self.config_dir, |name| {
+            name.strip_prefix(BACKUP_PREFIX)?.parse().ok()
+        })?;
+        if let Some(dir) = &self.central_dir {
+            backups.extend(list_dir_backups(dir, |name| name.parse().ok())?);
+        }
+        backups.sort_by_key(|b| std::cmp::Reverse(b.1));
+        Ok(backups)
+    }
+
+    /// Remove all but the newest `retention` backups
+    pub(crate) fn cleanup(&self, retention: usize) -> Result<(), String> {
+        let backups = self.list()?;
+        remove_backups(&prunable(&backups, Some(retention), None, now_secs()));
+        Ok(())
+    }
+}
+
+/// Files a backup shared with the previous one instead of copying
+#[derive(Default)]
+struct HardLinks {
+    linked: usize,
+    bytes: u64,
+    /// Set once linking fails (another filesystem, or one without hard
+    /// links); the rest of the backup is copied
+    unsupported: bool,
+}
+
+/// Copy `src` to `dst` for a backup. Files with the same size and
+/// modification time as at `previous` (the same path in the last backup) are
+/// hard-linked to it instead. Backups are never written to after they are
+/// made, so sharing is safe, and a pruned backup only drops its own links.
+fn backup_tree(
+    src: &Path,
+    dst: &Path,
+    previous: Option<&Path>,
+    links: &mut HardLinks,
+    progress: &mut Progress,
+) -> Result<(), String> {
+    let src_meta =
+        fs::symlink_metadata(src).map_err(|e| format!("Failed to read {}: {e}", src.display()))?;
+    if src_meta.file_type().is_symlink() {
+        progress.file_done(0);
+        return copy_link(src, &read_link(src)?, dst);
+    }
+    if src_meta.is_dir() {
+        fs::create_dir_all(dst)
+            .map_err(|e| format!("Failed to create directory {}: {e}", dst.display()))?;
+        for entry in fs::read_dir(src)
+            .map_err(|e| format!("Failed to read directory {}: {e}", src.display()))?
+        {
+            let entry = entry.map_err(|e| format!("Failed to read entry: {e}"))?;
+            let name = entry.file_name();
+            backup_tree(
+                &entry.path(),
+                &dst.join(&name),
+                previous.map(|p| p.join(&name)).as_deref(),
+                links,
+                progress,
+            )?;
+        }
+        return Ok(());
+    }
+
+    progress.file_done(src_meta.len());
+    if !links.unsupported
+        && let Some(previous) = previous
+        && let Ok(previous_meta) = fs::symlink_metadata(previous)
+        && previous_meta.is_file()
+        && previous_meta.len() == src_meta.len()
+        && previous_meta.modified().ok() == src_meta.modified().ok()
+    {
+        match fs::hard_link(previous, dst) {
+            Ok(()) => {
+                links.linked += 1;
+                links.bytes += src_meta.len();
+                return Ok(());
+            }
+            Err(_) => links.unsupported = true,
+        }
+    }
+
+    // Keeps the modification time, so the next backup can tell the file is
+    // unchanged
+    copy_file(src, dst, &src_meta).map(|_| ())
+}
+
+/// Directories in `dir` whose names `timestamp` parses. A missing `dir` has
+/// no backups.
+fn list_dir_backups(
+    dir: &Path,
+    timestamp: impl Fn(&str) -> Option<u64>,
+) -> Result<Vec<(PathBuf, u64)>, String> {
+    if !dir.is_dir() {
+        return Ok(Vec::new());
+    }
+    let entries = fs::read_dir(dir)
+        .map_err(|e| format!("Failed to read directory {}: {e}", dir.display()))?;
+
+    Ok(entries
+        .filter_map(std::result::Result::ok)
+        .filter_map(|entry| {
+            let path = entry.path();
+            if !path.is_dir() {
+                return None;
+            }
+            Some((path, timestamp(&entry.file_name().to_string_lossy())?))
+        })
+        .collect())
+}
+
+/// Backups (newest first) that fall outside the newest `keep` and are older
+/// than `older_than`. With both limits, a backup must break both to go.
+fn prunable(
+    backups: &[(PathBuf, u64)],
+    keep: Option<usize>,
+    older_than: Option<Duration>,
+    now: u64,
+) -> Vec<PathBuf> {
+    backups
+        .iter()
+        .enumerate()
+        .filter(|(index, _)| keep.is_none_or(|keep| *index >= keep))
+        .filter(|(_, (_, ts))| older_than.is_none_or(|age| now.saturating_sub(*ts) > age.as_secs()))
+        .map(|(_, (path, _))| path.clone())
+        .collect()
+}
+
+fn remove_backups(paths: &[PathBuf]) {
+    for path in paths {
+        if let Err(e) = fs::remove_dir_all(path) {
+            report::warn(&format!(
+                "Failed to remove old backup {}: {e}",
+                path.display()
+            ));
+        } else {
+            outln!("  Removed old backup: {}", path.display());
+            report::changed(path);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_age_reads_each_unit() {
+        assert_eq!(parse_age("90m"), Ok(Duration::from_secs(90 * 60)));
+        assert_eq!(parse_age("12h"), Ok(Duration::from_secs(12 * 3600)));
+        assert_eq!(parse_age("30d"), Ok(Duration::from_secs(30 * 86400)));
+        assert_eq!(parse_age("2w"), Ok(Duration::from_secs(14 * 86400)));
+        assert_eq!(parse_age("0d"), Ok(Duration::ZERO));
+    }
+
+    #[test]
+    fn parse_age_rejects_bad_ages() {
+        for value in [
+            "",
+            "30",
+            "d",
+            "-1d",
+            "1.5d",
+            "30s",
+            "30 d",
+            "30dd",
+            "9999999999999999w",
+        ] {
+            assert!(parse_age(value).is_err(), "{value:?}");
+        }
+    }
+
+    fn backups(timestamps: &[u64]) -> Vec<(PathBuf, u64)> {
+        timestamps
+            .iter()
+            .map(|ts| (PathBuf::from(ts.to_string()), *ts))
+            .collect()
+    }
+
+    fn names(paths: Vec<PathBuf>) -> Vec<String> {
+        paths.iter().map(|p| p.display().to_string()).collect()
+    }
+
+    #[test]
+    fn prunable_applies_both_limits() {
+        let day = 86400;
+        let now = 100 * day;
+        let list = backups(&[now - day, now - 3 * day, now - 10 * day, now - 20 * day]);
+        let week = Some(Duration::from_secs(7 * day));
+        let old = [(now - 10 * day).to_string(), (now - 20 * day).to_string()];
+
+        assert_eq!(names(prunable(&list, None, None, now)).len(), 4);
+        assert_eq!(names(prunable(&list, Some(2), None, now)), old);
+        assert_eq!(names(prunable(&list, None, week, now)), old);
+        // Both limits: outside the newest three and older than a week
+        assert_eq!(names(prunable(&list, Some(3), week, now)), old[1..]);
+        assert_eq!(names(prunable(&list, Some(0), week, now)), old);
+        assert!(prunable(&list, Some(4), None, now).is_empty());
+    }
+
+    fn item(name: &str) -> &'static dyn ItemHandler {
+        *HANDLERS.iter().find(|h| h.name() == name).unwrap()
+    }
+
+    #[test]
+    fn backups_share_unchanged_files() {
+        let root = tempfile::tempdir().unwrap();
+        let config_dir = root.path().join("cfg");
+        let plugin = config_dir.join("plugins").join("foo");
+        fs::create_dir_all(&plugin).unwrap();
+        fs::write(plugin.join("same.py"), "same").unwrap();
+        fs::write(plugin.join("changed.py"), "before").unwrap();
+        fs::write(config_dir.join("settings.json"), "{}").unwrap();
+        let items = [item("plugins"), item("settings.json"), item("themes")];
+
+        let store = BackupStore::new(None, "work", &config_dir);
+        let first = store.create(&items).unwrap().unwrap();
+        fs::write(plugin.join("changed.py"), "after a change").unwrap();
+        let second = store.create(&items).unwrap().unwrap();
+        assert_ne!(first, second);
+
+        let file = |backup: &Path, name| backup.join("plugins").join("foo").join(name);
+        assert_eq!(
+            fs::read_to_string(file(&first, "changed.py")).unwrap(),
+            "before"
+        );
+        assert_eq!(
+            fs::read_to_string(file(&second, "changed.py")).unwrap(),
+            "after a change"
+        );
+        assert_eq!(
+            fs::read_to_string(file(&second, "same.py")).unwrap(),
+            "same"
+        );
+        assert!(!second.join("themes").exists());
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::MetadataExt;
+            let inode = |path: PathBuf| fs::metadata(path).unwrap().ino();
+            assert_eq!(
+                inode(file(&first, "same.py")),
+                inode(file(&second, "same.py"))
+            );
+            assert_ne!(
+                inode(file(&first, "changed.py")),
+                inode(file(&second, "changed.py"))
+            );
+        }
+
+        let listed: Vec<_> = store.list().unwrap().into_iter().map(|(p, _)| p).collect();
+        assert_eq!(listed, [second.clone(), first.clone()]);
+        store.cleanup(1).unwrap();
+        assert!(!first.exists());
+        assert_eq!(
+            fs::read_to_string(file(&second, "same.py")).unwrap(),
+            "same"
+        );
+    }
+
+    #[test]
+    fn central_backups_are_listed_with_local_ones() {
+        let root = tempfile::tempdir().unwrap();
+        let config_dir = root.path().join("cfg");
+        let local = config_dir.join(format!("{BACKUP_PREFIX}100"));
+        fs::create_dir_all(&local).unwrap();
+        fs::write(config_dir.join("settings.json"), "{}").unwrap();
+
+        let central = root.path().join("backups");
+        let store = BackupStore::new(Some(&central), "work", &config_dir);
+        let created = store.create(&[item("settings.json")]).unwrap().unwrap();
+        assert!(created.starts_with(central.join("work")));
+        let listed: Vec<_> = store.list().unwrap().into_iter().map(|(p, _)| p).collect();
+        assert_eq!(listed, [created, local]);
+    }
+
+    #[test]
+    fn nothing_to_back_up_makes_no_backup() {
+        let root = tempfile::tempdir().unwrap();
+        let store = BackupStore::new(None, "work", root.path());
+        assert_eq!(store.create(&[item("settings.json")]), Ok(None));
+        assert!(store.list().unwrap().is_empty());
+    }
+}
@@ -0,0 +1,132 @@
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Names of Binary Ninja's core library, which carries its version string
+const CORE_LIBRARIES: &[&str] = &[
+    "libbinaryninjacore.so.1",
+    "libbinaryninjacore.so",
+    "binaryninjacore.dll",
+    "libbinaryninjacore.dylib",
+];
+
+/// A Binary Ninja version such as `4.2.6455`. The last number is the build,
+/// which is what plugins name as their minimum version.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) struct BnVersion {
+    pub version: String,
+    pub build: u64,
+}
+
+impl fmt::Display for BnVersion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.version)
+    }
+}
+
+impl BnVersion {
+    /// `4.2.6455` or `4.2.6455-stable`; the build is the third number
+    pub(crate) fn parse(text: &str) -> Option<Self> {
+        let version = text.trim();
+        let numbers = version.split(['-', ' ']).next()?;
+        let parts: Vec<&str> = numbers.split('.').collect();
+        if parts.len() != 3 || parts.iter().any(|p| p.parse::<u64>().is_err()) {
+            return None;
+        }
+        Some(BnVersion {
+            version: version.to_string(),
+            build: parts[2].parse().ok()?,
+        })
+    }
+}
+
+/// The version of the Binary Ninja installed in `install_dir`, from the app
+/// bundle's Info.plist on macOS or else the version string in its core
+/// library
+pub(crate) fn detect_version(install_dir: &Path) -> Option<BnVersion> {
+    plist_version(install_dir).or_else(|| library_version(install_dir))
+}
+
+fn plist_version(install_dir: &Path) -> Option<BnVersion> {
+    // install_dir may be the .app bundle or its Contents/MacOS
+    let candidates: Vec<PathBuf> = [
+        install_dir.join("Contents").join("Info.plist"),
+        install_dir.join("Info.plist"),
+    ]
+    .into_iter()
+    .chain(install_dir.parent().map(|p| p.join("Info.plist")))
+    .collect();
+    let content = candidates
+        .iter()
+        .find_map(|path| fs::read_to_string(path).ok())?;
+    ["CFBundleShortVersionString", "CFBundleVersion"]
+        .iter()
+        .find_map(|key| plist_string(&content, key))
+        .and_then(|value| BnVersion::parse(&value))
+}
+
+/// The `<string>` following `<key>{key}</key>` in an XML plist
+fn plist_string(content: &str, key: &str) -> Option<String> {
+    let rest = &content[content.find(&format!("<key>{key}</key>"))?..];
+    let start = rest.find("<string>")? + "<string>".len();
+    let end = rest[start..].find("</string>")?;
+    Some(rest[start..start + end].to_string())
+}
+
+fn library_version(install_dir: &Path) -> Option<BnVersion> {
+    let data = CORE_LIBRARIES
+        .iter()
+        .find_map(|name| fs::read(install_dir.join(name)).ok())?;
+    scan_version(&data)
+}
+
+/// The most frequent `X.Y.BUILD` string in `data`, where BUILD has four or
+/// five digits as Binary Ninja's builds do
+fn scan_version(data: &[u8]) -> Option<BnVersion> {
+    let mut counts: Vec<(&[u8], usize)> = Vec::new();
+    let mut i = 0;
+    while i < data.len() {
+        if let Some(len) = version_at(data, i) {
+            match counts.iter_mut().find(|(v, _)| *v == &data[i..i + len]) {
+                Some((_, count)) => *count += 1,
+                None => counts.push((&data[i..i + len], 1)),
+            }
+            i += len;
+        } else {
+            i += 1;
+        }
+    }
+    let (bytes, _) = counts.into_iter().max_by_key(|(_, count)| *count)?;
+    BnVersion::parse(std::str::from_utf8(bytes).ok()?)
+}
+
+/// Length of a `D.D[D].DDDD[D]` version starting at `start`, not preceded or
+/// followed by more digits or dots
+fn version_at(data: &[u8], start: usize) -> Option<usize> {
+    let boundary = |b: u8| !(b.is_ascii_alphanumeric() || b == b'.');
+    if start > 0 && !boundary(data[start - 1]) {
+        return None;
+    }
+    let mut end = start;
+    for (min, max) in [(1, 1), (1, 2), (4, 5)] {
+        if end > start {
+            if data.get(end) != Some(&b'.') {
+                return None;
+            }
+            end += 1;
+        }
+        let digits = data[end..]
+            .iter()
+            .take(max + 1)
+            .take_while(|b| b.is_ascii_digit())
+            .count();
+        if digits < min || digits > max {
+            return None;
+        }
+        end += digits;
+    }
+    match data.get(end) {
+        Some(&b) if b != b'-' && !boundary(b) => None,
+        _ => Some(end - start),
+    }
+}
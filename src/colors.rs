@@ -1,12 +1,101 @@
-use std::io::{self, Write};
-use termcolor::{Color, ColorChoice, ColorSpec, StandardStream, WriteColor};
+use crate::config::ColorMode;
+use crate::report;
+use std::env;
+use std::io::{self, IsTerminal, Write};
+use std::sync::Mutex;
+use termcolor::{Ansi, Color, ColorChoice, ColorSpec, StandardStream, WriteColor};
+
+/// Whether stdout and stderr get colors. Decided once, since stdout stops
+/// being the terminal when it is piped into the pager.
+struct Choices {
+    stdout: ColorChoice,
+    stderr: ColorChoice,
+}
+
+static CHOICES: Mutex<Option<Choices>> = Mutex::new(None);
+
+/// Decide on colors from `--color`, or else `global.color`. `auto` colors a
+/// terminal unless `NO_COLOR` is set or `TERM` is `dumb`.
+pub(crate) fn init(mode: ColorMode) {
+    let choices = Choices {
+        stdout: resolve(mode, io::stdout().is_terminal()),
+        stderr: resolve(mode, io::stderr().is_terminal()),
+    };
+    if let Ok(mut guard) = CHOICES.lock() {
+        *guard = Some(choices);
+    }
+}
+
+fn resolve(mode: ColorMode, terminal: bool) -> ColorChoice {
+    let no_color = env::var_os("NO_COLOR").is_some_and(|value| !value.is_empty());
+    let dumb = env::var_os("TERM").is_some_and(|term| term == "dumb");
+    decide(mode, terminal && !dumb, no_color)
+}
+
+/// `auto` colors a terminal that can show colors, unless `NO_COLOR` is set
+fn decide(mode: ColorMode, color_terminal: bool, no_color: bool) -> ColorChoice {
+    match mode {
+        ColorMode::Always => ColorChoice::Always,
+        ColorMode::Never => ColorChoice::Never,
+        ColorMode::Auto if color_terminal && !no_color => ColorChoice::Always,
+        ColorMode::Auto => ColorChoice::Never,
+    }
+}
+
+fn choice(stderr: bool) -> ColorChoice {
+    let mut guard = match CHOICES.lock() {
+        Ok(guard) => guard,
+        Err(_) => return ColorChoice::Never,
+    };
+    let choices = guard.get_or_insert_with(|| Choices {
+        stdout: resolve(ColorMode::Auto, io::stdout().is_terminal()),
+        stderr: resolve(ColorMode::Auto, io::stderr().is_terminal()),
+    });
+    if stderr {
+        choices.stderr
+    } else {
+        choices.stdout
+    }
+}
 
 pub(crate) fn stdout() -> StandardStream {
-    StandardStream::stdout(ColorChoice::Auto)
+    StandardStream::stdout(choice(false))
+}
+
+/// `text` in `color` for a line printed to stdout (or to stderr, where
+/// `outln!` and `dataln!` go when stdout carries data), plain when that has
+/// no colors
+pub(crate) fn paint(text: &str, color: Color) -> String {
+    paint_spec(
+        text,
+        ColorSpec::new().set_fg(Some(color)),
+        report::stdout_is_data(),
+    )
+}
+
+/// `text` in bold `color` for stderr, as warnings and errors are labeled
+pub(crate) fn paint_stderr(text: &str, color: Color) -> String {
+    paint_spec(
+        text,
+        ColorSpec::new().set_fg(Some(color)).set_bold(true),
+        true,
+    )
+}
+
+fn paint_spec(text: &str, spec: &ColorSpec, stderr: bool) -> String {
+    if choice(stderr) == ColorChoice::Never {
+        return text.to_string();
+    }
+    let mut ansi = Ansi::new(Vec::new());
+    let _ = ansi
+        .set_color(spec)
+        .and_then(|()| write!(ansi, "{text}"))
+        .and_then(|()| ansi.reset());
+    String::from_utf8_lossy(&ansi.into_inner()).into_owned()
 }
 
 pub(crate) fn writeln_colored(
-    stream: &mut StandardStream,
+    stream: &mut dyn WriteColor,
     text: &str,
     color: Color,
 ) -> io::Result<()> {
@@ -15,14 +104,44 @@ pub(crate) fn writeln_colored(
     stream.reset()
 }
 
-pub(crate) fn write_bold(stream: &mut StandardStream, text: &str) -> io::Result<()> {
+pub(crate) fn write_bold(stream: &mut dyn WriteColor, text: &str) -> io::Result<()> {
     stream.set_color(ColorSpec::new().set_bold(true))?;
     write!(stream, "{text}")?;
     stream.reset()
 }
 
-pub(crate) fn writeln_bold(stream: &mut StandardStream, text: &str) -> io::Result<()> {
+pub(crate) fn writeln_bold(stream: &mut dyn WriteColor, text: &str) -> io::Result<()> {
     stream.set_color(ColorSpec::new().set_bold(true))?;
     writeln!(stream, "{text}")?;
     stream.reset()
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn auto_colors_only_a_color_terminal() {
+        assert_eq!(decide(ColorMode::Auto, true, false), ColorChoice::Always);
+        assert_eq!(decide(ColorMode::Auto, false, false), ColorChoice::Never);
+    }
+
+    #[test]
+    fn no_color_turns_off_auto() {
+        assert_eq!(decide(ColorMode::Auto, true, true), ColorChoice::Never);
+    }
+
+    #[test]
+    fn explicit_modes_win() {
+        for (terminal, no_color) in [(true, true), (false, false), (false, true)] {
+            assert_eq!(
+                decide(ColorMode::Always, terminal, no_color),
+                ColorChoice::Always
+            );
+            assert_eq!(
+                decide(ColorMode::Never, terminal, no_color),
+                ColorChoice::Never
+            );
+        }
+    }
+}
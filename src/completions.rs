@@ -1,24 +1,73 @@
 use crate::ShellType;
+use crate::config::{find_config_file, load_config};
+use crate::report::dataln;
+use crate::session::load_session;
+use clap_complete::engine::{CompletionCandidate, PathCompleter, ValueCompleter};
+use std::env;
+use std::ffi::OsStr;
 
 pub(crate) fn print_instructions(shell: &ShellType) {
     match shell {
         ShellType::Bash => {
-            println!("# Add this to your ~/.bashrc:");
-            println!("source <(COMPLETE=bash bn-loader)");
+            dataln!("# Add this to your ~/.bashrc:");
+            dataln!("source <(COMPLETE=bash bn-loader)");
         }
         ShellType::Zsh => {
-            println!("# Add this to your ~/.zshrc:");
-            println!("source <(COMPLETE=zsh bn-loader)");
+            dataln!("# Add this to your ~/.zshrc:");
+            dataln!("source <(COMPLETE=zsh bn-loader)");
         }
         ShellType::Fish => {
-            println!("# Add this to ~/.config/fish/config.fish:");
-            println!("COMPLETE=fish bn-loader | source");
+            dataln!("# Add this to ~/.config/fish/config.fish:");
+            dataln!("COMPLETE=fish bn-loader | source");
         }
         ShellType::Powershell => {
-            println!("# Add this to your $PROFILE:");
-            println!("$env:COMPLETE = 'powershell'");
-            println!("bn-loader | Out-String | Invoke-Expression");
-            println!("Remove-Item Env:COMPLETE");
+            dataln!("# Add this to your $PROFILE:");
+            dataln!("$env:COMPLETE = 'powershell'");
+            dataln!("bn-loader | Out-String | Invoke-Expression");
+            dataln!("Remove-Item Env:COMPLETE");
         }
     }
 }
+
+/// Complete launch file arguments: the pending profile's recently opened
+/// files first, then ordinary paths
+pub(crate) fn launch_file_completer(current: &OsStr) -> Vec<CompletionCandidate> {
+    let current_str = current.to_string_lossy();
+    let mut candidates: Vec<CompletionCandidate> = pending_profile()
+        .and_then(|profile| load_session(&profile))
+        .map(|session| session.files)
+        .unwrap_or_default()
+        .into_iter()
+        .filter(|file| file.exists() && file.to_string_lossy().starts_with(current_str.as_ref()))
+        .map(|file| CompletionCandidate::new(file).help(Some("recently opened".into())))
+        .collect();
+
+    let paths: Vec<CompletionCandidate> = PathCompleter::file()
+        .complete(current)
+        .into_iter()
+        .filter(|path| !candidates.iter().any(|c| c.get_value() == path.get_value()))
+        .collect();
+    candidates.extend(paths);
+    candidates
+}
+
+/// Find the profile named on the command line being completed.
+///
+/// During dynamic completion the shell passes the words after `--`.
+fn pending_profile() -> Option<String> {
+    let config = find_config_file(None).and_then(|p| load_config(&p).ok())?;
+    let mut words = env::args().skip_while(|a| a != "--").skip(2);
+
+    while let Some(word) = words.next() {
+        if word.starts_with('-') {
+            // Skip the value of options that take one
+            if matches!(word.as_str(), "-c" | "--config" | "--log-file") {
+                words.next();
+            }
+            continue;
+        }
+        return config.profiles.contains_key(&word).then_some(word);
+    }
+
+    None
+}
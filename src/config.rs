@@ -1,3 +1,7 @@
+use crate::isolation::NetworkMode;
+use crate::repositories::parse_size;
+use clap::ValueEnum;
+use serde::de::{self, Deserializer};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::env;
@@ -18,7 +22,7 @@ fn default_executable() -> String {
 }
 
 /// Get the user's home directory (cross-platform)
-fn home_dir() -> Option<PathBuf> {
+pub(crate) fn home_dir() -> Option<PathBuf> {
     // Try HOME first (works on all platforms, required for WSL/Cygwin)
     if let Ok(home) = env::var("HOME") {
         return Some(PathBuf::from(home));
@@ -61,7 +65,11 @@ fn default_backup_retention() -> usize {
     5
 }
 
-#[derive(Deserialize, Serialize, Clone, Copy, Default, PartialEq, Eq)]
+fn default_summary_after_secs() -> u64 {
+    30
+}
+
+#[derive(Deserialize, Serialize, Clone, Copy, Default, PartialEq, Eq, ValueEnum)]
 #[serde(rename_all = "lowercase")]
 pub(crate) enum ColorMode {
     #[default]
@@ -70,7 +78,15 @@ pub(crate) enum ColorMode {
     Never,
 }
 
-#[derive(Deserialize, Serialize, Clone, Default)]
+#[derive(Deserialize, Serialize, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub(crate) enum Rendering {
+    #[default]
+    Default,
+    Software,
+}
+
+#[derive(Deserialize, Serialize, Clone)]
 pub(crate) struct GlobalConfig {
     /// Default profile to launch when no argument given
     #[serde(default)]
@@ -88,9 +104,45 @@ pub(crate) struct GlobalConfig {
     #[serde(default = "default_backup_retention")]
     pub backup_retention: usize,
 
+    /// Keep sync backups here, per profile, instead of in each config dir
+    #[serde(default)]
+    pub backup_dir: Option<PathBuf>,
+
     /// Default debug mode for all profiles
     #[serde(default)]
     pub debug: bool,
+
+    /// Print a wrap-up summary after long commands (such as sync) that take
+    /// at least this many seconds
+    #[serde(default = "default_summary_after_secs")]
+    pub summary_after_secs: u64,
+
+    /// Also show a desktop notification with the summary
+    #[serde(default)]
+    pub notify: bool,
+
+    /// Keep the plugins in `plugins/` in one store under the cache dir, keyed
+    /// by content, and link them into profiles
+    #[serde(default)]
+    pub plugin_store: bool,
+}
+
+// Used when the config has no [global] section, so it must agree with the
+// serde defaults above
+impl Default for GlobalConfig {
+    fn default() -> Self {
+        Self {
+            default_profile: None,
+            color: ColorMode::Auto,
+            check_updates: default_true(),
+            backup_retention: default_backup_retention(),
+            backup_dir: None,
+            debug: false,
+            summary_after_secs: default_summary_after_secs(),
+            notify: false,
+            plugin_store: false,
+        }
+    }
 }
 
 #[derive(Deserialize, Serialize, Clone, Default)]
@@ -98,9 +150,30 @@ pub(crate) struct Config {
     #[serde(default)]
     pub global: GlobalConfig,
     #[serde(default)]
+    pub installations: HashMap<String, Installation>,
+    #[serde(default)]
     pub profiles: HashMap<String, Profile>,
     #[serde(default)]
     pub sync: SyncConfig,
+    #[serde(default)]
+    pub diff: DiffConfig,
+}
+
+/// How sync puts directory items into the target
+#[derive(Deserialize, Serialize, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub(crate) enum SyncMode {
+    #[default]
+    Copy,
+    /// Replace directory items with links to the source's (junctions on Windows)
+    Symlink,
+}
+
+#[derive(Deserialize, Serialize, Default, Clone)]
+pub(crate) struct DiffConfig {
+    /// Settings keys `diff` leaves out, as globs such as `ui.window.*`
+    #[serde(default)]
+    pub ignore_keys: Vec<String>,
 }
 
 #[derive(Deserialize, Serialize, Default, Clone)]
@@ -108,37 +181,136 @@ pub(crate) struct SyncConfig {
     /// Additional exclusion patterns (merged with defaults)
     #[serde(default)]
     pub exclusions: Vec<String>,
+    /// Target profile -> exclusion patterns that only apply when syncing into it
+    #[serde(default)]
+    pub profile_exclusions: HashMap<String, Vec<String>>,
+    /// Only sync these items (default: all)
+    #[serde(default)]
+    pub only: Vec<String>,
+    /// copy, or symlink to share directory items with the source
+    #[serde(default)]
+    pub mode: SyncMode,
+    /// Named sync shapes, used with `sync --preset <name>`
+    #[serde(default)]
+    pub presets: HashMap<String, SyncPreset>,
+    /// Shell command run after each target is synced
+    #[serde(default)]
+    pub post_hook: Option<String>,
+    /// Files larger than this are left out of directory items
+    #[serde(default, deserialize_with = "deserialize_size")]
+    pub max_file_size: Option<u64>,
+}
+
+/// A size in bytes, or a string such as `"100MB"`
+fn deserialize_size<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Option<u64>, D::Error> {
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Size {
+        Bytes(u64),
+        Text(String),
+    }
+    match Option::<Size>::deserialize(deserializer)? {
+        None => Ok(None),
+        Some(Size::Bytes(bytes)) => Ok(Some(bytes)),
+        Some(Size::Text(text)) => parse_size(&text).map(Some).map_err(de::Error::custom),
+    }
+}
+
+/// A recurring sync: which items, extra exclusions, and where to
+#[derive(Deserialize, Serialize, Default, Clone)]
+pub(crate) struct SyncPreset {
+    /// Items to sync (default: `sync.only`, or all)
+    #[serde(default)]
+    pub items: Vec<String>,
+    /// Exclusions added to the shared ones
+    #[serde(default)]
+    pub exclusions: Vec<String>,
+    /// Target profiles when `--to` isn't given (default: all other profiles)
+    #[serde(default)]
+    pub targets: Vec<String>,
+}
+
+/// A Binary Ninja installation that profiles refer to by ID, so moving it
+/// only means updating one path
+#[derive(Deserialize, Serialize, Clone)]
+pub(crate) struct Installation {
+    pub path: PathBuf,
 }
 
 #[derive(Deserialize, Serialize, Clone)]
 pub(crate) struct Profile {
+    /// Filled in from `installation` when that is set
+    #[serde(default)]
     pub install_dir: PathBuf,
+    /// ID of an entry in `[installations]`, instead of an install_dir
+    #[serde(default)]
+    pub installation: Option<String>,
     pub config_dir: PathBuf,
     #[serde(default = "default_executable")]
     pub executable: String,
     #[serde(default)]
     pub debug: bool,
+    /// Icon for generated shortcuts (defaults to the executable's icon)
+    #[serde(default)]
+    pub icon: Option<PathBuf>,
+    /// Network access for Binary Ninja: default or blocked
+    #[serde(default)]
+    pub network: NetworkMode,
+    /// Python interpreter written to `python.interpreter` in settings.json
+    #[serde(default)]
+    pub python_interpreter: Option<PathBuf>,
+    /// Virtualenv written to `python.virtualenv` in settings.json
+    #[serde(default)]
+    pub python_virtualenv: Option<PathBuf>,
+    /// Working directory for Binary Ninja (defaults to install_dir)
+    #[serde(default)]
+    pub working_dir: Option<PathBuf>,
+    /// Graphics rendering: default, or software for VMs without working GPU drivers
+    #[serde(default)]
+    pub rendering: Rendering,
+    /// Windows account to launch as
+    #[serde(default)]
+    pub run_as: Option<String>,
+    /// Launch as administrator through a UAC elevation
+    #[serde(default)]
+    pub elevated: bool,
+    /// Profile to sync from before every launch
+    #[serde(default)]
+    pub auto_sync_from: Option<String>,
+    /// Sync preset for the launch-time sync
+    #[serde(default)]
+    pub auto_sync_preset: Option<String>,
 }
 
 impl Default for Profile {
     fn default() -> Self {
         Self {
             install_dir: PathBuf::new(),
+            installation: None,
             config_dir: PathBuf::new(),
             executable: default_executable(),
             debug: false,
+            icon: None,
+            network: NetworkMode::Default,
+            python_interpreter: None,
+            python_virtualenv: None,
+            working_dir: None,
+            run_as: None,
+            elevated: false,
+            rendering: Rendering::Default,
+            auto_sync_from: None,
+            auto_sync_preset: None,
         }
     }
 }
 
 /// Find config file in order of precidence
-pub(crate) fn find_config_file(custom_path: Option<&str>) -> Option<PathBuf> {
+pub(crate) fn find_config_file(custom_path: Option<&Path>) -> Option<PathBuf> {
     if let Some(path) = custom_path {
-        let p = PathBuf::from(path);
-        if p.exists() {
-            return Some(p);
+        if path.exists() {
+            return Some(path.to_path_buf());
         }
-        eprintln!("Error: Config file not found: {path}");
+        eprintln!("Error: Config file not found: {}", path.display());
         return None;
     }
 
@@ -154,5 +326,106 @@ pub(crate) fn find_config_file(custom_path: Option<&str>) -> Option<PathBuf> {
 pub(crate) fn load_config(path: &Path) -> Result<Config, String> {
     let content =
         fs::read_to_string(path).map_err(|e| format!("Failed to read config file: {e}"))?;
-    toml::from_str(&content).map_err(|e| format!("Failed to parse config file: {e}"))
+    let mut config: Config =
+        toml::from_str(&content).map_err(|e| format!("Failed to parse config file: {e}"))?;
+    resolve_installations(&mut config)?;
+    validate_auto_sync(&config)?;
+    validate_run_as(&config)?;
+    Ok(config)
+}
+
+/// Check `auto_sync_from` and `auto_sync_preset` name things that exist
+/// A profile runs either elevated or as another account, not both
+fn validate_run_as(config: &Config) -> Result<(), String> {
+    for (name, profile) in &config.profiles {
+        if profile.elevated
+            && let Some(user) = &profile.run_as
+        {
+            return Err(format!(
+                "Profile '{name}' sets both elevated and run_as = \"{user}\"; use one"
+            ));
+        }
+    }
+    Ok(())
+}
+
+fn validate_auto_sync(config: &Config) -> Result<(), String> {
+    for (name, profile) in &config.profiles {
+        if let Some(source) = &profile.auto_sync_from {
+            if source == name {
+                return Err(format!("Profile '{name}' can't auto-sync from itself"));
+            }
+            if !config.profiles.contains_key(source) {
+                return Err(format!(
+                    "Profile '{name}' auto-syncs from unknown profile '{source}'"
+                ));
+            }
+        }
+        if let Some(preset) = &profile.auto_sync_preset {
+            if profile.auto_sync_from.is_none() {
+                return Err(format!(
+                    "Profile '{name}' sets auto_sync_preset without auto_sync_from"
+                ));
+            }
+            if !config.sync.presets.contains_key(preset) {
+                return Err(format!(
+                    "Profile '{name}' uses unknown sync preset '{preset}'"
+                ));
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Point profiles that name an installation at its path
+fn resolve_installations(config: &mut Config) -> Result<(), String> {
+    for (name, profile) in &mut config.profiles {
+        match &profile.installation {
+            Some(id) => {
+                if !profile.install_dir.as_os_str().is_empty() {
+                    return Err(format!(
+                        "Profile '{name}' sets both install_dir and installation"
+                    ));
+                }
+                let installation = config
+                    .installations
+                    .get(id)
+                    .ok_or_else(|| format!("Profile '{name}' uses unknown installation '{id}'"))?;
+                profile.install_dir = installation.path.clone();
+            }
+            None if profile.install_dir.as_os_str().is_empty() => {
+                return Err(format!(
+                    "Profile '{name}' needs an install_dir or installation"
+                ));
+            }
+            None => {}
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(toml: &str) -> Config {
+        toml::from_str(toml).expect("a valid config")
+    }
+
+    #[test]
+    fn elevated_and_run_as_are_rejected_together() {
+        let config = parse(
+            "[profiles.work]\ninstall_dir = '/bn'\nconfig_dir = '/cfg'\nelevated = true\nrun_as = 'admin'\n",
+        );
+        let error = validate_run_as(&config).unwrap_err();
+        assert!(error.contains("'work'"), "{error}");
+    }
+
+    #[test]
+    fn run_as_alone_is_an_account() {
+        let config =
+            parse("[profiles.work]\ninstall_dir = '/bn'\nconfig_dir = '/cfg'\nrun_as = 'admin'\n");
+        assert!(validate_run_as(&config).is_ok());
+        assert!(!config.profiles["work"].elevated);
+    }
 }
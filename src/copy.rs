@@ -0,0 +1,211 @@
+use crate::progress::Progress;
+use crate::sync::remove_path;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Copy a file, directory tree or link, replacing whatever is at the
+/// destination. Links inside a tree are recreated as links, and files keep
+/// their permissions and modification times. A link at `src` itself is
+/// recreated pointing at the same place.
+pub(crate) fn copy_item(src: &Path, dst: &Path) -> Result<(), String> {
+    let metadata =
+        fs::symlink_metadata(src).map_err(|e| format!("Failed to read {}: {e}", src.display()))?;
+    if let Some(parent) = dst.parent() {
+        fs::create_dir_all(parent).map_err(|e| format!("Failed to create directory: {e}"))?;
+    }
+
+    if metadata.file_type().is_symlink() {
+        if fs::symlink_metadata(dst).is_ok() {
+            remove_path(dst)?;
+        }
+        // Relative to where the link is now, not where the copy goes
+        let target = read_link(src)?;
+        let target = match src.parent() {
+            Some(parent) if target.is_relative() => parent.join(target),
+            _ => target,
+        };
+        return copy_link(src, &target, dst);
+    }
+
+    if metadata.is_dir() {
+        let label = src
+            .file_name()
+            .map(|n| n.to_string_lossy().into_owned())
+            .unwrap_or_default();
+        let mut progress = Progress::for_tree(&label, src, &label, &|_, _| false);
+        return copy_dir_recursive(src, dst, &mut progress);
+    }
+
+    copy_file(src, dst, &metadata).map(|_| ())
+}
+
+/// Copy a file's contents and permissions, then its modification time.
+/// Returns the bytes copied.
+pub(crate) fn copy_file(src: &Path, dst: &Path, src_meta: &fs::Metadata) -> Result<u64, String> {
+    let bytes = fs::copy(src, dst).map_err(|e| format!("Failed to copy {}: {e}", src.display()))?;
+    set_modified(dst, src_meta);
+    Ok(bytes)
+}
+
+pub(crate) fn read_link(path: &Path) -> Result<PathBuf, String> {
+    fs::read_link(path).map_err(|e| format!("Failed to read link {}: {e}", path.display()))
+}
+
+fn copy_dir_recursive(src: &Path, dst: &Path, progress: &mut Progress) -> Result<(), String> {
+    if fs::symlink_metadata(dst).is_ok() {
+        remove_path(dst)?;
+    }
+
+    fs::create_dir_all(dst)
+        .map_err(|e| format!("Failed to create directory {}: {}", dst.display(), e))?;
+
+    for entry in fs::read_dir(src)
+        .map_err(|e| format!("Failed to read directory {}: {}", src.display(), e))?
+    {
+        let entry = entry.map_err(|e| format!("Failed to read entry: {e}"))?;
+        let src_path = entry.path();
+        let dst_path = dst.join(entry.file_name());
+        let metadata = fs::symlink_metadata(&src_path)
+            .map_err(|e| format!("Failed to read {}: {e}", src_path.display()))?;
+
+        if metadata.file_type().is_symlink() {
+            copy_link(&src_path, &read_link(&src_path)?, &dst_path)?;
+            progress.file_done(0);
+        } else if metadata.is_dir() {
+            copy_dir_recursive(&src_path, &dst_path, progress)?;
+        } else {
+            let bytes = copy_file(&src_path, &dst_path, &metadata)?;
+            progress.file_done(bytes);
+        }
+    }
+
+    // Last, since a read-only directory can't be filled
+    if let Ok(metadata) = fs::metadata(src) {
+        let _ = fs::set_permissions(dst, metadata.permissions());
+    }
+    Ok(())
+}
+
+/// Give the target the source's modification time, so unchanged files can be
+/// recognized without hashing. Best effort: failing only costs a hash later.
+pub(crate) fn set_modified(dst: &Path, src_meta: &fs::Metadata) {
+    let Ok(modified) = src_meta.modified() else {
+        return;
+    };
+    let mut options = fs::File::options();
+    // Enough to change timestamps, even on a read-only copy
+    #[cfg(windows)]
+    {
+        use std::os::windows::fs::OpenOptionsExt;
+        const FILE_WRITE_ATTRIBUTES: u32 = 0x100;
+        options.access_mode(FILE_WRITE_ATTRIBUTES);
+    }
+    #[cfg(not(windows))]
+    options.read(true);
+
+    if let Ok(file) = options.open(dst) {
+        let _ = file.set_modified(modified);
+    }
+}
+
+/// Recreate the link at `src` at `dst`, pointing at `target`
+#[cfg(unix)]
+pub(crate) fn copy_link(_src: &Path, target: &Path, dst: &Path) -> Result<(), String> {
+    std::os::unix::fs::symlink(target, dst).map_err(|e| {
+        format!(
+            "Failed to link {} -> {}: {e}",
+            dst.display(),
+            target.display()
+        )
+    })
+}
+
+/// Symlinks need Developer Mode or administrator rights on Windows. Without
+/// them a directory link becomes a junction, and as a last resort the link's
+/// contents are copied.
+#[cfg(windows)]
+pub(crate) fn copy_link(src: &Path, target: &Path, dst: &Path) -> Result<(), String> {
+    use std::os::windows::fs::{symlink_dir, symlink_file};
+
+    let is_dir = fs::metadata(src).is_ok_and(|m| m.is_dir());
+    let linked = if is_dir {
+        symlink_dir(target, dst)
+    } else {
+        symlink_file(target, dst)
+    };
+    if linked.is_ok() {
+        return Ok(());
+    }
+
+    let resolved =
+        fs::canonicalize(src).map_err(|e| format!("Failed to resolve {}: {e}", src.display()))?;
+    if is_dir && crate::sync::create_dir_link(&resolved, dst).is_ok() {
+        return Ok(());
+    }
+
+    crate::report::warn(&format!(
+        "Copied {} instead of linking it (symlinks need Developer Mode or administrator rights)",
+        src.display()
+    ));
+    if is_dir {
+        copy_dir_recursive(&resolved, dst, &mut Progress::hidden())
+    } else {
+        let metadata = fs::metadata(&resolved)
+            .map_err(|e| format!("Failed to read {}: {e}", resolved.display()))?;
+        copy_file(&resolved, dst, &metadata).map(|_| ())
+    }
+}
+
+#[cfg(not(any(unix, windows)))]
+pub(crate) fn copy_link(src: &Path, _target: &Path, _dst: &Path) -> Result<(), String> {
+    Err(format!(
+        "Can't copy link {}: links aren't supported on this platform",
+        src.display()
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::strategies::{file_name, relative_path};
+    use proptest::prelude::*;
+    use std::collections::BTreeSet;
+
+    proptest! {
+        #![proptest_config(ProptestConfig::with_cases(32))]
+
+        #[test]
+        fn copy_item_copies_trees_with_odd_names(
+            dir in file_name(),
+            files in prop::collection::btree_set(file_name(), 1..5),
+            dst in relative_path(),
+        ) {
+            let root = tempfile::tempdir().unwrap();
+            let src = root.path().join("src").join(&dir);
+            fs::create_dir_all(&src).unwrap();
+            for file in &files {
+                fs::write(src.join(file), file).unwrap();
+            }
+            // File names from the strategy never have a `.`
+            let first = Path::new(files.first().unwrap());
+            #[cfg(unix)]
+            std::os::unix::fs::symlink(first, src.join("first.link")).unwrap();
+
+            let dst = root.path().join("dst").join(dst);
+            copy_item(&src, &dst).unwrap();
+
+            let copied: BTreeSet<_> = fs::read_dir(&dst)
+                .unwrap()
+                .map(|entry| entry.unwrap().file_name().into_string().unwrap())
+                .filter(|name| name != "first.link")
+                .collect();
+            prop_assert_eq!(&copied, &files);
+            for file in &files {
+                prop_assert_eq!(fs::read_to_string(dst.join(file)).unwrap(), file.as_str());
+            }
+            if cfg!(unix) {
+                prop_assert_eq!(read_link(&dst.join("first.link")).unwrap(), first);
+            }
+        }
+    }
+}
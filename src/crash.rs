@@ -0,0 +1,106 @@
+use crate::config::{Profile, cache_dir};
+use crate::history::LaunchRecord;
+use crate::plugins::list_plugins;
+use crate::report;
+use std::fmt::Write as _;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+const CRASHES_DIR: &str = "crashes";
+const LOG_TAIL_FILE: &str = "debug-log-tail.txt";
+const INFO_FILE: &str = "crash-info.txt";
+const PLUGINS_FILE: &str = "plugins.txt";
+const LOG_TAIL_LINES: usize = 200;
+
+/// Collect everything useful for a bug report about an abnormal exit into
+/// a fresh directory under the cache dir, returning its path
+pub(crate) fn write_crash_bundle(
+    name: &str,
+    profile: &Profile,
+    record: &LaunchRecord,
+    log_path: Option<&Path>,
+) -> Result<PathBuf, String> {
+    let bundle_dir = cache_dir()
+        .ok_or("Could not determine cache directory")?
+        .join(CRASHES_DIR)
+        .join(format!("{name}-{}", record.timestamp));
+
+    fs::create_dir_all(&bundle_dir)
+        .map_err(|e| format!("Failed to create crash bundle directory: {e}"))?;
+
+    write_file(
+        &bundle_dir.join(INFO_FILE),
+        &crash_info(name, profile, record),
+    )?;
+
+    if let Some(log_path) = log_path {
+        match fs::read(log_path) {
+            Ok(log) => write_file(
+                &bundle_dir.join(LOG_TAIL_FILE),
+                &tail(&String::from_utf8_lossy(&log), LOG_TAIL_LINES),
+            )?,
+            Err(e) => report::warn(&format!(
+                "Could not read debug log {}: {e}",
+                log_path.display()
+            )),
+        }
+    }
+
+    let plugins = match list_plugins(profile) {
+        Ok(plugins) => plugins
+            .iter()
+            .map(|p| {
+                format!(
+                    "{} v{} [{}] ({})\n",
+                    p.name.as_deref().unwrap_or(&p.dir_name),
+                    p.version.as_deref().unwrap_or("?"),
+                    p.source.label(),
+                    p.dir_name
+                )
+            })
+            .collect(),
+        Err(e) => format!("Failed to list plugins: {e}\n"),
+    };
+    write_file(&bundle_dir.join(PLUGINS_FILE), &plugins)?;
+    report::changed(&bundle_dir);
+
+    Ok(bundle_dir)
+}
+
+fn crash_info(name: &str, profile: &Profile, record: &LaunchRecord) -> String {
+    let mut info = String::new();
+    let _ = writeln!(info, "profile:       {name}");
+    let _ = writeln!(info, "install_dir:   {}", profile.install_dir.display());
+    let _ = writeln!(info, "config_dir:    {}", profile.config_dir.display());
+    let _ = writeln!(info, "executable:    {}", profile.executable);
+    let _ = writeln!(info, "launched_at:   {} (unix time)", record.timestamp);
+    if let Some(secs) = record.duration_secs {
+        let _ = writeln!(info, "ran_for:       {secs}s");
+    }
+    if let Some(code) = record.exit_code {
+        let _ = writeln!(info, "exit_code:     {code}");
+    }
+    if let Some(signal) = record.signal {
+        let _ = writeln!(info, "signal:        {signal}");
+    }
+    let _ = writeln!(
+        info,
+        "os:            {} {}",
+        std::env::consts::OS,
+        std::env::consts::ARCH
+    );
+    let _ = writeln!(info, "bn-loader:     {}", env!("CARGO_PKG_VERSION"));
+    info
+}
+
+fn tail(text: &str, lines: usize) -> String {
+    let all: Vec<&str> = text.lines().collect();
+    let start = all.len().saturating_sub(lines);
+    let mut out = all[start..].join("\n");
+    out.push('\n');
+    out
+}
+
+fn write_file(path: &Path, content: &str) -> Result<(), String> {
+    fs::write(path, content).map_err(|e| format!("Failed to write {}: {e}", path.display()))
+}
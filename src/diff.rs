@@ -1,43 +1,400 @@
+use crate::backups::BackupStore;
+use crate::bn_version::{BnVersion, detect_version};
 use crate::colors::{stdout, write_bold, writeln_bold, writeln_colored};
-use crate::config::Profile;
+use crate::config::{Config, Profile, default_exclusions};
+use crate::diff_report::DiffReport;
+use crate::history::format_age;
+use crate::items::{DiffSide, HANDLERS, STARTUP_FILE};
+use crate::manifest::hash_files;
 use crate::plugins::{PluginInfo, list_plugins};
+use crate::pyenv::python_packages;
+use crate::repositories::format_size;
+use crate::settings::{KEYBINDINGS_FILE, SETTINGS_FILE, read_settings};
+use crate::snippets::{SNIPPETS_DIR, list_snippets};
+use crate::sync::build_glob_set;
+use globset::GlobSet;
 use serde_json::Value;
-use std::collections::HashSet;
+use serde_json::value::RawValue;
+use similar::TextDiff;
+use std::collections::{BTreeMap, BTreeSet, HashSet};
 use std::fs;
 use std::io::Write;
-use termcolor::Color;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+use termcolor::{Color, StandardStream, WriteColor};
 
-const SETTINGS_FILE: &str = "settings.json";
 const MAX_DIFF_DISPLAY: usize = 20;
 const MAX_VALUE_DISPLAY_LEN: usize = 30;
 const VALUE_PREVIEW_LEN: usize = 27;
+/// Lines of a unified diff shown per file
+const MAX_TEXT_DIFF_LINES: usize = 100;
+pub(crate) const THEMES_DIR: &str = "themes";
+const THEME_EXTENSION: &str = "bntheme";
+/// Setting naming the theme Binary Ninja uses
+const THEME_SETTING: &str = "ui.theme";
+/// Exit codes of `diff`, as with diff(1): the profiles differ, or they
+/// couldn't be compared
+pub(crate) const DIFFERENCES_EXIT_CODE: i32 = 1;
+pub(crate) const DIFF_ERROR_EXIT_CODE: i32 = 2;
 
+/// Resolve a diff argument: a profile name, or `profile@timestamp` for one of
+/// the profile's sync backups. A backup is treated as a profile whose config
+/// dir is the backup directory.
+pub(crate) fn resolve_diff_side(config: &Config, spec: &str) -> Result<Profile, String> {
+    let (name, timestamp) = match spec.rsplit_once('@') {
+        Some((name, timestamp)) => (name, Some(timestamp)),
+        None => (spec, None),
+    };
+    let profile = config
+        .profiles
+        .get(name)
+        .ok_or_else(|| format!("Profile '{name}' not found."))?;
+
+    let Some(timestamp) = timestamp else {
+        return Ok(profile.clone());
+    };
+
+    let backups = BackupStore::new(
+        config.global.backup_dir.as_deref(),
+        name,
+        &profile.config_dir,
+    )
+    .list()?;
+    let backup = timestamp
+        .parse::<u64>()
+        .ok()
+        .and_then(|ts| backups.iter().find(|(_, backup_ts)| *backup_ts == ts));
+
+    let Some((backup_dir, _)) = backup else {
+        if backups.is_empty() {
+            return Err(format!("Profile '{name}' has no sync backups."));
+        }
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or(0);
+        let available: Vec<String> = backups
+            .iter()
+            .map(|(_, ts)| format!("  {name}@{ts} ({})", format_age(now.saturating_sub(*ts))))
+            .collect();
+        return Err(format!(
+            "No backup '{spec}'. Available backups:\n{}",
+            available.join("\n")
+        ));
+    };
+
+    Ok(Profile {
+        config_dir: backup_dir.clone(),
+        ..profile.clone()
+    })
+}
+
+/// `diff` section for the Python packages, which aren't a sync item
+const PYTHON_SECTION: &str = "python";
+
+#[derive(Default)]
+pub(crate) struct DiffOptions<'a> {
+    /// Also compare the Python packages each profile's environment sees
+    pub python: bool,
+    /// Only compare these sections (`settings`, `plugins`, ...)
+    pub only: Vec<&'a str>,
+    /// Leave these sections out
+    pub skip: Vec<&'a str>,
+    /// Show every difference and complete values instead of truncating them
+    pub full: bool,
+    /// Settings keys left out of the settings comparison (`diff.ignore_keys`)
+    pub ignore_keys: GlobSet,
+    /// Write the comparison to this HTML or Markdown file instead
+    pub output: Option<&'a Path>,
+    /// Print the comparison as JSON instead (`--json`)
+    pub json: bool,
+}
+
+impl DiffOptions<'_> {
+    /// Whether the section for an item (or `python`) is compared. Sections
+    /// are named after the item, with or without its extension.
+    fn includes(&self, item: &str) -> bool {
+        let named = |wanted: &&str| *wanted == item || *wanted == section_name(item);
+        (self.only.is_empty() || self.only.iter().any(named)) && !self.skip.iter().any(named)
+    }
+}
+
+/// `settings` for settings.json
+fn section_name(item: &str) -> &str {
+    item.split_once('.').map_or(item, |(stem, _)| stem)
+}
+
+/// Names `diff --only` and `--skip` accept
+pub(crate) fn diff_sections() -> Vec<&'static str> {
+    HANDLERS
+        .iter()
+        .filter(|handler| handler.diff_title().is_some())
+        .map(|handler| section_name(handler.name()))
+        .chain([PYTHON_SECTION])
+        .collect()
+}
+
+fn validate_sections(options: &DiffOptions) -> Result<(), String> {
+    let known = diff_sections();
+    let items: Vec<&str> = HANDLERS.iter().map(|handler| handler.name()).collect();
+    match options
+        .only
+        .iter()
+        .chain(&options.skip)
+        .find(|name| !known.contains(name) && !items.contains(name))
+    {
+        Some(unknown) => Err(format!(
+            "Unknown diff section '{unknown}' (expected one of: {})",
+            known.join(", ")
+        )),
+        None => Ok(()),
+    }
+}
+
+/// Where the sections of a diff go
+enum DiffOutput<'a> {
+    Terminal { out: StandardStream, first: bool },
+    Report(DiffReport<'a>),
+}
+
+impl DiffOutput<'_> {
+    fn note(&mut self, note: String) -> Result<(), String> {
+        match self {
+            DiffOutput::Terminal { out, .. } => {
+                writeln!(out, "Note: {note}\n").map_err(|e| e.to_string())
+            }
+            DiffOutput::Report(report) => {
+                report.note(note);
+                Ok(())
+            }
+        }
+    }
+
+    /// Start a section, returning where its contents go
+    fn section(&mut self, title: &str) -> Result<&mut dyn WriteColor, String> {
+        match self {
+            DiffOutput::Terminal { out, first } => {
+                if !*first {
+                    writeln!(out).map_err(|e| e.to_string())?;
+                }
+                *first = false;
+                writeln_bold(out, &format!("=== {title} ===")).map_err(|e| e.to_string())?;
+                Ok(out)
+            }
+            DiffOutput::Report(report) => Ok(report.section(title)),
+        }
+    }
+
+    /// Record how many differences the last section found
+    fn count(&mut self, differences: usize) {
+        if let DiffOutput::Report(report) = self {
+            report.count(differences);
+        }
+    }
+}
+
+/// Print every section's differences, or write them to `options.output`.
+/// Returns how many there are.
 pub(crate) fn diff_profiles(
     name1: &str,
     profile1: &Profile,
     name2: &str,
     profile2: &Profile,
-) -> Result<(), String> {
-    let mut out = stdout();
+    options: &DiffOptions,
+) -> Result<usize, String> {
+    validate_sections(options)?;
+    let versions = detect_versions(profile1, profile2);
+    let mut output = match options.output {
+        Some(path) => {
+            let mut report = DiffReport::new(path, name1, name2)?;
+            if let Some((text, _)) = describe_versions(name1, name2, &versions) {
+                report.note(text);
+            }
+            DiffOutput::Report(report)
+        }
+        None if options.json => DiffOutput::Report(DiffReport::json(name1, name2, &versions)),
+        None => {
+            let mut out = stdout();
+            write_bold(&mut out, "Comparing profiles: ")
+                .and_then(|()| writeln!(out, "'{name1}' vs '{name2}'"))
+                .map_err(|e| e.to_string())?;
+            match describe_versions(name1, name2, &versions) {
+                Some((versions, true)) => writeln_colored(&mut out, &versions, Color::Yellow),
+                Some((versions, false)) => writeln!(out, "{versions}"),
+                None => Ok(()),
+            }
+            .and_then(|()| writeln!(out))
+            .map_err(|e| e.to_string())?;
+            DiffOutput::Terminal { out, first: true }
+        }
+    };
 
-    write_bold(&mut out, "Comparing profiles: ")
-        .and_then(|()| writeln!(out, "'{name1}' vs '{name2}'\n"))
-        .map_err(|e| e.to_string())?;
+    // Backups only hold the items a sync was about to overwrite
+    for (name, profile) in [(name1, profile1), (name2, profile2)] {
+        if name.contains('@') {
+            let items: Vec<&str> = HANDLERS
+                .iter()
+                .filter(|h| h.identify(&profile.config_dir))
+                .map(|h| h.name())
+                .collect();
+            output.note(format!("backup '{name}' contains: {}", items.join(", ")))?;
+        }
+    }
 
-    diff_plugins(&mut out, name1, profile1, name2, profile2)?;
-    writeln!(out).map_err(|e| e.to_string())?;
-    diff_settings(&mut out, name1, profile1, name2, profile2)?;
+    let left = DiffSide {
+        name: name1,
+        profile: profile1,
+    };
+    let right = DiffSide {
+        name: name2,
+        profile: profile2,
+    };
 
-    Ok(())
+    let mut differences = 0;
+    for handler in HANDLERS {
+        let Some(title) = handler.diff_title() else {
+            continue;
+        };
+        if !options.includes(handler.name()) {
+            continue;
+        }
+        let count = handler.diff(output.section(title)?, &left, &right, options)?;
+        output.count(count);
+        differences += count;
+    }
+
+    // Naming the section is enough to compare it
+    let python = options.python || options.only.contains(&PYTHON_SECTION);
+    if python && options.includes(PYTHON_SECTION) {
+        let out = output.section("Python Packages")?;
+        let count = diff_python(out, name1, profile1, name2, profile2, options)?;
+        output.count(count);
+        differences += count;
+    }
+
+    if let DiffOutput::Report(report) = output {
+        report.write(differences)?;
+    }
+    Ok(differences)
 }
 
-fn diff_plugins(
-    out: &mut termcolor::StandardStream,
+/// The Binary Ninja version of each profile's install
+fn detect_versions(profile1: &Profile, profile2: &Profile) -> [Option<BnVersion>; 2] {
+    let version1 = detect_version(&profile1.install_dir);
+    let version2 = if profile2.install_dir == profile1.install_dir {
+        version1.clone()
+    } else {
+        detect_version(&profile2.install_dir)
+    };
+    [version1, version2]
+}
+
+/// The Binary Ninja version of each profile, and whether they differ, since
+/// a different build can explain differing behavior. `None` if neither is
+/// known.
+fn describe_versions(
+    name1: &str,
+    name2: &str,
+    [version1, version2]: &[Option<BnVersion>; 2],
+) -> Option<(String, bool)> {
+    if version1.is_none() && version2.is_none() {
+        return None;
+    }
+    let describe = |version: &Option<BnVersion>| {
+        version
+            .as_ref()
+            .map_or_else(|| "unknown".to_string(), ToString::to_string)
+    };
+    let build = |version: &Option<BnVersion>| version.as_ref().map(|v| v.build);
+    if build(version1) == build(version2) {
+        Some((
+            format!("Binary Ninja: {} in both", describe(version1)),
+            false,
+        ))
+    } else {
+        Some((
+            format!(
+                "Binary Ninja: {} in '{name1}', {} in '{name2}'",
+                describe(version1),
+                describe(version2)
+            ),
+            true,
+        ))
+    }
+}
+
+/// "Works in one profile but not the other" is often a dependency skew
+/// between the Python environments rather than a Binary Ninja setting
+fn diff_python(
+    out: &mut dyn WriteColor,
     name1: &str,
     profile1: &Profile,
     name2: &str,
     profile2: &Profile,
-) -> Result<(), String> {
+    options: &DiffOptions,
+) -> Result<usize, String> {
+    let python1 = python_packages(profile1);
+    let python2 = python_packages(profile2);
+
+    for (name, python) in [(name1, &python1), (name2, &python2)] {
+        if python.site_dirs.is_empty() {
+            writeln!(out, "  {name}: no Python environment found").map_err(|e| e.to_string())?;
+        } else {
+            let dirs: Vec<String> = python
+                .site_dirs
+                .iter()
+                .map(|d| d.display().to_string())
+                .collect();
+            writeln!(
+                out,
+                "  {name}: {} packages in {}",
+                python.packages.len(),
+                dirs.join(", ")
+            )
+            .map_err(|e| e.to_string())?;
+        }
+    }
+
+    let mut diffs = Vec::new();
+    for (key, (package, version)) in &python1.packages {
+        match python2.packages.get(key) {
+            None => diffs.push(DiffEntry {
+                kind: DiffKind::Removed,
+                text: format!("- {package} {version} (only in '{name1}')"),
+            }),
+            Some((_, other)) if other != version => diffs.push(DiffEntry {
+                kind: DiffKind::Changed,
+                text: format!("~ {package} : {version} -> {other}"),
+            }),
+            Some(_) => {}
+        }
+    }
+    for (key, (package, version)) in &python2.packages {
+        if !python1.packages.contains_key(key) {
+            diffs.push(DiffEntry {
+                kind: DiffKind::Added,
+                text: format!("+ {package} {version} (only in '{name2}')"),
+            });
+        }
+    }
+
+    if diffs.is_empty() {
+        writeln!(out, "  (no differences)").map_err(|e| e.to_string())?;
+        return Ok(0);
+    }
+
+    writeln!(out, "\n  {} differences found:\n", diffs.len()).map_err(|e| e.to_string())?;
+    write_entries(out, &diffs, options.full)?;
+    Ok(diffs.len())
+}
+
+pub(crate) fn diff_plugins(
+    out: &mut dyn WriteColor,
+    name1: &str,
+    profile1: &Profile,
+    name2: &str,
+    profile2: &Profile,
+) -> Result<usize, String> {
     let plugins1 = list_plugins(profile1)?;
     let plugins2 = list_plugins(profile2)?;
 
@@ -64,7 +421,6 @@ fn diff_plugins(
         })
         .collect();
 
-    writeln_bold(out, "=== Plugins ===").map_err(|e| e.to_string())?;
     writeln!(
         out,
         "  {} has {} plugins, {} has {} plugins",
@@ -110,90 +466,326 @@ fn diff_plugins(
         }
     }
 
-    if only_in_1.is_empty() && only_in_2.is_empty() && version_diffs.is_empty() {
+    // Installed in both, but switched off in one
+    let state_diffs: Vec<_> = in_both
+        .iter()
+        .filter(|(p1, p2)| p1.is_disabled() != p2.is_disabled())
+        .collect();
+
+    if !state_diffs.is_empty() {
+        writeln!(out, "\n  Disabled in one profile:").map_err(|e| e.to_string())?;
+        for (p1, _) in &state_diffs {
+            let name = p1.name.as_deref().unwrap_or(&p1.dir_name);
+            let (disabled_in, enabled_in) = if p1.is_disabled() {
+                (name1, name2)
+            } else {
+                (name2, name1)
+            };
+            writeln_colored(
+                out,
+                &format!("    ~ {name} : disabled in '{disabled_in}', enabled in '{enabled_in}'"),
+                Color::Yellow,
+            )
+            .map_err(|e| e.to_string())?;
+        }
+    }
+
+    if only_in_1.is_empty()
+        && only_in_2.is_empty()
+        && version_diffs.is_empty()
+        && state_diffs.is_empty()
+    {
         writeln!(out, "  (no differences)").map_err(|e| e.to_string())?;
     }
 
-    Ok(())
+    Ok(only_in_1.len() + only_in_2.len() + version_diffs.len() + state_diffs.len())
 }
 
-enum DiffKind {
+pub(crate) enum DiffKind {
     Added,   // + green
     Removed, // - red
     Changed, // ~ yellow
 }
 
-struct DiffEntry {
-    kind: DiffKind,
-    text: String,
+pub(crate) struct DiffEntry {
+    pub kind: DiffKind,
+    pub text: String,
+}
+
+/// Print entries as colored +/-/~ lines, up to [`MAX_DIFF_DISPLAY`] of them
+/// unless `full`
+pub(crate) fn write_entries(
+    out: &mut dyn WriteColor,
+    diffs: &[DiffEntry],
+    full: bool,
+) -> Result<(), String> {
+    let shown = if full { diffs.len() } else { MAX_DIFF_DISPLAY };
+    for diff in diffs.iter().take(shown) {
+        let color = match diff.kind {
+            DiffKind::Added => Color::Green,
+            DiffKind::Removed => Color::Red,
+            DiffKind::Changed => Color::Yellow,
+        };
+        writeln_colored(out, &format!("  {}", diff.text), color).map_err(|e| e.to_string())?;
+    }
+    if diffs.len() > shown {
+        writeln!(
+            out,
+            "  ... and {} more (--full shows all)",
+            diffs.len() - shown
+        )
+        .map_err(|e| e.to_string())?;
+    }
+    Ok(())
 }
 
-fn diff_settings(
-    out: &mut termcolor::StandardStream,
+pub(crate) fn diff_settings(
+    out: &mut dyn WriteColor,
     name1: &str,
     profile1: &Profile,
     name2: &str,
     profile2: &Profile,
-) -> Result<(), String> {
+    options: &DiffOptions,
+) -> Result<usize, String> {
     let settings1_path = profile1.config_dir.join(SETTINGS_FILE);
     let settings2_path = profile2.config_dir.join(SETTINGS_FILE);
 
-    let settings1: Option<Value> = if settings1_path.exists() {
-        fs::read_to_string(&settings1_path)
-            .ok()
-            .and_then(|s| serde_json::from_str(&s).ok())
-    } else {
-        None
-    };
-
-    let settings2: Option<Value> = if settings2_path.exists() {
-        fs::read_to_string(&settings2_path)
-            .ok()
-            .and_then(|s| serde_json::from_str(&s).ok())
-    } else {
-        None
-    };
-
-    writeln_bold(out, "=== Settings ===").map_err(|e| e.to_string())?;
+    let text1 = fs::read_to_string(&settings1_path).ok();
+    let text2 = fs::read_to_string(&settings2_path).ok();
+    let settings1 = text1.as_deref().and_then(parse_raw_object);
+    let settings2 = text2.as_deref().and_then(parse_raw_object);
 
     match (&settings1, &settings2) {
         (None, None) => {
             writeln!(out, "  Neither profile has {SETTINGS_FILE}").map_err(|e| e.to_string())?;
+            Ok(0)
         }
         (Some(_), None) => {
             writeln!(out, "  Only '{name1}' has {SETTINGS_FILE}").map_err(|e| e.to_string())?;
+            Ok(1)
         }
         (None, Some(_)) => {
             writeln!(out, "  Only '{name2}' has {SETTINGS_FILE}").map_err(|e| e.to_string())?;
+            Ok(1)
         }
         (Some(v1), Some(v2)) => {
-            let diffs = diff_json_objects(v1, v2, "");
+            let diffs = diff_raw_objects(v1, v2, options);
             if diffs.is_empty() {
                 writeln!(out, "  (no differences)").map_err(|e| e.to_string())?;
             } else {
                 writeln!(out, "  {} differences found:\n", diffs.len())
                     .map_err(|e| e.to_string())?;
-                for diff in diffs.iter().take(MAX_DIFF_DISPLAY) {
-                    let color = match diff.kind {
-                        DiffKind::Added => Color::Green,
-                        DiffKind::Removed => Color::Red,
-                        DiffKind::Changed => Color::Yellow,
-                    };
-                    writeln_colored(out, &format!("  {}", diff.text), color)
-                        .map_err(|e| e.to_string())?;
+                write_entries(out, &diffs, options.full)?;
+            }
+            Ok(diffs.len())
+        }
+    }
+}
+
+/// Action -> the shortcuts bound to it. An empty list unbinds a default.
+type Keybindings = BTreeMap<String, Vec<String>>;
+
+fn read_keybindings(profile: &Profile) -> Option<Keybindings> {
+    let text = fs::read_to_string(profile.config_dir.join(KEYBINDINGS_FILE)).ok()?;
+    let Value::Object(object) = serde_json::from_str(&text).ok()? else {
+        return None;
+    };
+    Some(
+        object
+            .into_iter()
+            .map(|(action, keys)| {
+                let keys = match keys {
+                    Value::Array(keys) => keys
+                        .iter()
+                        .filter_map(Value::as_str)
+                        .map(str::to_string)
+                        .collect(),
+                    Value::String(key) => vec![key],
+                    _ => Vec::new(),
+                };
+                (action, keys)
+            })
+            .collect(),
+    )
+}
+
+fn format_keys(keys: &[String]) -> String {
+    if keys.is_empty() {
+        "(unbound)".to_string()
+    } else {
+        keys.join(", ")
+    }
+}
+
+pub(crate) fn diff_keybindings(
+    out: &mut dyn WriteColor,
+    name1: &str,
+    profile1: &Profile,
+    name2: &str,
+    profile2: &Profile,
+    options: &DiffOptions,
+) -> Result<usize, String> {
+    let bindings1 = read_keybindings(profile1);
+    let bindings2 = read_keybindings(profile2);
+
+    let mut diffs = Vec::new();
+    // A file only one profile has counts as one difference
+    let mut missing = 0;
+    match (&bindings1, &bindings2) {
+        (None, None) => {
+            writeln!(out, "  Neither profile has {KEYBINDINGS_FILE}").map_err(|e| e.to_string())?;
+        }
+        (Some(_), None) => {
+            writeln!(out, "  Only '{name1}' has {KEYBINDINGS_FILE}").map_err(|e| e.to_string())?;
+            missing = 1;
+        }
+        (None, Some(_)) => {
+            writeln!(out, "  Only '{name2}' has {KEYBINDINGS_FILE}").map_err(|e| e.to_string())?;
+            missing = 1;
+        }
+        (Some(b1), Some(b2)) => {
+            for (action, keys1) in b1 {
+                match b2.get(action) {
+                    None => diffs.push(DiffEntry {
+                        kind: DiffKind::Removed,
+                        text: format!("- {action} [{}] (only in '{name1}')", format_keys(keys1)),
+                    }),
+                    Some(keys2) => {
+                        // Order doesn't matter to Binary Ninja
+                        let set1: BTreeSet<&String> = keys1.iter().collect();
+                        let set2: BTreeSet<&String> = keys2.iter().collect();
+                        if set1 != set2 {
+                            diffs.push(DiffEntry {
+                                kind: DiffKind::Changed,
+                                text: format!(
+                                    "~ {action} : {} -> {}",
+                                    format_keys(keys1),
+                                    format_keys(keys2)
+                                ),
+                            });
+                        }
+                    }
                 }
-                if diffs.len() > MAX_DIFF_DISPLAY {
-                    writeln!(out, "  ... and {} more", diffs.len() - MAX_DIFF_DISPLAY)
-                        .map_err(|e| e.to_string())?;
+            }
+            for (action, keys2) in b2 {
+                if !b1.contains_key(action) {
+                    diffs.push(DiffEntry {
+                        kind: DiffKind::Added,
+                        text: format!("+ {action} [{}] (only in '{name2}')", format_keys(keys2)),
+                    });
                 }
             }
+            if diffs.is_empty() {
+                writeln!(out, "  (no differences)").map_err(|e| e.to_string())?;
+            } else {
+                writeln!(out, "  {} differences found:\n", diffs.len())
+                    .map_err(|e| e.to_string())?;
+                write_entries(out, &diffs, options.full)?;
+            }
         }
     }
 
-    Ok(())
+    // A shortcut bound to several actions only triggers one of them
+    let mut conflicts = Vec::new();
+    for (name, bindings) in [(name1, &bindings1), (name2, &bindings2)] {
+        let Some(bindings) = bindings else {
+            continue;
+        };
+        let mut by_key: BTreeMap<&str, Vec<&str>> = BTreeMap::new();
+        for (action, keys) in bindings {
+            for key in keys {
+                by_key.entry(key).or_default().push(action);
+            }
+        }
+        conflicts.extend(
+            by_key
+                .into_iter()
+                .filter(|(_, actions)| actions.len() > 1)
+                .map(|(key, actions)| DiffEntry {
+                    kind: DiffKind::Changed,
+                    text: format!("~ {key} : {} (in '{name}')", actions.join(", ")),
+                }),
+        );
+    }
+    if !conflicts.is_empty() {
+        writeln!(out, "\n  Conflicting shortcuts:").map_err(|e| e.to_string())?;
+        write_entries(out, &conflicts, options.full)?;
+    }
+
+    // Conflicts are within a profile, so they aren't differences
+    Ok(diffs.len() + missing)
+}
+
+/// Top-level keys with their values left unparsed, borrowed from the file
+/// contents so that unchanged subtrees never become `Value`s
+type RawObject<'a> = BTreeMap<String, &'a RawValue>;
+
+fn parse_raw_object(text: &str) -> Option<RawObject<'_>> {
+    serde_json::from_str(text).ok()
+}
+
+fn diff_raw_objects(o1: &RawObject, o2: &RawObject, options: &DiffOptions) -> Vec<DiffEntry> {
+    let mut diffs = Vec::new();
+
+    // Keys only one side has are parsed for their value in full mode only
+    let value = |raw: &RawValue| {
+        serde_json::from_str::<Value>(raw.get())
+            .map(|v| full_value(&v, options.full))
+            .unwrap_or_default()
+    };
+    let ignored = |key: &str| options.ignore_keys.is_match(key);
+
+    for (key, raw) in o1
+        .iter()
+        .filter(|(key, _)| !o2.contains_key(*key) && !ignored(key))
+    {
+        diffs.push(DiffEntry {
+            kind: DiffKind::Removed,
+            text: format!("- {key}{} (only in first)", value(raw)),
+        });
+    }
+
+    for (key, raw) in o2
+        .iter()
+        .filter(|(key, _)| !o1.contains_key(*key) && !ignored(key))
+    {
+        diffs.push(DiffEntry {
+            kind: DiffKind::Added,
+            text: format!("+ {key}{} (only in second)", value(raw)),
+        });
+    }
+
+    for (key, raw1) in o1 {
+        let Some(raw2) = o2.get(key) else {
+            continue;
+        };
+        if ignored(key) {
+            continue;
+        }
+        if raw1.get() == raw2.get() {
+            continue;
+        }
+        // Different text can still be the same value (whitespace, key order)
+        if let (Ok(v1), Ok(v2)) = (
+            serde_json::from_str::<Value>(raw1.get()),
+            serde_json::from_str::<Value>(raw2.get()),
+        ) {
+            diffs.extend(diff_json_objects(&v1, &v2, key, options));
+        }
+    }
+
+    diffs
 }
 
-fn diff_json_objects(v1: &Value, v2: &Value, prefix: &str) -> Vec<DiffEntry> {
+/// Differences between two settings values, by dotted path. Paths matching
+/// `diff.ignore_keys` are left out along with everything under them.
+fn diff_json_objects(
+    v1: &Value,
+    v2: &Value,
+    prefix: &str,
+    options: &DiffOptions,
+) -> Vec<DiffEntry> {
+    let full = options.full;
     let mut diffs = Vec::new();
 
     match (v1, v2) {
@@ -207,9 +799,12 @@ fn diff_json_objects(v1: &Value, v2: &Value, prefix: &str) -> Vec<DiffEntry> {
                 } else {
                     format!("{prefix}.{key}")
                 };
+                if options.ignore_keys.is_match(&path) {
+                    continue;
+                }
                 diffs.push(DiffEntry {
                     kind: DiffKind::Removed,
-                    text: format!("- {path} (only in first)"),
+                    text: format!("- {path}{} (only in first)", full_value(&o1[*key], full)),
                 });
             }
 
@@ -219,9 +814,12 @@ fn diff_json_objects(v1: &Value, v2: &Value, prefix: &str) -> Vec<DiffEntry> {
                 } else {
                     format!("{prefix}.{key}")
                 };
+                if options.ignore_keys.is_match(&path) {
+                    continue;
+                }
                 diffs.push(DiffEntry {
                     kind: DiffKind::Added,
-                    text: format!("+ {path} (only in second)"),
+                    text: format!("+ {path}{} (only in second)", full_value(&o2[*key], full)),
                 });
             }
 
@@ -231,12 +829,18 @@ fn diff_json_objects(v1: &Value, v2: &Value, prefix: &str) -> Vec<DiffEntry> {
                 } else {
                     format!("{prefix}.{key}")
                 };
-                diffs.extend(diff_json_objects(&o1[*key], &o2[*key], &path));
+                if options.ignore_keys.is_match(&path) {
+                    continue;
+                }
+                diffs.extend(diff_json_objects(&o1[*key], &o2[*key], &path, options));
             }
         }
+        (Value::Array(a1), Value::Array(a2)) if a1 != a2 => {
+            diffs.extend(diff_json_arrays(a1, a2, prefix, full));
+        }
         _ if v1 != v2 => {
-            let s1 = format_value(v1);
-            let s2 = format_value(v2);
+            let s1 = format_value(v1, full);
+            let s2 = format_value(v2, full);
             diffs.push(DiffEntry {
                 kind: DiffKind::Changed,
                 text: format!("~ {prefix} : {s1} -> {s2}"),
@@ -248,11 +852,69 @@ fn diff_json_objects(v1: &Value, v2: &Value, prefix: &str) -> Vec<DiffEntry> {
     diffs
 }
 
-fn format_value(v: &Value) -> String {
+/// Entries only one array has. Settings arrays are mostly lists of paths or
+/// names, so entries are matched by value rather than by position.
+fn diff_json_arrays(a1: &[Value], a2: &[Value], prefix: &str, full: bool) -> Vec<DiffEntry> {
+    let mut only_in_2: Vec<&Value> = a2.iter().collect();
+    let mut only_in_1 = Vec::new();
+    for item in a1 {
+        match only_in_2.iter().position(|other| *other == item) {
+            Some(index) => {
+                only_in_2.remove(index);
+            }
+            None => only_in_1.push(item),
+        }
+    }
+
+    let mut diffs: Vec<DiffEntry> = only_in_1
+        .into_iter()
+        .map(|item| DiffEntry {
+            kind: DiffKind::Removed,
+            text: format!("- {prefix} : {} (only in first)", format_value(item, full)),
+        })
+        .collect();
+    diffs.extend(only_in_2.into_iter().map(|item| DiffEntry {
+        kind: DiffKind::Added,
+        text: format!("+ {prefix} : {} (only in second)", format_value(item, full)),
+    }));
+    if diffs.is_empty() {
+        diffs.push(DiffEntry {
+            kind: DiffKind::Changed,
+            text: format!(
+                "~ {prefix} : the same {} entries in another order",
+                a1.len()
+            ),
+        });
+    }
+    diffs
+}
+
+/// ` = value` after a key only one side has, in full mode
+fn full_value(v: &Value, full: bool) -> String {
+    if full {
+        format!(" = {}", format_value(v, true))
+    } else {
+        String::new()
+    }
+}
+
+/// A value for a one-line summary, or in full: complete strings and
+/// pretty-printed arrays and objects, indented to sit under the entry
+pub(crate) fn format_value(v: &Value, full: bool) -> String {
+    if full {
+        return match v {
+            Value::Array(_) | Value::Object(_) => serde_json::to_string_pretty(v)
+                .unwrap_or_else(|_| v.to_string())
+                .replace('\n', "\n      "),
+            _ => v.to_string(),
+        };
+    }
     match v {
         Value::String(s) => {
-            if s.len() > MAX_VALUE_DISPLAY_LEN {
-                format!("\"{}...\"", &s[..VALUE_PREVIEW_LEN])
+            if s.chars().count() > MAX_VALUE_DISPLAY_LEN {
+                // Truncate on char boundaries so non-ASCII values don't panic
+                let preview: String = s.chars().take(VALUE_PREVIEW_LEN).collect();
+                format!("\"{preview}...\"")
             } else {
                 format!("{s:?}")
             }
@@ -262,3 +924,605 @@ fn format_value(v: &Value) -> String {
         _ => v.to_string(),
     }
 }
+
+/// Print a unified diff of two versions of a text file, or a note where
+/// either isn't UTF-8
+fn write_text_diff(
+    out: &mut dyn WriteColor,
+    (label1, bytes1): (&str, &[u8]),
+    (label2, bytes2): (&str, &[u8]),
+    full: bool,
+) -> Result<(), String> {
+    let (Ok(text1), Ok(text2)) = (std::str::from_utf8(bytes1), std::str::from_utf8(bytes2)) else {
+        writeln!(out, "  (not text; contents differ)").map_err(|e| e.to_string())?;
+        return Ok(());
+    };
+    let diff = TextDiff::from_lines(text1, text2);
+    let unified = diff.unified_diff().header(label1, label2).to_string();
+    let lines: Vec<&str> = unified.lines().collect();
+    let shown = if full {
+        lines.len()
+    } else {
+        MAX_TEXT_DIFF_LINES
+    };
+    for line in lines.iter().take(shown) {
+        let color = if line.starts_with("+++") || line.starts_with("---") {
+            None
+        } else if line.starts_with('+') {
+            Some(Color::Green)
+        } else if line.starts_with('-') {
+            Some(Color::Red)
+        } else if line.starts_with("@@") {
+            Some(Color::Cyan)
+        } else {
+            None
+        };
+        match color {
+            Some(color) => writeln_colored(out, &format!("    {line}"), color),
+            None => writeln!(out, "    {line}"),
+        }
+        .map_err(|e| e.to_string())?;
+    }
+    if lines.len() > shown {
+        writeln!(out, "    ... and {} more lines", lines.len() - shown)
+            .map_err(|e| e.to_string())?;
+    }
+    Ok(())
+}
+
+pub(crate) fn diff_startup(
+    out: &mut dyn WriteColor,
+    name1: &str,
+    profile1: &Profile,
+    name2: &str,
+    profile2: &Profile,
+    options: &DiffOptions,
+) -> Result<usize, String> {
+    let script1 = fs::read(profile1.config_dir.join(STARTUP_FILE)).ok();
+    let script2 = fs::read(profile2.config_dir.join(STARTUP_FILE)).ok();
+
+    let differs = script1 != script2;
+    match (&script1, &script2) {
+        (None, None) => {
+            writeln!(out, "  Neither profile has {STARTUP_FILE}").map_err(|e| e.to_string())?;
+        }
+        (Some(_), None) => {
+            writeln!(out, "  Only '{name1}' has {STARTUP_FILE}").map_err(|e| e.to_string())?;
+        }
+        (None, Some(_)) => {
+            writeln!(out, "  Only '{name2}' has {STARTUP_FILE}").map_err(|e| e.to_string())?;
+        }
+        (Some(s1), Some(s2)) if s1 == s2 => {
+            writeln!(out, "  (no differences)").map_err(|e| e.to_string())?;
+        }
+        (Some(s1), Some(s2)) => {
+            write_text_diff(
+                out,
+                (&format!("{name1}/{STARTUP_FILE}"), s1),
+                (&format!("{name2}/{STARTUP_FILE}"), s2),
+                options.full,
+            )?;
+        }
+    }
+    Ok(usize::from(differs))
+}
+
+pub(crate) fn diff_snippets(
+    out: &mut dyn WriteColor,
+    name1: &str,
+    profile1: &Profile,
+    name2: &str,
+    profile2: &Profile,
+    options: &DiffOptions,
+) -> Result<usize, String> {
+    let snippets1 = list_snippets(&profile1.config_dir)?;
+    let snippets2 = list_snippets(&profile2.config_dir)?;
+
+    let mut only = Vec::new();
+    for snippet in &snippets1 {
+        if !snippets2.iter().any(|s| s.name == snippet.name) {
+            only.push(DiffEntry {
+                kind: DiffKind::Removed,
+                text: format!("- {} (only in '{name1}')", snippet.name),
+            });
+        }
+    }
+    for snippet in &snippets2 {
+        if !snippets1.iter().any(|s| s.name == snippet.name) {
+            only.push(DiffEntry {
+                kind: DiffKind::Added,
+                text: format!("+ {} (only in '{name2}')", snippet.name),
+            });
+        }
+    }
+
+    let mut changed = Vec::new();
+    for snippet1 in &snippets1 {
+        let Some(snippet2) = snippets2.iter().find(|s| s.name == snippet1.name) else {
+            continue;
+        };
+        let (Ok(content1), Ok(content2)) = (fs::read(&snippet1.path), fs::read(&snippet2.path))
+        else {
+            continue;
+        };
+        if content1 != content2 {
+            changed.push((snippet1, content1, content2));
+        }
+    }
+
+    writeln!(
+        out,
+        "  {name1} has {} snippets, {name2} has {} snippets",
+        snippets1.len(),
+        snippets2.len()
+    )
+    .map_err(|e| e.to_string())?;
+    if !only.is_empty() {
+        writeln!(out).map_err(|e| e.to_string())?;
+        write_entries(out, &only, options.full)?;
+    }
+    for (snippet, content1, content2) in &changed {
+        writeln!(out).map_err(|e| e.to_string())?;
+        writeln_colored(out, &format!("  ~ {}", snippet.name), Color::Yellow)
+            .map_err(|e| e.to_string())?;
+        let file = format!("{SNIPPETS_DIR}/{}.py", snippet.name);
+        write_text_diff(
+            out,
+            (&format!("{name1}/{file}"), content1),
+            (&format!("{name2}/{file}"), content2),
+            options.full,
+        )?;
+    }
+    if only.is_empty() && changed.is_empty() {
+        writeln!(out, "  (no differences)").map_err(|e| e.to_string())?;
+    }
+    Ok(only.len() + changed.len())
+}
+
+/// A `.bntheme` file, by the name Binary Ninja lists it under
+struct Theme {
+    name: String,
+    version: Option<String>,
+    content: Vec<u8>,
+}
+
+/// Every theme under a profile's `themes/`, including ones in subfolders
+/// (where theme repositories are usually cloned)
+fn list_themes(config_dir: &Path) -> Vec<Theme> {
+    let mut files = Vec::new();
+    collect_theme_files(&config_dir.join(THEMES_DIR), &mut files);
+    let mut themes: Vec<Theme> = files
+        .into_iter()
+        .filter_map(|path| {
+            let content = fs::read(&path).ok()?;
+            let json: Option<Value> = serde_json::from_slice(&content).ok();
+            let field = |key: &str| {
+                json.as_ref()
+                    .and_then(|json| json.get(key))
+                    .and_then(|value| match value {
+                        Value::String(s) => Some(s.clone()),
+                        Value::Number(n) => Some(n.to_string()),
+                        _ => None,
+                    })
+            };
+            let name =
+                field("name").or_else(|| Some(path.file_stem()?.to_string_lossy().into_owned()))?;
+            Some(Theme {
+                name,
+                version: field("version"),
+                content,
+            })
+        })
+        .collect();
+    themes.sort_by_key(|theme| theme.name.to_lowercase());
+    themes
+}
+
+fn collect_theme_files(dir: &Path, files: &mut Vec<PathBuf>) {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return;
+    };
+    for entry in entries.filter_map(|e| e.ok()) {
+        let path = entry.path();
+        if entry.file_name().to_string_lossy().starts_with('.') {
+            continue;
+        }
+        if path.is_dir() {
+            collect_theme_files(&path, files);
+        } else if path.extension().is_some_and(|ext| ext == THEME_EXTENSION) {
+            files.push(path);
+        }
+    }
+}
+
+/// The theme a profile has selected, as a flat `ui.theme` key the way Binary
+/// Ninja writes it or nested under `ui`
+fn active_theme(profile: &Profile) -> Option<String> {
+    let settings = read_settings(&profile.config_dir).ok()?;
+    let (parent, leaf) = THEME_SETTING.split_once('.')?;
+    settings
+        .get(THEME_SETTING)
+        .or_else(|| settings.get(parent).and_then(|ui| ui.get(leaf)))
+        .and_then(Value::as_str)
+        .map(str::to_string)
+}
+
+pub(crate) fn diff_themes(
+    out: &mut dyn WriteColor,
+    name1: &str,
+    profile1: &Profile,
+    name2: &str,
+    profile2: &Profile,
+    options: &DiffOptions,
+) -> Result<usize, String> {
+    let active1 = active_theme(profile1);
+    let active2 = active_theme(profile2);
+    let describe = |theme: &Option<String>| match theme {
+        Some(theme) => format!("{theme:?}"),
+        None => "(default)".to_string(),
+    };
+    writeln!(
+        out,
+        "  Active theme: {} in '{name1}', {} in '{name2}'",
+        describe(&active1),
+        describe(&active2)
+    )
+    .map_err(|e| e.to_string())?;
+
+    let themes1 = list_themes(&profile1.config_dir);
+    let themes2 = list_themes(&profile2.config_dir);
+    writeln!(
+        out,
+        "  {name1} has {} themes, {name2} has {} themes",
+        themes1.len(),
+        themes2.len()
+    )
+    .map_err(|e| e.to_string())?;
+
+    let mut diffs = Vec::new();
+    if active1 != active2 {
+        diffs.push(DiffEntry {
+            kind: DiffKind::Changed,
+            text: format!(
+                "~ {THEME_SETTING} : {} -> {}",
+                describe(&active1),
+                describe(&active2)
+            ),
+        });
+    }
+    for theme in &themes1 {
+        match themes2.iter().find(|t| t.name == theme.name) {
+            None => diffs.push(DiffEntry {
+                kind: DiffKind::Removed,
+                text: format!("- {} (only in '{name1}')", theme.name),
+            }),
+            Some(other) if other.version != theme.version => diffs.push(DiffEntry {
+                kind: DiffKind::Changed,
+                text: format!(
+                    "~ {} : version {} -> {}",
+                    theme.name,
+                    theme.version.as_deref().unwrap_or("?"),
+                    other.version.as_deref().unwrap_or("?")
+                ),
+            }),
+            Some(other) if other.content != theme.content => diffs.push(DiffEntry {
+                kind: DiffKind::Changed,
+                text: format!("~ {} : contents differ", theme.name),
+            }),
+            Some(_) => {}
+        }
+    }
+    for theme in &themes2 {
+        if !themes1.iter().any(|t| t.name == theme.name) {
+            diffs.push(DiffEntry {
+                kind: DiffKind::Added,
+                text: format!("+ {} (only in '{name2}')", theme.name),
+            });
+        }
+    }
+
+    if diffs.is_empty() {
+        writeln!(out, "  (no differences)").map_err(|e| e.to_string())?;
+    } else {
+        writeln!(out, "\n  {} differences found:\n", diffs.len()).map_err(|e| e.to_string())?;
+        write_entries(out, &diffs, options.full)?;
+    }
+    Ok(diffs.len())
+}
+
+/// The files of a directory item with their sizes and digests, by path
+/// relative to it
+fn library_files(dir: &Path) -> Result<BTreeMap<String, (u64, String)>, String> {
+    if !dir.is_dir() {
+        return Ok(BTreeMap::new());
+    }
+    let hashes = hash_files(dir, &build_glob_set(&default_exclusions())?)?;
+    Ok(hashes
+        .into_iter()
+        .map(|(rel, hash)| {
+            let size = fs::metadata(dir.join(&rel)).map(|m| m.len()).unwrap_or(0);
+            (rel, (size, hash))
+        })
+        .collect())
+}
+
+/// Compare a directory of binary libraries (`types/`, `signatures/`) file by
+/// file
+pub(crate) fn diff_library(
+    out: &mut dyn WriteColor,
+    item: &str,
+    name1: &str,
+    profile1: &Profile,
+    name2: &str,
+    profile2: &Profile,
+    options: &DiffOptions,
+) -> Result<usize, String> {
+    let files1 = library_files(&profile1.config_dir.join(item))?;
+    let files2 = library_files(&profile2.config_dir.join(item))?;
+    let total = |files: &BTreeMap<String, (u64, String)>| {
+        format_size(files.values().map(|(size, _)| size).sum())
+    };
+    writeln!(
+        out,
+        "  {name1} has {} files ({}), {name2} has {} files ({})",
+        files1.len(),
+        total(&files1),
+        files2.len(),
+        total(&files2)
+    )
+    .map_err(|e| e.to_string())?;
+
+    let mut diffs = Vec::new();
+    for (rel, (size1, hash1)) in &files1 {
+        match files2.get(rel) {
+            None => diffs.push(DiffEntry {
+                kind: DiffKind::Removed,
+                text: format!("- {rel} ({}, only in '{name1}')", format_size(*size1)),
+            }),
+            Some((size2, hash2)) if hash1 != hash2 => diffs.push(DiffEntry {
+                kind: DiffKind::Changed,
+                text: if size1 == size2 {
+                    format!("~ {rel} : contents differ ({})", format_size(*size1))
+                } else {
+                    format!(
+                        "~ {rel} : {} -> {}",
+                        format_size(*size1),
+                        format_size(*size2)
+                    )
+                },
+            }),
+            Some(_) => {}
+        }
+    }
+    for (rel, (size2, _)) in &files2 {
+        if !files1.contains_key(rel) {
+            diffs.push(DiffEntry {
+                kind: DiffKind::Added,
+                text: format!("+ {rel} ({}, only in '{name2}')", format_size(*size2)),
+            });
+        }
+    }
+
+    if diffs.is_empty() {
+        writeln!(out, "  (no differences)").map_err(|e| e.to_string())?;
+    } else {
+        writeln!(out, "\n  {} differences found:\n", diffs.len()).map_err(|e| e.to_string())?;
+        write_entries(out, &diffs, options.full)?;
+    }
+    Ok(diffs.len())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::peak_memory::measure;
+    use serde_json::json;
+
+    /// Entries in a fixed order: `diff_json_objects` walks keys in hash order
+    fn sorted(entries: Vec<DiffEntry>) -> Vec<(char, String)> {
+        let mut entries: Vec<_> = entries
+            .into_iter()
+            .map(|entry| {
+                let kind = match entry.kind {
+                    DiffKind::Added => '+',
+                    DiffKind::Removed => '-',
+                    DiffKind::Changed => '~',
+                };
+                (kind, entry.text)
+            })
+            .collect();
+        entries.sort();
+        entries
+    }
+
+    fn raw_diff(text1: &str, text2: &str, options: &DiffOptions) -> Vec<(char, String)> {
+        let o1 = parse_raw_object(text1).unwrap();
+        let o2 = parse_raw_object(text2).unwrap();
+        sorted(diff_raw_objects(&o1, &o2, options))
+    }
+
+    fn value_diff(text1: &str, text2: &str, options: &DiffOptions) -> Vec<(char, String)> {
+        let v1: Value = serde_json::from_str(text1).unwrap();
+        let v2: Value = serde_json::from_str(text2).unwrap();
+        sorted(diff_json_objects(&v1, &v2, "", options))
+    }
+
+    const PAIRS: &[(&str, &str)] = &[
+        (r#"{"a": 1}"#, r#"{"a": 1}"#),
+        (
+            r#"{"a": {"x": 1, "y": [1, 2]}}"#,
+            r#"{ "a" : { "y":[1,2], "x":1 } }"#,
+        ),
+        (r#"{"a": 1, "b": 2}"#, r#"{"b": 3, "c": 4}"#),
+        (
+            r#"{"ui": {"font": "Mono", "size": 12}, "analysis": {"mode": "full"}}"#,
+            r#"{"ui": {"font": "Sans", "theme": "dark"}, "analysis": {"mode": 2}}"#,
+        ),
+        (
+            r#"{"paths": ["a", "b", "c"]}"#,
+            r#"{"paths": ["c", "b", "a"]}"#,
+        ),
+        (r#"{"paths": ["a", "b", "b"]}"#, r#"{"paths": ["b", "d"]}"#),
+        (r#"{"a": [1, {"x": 1}]}"#, r#"{"a": {"x": 1}}"#),
+        (
+            r#"{"a": null, "b": {}}"#,
+            r#"{"a": false, "b": {"c": {"d": []}}}"#,
+        ),
+        (
+            r#"{"ünï": "🦀 crab"}"#,
+            r#"{"ünï": "🦀 crabs", "new key": "x"}"#,
+        ),
+    ];
+
+    #[test]
+    fn raw_diff_matches_value_diff() {
+        let long = format!(r#"{{"s": "{}"}}"#, "x".repeat(200));
+        let pairs = PAIRS
+            .iter()
+            .copied()
+            .chain([(long.as_str(), r#"{"s": "y"}"#)]);
+        let ignore = DiffOptions {
+            ignore_keys: build_glob_set(&["ui.font".to_string(), "b".to_string()]).unwrap(),
+            ..Default::default()
+        };
+        let full = DiffOptions {
+            full: true,
+            ..Default::default()
+        };
+        for (text1, text2) in pairs {
+            for options in [&DiffOptions::default(), &full, &ignore] {
+                for (a, b) in [(text1, text2), (text2, text1)] {
+                    assert_eq!(
+                        raw_diff(a, b, options),
+                        value_diff(a, b, options),
+                        "{a} vs {b}"
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn formatting_alone_is_no_difference() {
+        let (text1, text2) = PAIRS[1];
+        assert_eq!(raw_diff(text1, text2, &DiffOptions::default()), []);
+    }
+
+    fn array_diff(a1: Value, a2: Value) -> Vec<(char, String)> {
+        let (Value::Array(a1), Value::Array(a2)) = (a1, a2) else {
+            panic!("not arrays");
+        };
+        sorted(diff_json_arrays(&a1, &a2, "paths", false))
+    }
+
+    #[test]
+    fn arrays_are_compared_by_value() {
+        assert_eq!(
+            array_diff(json!(["/a", "/b", "/c"]), json!(["/c", "/d", "/a"])),
+            [
+                ('+', r#"+ paths : "/d" (only in second)"#.to_string()),
+                ('-', r#"- paths : "/b" (only in first)"#.to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn arrays_count_repeated_entries() {
+        assert_eq!(
+            array_diff(json!(["x", "x", "y"]), json!(["x", "y", "y"])),
+            [
+                ('+', r#"+ paths : "y" (only in second)"#.to_string()),
+                ('-', r#"- paths : "x" (only in first)"#.to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn reordered_arrays_are_one_change() {
+        assert_eq!(
+            array_diff(json!([1, { "a": 1 }, [2]]), json!([[2], 1, { "a": 1 }])),
+            [(
+                '~',
+                "~ paths : the same 3 entries in another order".to_string()
+            )]
+        );
+    }
+
+    #[test]
+    fn equal_arrays_are_no_difference() {
+        let v = json!({ "paths": ["/a", { "b": [1, 2] }] });
+        assert!(diff_json_objects(&v, &v.clone(), "", &DiffOptions::default()).is_empty());
+    }
+
+    #[test]
+    fn array_entries_are_summarized_unless_full() {
+        let long = "x".repeat(200);
+        let summary = array_diff(json!([]), json!([long.clone()]));
+        assert!(summary[0].1.len() < 150, "{}", summary[0].1);
+        let v1 = json!({ "paths": [] });
+        let v2 = json!({ "paths": [long.clone()] });
+        let options = DiffOptions {
+            full: true,
+            ..Default::default()
+        };
+        let full = sorted(diff_json_objects(&v1, &v2, "", &options));
+        assert_eq!(
+            full,
+            [('+', format!("+ paths : \"{long}\" (only in second)"))]
+        );
+    }
+
+    /// A settings.json with `keys` top-level keys, each with a long value;
+    /// `changed` of them differ between the two versions
+    fn settings_pair(keys: usize, changed: usize) -> (String, String) {
+        let settings = |variant: usize| {
+            let object: serde_json::Map<_, _> = (0..keys)
+                .map(|i| {
+                    let value = json!({
+                        "enabled": true,
+                        "value": if i < changed { variant } else { 0 },
+                        "paths": [format!("/opt/{i}"), format!("/usr/{i}")],
+                        "description": "z".repeat(500),
+                    });
+                    (format!("plugin{i}.setting"), value)
+                })
+                .collect();
+            serde_json::to_string_pretty(&Value::Object(object)).unwrap()
+        };
+        (settings(1), settings(2))
+    }
+
+    /// Borrowing top-level values as `RawValue` against parsing both files
+    /// into `Value`s, as `diff` did before
+    #[test]
+    #[ignore = "benchmark; run with --release -- --ignored --test-threads=1 --nocapture"]
+    fn bench_diff_settings() {
+        let dir = tempfile::tempdir().unwrap();
+        let (text1, text2) = settings_pair(20_000, 100);
+        let path1 = dir.path().join("settings1.json");
+        let path2 = dir.path().join("settings2.json");
+        fs::write(&path1, text1).unwrap();
+        fs::write(&path2, text2).unwrap();
+        let options = DiffOptions::default();
+
+        let (old, old_usage) = measure(|| {
+            let read =
+                |path| serde_json::from_str::<Value>(&fs::read_to_string(path).unwrap()).unwrap();
+            diff_json_objects(&read(&path1), &read(&path2), "", &options)
+        });
+        let (new, new_usage) = measure(|| {
+            let text1 = fs::read_to_string(&path1).unwrap();
+            let text2 = fs::read_to_string(&path2).unwrap();
+            let o1 = parse_raw_object(&text1).unwrap();
+            let o2 = parse_raw_object(&text2).unwrap();
+            diff_raw_objects(&o1, &o2, &options)
+        });
+
+        let size = fs::metadata(&path1).unwrap().len();
+        println!("settings.json, 2 x {:.1} MB", size as f64 / 1_000_000.0);
+        println!("  read_to_string + from_str::<Value>: {old_usage}");
+        println!("  RawValue:                           {new_usage}");
+        assert_eq!(sorted(old), sorted(new));
+        assert!(new_usage.peak < old_usage.peak);
+    }
+}
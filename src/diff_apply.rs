@@ -0,0 +1,307 @@
+use crate::config::{Config, Profile};
+use crate::diff::format_value;
+use crate::items::DiffSide;
+use crate::plugin_manager::{CopyOptions, back_up, run_copy};
+use crate::plugins::list_plugins;
+use crate::report::{self, outln};
+use crate::safety::guard_config_dir;
+use crate::settings::{
+    KEYBINDINGS_FILE, SETTINGS_FILE, leaf_values, read_settings, set_key, update_settings,
+    write_bn_json,
+};
+use crate::sync::ask;
+use serde_json::{Map, Value};
+use std::collections::HashSet;
+use std::fs;
+use std::path::Path;
+
+/// Longest joined list of strings shown instead of an item count
+const MAX_LIST_DISPLAY_LEN: usize = 40;
+
+/// Which part of a profile a difference is in
+#[derive(Clone, Copy)]
+pub(crate) enum Section {
+    Setting,
+    Plugin,
+    Keybinding,
+}
+
+/// The sections compared value by value, in the order they are shown
+pub(crate) const SECTIONS: [Section; 3] = [Section::Setting, Section::Plugin, Section::Keybinding];
+
+impl Section {
+    pub(crate) fn title(self) -> &'static str {
+        match self {
+            Section::Setting => "Settings",
+            Section::Plugin => "Plugins",
+            Section::Keybinding => "Key Bindings",
+        }
+    }
+
+    fn label(self) -> &'static str {
+        match self {
+            Section::Setting => "setting",
+            Section::Plugin => "plugin",
+            Section::Keybinding => "key binding",
+        }
+    }
+
+    /// The item backed up before the section is written
+    fn item(self) -> &'static str {
+        match self {
+            Section::Setting => SETTINGS_FILE,
+            Section::Plugin => "plugins",
+            Section::Keybinding => KEYBINDINGS_FILE,
+        }
+    }
+
+    /// How a key the profile doesn't have is shown
+    fn missing(self) -> &'static str {
+        match self {
+            Section::Plugin => "(not installed)",
+            Section::Setting | Section::Keybinding => "(unset)",
+        }
+    }
+
+    /// Every setting (by dotted path, as `diff` shows them), plugin (by
+    /// directory, with its version) or action of a profile
+    pub(crate) fn values(self, profile: &Profile) -> Result<Map<String, Value>, String> {
+        match self {
+            Section::Setting => Ok(leaf_values(&read_settings(&profile.config_dir)?)),
+            Section::Keybinding => read_keybindings(&profile.config_dir),
+            Section::Plugin => Ok(list_plugins(profile)?
+                .into_iter()
+                .map(|plugin| {
+                    let version = plugin.version.unwrap_or_else(|| "?".to_string());
+                    (plugin.dir_name, Value::String(version))
+                })
+                .collect()),
+        }
+    }
+
+    /// A value from [`Section::values`], or its absence
+    pub(crate) fn describe(self, value: Option<&Value>) -> String {
+        let Some(value) = value else {
+            return self.missing().to_string();
+        };
+        // Short lists of strings, such as shortcuts, are shown in full
+        if let Value::Array(items) = value
+            && let Some(items) = items.iter().map(Value::as_str).collect::<Option<Vec<_>>>()
+        {
+            let joined = items.join(", ");
+            if joined.chars().count() <= MAX_LIST_DISPLAY_LEN {
+                return format!("[{joined}]");
+            }
+        }
+        match (self, value) {
+            (Section::Plugin, Value::String(version)) => version.clone(),
+            _ => format_value(value, false),
+        }
+    }
+}
+
+/// A settings key, plugin or action that differs between the two profiles
+struct Difference {
+    section: Section,
+    key: String,
+    summary: String,
+    /// Whether each side has it, i.e. can copy it to the other
+    in_left: bool,
+    in_right: bool,
+}
+
+pub(crate) struct DiffApplyOptions<'a> {
+    pub left: &'a DiffSide<'a>,
+    pub right: &'a DiffSide<'a>,
+    pub allow_suspicious_dirs: bool,
+}
+
+/// Walk through the differences `diff` found and offer to copy each settings
+/// key, plugin or key binding from one profile to the other
+pub(crate) fn run_diff_apply(config: &Config, options: &DiffApplyOptions) -> Result<(), String> {
+    let (left, right) = (options.left, options.right);
+    if left.profile.config_dir == right.profile.config_dir {
+        outln!("\nThe profiles share a config directory; there is nothing to apply.");
+        return Ok(());
+    }
+    // Backups can be copied from, but never written to
+    let left_live = config.profiles.contains_key(left.name);
+    let right_live = config.profiles.contains_key(right.name);
+    if !left_live && !right_live {
+        return Err("--apply needs at least one of the profiles to be a live profile".to_string());
+    }
+
+    let mut differences = Vec::new();
+    for section in SECTIONS {
+        differences.extend(key_differences(
+            section,
+            &section.values(left.profile)?,
+            &section.values(right.profile)?,
+        ));
+    }
+    if differences.is_empty() {
+        outln!("\nNo settings, plugins or key bindings to apply.");
+        return Ok(());
+    }
+
+    for (live, side) in [(left_live, left), (right_live, right)] {
+        if live {
+            guard_config_dir(
+                side.name,
+                &side.profile.config_dir,
+                options.allow_suspicious_dirs,
+            )?;
+        }
+    }
+
+    outln!(
+        "\n{} difference(s) to apply. For each, `>` copies '{}' to '{}', `<` copies '{}' to '{}'.",
+        differences.len(),
+        left.name,
+        right.name,
+        right.name,
+        left.name
+    );
+    // Each item is backed up once per profile, before its first change
+    let mut backed_up: HashSet<(bool, &'static str)> = HashSet::new();
+    let mut applied = 0;
+    for (index, difference) in differences.iter().enumerate() {
+        let can_push = difference.in_left && right_live;
+        let can_pull = difference.in_right && left_live;
+        if !can_push && !can_pull {
+            continue;
+        }
+        outln!(
+            "\n[{}/{}] {} {}: {}",
+            index + 1,
+            differences.len(),
+            difference.section.label(),
+            difference.key,
+            difference.summary
+        );
+        let mut choices = Vec::new();
+        if can_push {
+            choices.push(format!("> to '{}'", right.name));
+        }
+        if can_pull {
+            choices.push(format!("< to '{}'", left.name));
+        }
+        choices.extend(["s skip".to_string(), "q quit".to_string()]);
+        let answer = ask(&format!("  Copy? [{}] ", choices.join(", ")))?;
+
+        let (from, to, to_left) = match answer.as_str() {
+            ">" if can_push => (left, right, false),
+            "<" if can_pull => (right, left, true),
+            "q" | "Q" => break,
+            _ => continue,
+        };
+        let section = difference.section;
+        // run_copy backs up plugins itself
+        if !matches!(section, Section::Plugin) && backed_up.insert((to_left, section.item())) {
+            back_up(config, to.name, to.profile, &[section.item()])?;
+        }
+        let result = match section {
+            Section::Setting => copy_setting(from, to, &difference.key),
+            Section::Keybinding => copy_keybinding(from, to, &difference.key),
+            Section::Plugin => run_copy(
+                config,
+                &CopyOptions {
+                    name: &difference.key,
+                    from: from.name,
+                    to: to.name,
+                    force: true,
+                    yes: true,
+                    allow_suspicious_dirs: options.allow_suspicious_dirs,
+                },
+            ),
+        };
+        // One that can't be copied (e.g. through a linked directory) shouldn't
+        // end the walk
+        if let Err(e) = result {
+            report::warn(&e);
+            continue;
+        }
+        // run_copy reports plugins itself
+        if !matches!(section, Section::Plugin) {
+            outln!("  Copied {} to '{}'", difference.key, to.name);
+        }
+        applied += 1;
+    }
+
+    outln!(
+        "\nApplied {applied} of {} difference(s).",
+        differences.len()
+    );
+    Ok(())
+}
+
+fn object(value: Value) -> Map<String, Value> {
+    match value {
+        Value::Object(map) => map,
+        _ => Map::new(),
+    }
+}
+
+/// Keys of two [`Section::values`] maps whose values differ
+fn key_differences(
+    section: Section,
+    left: &Map<String, Value>,
+    right: &Map<String, Value>,
+) -> Vec<Difference> {
+    let mut keys: Vec<&String> = left.keys().chain(right.keys()).collect();
+    keys.sort();
+    keys.dedup();
+    keys.into_iter()
+        .filter_map(|key| {
+            let (value1, value2) = (left.get(key), right.get(key));
+            if value1 == value2 {
+                return None;
+            }
+            Some(Difference {
+                section,
+                key: key.clone(),
+                summary: format!(
+                    "{} -> {}",
+                    section.describe(value1),
+                    section.describe(value2)
+                ),
+                in_left: value1.is_some(),
+                in_right: value2.is_some(),
+            })
+        })
+        .collect()
+}
+
+fn read_keybindings(config_dir: &Path) -> Result<Map<String, Value>, String> {
+    let path = config_dir.join(KEYBINDINGS_FILE);
+    match fs::read_to_string(&path) {
+        Ok(content) => serde_json::from_str::<Value>(&content)
+            .map(object)
+            .map_err(|e| format!("Failed to parse {}: {e}", path.display())),
+        Err(_) => Ok(Map::new()),
+    }
+}
+
+/// Copy one setting by its dotted path, leaving the rest of the object it
+/// is in alone
+fn copy_setting(from: &DiffSide, to: &DiffSide, key: &str) -> Result<(), String> {
+    let Some(value) = leaf_values(&read_settings(&from.profile.config_dir)?).remove(key) else {
+        return Ok(());
+    };
+    update_settings(&to.profile.config_dir, |settings| {
+        set_key(settings, key, value);
+    })?;
+    Ok(())
+}
+
+fn copy_keybinding(from: &DiffSide, to: &DiffSide, action: &str) -> Result<(), String> {
+    let Some(keys) = read_keybindings(&from.profile.config_dir)?.remove(action) else {
+        return Ok(());
+    };
+    let mut target = read_keybindings(&to.profile.config_dir)?;
+    target.insert(action.to_string(), keys);
+    write_bn_json(
+        &to.profile.config_dir.join(KEYBINDINGS_FILE),
+        &Value::Object(target),
+    )
+}
@@ -0,0 +1,335 @@
+use crate::colors::{stdout, write_bold, writeln_bold, writeln_colored};
+use crate::config::{Config, Profile};
+use crate::diff_apply::{SECTIONS, Section};
+use crate::items::DiffSide;
+use crate::plugin_manager::{find_profile, profile_groups};
+use crate::report::{self, dataln};
+use serde::Serialize;
+use serde_json::{Map, Value};
+use std::collections::BTreeMap;
+use std::io::Write;
+use termcolor::{Color, StandardStream};
+
+/// How a key changed relative to the base
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Change {
+    /// Changed in one profile, unchanged in the other: sync that way
+    OnlyLeft,
+    OnlyRight,
+    /// Changed the same way in both
+    Both,
+    /// Changed differently in each, so either direction loses something
+    Divergent,
+}
+
+/// Compare two profiles against a common base, sorting each setting (by
+/// dotted path), plugin and key binding by which side changed it. Returns the number of
+/// changes.
+pub(crate) fn diff_three_way(
+    base: &DiffSide,
+    left: &DiffSide,
+    right: &DiffSide,
+) -> Result<usize, String> {
+    let mut out = stdout();
+    write_bold(&mut out, "Comparing profiles: ")
+        .and_then(|()| {
+            writeln!(
+                out,
+                "'{}' vs '{}' (base '{}')\n",
+                left.name, right.name, base.name
+            )
+        })
+        .map_err(|e| e.to_string())?;
+
+    let (mut changes, mut divergent) = (0, 0);
+    for (index, section) in SECTIONS.into_iter().enumerate() {
+        if index > 0 {
+            writeln!(out).map_err(|e| e.to_string())?;
+        }
+        writeln_bold(&mut out, &format!("=== {} ===", section.title()))
+            .map_err(|e| e.to_string())?;
+        let (section_changes, section_divergent) =
+            diff_section(&mut out, section, base, left, right)?;
+        changes += section_changes;
+        divergent += section_divergent;
+    }
+
+    if divergent > 0 {
+        writeln!(
+            out,
+            "\n{divergent} change(s) diverge; syncing either way would lose the other profile's."
+        )
+        .map_err(|e| e.to_string())?;
+    }
+    Ok(changes)
+}
+
+/// Print one section's changes grouped by side. Returns how many there are
+/// and how many of them diverge.
+fn diff_section(
+    out: &mut StandardStream,
+    section: Section,
+    base: &DiffSide,
+    left: &DiffSide,
+    right: &DiffSide,
+) -> Result<(usize, usize), String> {
+    let base_values = section.values(base.profile)?;
+    let left_values = section.values(left.profile)?;
+    let right_values = section.values(right.profile)?;
+
+    let mut groups: [Vec<String>; 4] = Default::default();
+    for (key, change) in classify(&base_values, &left_values, &right_values) {
+        let (old, a, b) = (
+            base_values.get(key),
+            left_values.get(key),
+            right_values.get(key),
+        );
+        let line = match change {
+            Change::OnlyLeft | Change::Both => format!(
+                "~ {key} : {} -> {}",
+                section.describe(old),
+                section.describe(a)
+            ),
+            Change::OnlyRight => format!(
+                "~ {key} : {} -> {}",
+                section.describe(old),
+                section.describe(b)
+            ),
+            Change::Divergent => format!(
+                "~ {key} : {} -> '{}': {}, '{}': {}",
+                section.describe(old),
+                left.name,
+                section.describe(a),
+                right.name,
+                section.describe(b)
+            ),
+        };
+        groups[change as usize].push(line);
+    }
+
+    if groups.iter().all(Vec::is_empty) {
+        writeln!(out, "  (no changes since '{}')", base.name).map_err(|e| e.to_string())?;
+        return Ok((0, 0));
+    }
+    let headings = [
+        (format!("Changed only in '{}':", left.name), Color::Green),
+        (format!("Changed only in '{}':", right.name), Color::Green),
+        ("Changed the same way in both:".to_string(), Color::Yellow),
+        ("Changed differently in both:".to_string(), Color::Red),
+    ];
+    for ((heading, color), lines) in headings.iter().zip(&groups) {
+        if lines.is_empty() {
+            continue;
+        }
+        writeln!(out, "  {heading}").map_err(|e| e.to_string())?;
+        for line in lines {
+            writeln_colored(out, &format!("    {line}"), *color).map_err(|e| e.to_string())?;
+        }
+    }
+    Ok((
+        groups.iter().map(Vec::len).sum(),
+        groups[Change::Divergent as usize].len(),
+    ))
+}
+
+/// Every key that changed since the base, sorted, with how it changed.
+/// Settings are compared leaf by leaf, so two profiles changing different
+/// keys under `ui` don't diverge.
+fn classify<'a>(
+    base: &'a Map<String, Value>,
+    left: &'a Map<String, Value>,
+    right: &'a Map<String, Value>,
+) -> Vec<(&'a String, Change)> {
+    let mut keys: Vec<&String> = base.keys().chain(left.keys()).chain(right.keys()).collect();
+    keys.sort();
+    keys.dedup();
+
+    keys.into_iter()
+        .filter_map(|key| {
+            let (old, a, b) = (base.get(key), left.get(key), right.get(key));
+            let change = match (a != old, b != old) {
+                (false, false) => return None,
+                (true, false) => Change::OnlyLeft,
+                (false, true) => Change::OnlyRight,
+                (true, true) if a == b => Change::Both,
+                (true, true) => Change::Divergent,
+            };
+            Some((key, change))
+        })
+        .collect()
+}
+
+/// How many keys of two [`Section::values`] maps differ
+fn count_differences(left: &Map<String, Value>, right: &Map<String, Value>) -> usize {
+    let only_right = right.keys().filter(|key| !left.contains_key(*key)).count();
+    left.iter()
+        .filter(|(key, value)| right.get(*key) != Some(value))
+        .count()
+        + only_right
+}
+
+/// One row of `diff --all --json`
+#[derive(Serialize)]
+struct DriftOutput {
+    profile: String,
+    /// Section title -> how many keys differ from the baseline
+    differences: BTreeMap<&'static str, usize>,
+}
+
+/// Compare every profile with a baseline (the default profile unless
+/// named), one row per config directory. Returns how many have drifted.
+pub(crate) fn run_drift_summary(
+    config: &Config,
+    baseline: Option<&str>,
+    json: bool,
+) -> Result<usize, String> {
+    let baseline = baseline
+        .or(config.global.default_profile.as_deref())
+        .ok_or_else(|| {
+            "No default profile to compare against; name a baseline profile".to_string()
+        })?;
+    let base = find_profile(config, baseline)?;
+    let base_values = SECTIONS
+        .into_iter()
+        .map(|section| section.values(base))
+        .collect::<Result<Vec<_>, String>>()?;
+
+    let groups: Vec<(String, &Profile)> = profile_groups(config, None)?
+        .into_iter()
+        .map(|(names, profile)| (names.join("/"), profile))
+        .collect();
+    let width = groups
+        .iter()
+        .map(|(label, _)| label.len())
+        .max()
+        .unwrap_or(0)
+        .max("Profile".len());
+
+    if !json {
+        dataln!("Drift from '{baseline}':\n");
+        let mut header = format!("  {:<width$}", "Profile");
+        for section in SECTIONS {
+            header.push_str(&format!("  {:<14}", section.title()));
+        }
+        dataln!("{}", header.trim_end());
+    }
+
+    let mut drifted = 0;
+    let mut rows = Vec::new();
+    for (label, profile) in &groups {
+        if profile.config_dir == base.config_dir {
+            if !json && label.split('/').any(|name| name != baseline) {
+                dataln!("  {label:<width$}  (baseline's config directory)");
+            }
+            continue;
+        }
+        let mut row = format!("  {label:<width$}");
+        let mut differences = BTreeMap::new();
+        for (section, base_values) in SECTIONS.into_iter().zip(&base_values) {
+            let count = count_differences(base_values, &section.values(profile)?);
+            let cell = match count {
+                0 => "identical".to_string(),
+                count => format!("{count} differ"),
+            };
+            row.push_str(&format!("  {cell:<14}"));
+            differences.insert(section.title(), count);
+        }
+        if differences.values().any(|&count| count > 0) {
+            drifted += 1;
+        }
+        if json {
+            rows.push(DriftOutput {
+                profile: label.clone(),
+                differences,
+            });
+        } else {
+            dataln!("{}", row.trim_end());
+        }
+    }
+
+    if json {
+        report::print_json(&serde_json::json!({
+            "baseline": baseline,
+            "profiles": rows,
+            "drifted": drifted,
+        }))?;
+        return Ok(drifted);
+    }
+
+    dataln!(
+        "\n{drifted} of {} other profile(s) have drifted. `bn-loader diff {baseline} <profile>` shows the details.",
+        groups.len().saturating_sub(1)
+    );
+    Ok(drifted)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::settings::leaf_values;
+    use serde_json::json;
+
+    fn changes(base: Value, left: Value, right: Value) -> Vec<(String, Change)> {
+        let (base, left, right) = (leaf_values(&base), leaf_values(&left), leaf_values(&right));
+        classify(&base, &left, &right)
+            .into_iter()
+            .map(|(key, change)| (key.clone(), change))
+            .collect()
+    }
+
+    #[test]
+    fn different_keys_in_one_object_dont_diverge() {
+        let base = json!({ "ui": { "theme": "light", "font": { "size": 10 } } });
+        let left = json!({ "ui": { "theme": "dark", "font": { "size": 10 } } });
+        let right = json!({ "ui": { "theme": "light", "font": { "size": 12 } } });
+        assert_eq!(
+            changes(base, left, right),
+            [
+                ("ui.font.size".to_string(), Change::OnlyRight),
+                ("ui.theme".to_string(), Change::OnlyLeft),
+            ]
+        );
+    }
+
+    #[test]
+    fn keys_are_classified_by_who_changed_them() {
+        let base = json!({ "a": 1, "b": 1, "c": 1, "d": 1 });
+        let left = json!({ "a": 2, "b": 1, "c": 2, "d": 2, "new": true });
+        let right = json!({ "a": 1, "b": 2, "c": 2, "d": 3 });
+        assert_eq!(
+            changes(base, left, right),
+            [
+                ("a".to_string(), Change::OnlyLeft),
+                ("b".to_string(), Change::OnlyRight),
+                ("c".to_string(), Change::Both),
+                ("d".to_string(), Change::Divergent),
+                ("new".to_string(), Change::OnlyLeft),
+            ]
+        );
+    }
+
+    #[test]
+    fn removals_are_changes() {
+        let base = json!({ "ui": { "theme": "light" }, "x": 1 });
+        let left = json!({ "x": 1 });
+        let right = json!({ "ui": { "theme": "dark" }, "x": 1 });
+        assert_eq!(
+            changes(base, left, right),
+            [("ui.theme".to_string(), Change::Divergent)]
+        );
+    }
+
+    #[test]
+    fn unchanged_profiles_have_no_changes() {
+        let base = json!({ "ui": { "theme": "light" } });
+        assert!(changes(base.clone(), base.clone(), base).is_empty());
+    }
+
+    #[test]
+    fn count_differences_counts_both_sides() {
+        let left = leaf_values(&json!({ "ui": { "theme": "a", "size": 1 }, "only_left": 1 }));
+        let right = leaf_values(&json!({ "ui": { "theme": "b", "size": 1 }, "only_right": 1 }));
+        assert_eq!(count_differences(&left, &right), 3);
+        assert_eq!(count_differences(&left, &left), 0);
+    }
+}
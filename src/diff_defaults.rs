@@ -0,0 +1,262 @@
+use crate::bn_version::{BnVersion, detect_version};
+use crate::colors::{stdout, write_bold, writeln_bold};
+use crate::config::{ENV_VAR_NAME, Profile, cache_dir};
+use crate::diff::{DiffEntry, DiffKind, DiffOptions, format_value, write_entries};
+use crate::pyenv::pip_interpreter;
+use crate::report;
+use crate::settings::read_settings;
+use serde_json::{Map, Value};
+use std::collections::BTreeMap;
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+/// Prints every setting's default as one JSON object, using the Python API
+/// directory passed as the first argument
+const DEFAULTS_SCRIPT: &str = "import json, sys
+sys.path.insert(0, sys.argv[1])
+import binaryninja
+from binaryninja.enums import SettingsScope
+settings = binaryninja.Settings()
+print(json.dumps({key: json.loads(settings.get_json(key, scope=SettingsScope.SettingsDefaultScope)) for key in settings.keys()}))";
+
+/// Cache directory of the defaults read from each Binary Ninja version
+const SNAPSHOT_DIR: &str = "settings-defaults";
+
+#[cfg(windows)]
+const DEFAULT_PYTHON: &str = "python";
+#[cfg(not(windows))]
+const DEFAULT_PYTHON: &str = "python3";
+
+/// Default settings, and the Binary Ninja they came from
+struct Defaults {
+    values: Map<String, Value>,
+    source: String,
+}
+
+/// Compare a profile's settings with Binary Ninja's defaults, listing the
+/// keys it overrides. Returns how many there are.
+pub(crate) fn diff_defaults(
+    name: &str,
+    profile: &Profile,
+    options: &DiffOptions,
+) -> Result<usize, String> {
+    let defaults = load_defaults(profile)?;
+    let mut settings = BTreeMap::new();
+    flatten_settings(
+        &read_settings(&profile.config_dir)?,
+        "",
+        &defaults.values,
+        &mut settings,
+    );
+
+    let mut out = stdout();
+    write_bold(&mut out, "Comparing profile: ")
+        .and_then(|()| writeln!(out, "'{name}' vs the defaults of {}\n", defaults.source))
+        .and_then(|()| writeln_bold(&mut out, "=== Settings ==="))
+        .map_err(|e| e.to_string())?;
+
+    let mut diffs = Vec::new();
+    let mut unchanged = 0;
+    for (key, value) in &settings {
+        if options.ignore_keys.is_match(key) {
+            continue;
+        }
+        match defaults.values.get(key) {
+            Some(default) if default == value => unchanged += 1,
+            Some(default) => diffs.push(DiffEntry {
+                kind: DiffKind::Changed,
+                text: format!(
+                    "~ {key} : {} -> {}",
+                    format_value(default, options.full),
+                    format_value(value, options.full)
+                ),
+            }),
+            // Plugins' settings aren't registered, since none are loaded
+            None => diffs.push(DiffEntry {
+                kind: DiffKind::Added,
+                text: format!(
+                    "+ {key} = {} (not a built-in setting)",
+                    format_value(value, options.full)
+                ),
+            }),
+        }
+    }
+
+    if diffs.is_empty() {
+        writeln!(out, "  (no settings changed from the defaults)").map_err(|e| e.to_string())?;
+    } else {
+        writeln!(
+            out,
+            "  {} setting(s) changed from the defaults:\n",
+            diffs.len()
+        )
+        .map_err(|e| e.to_string())?;
+        write_entries(&mut out, &diffs, options.full)?;
+    }
+    if unchanged > 0 {
+        writeln!(
+            out,
+            "\n  {unchanged} more setting(s) are set to their default value."
+        )
+        .map_err(|e| e.to_string())?;
+    }
+    Ok(diffs.len())
+}
+
+/// Settings by their dotted key, whether settings.json nests them by group
+/// or not
+fn flatten_settings(
+    value: &Value,
+    prefix: &str,
+    defaults: &Map<String, Value>,
+    settings: &mut BTreeMap<String, Value>,
+) {
+    let Value::Object(map) = value else {
+        return;
+    };
+    for (key, value) in map {
+        let path = if prefix.is_empty() {
+            key.clone()
+        } else {
+            format!("{prefix}.{key}")
+        };
+        match value {
+            Value::Object(_) if !defaults.contains_key(&path) => {
+                flatten_settings(value, &path, defaults, settings);
+            }
+            _ => {
+                settings.insert(path, value.clone());
+            }
+        }
+    }
+}
+
+/// Read the defaults from the profile's Binary Ninja, saving a snapshot for
+/// its version. Without a headless license the API can't be used, so the
+/// snapshot of the same (or else the newest) version is used instead.
+fn load_defaults(profile: &Profile) -> Result<Defaults, String> {
+    let version = detect_version(&profile.install_dir);
+    match query_defaults(profile) {
+        Ok(values) => {
+            if let Some(version) = &version
+                && let Err(e) = save_snapshot(version, &values)
+            {
+                report::warn(&e);
+            }
+            let source = match &version {
+                Some(version) => format!("Binary Ninja {version}"),
+                None => format!("the Binary Ninja in {}", profile.install_dir.display()),
+            };
+            Ok(Defaults { values, source })
+        }
+        Err(e) => {
+            let (snapshot, values) = find_snapshot(version.as_ref()).ok_or_else(|| {
+                format!(
+                    "Failed to read the default settings from Binary Ninja's Python API: {e}\nThis needs a license with headless support, or a snapshot in {} saved by an install of the same version.",
+                    snapshot_dir()
+                        .map(|dir| dir.display().to_string())
+                        .unwrap_or_else(|| SNAPSHOT_DIR.to_string())
+                )
+            })?;
+            if version.as_ref() != Some(&snapshot) {
+                report::warn(&format!(
+                    "Using the defaults of Binary Ninja {snapshot}, the newest snapshot, since the API couldn't be used: {e}"
+                ));
+            }
+            Ok(Defaults {
+                values,
+                source: format!("Binary Ninja {snapshot} (snapshot)"),
+            })
+        }
+    }
+}
+
+/// `python/` of an install, or `Contents/Resources/python` of a macOS bundle
+fn python_api_dir(install_dir: &Path) -> Option<PathBuf> {
+    [
+        install_dir.join("python"),
+        install_dir
+            .join("Contents")
+            .join("Resources")
+            .join("python"),
+    ]
+    .into_iter()
+    .chain(
+        install_dir
+            .parent()
+            .map(|dir| dir.join("Resources").join("python")),
+    )
+    .find(|dir| dir.join("binaryninja").is_dir())
+}
+
+fn query_defaults(profile: &Profile) -> Result<Map<String, Value>, String> {
+    let api_dir = python_api_dir(&profile.install_dir)
+        .ok_or_else(|| format!("no Python API found in {}", profile.install_dir.display()))?;
+    let interpreter = pip_interpreter(profile).unwrap_or_else(|| PathBuf::from(DEFAULT_PYTHON));
+    // The profile's directory holds its license; its plugins stay unloaded so
+    // only built-in settings are registered
+    let output = Command::new(&interpreter)
+        .args(["-c", DEFAULTS_SCRIPT])
+        .arg(&api_dir)
+        .env(ENV_VAR_NAME, &profile.config_dir)
+        .env("BN_DISABLE_USER_PLUGINS", "1")
+        .env("BN_DISABLE_REPOSITORY_PLUGINS", "1")
+        .output()
+        .map_err(|e| format!("failed to run {}: {e}", interpreter.display()))?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        let reason = stderr
+            .lines()
+            .rev()
+            .find(|line| !line.trim().is_empty())
+            .unwrap_or("no error output")
+            .trim()
+            .to_string();
+        return Err(format!(
+            "{} exited with {}: {reason}",
+            interpreter.display(),
+            output.status
+        ));
+    }
+    // Binary Ninja may log to stdout before the settings
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let json = stdout
+        .lines()
+        .rev()
+        .find(|line| line.starts_with('{'))
+        .ok_or("the Python API printed no settings")?;
+    serde_json::from_str(json).map_err(|e| format!("failed to parse the default settings: {e}"))
+}
+
+fn snapshot_dir() -> Option<PathBuf> {
+    cache_dir().map(|dir| dir.join(SNAPSHOT_DIR))
+}
+
+fn save_snapshot(version: &BnVersion, values: &Map<String, Value>) -> Result<(), String> {
+    let dir = snapshot_dir().ok_or_else(|| "Could not determine cache directory".to_string())?;
+    fs::create_dir_all(&dir)
+        .map_err(|e| format!("Failed to create directory {}: {e}", dir.display()))?;
+    let path = dir.join(format!("{}.json", version.version));
+    let json = serde_json::to_string_pretty(values)
+        .map_err(|e| format!("Failed to serialize default settings: {e}"))?;
+    fs::write(&path, json).map_err(|e| format!("Failed to write {}: {e}", path.display()))
+}
+
+/// The snapshot for `version`, or else the newest one
+fn find_snapshot(version: Option<&BnVersion>) -> Option<(BnVersion, Map<String, Value>)> {
+    let entries = fs::read_dir(snapshot_dir()?).ok()?;
+    let mut snapshots: Vec<(BnVersion, PathBuf)> = entries
+        .filter_map(|e| e.ok())
+        .map(|entry| entry.path())
+        .filter_map(|path| {
+            let version = BnVersion::parse(&path.file_stem()?.to_string_lossy())?;
+            Some((version, path))
+        })
+        .collect();
+    snapshots.sort_by_key(|(snapshot, _)| (Some(snapshot) == version, snapshot.build));
+    let (snapshot, path) = snapshots.pop()?;
+    let values = serde_json::from_str(&fs::read_to_string(path).ok()?).ok()?;
+    Some((snapshot, values))
+}
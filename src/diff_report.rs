@@ -0,0 +1,341 @@
+use crate::bn_version::BnVersion;
+use crate::report::{self, outln};
+use serde::Serialize;
+use std::fs;
+use std::io::{self, Write};
+use std::path::Path;
+use termcolor::{Color, ColorSpec, WriteColor};
+
+/// Stylesheet of HTML reports, matching the terminal's colors
+const HTML_STYLE: &str =
+    "body { font-family: sans-serif; margin: 2em auto; max-width: 60em; padding: 0 1em; }
+pre { background: #f6f8fa; border-radius: 4px; overflow-x: auto; padding: 0.75em 1em; }
+.note { color: #57606a; }
+.green { color: #1a7f37; }
+.red { color: #cf222e; }
+.yellow { color: #9a6700; }
+.cyan { color: #0969da; }
+.bold { font-weight: bold; }";
+
+enum ReportFormat {
+    Html,
+    Markdown,
+    /// `--json`, printed to stdout
+    Json,
+}
+
+impl ReportFormat {
+    fn from_path(path: &Path) -> Result<Self, String> {
+        let extension = path
+            .extension()
+            .map(|ext| ext.to_string_lossy().to_lowercase());
+        match extension.as_deref() {
+            Some("html" | "htm") => Ok(ReportFormat::Html),
+            Some("md" | "markdown") => Ok(ReportFormat::Markdown),
+            _ => Err(format!(
+                "Can't tell the report format of {} (use a .html or .md file)",
+                path.display()
+            )),
+        }
+    }
+}
+
+/// A run of text written in one color
+struct Span {
+    text: String,
+    spec: ColorSpec,
+}
+
+/// The output of one diff section, kept with its colors
+#[derive(Default)]
+pub(crate) struct SectionWriter {
+    lines: Vec<Vec<Span>>,
+    spec: ColorSpec,
+}
+
+impl SectionWriter {
+    /// The lines written, without the empty one after the last newline
+    fn lines(&self) -> &[Vec<Span>] {
+        match self.lines.split_last() {
+            Some((last, rest)) if last.is_empty() => rest,
+            _ => &self.lines,
+        }
+    }
+}
+
+impl Write for SectionWriter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let text = String::from_utf8_lossy(buf);
+        for (index, part) in text.split('\n').enumerate() {
+            if index > 0 || self.lines.is_empty() {
+                self.lines.push(Vec::new());
+            }
+            if !part.is_empty()
+                && let Some(line) = self.lines.last_mut()
+            {
+                line.push(Span {
+                    text: part.to_string(),
+                    spec: self.spec.clone(),
+                });
+            }
+        }
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+impl WriteColor for SectionWriter {
+    fn supports_color(&self) -> bool {
+        true
+    }
+
+    fn set_color(&mut self, spec: &ColorSpec) -> io::Result<()> {
+        self.spec = spec.clone();
+        Ok(())
+    }
+
+    fn reset(&mut self) -> io::Result<()> {
+        self.spec = ColorSpec::new();
+        Ok(())
+    }
+}
+
+struct ReportSection {
+    title: String,
+    differences: usize,
+    writer: SectionWriter,
+}
+
+/// A comparison of two profiles written to an HTML or Markdown file, or
+/// printed as JSON, instead of the terminal
+pub(crate) struct DiffReport<'a> {
+    /// `None` for JSON on stdout
+    path: Option<&'a Path>,
+    format: ReportFormat,
+    names: [String; 2],
+    /// Binary Ninja version of each profile, for JSON
+    versions: [Option<String>; 2],
+    title: String,
+    notes: Vec<String>,
+    sections: Vec<ReportSection>,
+}
+
+/// A line of a section in `diff --json`
+#[derive(Serialize)]
+struct LineOutput {
+    /// `added`, `removed` or `changed` for a difference (by its color), or
+    /// `info`
+    kind: &'static str,
+    text: String,
+}
+
+#[derive(Serialize)]
+struct SectionOutput<'a> {
+    title: &'a str,
+    differences: usize,
+    lines: Vec<LineOutput>,
+}
+
+impl<'a> DiffReport<'a> {
+    pub(crate) fn new(path: &'a Path, name1: &str, name2: &str) -> Result<Self, String> {
+        Ok(Self::with_format(
+            Some(path),
+            ReportFormat::from_path(path)?,
+            name1,
+            name2,
+        ))
+    }
+
+    /// A report printed to stdout as JSON
+    pub(crate) fn json(name1: &str, name2: &str, versions: &[Option<BnVersion>; 2]) -> Self {
+        let mut report = Self::with_format(None, ReportFormat::Json, name1, name2);
+        report.versions = versions
+            .clone()
+            .map(|version| version.map(|version| version.to_string()));
+        report
+    }
+
+    fn with_format(path: Option<&'a Path>, format: ReportFormat, name1: &str, name2: &str) -> Self {
+        DiffReport {
+            path,
+            format,
+            names: [name1.to_string(), name2.to_string()],
+            versions: [None, None],
+            title: format!("Comparing profiles '{name1}' and '{name2}'"),
+            notes: Vec::new(),
+            sections: Vec::new(),
+        }
+    }
+
+    pub(crate) fn note(&mut self, note: String) {
+        self.notes.push(note);
+    }
+
+    /// Start a section, returning where its contents go
+    pub(crate) fn section(&mut self, title: &str) -> &mut SectionWriter {
+        self.sections.push(ReportSection {
+            title: title.to_string(),
+            differences: 0,
+            writer: SectionWriter::default(),
+        });
+        let section = self.sections.last_mut().expect("a section was just added");
+        &mut section.writer
+    }
+
+    /// Record how many differences the last section found
+    pub(crate) fn count(&mut self, differences: usize) {
+        if let Some(section) = self.sections.last_mut() {
+            section.differences = differences;
+        }
+    }
+
+    /// Write the report file, or print the JSON
+    pub(crate) fn write(&self, differences: usize) -> Result<(), String> {
+        let summary = format!(
+            "{differences} difference(s), compared by bn-loader {}.",
+            env!("CARGO_PKG_VERSION")
+        );
+        let (content, path) = match (&self.format, self.path) {
+            (ReportFormat::Html, Some(path)) => (self.render_html(&summary), path),
+            (ReportFormat::Markdown, Some(path)) => (self.render_markdown(&summary), path),
+            _ => return report::print_json(&self.json_output(differences)),
+        };
+        fs::write(path, content).map_err(|e| format!("Failed to write {}: {e}", path.display()))?;
+        report::changed(path);
+        outln!(
+            "Wrote the comparison ({differences} difference(s)) to {}",
+            path.display()
+        );
+        Ok(())
+    }
+
+    fn json_output(&self, differences: usize) -> serde_json::Value {
+        let sections: Vec<SectionOutput> = self
+            .sections
+            .iter()
+            .map(|section| SectionOutput {
+                title: &section.title,
+                differences: section.differences,
+                lines: section
+                    .writer
+                    .lines()
+                    .iter()
+                    .map(|line| LineOutput {
+                        kind: line
+                            .iter()
+                            .find_map(|span| span.spec.fg())
+                            .and_then(line_kind)
+                            .unwrap_or("info"),
+                        text: line.iter().map(|span| span.text.as_str()).collect(),
+                    })
+                    .collect(),
+            })
+            .collect();
+        serde_json::json!({
+            "profiles": self.names,
+            "bn_versions": self.versions,
+            "differences": differences,
+            "notes": self.notes,
+            "sections": sections,
+        })
+    }
+
+    fn render_html(&self, summary: &str) -> String {
+        let title = escape_html(&self.title);
+        let mut html = format!(
+            "<!DOCTYPE html>\n<html lang=\"en\">\n<head>\n<meta charset=\"utf-8\">\n<title>{title}</title>\n<style>\n{HTML_STYLE}\n</style>\n</head>\n<body>\n<h1>{title}</h1>\n<p>{}</p>\n",
+            escape_html(summary)
+        );
+        for note in &self.notes {
+            html.push_str(&format!("<p class=\"note\">{}</p>\n", escape_html(note)));
+        }
+        for section in &self.sections {
+            html.push_str(&format!("<h2>{}</h2>\n<pre>", escape_html(&section.title)));
+            for line in section.writer.lines() {
+                for span in line {
+                    let classes: Vec<&str> = span
+                        .spec
+                        .fg()
+                        .and_then(color_class)
+                        .into_iter()
+                        .chain(span.spec.bold().then_some("bold"))
+                        .collect();
+                    let text = escape_html(&span.text);
+                    if classes.is_empty() {
+                        html.push_str(&text);
+                    } else {
+                        html.push_str(&format!(
+                            "<span class=\"{}\">{text}</span>",
+                            classes.join(" ")
+                        ));
+                    }
+                }
+                html.push('\n');
+            }
+            html.push_str("</pre>\n");
+        }
+        html.push_str("</body>\n</html>\n");
+        html
+    }
+
+    fn render_markdown(&self, summary: &str) -> String {
+        let mut markdown = format!("# {}\n\n{summary}\n", self.title);
+        for note in &self.notes {
+            markdown.push_str(&format!("\n> {note}\n"));
+        }
+        for section in &self.sections {
+            let lines: Vec<String> = section
+                .writer
+                .lines()
+                .iter()
+                .map(|line| line.iter().map(|span| span.text.as_str()).collect())
+                .collect();
+            // Longer than any run of backticks in the section, e.g. in a
+            // snippet's docstring
+            let longest = lines
+                .iter()
+                .flat_map(|line| line.split(|c| c != '`'))
+                .map(str::len)
+                .max()
+                .unwrap_or(0);
+            let fence = "`".repeat(longest.max(2) + 1);
+            markdown.push_str(&format!(
+                "\n## {}\n\n{fence}\n{}\n{fence}\n",
+                section.title,
+                lines.join("\n")
+            ));
+        }
+        markdown
+    }
+}
+
+/// What a colored line of the diff is
+fn line_kind(color: &Color) -> Option<&'static str> {
+    match color {
+        Color::Green => Some("added"),
+        Color::Red => Some("removed"),
+        Color::Yellow => Some("changed"),
+        _ => None,
+    }
+}
+
+/// CSS class of a color the diff uses
+fn color_class(color: &Color) -> Option<&'static str> {
+    match color {
+        Color::Green => Some("green"),
+        Color::Red => Some("red"),
+        Color::Yellow => Some("yellow"),
+        Color::Cyan => Some("cyan"),
+        _ => None,
+    }
+}
+
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
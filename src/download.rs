@@ -0,0 +1,266 @@
+use std::path::Path;
+
+/// Fetch a URL into memory
+#[cfg(feature = "plugin-index")]
+pub(crate) fn fetch(url: &str) -> Result<Vec<u8>, String> {
+    ureq::get(url)
+        .header("User-Agent", "bn-loader")
+        .call()
+        .map_err(|e| format!("Failed to download {url}: {e}"))?
+        .into_body()
+        .with_config()
+        // Plugin archives can be far larger than ureq's 10 MB default
+        .limit(u64::MAX)
+        .read_to_vec()
+        .map_err(|e| format!("Failed to download {url}: {e}"))
+}
+
+#[cfg(not(feature = "plugin-index"))]
+pub(crate) fn fetch(url: &str) -> Result<Vec<u8>, String> {
+    Err(format!(
+        "Can't download {url}: this build of bn-loader has no plugin downloads (built without the `plugin-index` feature)"
+    ))
+}
+
+/// Unpack a zip archive into `dst`. GitHub archives wrap everything in one
+/// `<repo>-<commit>/` directory, which is dropped; with `subdir`, only that
+/// part of the archive is unpacked. Returns the number of files written.
+#[cfg(feature = "plugin-index")]
+pub(crate) fn extract_zip(data: &[u8], dst: &Path, subdir: Option<&str>) -> Result<usize, String> {
+    use std::fs;
+    use std::io::{self, Cursor};
+    use std::path::PathBuf;
+
+    let mut archive = zip::ZipArchive::new(Cursor::new(data))
+        .map_err(|e| format!("Failed to read plugin archive: {e}"))?;
+
+    let mut names = Vec::with_capacity(archive.len());
+    for idx in 0..archive.len() {
+        let entry = archive
+            .by_index(idx)
+            .map_err(|e| format!("Failed to read plugin archive: {e}"))?;
+        // Entries escaping the destination (`../`, absolute paths) are dropped
+        names.push(entry.enclosed_name().map(|name| (name, entry.is_dir())));
+    }
+
+    let root = common_root(names.iter().flatten()).unwrap_or_default();
+    let prefix = match subdir.filter(|s| !s.is_empty()) {
+        Some(subdir) => root.join(subdir),
+        None => root,
+    };
+
+    let mut written = 0;
+    for (idx, name) in names.into_iter().enumerate() {
+        let Some((name, is_dir)) = name else {
+            continue;
+        };
+        let rel: PathBuf = match name.strip_prefix(&prefix) {
+            Ok(rel) if !rel.as_os_str().is_empty() => rel.to_path_buf(),
+            _ => continue,
+        };
+        let path = dst.join(rel);
+        if is_dir {
+            fs::create_dir_all(&path)
+                .map_err(|e| format!("Failed to create directory {}: {e}", path.display()))?;
+            continue;
+        }
+
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)
+                .map_err(|e| format!("Failed to create directory {}: {e}", parent.display()))?;
+        }
+        let mut entry = archive
+            .by_index(idx)
+            .map_err(|e| format!("Failed to read plugin archive: {e}"))?;
+        let mut file = fs::File::create(&path)
+            .map_err(|e| format!("Failed to create {}: {e}", path.display()))?;
+        io::copy(&mut entry, &mut file)
+            .map_err(|e| format!("Failed to extract {}: {e}", path.display()))?;
+        #[cfg(unix)]
+        if let Some(mode) = entry.unix_mode() {
+            use std::os::unix::fs::PermissionsExt;
+            let _ = fs::set_permissions(&path, fs::Permissions::from_mode(mode & 0o777));
+        }
+        written += 1;
+    }
+
+    if written == 0 {
+        return Err(match subdir {
+            Some(subdir) => format!("Plugin archive has no files under '{subdir}'"),
+            None => "Plugin archive is empty".to_string(),
+        });
+    }
+    Ok(written)
+}
+
+#[cfg(not(feature = "plugin-index"))]
+pub(crate) fn extract_zip(
+    _data: &[u8],
+    _dst: &Path,
+    _subdir: Option<&str>,
+) -> Result<usize, String> {
+    Err(
+        "This build of bn-loader can't unpack plugins (built without the `plugin-index` feature)"
+            .to_string(),
+    )
+}
+
+/// The directory every entry sits in, if there is exactly one
+#[cfg(feature = "plugin-index")]
+fn common_root<'a>(
+    mut names: impl Iterator<Item = &'a (std::path::PathBuf, bool)>,
+) -> Option<std::path::PathBuf> {
+    use std::path::{Component, PathBuf};
+
+    let first_component = |name: &PathBuf| match name.components().next() {
+        Some(Component::Normal(part)) => Some(PathBuf::from(part)),
+        _ => None,
+    };
+    let (first, first_is_dir) = names.next()?;
+    let root = first_component(first)?;
+    // A lone file at the top isn't a wrapping directory
+    if !first_is_dir && first.components().count() == 1 {
+        return None;
+    }
+    for (name, is_dir) in names {
+        if first_component(name).as_ref() != Some(&root)
+            || (!is_dir && name.components().count() == 1)
+        {
+            return None;
+        }
+    }
+    Some(root)
+}
+
+#[cfg(all(test, feature = "plugin-index"))]
+mod tests {
+    use super::*;
+    use std::fs;
+    use std::io::{Cursor, Write};
+    use zip::write::SimpleFileOptions;
+
+    /// A zip of `(name, contents)` entries; names ending in `/` are directories
+    fn archive(entries: &[(&str, &str)]) -> Vec<u8> {
+        let mut zip = zip::ZipWriter::new(Cursor::new(Vec::new()));
+        let options =
+            SimpleFileOptions::default().compression_method(zip::CompressionMethod::Stored);
+        for (name, contents) in entries {
+            if let Some(dir) = name.strip_suffix('/') {
+                zip.add_directory(dir, options).unwrap();
+            } else {
+                zip.start_file(*name, options).unwrap();
+                zip.write_all(contents.as_bytes()).unwrap();
+            }
+        }
+        zip.finish().unwrap().into_inner()
+    }
+
+    fn files(dir: &Path) -> Vec<String> {
+        let mut files: Vec<String> = file_paths(dir)
+            .into_iter()
+            .map(|path| {
+                path.strip_prefix(dir)
+                    .unwrap()
+                    .to_string_lossy()
+                    .replace('\\', "/")
+            })
+            .collect();
+        files.sort();
+        files
+    }
+
+    fn file_paths(dir: &Path) -> Vec<std::path::PathBuf> {
+        let mut found = Vec::new();
+        for entry in fs::read_dir(dir).unwrap() {
+            let path = entry.unwrap().path();
+            if path.is_dir() {
+                found.extend(file_paths(&path));
+            } else {
+                found.push(path);
+            }
+        }
+        found
+    }
+
+    #[test]
+    fn wrapping_directory_is_dropped() {
+        let data = archive(&[
+            ("repo-abc123/", ""),
+            ("repo-abc123/__init__.py", "init"),
+            ("repo-abc123/lib/util.py", "util"),
+        ]);
+        let dst = tempfile::tempdir().unwrap();
+        assert_eq!(extract_zip(&data, dst.path(), None), Ok(2));
+        assert_eq!(files(dst.path()), ["__init__.py", "lib/util.py"]);
+        assert_eq!(
+            fs::read_to_string(dst.path().join("__init__.py")).unwrap(),
+            "init"
+        );
+    }
+
+    #[test]
+    fn entries_escaping_the_destination_are_dropped() {
+        let data = archive(&[
+            ("repo/__init__.py", ""),
+            ("repo/../../evil.py", ""),
+            ("../evil.py", ""),
+            ("/tmp/evil.py", ""),
+        ]);
+        let parent = tempfile::tempdir().unwrap();
+        let dst = parent.path().join("plugin");
+        extract_zip(&data, &dst, None).unwrap();
+        assert_eq!(files(parent.path()), ["plugin/__init__.py"]);
+    }
+
+    #[test]
+    fn a_lone_top_level_file_keeps_every_path() {
+        let data = archive(&[("plugin.json", "{}"), ("src/__init__.py", "")]);
+        let dst = tempfile::tempdir().unwrap();
+        assert_eq!(extract_zip(&data, dst.path(), None), Ok(2));
+        assert_eq!(files(dst.path()), ["plugin.json", "src/__init__.py"]);
+    }
+
+    #[test]
+    fn subdir_is_unpacked_on_its_own() {
+        let data = archive(&[
+            ("repo-abc123/README.md", ""),
+            ("repo-abc123/plugins/mine/__init__.py", ""),
+            ("repo-abc123/plugins/mine/data/table.bin", ""),
+            ("repo-abc123/plugins/other/__init__.py", ""),
+        ]);
+        let dst = tempfile::tempdir().unwrap();
+        assert_eq!(extract_zip(&data, dst.path(), Some("plugins/mine")), Ok(2));
+        assert_eq!(files(dst.path()), ["__init__.py", "data/table.bin"]);
+    }
+
+    #[test]
+    fn nothing_to_unpack_is_an_error() {
+        let dst = tempfile::tempdir().unwrap();
+        let empty = archive(&[("repo/", "")]);
+        assert_eq!(
+            extract_zip(&empty, dst.path(), None),
+            Err("Plugin archive is empty".to_string())
+        );
+        let elsewhere = archive(&[("repo/other/__init__.py", "")]);
+        assert_eq!(
+            extract_zip(&elsewhere, dst.path(), Some("mine")),
+            Err("Plugin archive has no files under 'mine'".to_string())
+        );
+    }
+
+    #[test]
+    fn common_root_needs_one_directory_around_everything() {
+        let entries = |names: &[(&str, bool)]| -> Vec<(std::path::PathBuf, bool)> {
+            names
+                .iter()
+                .map(|(name, is_dir)| (name.into(), *is_dir))
+                .collect()
+        };
+        let root = |names: &[(&str, bool)]| common_root(entries(names).iter());
+        assert_eq!(root(&[("a/", true), ("a/x.py", false)]), Some("a".into()));
+        assert_eq!(root(&[("a/x.py", false), ("b/y.py", false)]), None);
+        assert_eq!(root(&[("a", true)]), Some("a".into()));
+        assert_eq!(root(&[("x.py", false)]), None);
+        assert_eq!(root(&[]), None);
+    }
+}
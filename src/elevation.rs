@@ -0,0 +1,132 @@
+use crate::config::Profile;
+use crate::shortcuts::ps_quote;
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD;
+use std::fmt::Write as _;
+use std::process::Command;
+
+/// Who Binary Ninja runs as, when not the current user
+pub(crate) enum RunAs<'a> {
+    Admin,
+    User(&'a str),
+}
+
+impl RunAs<'_> {
+    pub(crate) fn for_profile(profile: &Profile, elevated: bool) -> Option<RunAs<'_>> {
+        if elevated || profile.elevated {
+            return Some(RunAs::Admin);
+        }
+        profile.run_as.as_deref().map(RunAs::User)
+    }
+
+    pub(crate) fn describe(&self) -> String {
+        match self {
+            RunAs::Admin => "administrator (UAC prompt)".to_string(),
+            RunAs::User(user) => format!("{user} (runas)"),
+        }
+    }
+}
+
+/// Wrap a prepared launch so it starts through UAC or `runas`.
+///
+/// Neither passes our environment or working directory on, so the wrapped
+/// command is a PowerShell script that restores both before starting Binary Ninja.
+pub(crate) fn wrap_command(command: &Command, run_as: &RunAs) -> Result<Command, String> {
+    if !cfg!(windows) {
+        return Err("Elevated and run_as launches are only supported on Windows".to_string());
+    }
+
+    let mut script = String::new();
+    for (key, value) in command.get_envs() {
+        if let Some(value) = value {
+            let _ = write!(
+                script,
+                "[Environment]::SetEnvironmentVariable({}, {}); ",
+                ps_quote(&key.to_string_lossy()),
+                ps_quote(&value.to_string_lossy())
+            );
+        }
+    }
+    if let Some(dir) = command.get_current_dir() {
+        let _ = write!(
+            script,
+            "Set-Location -LiteralPath {}; ",
+            ps_quote(&dir.to_string_lossy())
+        );
+    }
+    let _ = write!(
+        script,
+        "& {}",
+        ps_quote(&command.get_program().to_string_lossy())
+    );
+    for arg in command.get_args() {
+        let _ = write!(script, " {}", ps_quote(&arg.to_string_lossy()));
+    }
+
+    // -EncodedCommand takes UTF-16LE base64, which sidesteps nested quoting
+    let utf16: Vec<u8> = script.encode_utf16().flat_map(u16::to_le_bytes).collect();
+    let encoded = STANDARD.encode(utf16);
+
+    let wrapped = match run_as {
+        RunAs::Admin => {
+            let mut wrapped = Command::new("powershell");
+            wrapped.args([
+                "-NoProfile",
+                "-NonInteractive",
+                "-Command",
+                &format!(
+                    "Start-Process -Verb RunAs -WindowStyle Hidden -FilePath powershell \
+                     -ArgumentList '-NoProfile','-EncodedCommand','{encoded}'"
+                ),
+            ]);
+            wrapped
+        }
+        RunAs::User(user) => {
+            // /env keeps our environment; runas asks for the password itself
+            let mut wrapped = Command::new("runas");
+            wrapped
+                .arg("/env")
+                .arg(format!("/user:{user}"))
+                .arg(format!(
+                    "powershell -NoProfile -WindowStyle Hidden -EncodedCommand {encoded}"
+                ));
+            wrapped
+        }
+    };
+
+    Ok(wrapped)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn an_account_named_admin_is_a_user() {
+        let profile = Profile {
+            run_as: Some("admin".to_string()),
+            ..Profile::default()
+        };
+        assert!(matches!(
+            RunAs::for_profile(&profile, false),
+            Some(RunAs::User("admin"))
+        ));
+    }
+
+    #[test]
+    fn elevated_asks_for_uac() {
+        let profile = Profile {
+            elevated: true,
+            ..Profile::default()
+        };
+        assert!(matches!(
+            RunAs::for_profile(&profile, false),
+            Some(RunAs::Admin)
+        ));
+        assert!(matches!(
+            RunAs::for_profile(&Profile::default(), true),
+            Some(RunAs::Admin)
+        ));
+        assert!(RunAs::for_profile(&Profile::default(), false).is_none());
+    }
+}
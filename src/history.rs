@@ -0,0 +1,206 @@
+use crate::config::cache_dir;
+use crate::report::{self, dataln};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::io::Write;
+use std::path::PathBuf;
+use std::process::ExitStatus;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+const HISTORY_FILE: &str = "history.jsonl";
+
+/// One launch of a profile. Duration and exit status are only known when
+/// bn-loader waited for Binary Ninja to exit.
+#[derive(Serialize, Deserialize, Clone)]
+pub(crate) struct LaunchRecord {
+    pub profile: String,
+    pub timestamp: u64,
+    #[serde(default)]
+    pub duration_secs: Option<u64>,
+    #[serde(default)]
+    pub exit_code: Option<i32>,
+    #[serde(default)]
+    pub signal: Option<i32>,
+}
+
+impl LaunchRecord {
+    pub(crate) fn new(profile: &str) -> Self {
+        Self {
+            profile: profile.to_string(),
+            timestamp: now(),
+            duration_secs: None,
+            exit_code: None,
+            signal: None,
+        }
+    }
+
+    pub(crate) fn set_exit(&mut self, status: ExitStatus, duration_secs: u64) {
+        self.duration_secs = Some(duration_secs);
+        self.exit_code = status.code();
+        self.signal = exit_signal(status);
+    }
+
+    /// Whether the launch is known to have ended badly
+    pub(crate) fn failed(&self) -> bool {
+        self.signal.is_some() || self.exit_code.is_some_and(|c| c != 0)
+    }
+}
+
+#[cfg(unix)]
+fn exit_signal(status: ExitStatus) -> Option<i32> {
+    use std::os::unix::process::ExitStatusExt;
+    status.signal()
+}
+
+#[cfg(not(unix))]
+fn exit_signal(_status: ExitStatus) -> Option<i32> {
+    None
+}
+
+pub(crate) fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+fn history_path() -> Option<PathBuf> {
+    cache_dir().map(|dir| dir.join(HISTORY_FILE))
+}
+
+/// Append a launch to the history file. Failures only warn.
+pub(crate) fn record_launch(record: &LaunchRecord) {
+    let Some(path) = history_path() else {
+        return;
+    };
+
+    if let Some(parent) = path.parent() {
+        let _ = fs::create_dir_all(parent);
+    }
+
+    let result = serde_json::to_string(record)
+        .map_err(|e| e.to_string())
+        .and_then(|line| {
+            fs::OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(&path)
+                .and_then(|mut file| writeln!(file, "{line}"))
+                .map_err(|e| e.to_string())
+        });
+
+    if let Err(e) = result {
+        report::warn(&format!("Failed to record launch history: {e}"));
+    }
+}
+
+/// Load all recorded launches, oldest first. Malformed lines are skipped.
+pub(crate) fn load_history() -> Vec<LaunchRecord> {
+    let Some(content) = history_path().and_then(|p| fs::read_to_string(p).ok()) else {
+        return Vec::new();
+    };
+
+    content
+        .lines()
+        .filter_map(|line| serde_json::from_str(line).ok())
+        .collect()
+}
+
+/// Time of each profile's most recent launch
+pub(crate) fn last_launches() -> BTreeMap<String, u64> {
+    let mut last = BTreeMap::new();
+    for record in load_history() {
+        let entry = last.entry(record.profile).or_insert(0);
+        *entry = record.timestamp.max(*entry);
+    }
+    last
+}
+
+#[derive(Default)]
+struct ProfileStats {
+    launches: usize,
+    last_launch: u64,
+    tracked_secs: u64,
+    failures: usize,
+}
+
+pub(crate) fn print_history(profile: Option<&str>, limit: usize) {
+    let records: Vec<LaunchRecord> = load_history()
+        .into_iter()
+        .filter(|r| profile.is_none_or(|p| r.profile == p))
+        .collect();
+
+    if records.is_empty() {
+        dataln!("No launches recorded yet.");
+        return;
+    }
+
+    let now = now();
+
+    dataln!("Recent launches:");
+    for record in records.iter().rev().take(limit) {
+        let duration = record
+            .duration_secs
+            .map(|d| format!("  ran {}", format_duration(d)))
+            .unwrap_or_default();
+        let outcome = match (record.exit_code, record.signal) {
+            (_, Some(sig)) => format!("  killed by signal {sig}"),
+            (Some(code), None) => format!("  exit {code}"),
+            (None, None) => String::new(),
+        };
+        dataln!(
+            "  {:<12} {:>10}{duration}{outcome}",
+            record.profile,
+            format_age(now.saturating_sub(record.timestamp))
+        );
+    }
+
+    let mut stats: BTreeMap<&str, ProfileStats> = BTreeMap::new();
+    for record in &records {
+        let entry = stats.entry(record.profile.as_str()).or_default();
+        entry.launches += 1;
+        entry.last_launch = entry.last_launch.max(record.timestamp);
+        entry.tracked_secs += record.duration_secs.unwrap_or(0);
+        if record.failed() {
+            entry.failures += 1;
+        }
+    }
+
+    dataln!("\nUsage by profile:");
+    dataln!(
+        "  {:<12} {:>8} {:>12} {:>10} {:>8}",
+        "PROFILE",
+        "LAUNCHES",
+        "LAST",
+        "TIME",
+        "FAILED"
+    );
+    for (name, s) in &stats {
+        dataln!(
+            "  {:<12} {:>8} {:>12} {:>10} {:>8}",
+            name,
+            s.launches,
+            format_age(now.saturating_sub(s.last_launch)),
+            format_duration(s.tracked_secs),
+            s.failures
+        );
+    }
+}
+
+pub(crate) fn format_duration(secs: u64) -> String {
+    match secs {
+        0..60 => format!("{secs}s"),
+        60..3600 => format!("{}m", secs / 60),
+        _ => format!("{}h{:02}m", secs / 3600, (secs % 3600) / 60),
+    }
+}
+
+pub(crate) fn format_age(secs: u64) -> String {
+    match secs {
+        0..60 => "just now".to_string(),
+        60..3600 => format!("{}m ago", secs / 60),
+        3600..86400 => format!("{}h ago", secs / 3600),
+        _ => format!("{}d ago", secs / 86400),
+    }
+}
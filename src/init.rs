@@ -1,7 +1,9 @@
-use crate::config::Config;
+use crate::config::{Config, Profile};
+use crate::report::{self, outln};
 use std::fs;
 use std::io::Write;
 use std::path::Path;
+use tracing::debug;
 
 const LICENSE_FILES: &[&str] = &["license.dat", "license.txt"];
 
@@ -35,14 +37,19 @@ pub(crate) fn run_init(
         ));
     }
 
-    println!("Initializing profile '{}'...", options.name);
-    println!("  Template:    {}", options.template);
-    println!("  Install dir: {}", template_profile.install_dir.display());
-    println!("  Config dir:  {}", options.config_dir.display());
+    // Build the config entry up front so invalid names or paths fail before
+    // anything is created on disk
+    let profile_toml = profile_entry(options.name, template_profile, options.config_dir)?;
+
+    outln!("Initializing profile '{}'...", options.name);
+    outln!("  Template:    {}", options.template);
+    outln!("  Install dir: {}", template_profile.install_dir.display());
+    outln!("  Config dir:  {}", options.config_dir.display());
 
     // Create the config directory
     fs::create_dir_all(options.config_dir)
         .map_err(|e| format!("Failed to create config directory: {e}"))?;
+    report::changed(options.config_dir);
 
     // Copy license files from template
     let mut copied_files = Vec::new();
@@ -50,40 +57,31 @@ pub(crate) fn run_init(
         let src = template_profile.config_dir.join(license_file);
         if src.exists() {
             let dst = options.config_dir.join(license_file);
+            debug!("Copying {} to {}", src.display(), dst.display());
             fs::copy(&src, &dst).map_err(|e| format!("Failed to copy {license_file}: {e}"))?;
             copied_files.push(*license_file);
         }
     }
 
     if copied_files.is_empty() {
-        eprintln!(
-            "Warning: No license files found in template profile at {}",
+        report::warn(&format!(
+            "No license files found in template profile at {}",
             template_profile.config_dir.display()
-        );
+        ));
     } else {
-        println!("  Copied:      {}", copied_files.join(", "));
+        outln!("  Copied:      {}", copied_files.join(", "));
     }
 
     // Append new profile to config file
-    append_profile_to_config(
-        config_path,
-        options.name,
-        &template_profile.install_dir,
-        options.config_dir,
-    )?;
+    append_profile_to_config(config_path, &profile_toml)?;
 
-    println!("\nProfile '{}' initialized successfully.", options.name);
-    println!("You can now launch it with: bn-loader {}", options.name);
+    outln!("\nProfile '{}' initialized successfully.", options.name);
+    outln!("You can now launch it with: bn-loader {}", options.name);
 
     Ok(())
 }
 
-fn append_profile_to_config(
-    config_path: &Path,
-    name: &str,
-    install_dir: &Path,
-    config_dir: &Path,
-) -> Result<(), String> {
+fn profile_entry(name: &str, template: &Profile, config_dir: &Path) -> Result<String, String> {
     // Validate profile name to prevent TOML injection
     if !is_valid_profile_name(name) {
         return Err(format!(
@@ -91,32 +89,139 @@ fn append_profile_to_config(
         ));
     }
 
+    // Use toml crate to properly escape path values; a template that uses an
+    // installation passes it on so the new profile follows relocations too
+    let install = match &template.installation {
+        Some(id) => format!("installation = {}", toml::Value::String(id.clone())),
+        None => format!("install_dir = {}", toml_path(&template.install_dir)?),
+    };
+    let config_escaped = toml_path(config_dir)?;
+    let key = toml_key(name);
+
+    Ok(format!(
+        "\n[profiles.{key}]\n{install}\nconfig_dir = {config_escaped}\n"
+    ))
+}
+
+fn append_profile_to_config(config_path: &Path, profile_toml: &str) -> Result<(), String> {
     let mut file = fs::OpenOptions::new()
         .append(true)
         .open(config_path)
         .map_err(|e| format!("Failed to open config file: {e}"))?;
 
-    // Use toml crate to properly escape path values
-    let install_str = install_dir.to_string_lossy();
-    let config_str = config_dir.to_string_lossy();
-    let install_escaped = toml::Value::String(install_str.into_owned());
-    let config_escaped = toml::Value::String(config_str.into_owned());
-
-    let profile_toml = format!(
-        "\n[profiles.{name}]\ninstall_dir = {install_escaped}\nconfig_dir = {config_escaped}\n"
-    );
-
     file.write_all(profile_toml.as_bytes())
         .map_err(|e| format!("Failed to write to config file: {e}"))?;
 
-    println!("  Added profile to: {}", config_path.display());
+    outln!("  Added profile to: {}", config_path.display());
+    report::changed(config_path);
 
     Ok(())
 }
 
+/// Convert a path to an escaped TOML string, refusing paths that aren't valid
+/// UTF-8 (a lossy conversion would silently write a different path)
+fn toml_path(path: &Path) -> Result<toml::Value, String> {
+    path.to_str()
+        .map(|s| toml::Value::String(s.to_string()))
+        .ok_or_else(|| format!("Path is not valid UTF-8: {}", path.display()))
+}
+
+/// Bare TOML keys only allow ASCII letters, digits, `-` and `_`; anything
+/// else is quoted. Not through `toml::Value`, which can pick a multi-line
+/// string that isn't allowed as a key.
+fn toml_key(name: &str) -> String {
+    toml_edit::Key::new(name).to_string()
+}
+
 fn is_valid_profile_name(name: &str) -> bool {
     !name.is_empty()
         && name
             .chars()
             .all(|c| c.is_alphanumeric() || c == '-' || c == '_')
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::strategies::{absolute_path, any_name};
+    use proptest::prelude::*;
+    use std::path::PathBuf;
+
+    fn config_with(profiles: Vec<(String, Profile)>) -> Config {
+        Config {
+            profiles: profiles.into_iter().collect(),
+            ..Config::default()
+        }
+    }
+
+    /// `Config` has no `PartialEq`; its serialized form stands in
+    fn same(a: &Config, b: &Config) -> bool {
+        serde_json::to_value(a).unwrap() == serde_json::to_value(b).unwrap()
+    }
+
+    #[test]
+    fn toml_key_quotes_only_when_needed() {
+        assert_eq!(toml_key("work-2_x"), "work-2_x");
+        assert_eq!(toml_key("réseau"), "\"réseau\"");
+        let key = toml_key("it's \"🦀\"");
+        let parsed: Config =
+            toml::from_str(&format!("[profiles.{key}]\nconfig_dir = '/c'\n")).unwrap();
+        assert!(parsed.profiles.contains_key("it's \"🦀\""), "{key}");
+    }
+
+    proptest! {
+        #[test]
+        fn profile_entry_round_trips(
+            name in r"[\p{L}\p{N}_-]{1,16}",
+            install_dir in absolute_path(),
+            config_dir in absolute_path(),
+            installation in proptest::option::of(any_name()),
+        ) {
+            prop_assume!(name != "template");
+            let template = Profile {
+                install_dir,
+                installation,
+                config_dir: PathBuf::from("/cfg/template"),
+                ..Profile::default()
+            };
+            let existing = config_with(vec![("template".to_string(), template.clone())]);
+            let text = toml::to_string(&existing).unwrap()
+                + &profile_entry(&name, &template, &config_dir).unwrap();
+
+            let parsed: Config = toml::from_str(&text).unwrap();
+            let added = match &template.installation {
+                Some(_) => Profile {
+                    installation: template.installation.clone(),
+                    ..Profile::default()
+                },
+                None => Profile {
+                    install_dir: template.install_dir.clone(),
+                    ..Profile::default()
+                },
+            };
+            let expected = config_with(vec![
+                ("template".to_string(), template),
+                (name, Profile { config_dir, ..added }),
+            ]);
+            prop_assert!(same(&parsed, &expected), "{text}");
+        }
+
+        #[test]
+        fn toml_key_and_path_round_trip(name in any_name(), config_dir in absolute_path()) {
+            let text = format!(
+                "[profiles.{}]\nconfig_dir = {}\n",
+                toml_key(&name),
+                toml_path(&config_dir).unwrap()
+            );
+            let parsed: Config = toml::from_str(&text).unwrap();
+            let expected = config_with(vec![(name, Profile { config_dir, ..Profile::default() })]);
+            prop_assert!(same(&parsed, &expected), "{text}");
+        }
+
+        #[test]
+        fn profile_entry_refuses_names_it_cant_write(name in any_name()) {
+            prop_assume!(!is_valid_profile_name(&name));
+            prop_assert!(profile_entry(&name, &Profile::default(), Path::new("/cfg")).is_err());
+        }
+    }
+}
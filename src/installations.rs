@@ -0,0 +1,108 @@
+use crate::config::{Config, DEFAULT_EXECUTABLE};
+use crate::report::{self, outln};
+use std::fs;
+use std::path::Path;
+use toml_edit::DocumentMut;
+
+pub(crate) struct RelocateOptions<'a> {
+    pub id: &'a str,
+    pub new_path: &'a Path,
+    pub config_path: &'a Path,
+}
+
+/// Move an installation to a new path, updating every profile that uses it
+pub(crate) fn relocate(config: &Config, options: &RelocateOptions) -> Result<(), String> {
+    let id = options.id;
+    let installation = config.installations.get(id).ok_or_else(|| {
+        let mut known: Vec<&str> = config.installations.keys().map(String::as_str).collect();
+        known.sort_unstable();
+        if known.is_empty() {
+            format!("Installation '{id}' not found (no [installations] in config)")
+        } else {
+            format!(
+                "Installation '{id}' not found (available: {})",
+                known.join(", ")
+            )
+        }
+    })?;
+
+    let new_path = std::path::absolute(options.new_path)
+        .map_err(|e| format!("Failed to resolve {}: {e}", options.new_path.display()))?;
+    let new_path_str = new_path
+        .to_str()
+        .ok_or_else(|| format!("Path is not valid UTF-8: {}", new_path.display()))?;
+
+    if new_path == installation.path {
+        outln!("Installation '{id}' is already at {}", new_path.display());
+        return Ok(());
+    }
+
+    let mut dependents: Vec<_> = config
+        .profiles
+        .iter()
+        .filter(|(_, profile)| profile.installation.as_deref() == Some(id))
+        .collect();
+    dependents.sort_by_key(|(name, _)| name.as_str());
+
+    // Profiles can name different executables; every one must be there
+    let mut executables: Vec<&str> = dependents
+        .iter()
+        .map(|(_, profile)| profile.executable.as_str())
+        .collect();
+    if executables.is_empty() {
+        executables.push(DEFAULT_EXECUTABLE);
+    }
+    for executable in executables {
+        let exe_path = new_path.join(executable);
+        if !exe_path.is_file() {
+            return Err(format!(
+                "Binary Ninja executable not found at {}",
+                exe_path.display()
+            ));
+        }
+    }
+
+    let content = fs::read_to_string(options.config_path)
+        .map_err(|e| format!("Failed to read config file: {e}"))?;
+    let mut document: DocumentMut = content
+        .parse()
+        .map_err(|e| format!("Failed to parse config file: {e}"))?;
+    // Keep any comment that trails the old value
+    let item = &mut document["installations"][id]["path"];
+    let decor = item.as_value().map(|v| v.decor().clone());
+    *item = toml_edit::value(new_path_str);
+    if let (Some(decor), Some(value)) = (decor, item.as_value_mut()) {
+        *value.decor_mut() = decor;
+    }
+    fs::write(options.config_path, document.to_string())
+        .map_err(|e| format!("Failed to write config file: {e}"))?;
+    report::changed(options.config_path);
+
+    outln!("Relocated installation '{id}':");
+    outln!("  From: {}", installation.path.display());
+    outln!("  To:   {}", new_path.display());
+    if dependents.is_empty() {
+        outln!("  No profiles use this installation.");
+    } else {
+        let names: Vec<&str> = dependents.iter().map(|(name, _)| name.as_str()).collect();
+        outln!("  Profiles: {}", names.join(", "));
+    }
+
+    // Profiles with a literal install_dir don't follow the installation
+    let mut stragglers: Vec<&str> = config
+        .profiles
+        .iter()
+        .filter(|(_, profile)| {
+            profile.installation.is_none() && profile.install_dir == installation.path
+        })
+        .map(|(name, _)| name.as_str())
+        .collect();
+    stragglers.sort_unstable();
+    for name in stragglers {
+        report::warn(&format!(
+            "Profile '{name}' sets install_dir to the old path; use installation = \"{id}\" so it follows"
+        ));
+    }
+
+    Ok(())
+}
@@ -0,0 +1,61 @@
+use serde::{Deserialize, Serialize};
+use std::env;
+use std::ffi::OsString;
+use std::path::PathBuf;
+
+#[derive(Deserialize, Serialize, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub(crate) enum NetworkMode {
+    #[default]
+    Default,
+    Blocked,
+}
+
+/// A program that runs Binary Ninja without network access
+pub(crate) struct Isolation {
+    pub description: &'static str,
+    pub program: PathBuf,
+    pub args: Vec<OsString>,
+}
+
+/// Find a way to run Binary Ninja with networking blocked.
+///
+/// Fails closed: if no mechanism is available the launch must not happen.
+pub(crate) fn network_isolation() -> Result<Isolation, String> {
+    if !cfg!(target_os = "linux") {
+        return Err(
+            "network = \"blocked\" is only enforceable on Linux (via firejail or unshare); \
+             refusing to launch without isolation"
+                .to_string(),
+        );
+    }
+
+    if let Some(program) = find_in_path("firejail") {
+        return Ok(Isolation {
+            description: "firejail --net=none",
+            program,
+            args: vec!["--quiet".into(), "--net=none".into()],
+        });
+    }
+
+    // An unprivileged user namespace gets its own, empty network namespace
+    if let Some(program) = find_in_path("unshare") {
+        return Ok(Isolation {
+            description: "unshare --net",
+            program,
+            args: vec!["--map-root-user".into(), "--net".into(), "--".into()],
+        });
+    }
+
+    Err(
+        "network = \"blocked\" requires firejail or unshare on PATH; refusing to launch without isolation"
+            .to_string(),
+    )
+}
+
+fn find_in_path(program: &str) -> Option<PathBuf> {
+    let paths = env::var_os("PATH")?;
+    env::split_paths(&paths)
+        .map(|dir| dir.join(program))
+        .find(|candidate| candidate.is_file())
+}
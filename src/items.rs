@@ -0,0 +1,426 @@
+use crate::config::Profile;
+use crate::config::SyncMode;
+use crate::diff::{
+    DiffOptions, THEMES_DIR, diff_keybindings, diff_library, diff_plugins, diff_settings,
+    diff_snippets, diff_startup, diff_themes,
+};
+use crate::repositories::{
+    REPOSITORIES_DIR, RepositoriesMode, merge_repositories, print_breakdown,
+};
+use crate::settings::{
+    KEYBINDINGS_FILE, SETTINGS_FILE, merge_settings, read_settings, update_settings,
+};
+use crate::snippets::SNIPPETS_DIR;
+use crate::sync::{FileAction, TransferStats, transfer_item};
+use globset::GlobSet;
+use serde::de::IgnoredAny;
+use std::fs::File;
+use std::io::BufReader;
+use std::path::Path;
+use termcolor::WriteColor;
+
+pub(crate) const STARTUP_FILE: &str = "startup.py";
+
+/// One side of a profile comparison
+pub(crate) struct DiffSide<'a> {
+    pub name: &'a str,
+    pub profile: &'a Profile,
+}
+
+/// Per-sync choices that affect how items are merged
+#[derive(Default)]
+pub(crate) struct MergeOptions {
+    pub repositories_mode: RepositoriesMode,
+    pub mode: SyncMode,
+    /// Paths inside copied directories to leave out (and leave alone in the target)
+    pub exclusions: GlobSet,
+    /// Also remove what the target has and the source doesn't where an item
+    /// would otherwise keep it (`--delete`)
+    pub delete: bool,
+    /// Work out what would change without writing anything
+    pub dry_run: bool,
+    /// Compare file contents even when size and modification time match
+    pub checksum: bool,
+    /// Leave files larger than this out of directory items (`sync.max_file_size`)
+    pub max_file_size: Option<u64>,
+}
+
+/// Knows how to handle one Binary Ninja config artifact (a file or directory
+/// inside a profile's `config_dir`).
+///
+/// Registering a handler in [`HANDLERS`] is enough for the item to show up in
+/// sync plans and, if it implements [`ItemHandler::diff`], in `diff` output.
+pub(crate) trait ItemHandler: Sync {
+    /// Path of the item relative to the config dir
+    fn name(&self) -> &'static str;
+
+    /// Short human-readable description
+    fn describe(&self) -> &'static str;
+
+    /// Whether the item is present in the given config dir
+    fn identify(&self, config_dir: &Path) -> bool {
+        config_dir.join(self.name()).exists()
+    }
+
+    /// Check the item is well-formed before it is copied anywhere
+    fn validate(&self, _config_dir: &Path) -> Result<(), String> {
+        Ok(())
+    }
+
+    /// Print extra sync plan details about the item in the source config dir
+    fn print_plan(&self, _config_dir: &Path, _options: &MergeOptions) -> Result<(), String> {
+        Ok(())
+    }
+
+    /// Bring the item from the source config dir into the target config dir
+    fn merge(
+        &self,
+        source_dir: &Path,
+        target_dir: &Path,
+        options: &MergeOptions,
+    ) -> Result<TransferStats, String> {
+        transfer_item(
+            &source_dir.join(self.name()),
+            &target_dir.join(self.name()),
+            self.name(),
+            options.mode,
+            options,
+        )
+    }
+
+    /// Section title for `diff` output, or `None` if the item isn't compared
+    fn diff_title(&self) -> Option<&'static str> {
+        None
+    }
+
+    /// Write a comparison of the item between two profiles. Returns the
+    /// number of differences found.
+    fn diff(
+        &self,
+        _out: &mut dyn WriteColor,
+        _left: &DiffSide,
+        _right: &DiffSide,
+        _options: &DiffOptions,
+    ) -> Result<usize, String> {
+        Ok(0)
+    }
+}
+
+/// Every known item, in sync order
+pub(crate) static HANDLERS: &[&dyn ItemHandler] = &[
+    &PluginsItem,
+    &RepositoriesItem,
+    &LibraryItem::new("signatures", "Custom signatures", "Signature Libraries"),
+    &ThemesItem,
+    &SnippetsItem,
+    &LibraryItem::new("types", "Type libraries", "Type Libraries"),
+    &SettingsItem,
+    &StartupItem,
+    &KeybindingsItem,
+];
+
+/// A directory of binary libraries, compared file by file
+struct LibraryItem {
+    name: &'static str,
+    description: &'static str,
+    title: &'static str,
+}
+
+impl LibraryItem {
+    const fn new(name: &'static str, description: &'static str, title: &'static str) -> Self {
+        Self {
+            name,
+            description,
+            title,
+        }
+    }
+}
+
+impl ItemHandler for LibraryItem {
+    fn name(&self) -> &'static str {
+        self.name
+    }
+
+    fn describe(&self) -> &'static str {
+        self.description
+    }
+
+    fn diff_title(&self) -> Option<&'static str> {
+        Some(self.title)
+    }
+
+    fn diff(
+        &self,
+        out: &mut dyn WriteColor,
+        left: &DiffSide,
+        right: &DiffSide,
+        options: &DiffOptions,
+    ) -> Result<usize, String> {
+        diff_library(
+            out,
+            self.name,
+            left.name,
+            left.profile,
+            right.name,
+            right.profile,
+            options,
+        )
+    }
+}
+
+struct ThemesItem;
+
+impl ItemHandler for ThemesItem {
+    fn name(&self) -> &'static str {
+        THEMES_DIR
+    }
+
+    fn describe(&self) -> &'static str {
+        "UI themes"
+    }
+
+    fn diff_title(&self) -> Option<&'static str> {
+        Some("Themes")
+    }
+
+    fn diff(
+        &self,
+        out: &mut dyn WriteColor,
+        left: &DiffSide,
+        right: &DiffSide,
+        options: &DiffOptions,
+    ) -> Result<usize, String> {
+        diff_themes(
+            out,
+            left.name,
+            left.profile,
+            right.name,
+            right.profile,
+            options,
+        )
+    }
+}
+
+struct SnippetsItem;
+
+impl ItemHandler for SnippetsItem {
+    fn name(&self) -> &'static str {
+        SNIPPETS_DIR
+    }
+
+    fn describe(&self) -> &'static str {
+        "Code snippets"
+    }
+
+    fn diff_title(&self) -> Option<&'static str> {
+        Some("Snippets")
+    }
+
+    fn diff(
+        &self,
+        out: &mut dyn WriteColor,
+        left: &DiffSide,
+        right: &DiffSide,
+        options: &DiffOptions,
+    ) -> Result<usize, String> {
+        diff_snippets(
+            out,
+            left.name,
+            left.profile,
+            right.name,
+            right.profile,
+            options,
+        )
+    }
+}
+
+struct StartupItem;
+
+impl ItemHandler for StartupItem {
+    fn name(&self) -> &'static str {
+        STARTUP_FILE
+    }
+
+    fn describe(&self) -> &'static str {
+        "Startup script"
+    }
+
+    fn diff_title(&self) -> Option<&'static str> {
+        Some("Startup Script")
+    }
+
+    fn diff(
+        &self,
+        out: &mut dyn WriteColor,
+        left: &DiffSide,
+        right: &DiffSide,
+        options: &DiffOptions,
+    ) -> Result<usize, String> {
+        diff_startup(
+            out,
+            left.name,
+            left.profile,
+            right.name,
+            right.profile,
+            options,
+        )
+    }
+}
+
+struct KeybindingsItem;
+
+impl ItemHandler for KeybindingsItem {
+    fn name(&self) -> &'static str {
+        KEYBINDINGS_FILE
+    }
+
+    fn describe(&self) -> &'static str {
+        "Key bindings"
+    }
+
+    fn validate(&self, config_dir: &Path) -> Result<(), String> {
+        validate_json(&config_dir.join(self.name()))
+    }
+
+    fn diff_title(&self) -> Option<&'static str> {
+        Some("Key Bindings")
+    }
+
+    fn diff(
+        &self,
+        out: &mut dyn WriteColor,
+        left: &DiffSide,
+        right: &DiffSide,
+        options: &DiffOptions,
+    ) -> Result<usize, String> {
+        diff_keybindings(
+            out,
+            left.name,
+            left.profile,
+            right.name,
+            right.profile,
+            options,
+        )
+    }
+}
+
+struct PluginsItem;
+
+impl ItemHandler for PluginsItem {
+    fn name(&self) -> &'static str {
+        "plugins"
+    }
+
+    fn describe(&self) -> &'static str {
+        "Manual plugin installations"
+    }
+
+    fn diff_title(&self) -> Option<&'static str> {
+        Some("Plugins")
+    }
+
+    fn diff(
+        &self,
+        out: &mut dyn WriteColor,
+        left: &DiffSide,
+        right: &DiffSide,
+        _options: &DiffOptions,
+    ) -> Result<usize, String> {
+        diff_plugins(out, left.name, left.profile, right.name, right.profile)
+    }
+}
+
+struct RepositoriesItem;
+
+impl ItemHandler for RepositoriesItem {
+    fn name(&self) -> &'static str {
+        REPOSITORIES_DIR
+    }
+
+    fn describe(&self) -> &'static str {
+        "Plugin manager data"
+    }
+
+    fn print_plan(&self, config_dir: &Path, options: &MergeOptions) -> Result<(), String> {
+        print_breakdown(config_dir, options.repositories_mode)
+    }
+
+    fn merge(
+        &self,
+        source_dir: &Path,
+        target_dir: &Path,
+        options: &MergeOptions,
+    ) -> Result<TransferStats, String> {
+        merge_repositories(source_dir, target_dir, options)
+    }
+}
+
+struct SettingsItem;
+
+impl ItemHandler for SettingsItem {
+    fn name(&self) -> &'static str {
+        SETTINGS_FILE
+    }
+
+    fn describe(&self) -> &'static str {
+        "Binary Ninja settings (merged by key)"
+    }
+
+    fn validate(&self, config_dir: &Path) -> Result<(), String> {
+        validate_json(&config_dir.join(self.name()))
+    }
+
+    /// Merge by key so settings that only exist in the target survive
+    fn merge(
+        &self,
+        source_dir: &Path,
+        target_dir: &Path,
+        options: &MergeOptions,
+    ) -> Result<TransferStats, String> {
+        let source = read_settings(source_dir)?;
+        let changed = if options.dry_run {
+            let target = read_settings(target_dir)?;
+            let mut merged = target.clone();
+            merge_settings(&mut merged, &source);
+            merged != target
+        } else {
+            update_settings(target_dir, |target| merge_settings(target, &source))?
+        };
+        let mut stats = TransferStats::default();
+        if changed {
+            stats.record(SETTINGS_FILE, FileAction::Updated);
+        } else {
+            stats.unchanged = 1;
+        }
+        Ok(stats)
+    }
+
+    fn diff_title(&self) -> Option<&'static str> {
+        Some("Settings")
+    }
+
+    fn diff(
+        &self,
+        out: &mut dyn WriteColor,
+        left: &DiffSide,
+        right: &DiffSide,
+        options: &DiffOptions,
+    ) -> Result<usize, String> {
+        diff_settings(
+            out,
+            left.name,
+            left.profile,
+            right.name,
+            right.profile,
+            options,
+        )
+    }
+}
+
+/// Check syntax only, streaming the file without building a `Value`
+fn validate_json(path: &Path) -> Result<(), String> {
+    let file = File::open(path).map_err(|e| format!("Failed to read {}: {e}", path.display()))?;
+    serde_json::from_reader::<_, IgnoredAny>(BufReader::new(file))
+        .map(|_| ())
+        .map_err(|e| format!("Invalid JSON in {}: {e}", path.display()))
+}
@@ -1,13 +1,52 @@
-use crate::config::{ENV_VAR_NAME, Profile};
-use std::path::{Path, PathBuf};
-use std::process::Command;
+use crate::config::{ENV_VAR_NAME, Profile, Rendering, cache_dir};
+use crate::crash::write_crash_bundle;
+use crate::elevation::{RunAs, wrap_command};
+use crate::history::{LaunchRecord, format_duration, record_launch};
+use crate::isolation::{NetworkMode, network_isolation};
+use crate::report::{self, outln};
+use crate::safety::guard_config_dir;
+use crate::session::save_session;
+use crate::settings::{set_key, update_settings};
+use serde_json::Value;
+use std::env;
+use std::ffi::OsString;
+use std::fs;
+use std::path::{self, Path, PathBuf};
+use std::process::{Child, Command, Stdio};
+use std::time::Instant;
+use tracing::debug;
 
-const DEBUG_LOG_FILENAME: &str = "bn-loader-debug.log";
+pub(crate) const DEBUG_LOG_FILENAME: &str = "bn-loader-debug.log";
+const DETACHED_LOGS_DIR: &str = "logs";
+
+/// Make Qt and Mesa render on the CPU: Qt Quick, Qt's OpenGL selection on
+/// Windows, the xcb platform plugin, and Mesa's llvmpipe on Linux
+const SOFTWARE_RENDERING_ENV: &[(&str, &str)] = &[
+    ("QT_QUICK_BACKEND", "software"),
+    ("QT_OPENGL", "software"),
+    ("QT_XCB_FORCE_SOFTWARE_OPENGL", "1"),
+    ("LIBGL_ALWAYS_SOFTWARE", "1"),
+];
 
 #[derive(Default)]
 pub(crate) struct LaunchOptions<'a> {
     pub debug: bool,
-    pub log_file: Option<&'a PathBuf>,
+    /// Where Binary Ninja writes its debug log
+    pub bn_log_file: Option<&'a PathBuf>,
+    /// bn-loader's own `--log-file`, which Binary Ninja must not write to
+    pub log_file: Option<&'a Path>,
+    pub dry_run: bool,
+    /// Files or extra arguments passed through to Binary Ninja
+    pub args: &'a [OsString],
+    /// Block until Binary Ninja exits and record its exit status
+    pub wait: bool,
+    /// Fully detach Binary Ninja from the terminal
+    pub detach: bool,
+    /// Launch as administrator regardless of the profile's `elevated` and
+    /// `run_as`
+    pub elevated: bool,
+    /// Launch even if config_dir doesn't look like a Binary Ninja directory
+    pub allow_suspicious_dirs: bool,
 }
 
 pub(crate) fn launch_profile(
@@ -28,6 +67,13 @@ pub(crate) fn launch_profile(
         return Err(format!("Executable not found: {}", exe_path.display()));
     }
 
+    // Binary Ninja and the settings bn-loader applies both write into it
+    guard_config_dir(
+        name,
+        &profile.config_dir,
+        options.allow_suspicious_dirs || options.dry_run,
+    )?;
+
     if !profile.config_dir.exists() {
         return Err(format!(
             "Config directory does not exist: {}",
@@ -35,59 +81,371 @@ pub(crate) fn launch_profile(
         ));
     }
 
+    let working_dir = profile.working_dir.as_ref().unwrap_or(&profile.install_dir);
+    if !working_dir.is_dir() {
+        return Err(format!(
+            "Working directory does not exist: {}",
+            working_dir.display()
+        ));
+    }
+
     let use_debug = options.debug || profile.debug;
 
-    println!("Launching profile '{name}'...");
-    println!("  Install dir: {}", profile.install_dir.display());
-    println!("  Config dir:  {}", profile.config_dir.display());
-    println!("  Executable:  {}", profile.executable);
+    outln!("Launching profile '{name}'...");
+    outln!("  Install dir: {}", profile.install_dir.display());
+    outln!("  Config dir:  {}", profile.config_dir.display());
+    outln!("  Executable:  {}", profile.executable);
+    if profile.working_dir.is_some() {
+        outln!("  Working dir: {}", working_dir.display());
+    }
+
+    let log_path = use_debug.then(|| {
+        options
+            .bn_log_file
+            .cloned()
+            .unwrap_or_else(|| profile.config_dir.join(DEBUG_LOG_FILENAME))
+    });
+    if let (Some(bn_log), Some(own_log)) = (&log_path, options.log_file)
+        && same_path(bn_log, own_log)
+    {
+        // Two writers appending to one file garble each other's lines
+        return Err(format!(
+            "Binary Ninja's debug log can't go to the --log-file too ({}); pick another file with --bn-log-file",
+            own_log.display()
+        ));
+    }
+
+    let args: Vec<OsString> = match env::current_dir() {
+        Ok(cwd) => options
+            .args
+            .iter()
+            .map(|arg| resolve_arg(arg, &cwd))
+            .collect(),
+        Err(_) => options.args.to_vec(),
+    };
+    let mut command = build_command(profile, &exe_path, working_dir, log_path.as_deref(), &args)?;
+    debug!("Built {command:?}");
+
+    if let Some(run_as) = RunAs::for_profile(profile, options.elevated) {
+        // Binary Ninja is started by a helper that exits right away
+        if options.wait || options.detach {
+            return Err(
+                "--wait and --detach can't be used with elevated or run_as launches".to_string(),
+            );
+        }
+        outln!("  Run as:      {}", run_as.describe());
+        command = wrap_command(&command, &run_as)?;
+    }
+
+    if options.detach {
+        let log = detached_log_path(name)?;
+        outln!("  Detached:    output -> {}", log.display());
+        if !options.dry_run {
+            detach(&mut command, &log)?;
+        }
+    }
+
+    // Last, so a launch that fails its checks leaves settings.json alone
+    apply_python_settings(profile, options.dry_run)?;
+
+    if options.dry_run {
+        print_command(&command);
+        outln!("\n[Dry run] Binary Ninja was not launched.");
+        return Ok(());
+    }
+
+    let started = Instant::now();
+    let mut record = LaunchRecord::new(name);
+    let mut child = match &log_path {
+        Some(log_path) => launch_debug(&mut command, log_path)?,
+        None => launch_normal(&mut command)?,
+    };
+
+    // Remember opened files so `--resume` can bring them back
+    let opened: Vec<&Path> = args.iter().map(Path::new).filter(|p| p.is_file()).collect();
+    if !opened.is_empty() {
+        save_session(name, &opened);
+    }
+
+    if !options.wait {
+        record_launch(&record);
+        return Ok(());
+    }
+
+    outln!("\nWaiting for Binary Ninja to exit...");
+    let status = child
+        .wait()
+        .map_err(|e| format!("Failed to wait for Binary Ninja: {e}"))?;
+    report::timing("run", started.elapsed());
+    let elapsed = started.elapsed().as_secs();
+    record.set_exit(status, elapsed);
+    record_launch(&record);
+
+    outln!(
+        "Binary Ninja exited ({status}) after {}.",
+        format_duration(elapsed)
+    );
 
-    if use_debug {
-        launch_debug(profile, &exe_path, options)
-    } else {
-        launch_normal(profile, &exe_path)
+    if record.failed() {
+        match write_crash_bundle(name, profile, &record, log_path.as_deref()) {
+            Ok(bundle) => outln!("Crash details collected in: {}", bundle.display()),
+            Err(e) => report::warn(&format!("Failed to write crash bundle: {e}")),
+        }
+        return Err(format!("Binary Ninja exited abnormally ({status})"));
     }
+
+    Ok(())
 }
 
-fn launch_normal(profile: &Profile, exe_path: &Path) -> Result<(), String> {
-    Command::new(exe_path)
-        .current_dir(&profile.install_dir)
-        .env(ENV_VAR_NAME, &profile.config_dir)
-        .spawn()
-        .map_err(|e| format!("Failed to launch Binary Ninja: {e}"))?;
+/// Point Binary Ninja at the profile's Python environment via settings.json
+fn apply_python_settings(profile: &Profile, dry_run: bool) -> Result<(), String> {
+    let keys = [
+        ("python.interpreter", "Python:", &profile.python_interpreter),
+        (
+            "python.virtualenv",
+            "Virtualenv:",
+            &profile.python_virtualenv,
+        ),
+    ];
+
+    let mut values = Vec::new();
+    for (key, label, path) in keys {
+        let Some(path) = path else {
+            continue;
+        };
+        let value = path
+            .to_str()
+            .ok_or_else(|| format!("Path for {key} is not valid UTF-8: {}", path.display()))?;
+        outln!("  {label:<12} {value}");
+        values.push((key, value));
+    }
+
+    if values.is_empty() || dry_run {
+        return Ok(());
+    }
+
+    update_settings(&profile.config_dir, |settings| {
+        for (key, value) in values {
+            set_key(settings, key, Value::String(value.to_string()));
+        }
+    })?;
+
     Ok(())
 }
 
-fn launch_debug(profile: &Profile, exe_path: &Path, options: &LaunchOptions) -> Result<(), String> {
-    let log_path = options
-        .log_file
-        .cloned()
-        .unwrap_or_else(|| profile.config_dir.join(DEBUG_LOG_FILENAME));
+fn build_command(
+    profile: &Profile,
+    exe_path: &Path,
+    working_dir: &Path,
+    log_path: Option<&Path>,
+    args: &[OsString],
+) -> Result<Command, String> {
+    let mut command = match profile.network {
+        NetworkMode::Default => Command::new(exe_path),
+        NetworkMode::Blocked => {
+            let isolation = network_isolation()?;
+            outln!("  Network:     blocked ({})", isolation.description);
+            let mut command = Command::new(&isolation.program);
+            command.args(&isolation.args).arg(exe_path);
+            command
+        }
+    };
+    command
+        .current_dir(working_dir)
+        .env(ENV_VAR_NAME, &profile.config_dir);
 
-    println!("  Debug mode: enabled");
-    println!("  Log file:   {}", log_path.display());
+    if profile.rendering == Rendering::Software {
+        outln!("  Rendering:   software");
+        command.envs(SOFTWARE_RENDERING_ENV.iter().copied());
+    }
 
     // Use Binary Ninja's native debug flags: -d for debug mode, -l for log file
-    let child = Command::new(exe_path)
-        .current_dir(&profile.install_dir)
-        .env(ENV_VAR_NAME, &profile.config_dir)
-        .arg("-d")
-        .arg("-l")
-        .arg(&log_path)
+    if let Some(log_path) = log_path {
+        command.arg("-d").arg("-l").arg(log_path);
+    }
+
+    command.args(args);
+
+    Ok(command)
+}
+
+fn same_path(a: &Path, b: &Path) -> bool {
+    match (path::absolute(a), path::absolute(b)) {
+        (Ok(a), Ok(b)) => a == b,
+        _ => a == b,
+    }
+}
+
+/// Binary Ninja runs from the profile's working directory, so relative paths to
+/// files that exist from `cwd`, our working directory, are made absolute
+/// before passing them on
+fn resolve_arg(arg: &OsString, cwd: &Path) -> OsString {
+    let path = Path::new(arg);
+    if path.is_relative()
+        && cwd.join(path).exists()
+        && let Ok(absolute) = path::absolute(cwd.join(path))
+    {
+        return absolute.into_os_string();
+    }
+    arg.clone()
+}
+
+fn detached_log_path(name: &str) -> Result<PathBuf, String> {
+    cache_dir()
+        .map(|dir| dir.join(DETACHED_LOGS_DIR).join(format!("{name}.log")))
+        .ok_or_else(|| "Could not determine cache directory for detached log".to_string())
+}
+
+/// Run Binary Ninja in its own session with stdio going to a log file, so
+/// closing the terminal can't take it down
+fn detach(command: &mut Command, log: &Path) -> Result<(), String> {
+    if let Some(parent) = log.parent() {
+        fs::create_dir_all(parent).map_err(|e| format!("Failed to create log directory: {e}"))?;
+    }
+    let stdout = fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(log)
+        .map_err(|e| format!("Failed to open {}: {e}", log.display()))?;
+    let stderr = stdout
+        .try_clone()
+        .map_err(|e| format!("Failed to open {}: {e}", log.display()))?;
+
+    command.stdin(Stdio::null()).stdout(stdout).stderr(stderr);
+    new_session(command);
+    Ok(())
+}
+
+#[cfg(unix)]
+fn new_session(command: &mut Command) {
+    use std::os::unix::process::CommandExt;
+    // SAFETY: setsid is async-signal-safe and touches no parent state
+    unsafe {
+        command.pre_exec(|| {
+            if libc::setsid() == -1 {
+                return Err(std::io::Error::last_os_error());
+            }
+            Ok(())
+        });
+    }
+}
+
+#[cfg(windows)]
+fn new_session(command: &mut Command) {
+    use std::os::windows::process::CommandExt;
+    const DETACHED_PROCESS: u32 = 0x0000_0008;
+    const CREATE_NEW_PROCESS_GROUP: u32 = 0x0000_0200;
+    command.creation_flags(DETACHED_PROCESS | CREATE_NEW_PROCESS_GROUP);
+}
+
+#[cfg(not(any(unix, windows)))]
+fn new_session(_command: &mut Command) {}
+
+/// Print everything needed to reproduce the launch by hand
+fn print_command(command: &Command) {
+    outln!("\nCommand:");
+    outln!(
+        "  Program:     {}",
+        Path::new(command.get_program()).display()
+    );
+    if let Some(dir) = command.get_current_dir() {
+        outln!("  Working dir: {}", dir.display());
+    }
+    for (key, value) in command.get_envs() {
+        let value = value.map_or_else(
+            || "(removed)".to_string(),
+            |v| v.to_string_lossy().into_owned(),
+        );
+        outln!("  Env:         {}={value}", key.to_string_lossy());
+    }
+    for arg in command.get_args() {
+        outln!("  Arg:         {}", arg.to_string_lossy());
+    }
+}
+
+fn launch_normal(command: &mut Command) -> Result<Child, String> {
+    command
+        .spawn()
+        .map_err(|e| format!("Failed to launch Binary Ninja: {e}"))
+}
+
+fn launch_debug(command: &mut Command, log_path: &Path) -> Result<Child, String> {
+    outln!("  Debug mode: enabled");
+    outln!("  Log file:   {}", log_path.display());
+
+    let child = command
         .spawn()
         .map_err(|e| format!("Failed to launch Binary Ninja: {e}"))?;
 
-    println!("\nBinary Ninja launched (PID: {}).", child.id());
-    println!("Debug logs will be written to: {}", log_path.display());
+    outln!("\nBinary Ninja launched (PID: {}).", child.id());
+    outln!("Debug logs will be written to: {}", log_path.display());
 
     #[cfg(windows)]
-    println!(
+    outln!(
         "\nTo monitor: Get-Content -Path \"{}\" -Wait",
         log_path.display()
     );
 
     #[cfg(not(windows))]
-    println!("\nTo monitor: tail -f \"{}\"", log_path.display());
+    outln!("\nTo monitor: tail -f \"{}\"", log_path.display());
 
-    Ok(())
+    Ok(child)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::strategies::{absolute_path, any_name, file_name, relative_path};
+    use proptest::prelude::*;
+
+    proptest! {
+        #[test]
+        fn build_command_passes_paths_through(
+            exe_path in absolute_path(),
+            working_dir in absolute_path(),
+            config_dir in absolute_path(),
+            log_path in proptest::option::of(absolute_path()),
+            args in prop::collection::vec(any_name(), 0..4),
+        ) {
+            let profile = Profile { config_dir: config_dir.clone(), ..Profile::default() };
+            let args: Vec<OsString> = args.into_iter().map(OsString::from).collect();
+            let command =
+                build_command(&profile, &exe_path, &working_dir, log_path.as_deref(), &args).unwrap();
+
+            prop_assert_eq!(command.get_program(), exe_path.as_os_str());
+            prop_assert_eq!(command.get_current_dir(), Some(working_dir.as_path()));
+            let env: Vec<_> = command.get_envs().collect();
+            prop_assert_eq!(env, [(ENV_VAR_NAME.as_ref(), Some(config_dir.as_os_str()))]);
+
+            let mut expected: Vec<OsString> = match &log_path {
+                Some(log) => vec!["-d".into(), "-l".into(), log.into()],
+                None => Vec::new(),
+            };
+            expected.extend(args);
+            prop_assert_eq!(command.get_args().collect::<Vec<_>>(), expected);
+        }
+
+        #[test]
+        fn resolve_arg_leaves_other_args_alone(arg in any_name(), path in absolute_path()) {
+            let cwd = tempfile::tempdir().unwrap();
+            let arg = OsString::from(arg);
+            prop_assume!(!cwd.path().join(&arg).exists());
+            prop_assert_eq!(resolve_arg(&arg, cwd.path()), arg);
+            let path = path.into_os_string();
+            prop_assert_eq!(resolve_arg(&path, cwd.path()), path);
+        }
+
+        #[test]
+        fn resolve_arg_makes_existing_paths_absolute(dir in relative_path(), name in file_name()) {
+            let cwd = tempfile::tempdir().unwrap();
+            fs::create_dir_all(cwd.path().join(&dir)).unwrap();
+            fs::write(cwd.path().join(&dir).join(&name), &name).unwrap();
+
+            let arg = dir.join(&name).into_os_string();
+            let resolved = PathBuf::from(resolve_arg(&arg, cwd.path()));
+            prop_assert!(resolved.is_absolute(), "{}", resolved.display());
+            prop_assert!(resolved.ends_with(&name));
+            prop_assert_eq!(fs::read_to_string(resolved).unwrap(), name);
+        }
+    }
 }
@@ -0,0 +1,158 @@
+use crate::colors::paint_stderr;
+use crate::report;
+use std::fmt;
+use std::fs::{File, OpenOptions};
+use std::io::{self, Write};
+use std::path::Path;
+use std::sync::Mutex;
+use termcolor::Color;
+use tracing::field::Field;
+use tracing::{Event, Level, Metadata, Subscriber};
+use tracing_subscriber::Layer;
+use tracing_subscriber::filter::filter_fn;
+use tracing_subscriber::fmt::format::{Writer, debug_fn};
+use tracing_subscriber::fmt::{FmtContext, FormatEvent, FormatFields, MakeWriter};
+use tracing_subscriber::layer::SubscriberExt;
+use tracing_subscriber::registry::LookupSpan;
+use tracing_subscriber::util::SubscriberInitExt;
+
+/// Target of bn-loader's own events; dependencies' are left out
+const TARGET: &str = env!("CARGO_CRATE_NAME");
+
+/// Events as bn-loader prints them: information as plain lines, anything
+/// else prefixed with its level
+struct HumanFormat;
+
+impl<S, N> FormatEvent<S, N> for HumanFormat
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+    N: for<'a> FormatFields<'a> + 'static,
+{
+    fn format_event(
+        &self,
+        ctx: &FmtContext<'_, S, N>,
+        mut writer: Writer<'_>,
+        event: &Event<'_>,
+    ) -> fmt::Result {
+        let prefix = match *event.metadata().level() {
+            Level::ERROR => paint_stderr("Error:", Color::Red) + " ",
+            Level::WARN => paint_stderr("Warning:", Color::Yellow) + " ",
+            Level::INFO => String::new(),
+            Level::DEBUG => "debug: ".to_string(),
+            Level::TRACE => "trace: ".to_string(),
+        };
+        write!(writer, "{prefix}")?;
+        ctx.field_format().format_fields(writer.by_ref(), event)?;
+        writeln!(writer)
+    }
+}
+
+/// Information goes to stdout, unless it is reserved for data or silenced;
+/// everything else goes to stderr
+struct Terminal;
+
+impl<'a> MakeWriter<'a> for Terminal {
+    type Writer = Box<dyn Write + 'a>;
+
+    fn make_writer(&'a self) -> Self::Writer {
+        Box::new(io::stdout())
+    }
+
+    fn make_writer_for(&'a self, meta: &Metadata<'_>) -> Self::Writer {
+        if *meta.level() != Level::INFO {
+            Box::new(io::stderr())
+        } else if report::quiet() {
+            Box::new(io::sink())
+        } else if report::stdout_is_data() {
+            Box::new(io::stderr())
+        } else {
+            Box::new(io::stdout())
+        }
+    }
+}
+
+/// Most detailed level shown on the terminal: `-q` leaves warnings and
+/// errors, each `-v` adds a level
+fn terminal_level(verbose: u8, quiet: bool) -> Level {
+    match (quiet, verbose) {
+        (true, _) => Level::WARN,
+        (false, 0) => Level::INFO,
+        (false, 1) => Level::DEBUG,
+        (false, _) => Level::TRACE,
+    }
+}
+
+/// A message as it was written, with the colors
+/// [`paint`](crate::colors::paint) adds kept for the terminal
+fn write_message(writer: &mut Writer<'_>, field: &Field, value: &dyn fmt::Debug) -> fmt::Result {
+    if field.name() == "message" {
+        write!(writer, "{value:?}")
+    } else {
+        write!(writer, " {field}={value:?}")
+    }
+}
+
+/// A message without colors, for the log file
+fn write_plain_message(
+    writer: &mut Writer<'_>,
+    field: &Field,
+    value: &dyn fmt::Debug,
+) -> fmt::Result {
+    let text = format!("{value:?}");
+    let mut plain = String::with_capacity(text.len());
+    let mut chars = text.chars();
+    while let Some(c) = chars.next() {
+        if c == '\x1b' {
+            // Up to the letter that ends the escape sequence
+            chars.by_ref().find(char::is_ascii_alphabetic);
+        } else {
+            plain.push(c);
+        }
+    }
+    write_message(writer, field, &format_args!("{plain}"))
+}
+
+fn open_log(path: &Path) -> Result<File, String> {
+    OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .map_err(|e| format!("Failed to open log file {}: {e}", path.display()))
+}
+
+/// Send bn-loader's output through `tracing`: to the terminal at the level
+/// `-v`/`-q` choose, and with timestamps down to debug (trace with `-vv`) to
+/// `log_file` if given. The terminal is set up even if the file can't be
+/// opened.
+pub(crate) fn init(verbose: u8, quiet: bool, log_file: Option<&Path>) -> Result<(), String> {
+    let level = terminal_level(verbose, quiet);
+    let terminal = tracing_subscriber::fmt::layer()
+        .event_format(HumanFormat)
+        .fmt_fields(debug_fn(write_message))
+        .with_writer(Terminal)
+        .with_filter(filter_fn(move |meta| {
+            meta.target().starts_with(TARGET) && *meta.level() <= level
+        }));
+
+    let file_level = level.max(Level::DEBUG);
+    let (file, result) = match log_file.map(open_log).transpose() {
+        Ok(file) => (file, Ok(())),
+        Err(e) => (None, Err(e)),
+    };
+    let file = file.map(|file| {
+        tracing_subscriber::fmt::layer()
+            .with_target(false)
+            .fmt_fields(debug_fn(write_plain_message))
+            .with_writer(Mutex::new(file))
+            .with_filter(filter_fn(move |meta| {
+                meta.target().starts_with(TARGET) && *meta.level() <= file_level
+            }))
+    });
+
+    tracing_subscriber::registry()
+        .with(terminal)
+        .with(file)
+        .try_init()
+        .map_err(|e| format!("Failed to set up logging: {e}"))?;
+    result
+}
@@ -1,25 +1,110 @@
+mod associate;
+mod backups;
+mod bn_version;
 mod colors;
 mod completions;
 mod config;
+mod copy;
+mod crash;
 mod diff;
+mod diff_apply;
+mod diff_base;
+mod diff_defaults;
+mod diff_report;
+mod download;
+mod elevation;
+mod history;
 mod init;
+mod installations;
+mod isolation;
+mod items;
 mod launch;
+mod logging;
+mod manifest;
+mod migrate;
+mod notify;
+mod pager;
+#[cfg(test)]
+mod peak_memory;
+mod picker;
+mod plugin_compat;
+mod plugin_conflicts;
+mod plugin_deps;
+mod plugin_index;
+mod plugin_link;
+mod plugin_list;
+mod plugin_lock;
+mod plugin_manager;
+mod plugin_matrix;
+mod plugin_store;
+mod plugin_verify;
 mod plugins;
+mod progress;
+mod pyenv;
+mod report;
+mod repositories;
+mod safety;
+mod session;
+mod settings;
+mod shim;
+mod shortcuts;
+mod snippets;
+#[cfg(test)]
+mod strategies;
 mod sync;
+mod sync_report;
+#[cfg(feature = "tui")]
+mod tui;
+#[cfg(feature = "updates")]
 mod update;
 
-use clap::{CommandFactory, Parser, Subcommand, ValueEnum};
+use associate::{AssociateOptions, run_associate};
+use backups::{PruneOptions, RestoreOptions, parse_age, print_backups, run_prune, run_restore};
+use bn_version::{BnVersion, detect_version};
+use clap::{ArgAction, CommandFactory, Parser, Subcommand, ValueEnum};
 use clap_complete::CompleteEnv;
-use clap_complete::engine::{ArgValueCandidates, CompletionCandidate};
-use config::{CONFIG_FILE_NAME, Config, find_config_file, load_config};
-use diff::diff_profiles;
+use clap_complete::engine::{ArgValueCandidates, ArgValueCompleter, CompletionCandidate};
+use config::{CONFIG_FILE_NAME, ColorMode, Config, Profile, find_config_file, load_config};
+use diff::{
+    DIFF_ERROR_EXIT_CODE, DIFFERENCES_EXIT_CODE, DiffOptions, diff_profiles, resolve_diff_side,
+};
+use diff_apply::{DiffApplyOptions, run_diff_apply};
+use diff_base::{diff_three_way, run_drift_summary};
+use diff_defaults::diff_defaults;
 use init::{InitOptions, run_init};
+use installations::{RelocateOptions, relocate};
+use items::DiffSide;
 use launch::{LaunchOptions, launch_profile};
-use plugins::{list_plugins, print_plugins};
+use migrate::{MigrateOptions, MigrateSource, run_migrate};
+use plugin_compat::run_compat;
+use plugin_conflicts::run_conflicts;
+use plugin_deps::{DepsOptions, run_deps};
+use plugin_link::{LinkOptions, run_link, run_unlink};
+use plugin_list::{ListApplyOptions, PLUGIN_LIST_FILE, run_export, run_list_apply};
+use plugin_lock::{ApplyOptions, LOCK_FILE, run_apply, run_lock};
+use plugin_manager::{
+    CopyOptions, InstallOptions, RemoveOptions, ToggleOptions, UpdateOptions, run_copy, run_du,
+    run_install, run_outdated, run_remove, run_search, run_show, run_toggle, run_update,
+};
+use plugin_matrix::run_matrix;
+use plugin_store::{DedupeOptions, run_dedupe, run_gc};
+use plugin_verify::VerifyOptions;
+use plugins::{
+    PluginSort, PluginSource, filter_plugins, list_plugins, print_plugins, print_plugins_json,
+    sort_plugins,
+};
+use report::{dataln, outln};
+use repositories::{RepositoriesMode, format_size, tree_size};
+use shim::{ShimOptions, install_shim, remove_shim};
+use shortcuts::{ShortcutOptions, create_shortcuts};
+use snippets::{SnippetAddOptions, SnippetCopyOptions, SnippetRemoveOptions};
+use std::collections::{BTreeMap, HashMap};
 use std::env;
+use std::ffi::OsString;
 use std::path::{Path, PathBuf};
-use std::process;
-use sync::{SyncOptions, run_sync};
+use std::time::Duration;
+use sync::{DRIFT_EXIT_CODE, SyncOptions, auto_sync, build_glob_set, run_sync, run_verify};
+use sync_report::ReportFormat;
 
 /// Get profile names from config for shell completion
 fn profile_completer() -> Vec<CompletionCandidate> {
@@ -29,6 +114,51 @@ fn profile_completer() -> Vec<CompletionCandidate> {
         .unwrap_or_default()
 }
 
+/// Get installation IDs from config for shell completion
+fn installation_completer() -> Vec<CompletionCandidate> {
+    find_config_file(None)
+        .and_then(|p| load_config(&p).ok())
+        .map(|c| {
+            c.installations
+                .iter()
+                .map(|(id, installation)| {
+                    CompletionCandidate::new(id)
+                        .help(Some(installation.path.display().to_string().into()))
+                })
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// Get sync preset names from config for shell completion
+fn preset_completer() -> Vec<CompletionCandidate> {
+    find_config_file(None)
+        .and_then(|p| load_config(&p).ok())
+        .map(|c| {
+            c.sync
+                .presets
+                .keys()
+                .map(CompletionCandidate::new)
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// Get sync item names for shell completion
+fn item_completer() -> Vec<CompletionCandidate> {
+    items::HANDLERS
+        .iter()
+        .map(|h| CompletionCandidate::new(h.name()).help(Some(h.describe().into())))
+        .collect()
+}
+
+fn diff_section_completer() -> Vec<CompletionCandidate> {
+    diff::diff_sections()
+        .into_iter()
+        .map(CompletionCandidate::new)
+        .collect()
+}
+
 #[derive(Parser)]
 #[command(name = "bn-loader", version, about = "Binary Ninja profile launcher")]
 struct Cli {
@@ -39,58 +169,816 @@ struct Cli {
     #[arg(long, short = 'c', global = true)]
     config: Option<PathBuf>,
 
+    /// Write a JSON summary of the run to this file on exit
+    #[arg(long, global = true, value_name = "FILE")]
+    result_file: Option<PathBuf>,
+
+    /// Modify config directories even if they don't look like Binary Ninja's
+    #[arg(long, global = true)]
+    i_know_what_im_doing: bool,
+
+    /// Don't draw progress bars for large copies
+    #[arg(long, global = true)]
+    no_progress: bool,
+
+    /// Don't page long diffs and plugin listings
+    #[arg(long, global = true)]
+    no_pager: bool,
+
+    /// Print structured JSON instead of text (--list, --check-update,
+    /// plugins, diff and sync)
+    #[arg(long, global = true)]
+    json: bool,
+
+    /// Show more of what bn-loader does (-vv for every file)
+    #[arg(long, short, global = true, action = ArgAction::Count)]
+    verbose: u8,
+
+    /// When to color output (default: `global.color` from the config)
+    #[arg(long, global = true, value_enum, value_name = "WHEN")]
+    color: Option<ColorMode>,
+
+    /// Hide progress and information, printing only results, warnings and errors
+    #[arg(long, short, global = true, conflicts_with = "verbose")]
+    quiet: bool,
+
+    /// Log what bn-loader does to this file, with timestamps (with --debug
+    /// and no --bn-log-file, still Binary Ninja's debug log, as it used to be)
+    #[arg(long, global = true, value_name = "FILE")]
+    log_file: Option<PathBuf>,
+
     /// List available profiles
     #[arg(long, short = 'l')]
     list: bool,
 
+    /// With --list, print one tab-separated line per profile in a format
+    /// that won't change between releases
+    #[arg(long, requires = "list", conflicts_with = "json")]
+    porcelain: bool,
+
+    /// With --list, show each profile's plugin count, config size and last
+    /// launch as a table
+    #[arg(long, requires = "list", conflicts_with = "porcelain")]
+    long: bool,
+
     /// Profile name to launch
     #[arg(conflicts_with = "list", add = ArgValueCandidates::new(profile_completer))]
     profile: Option<String>,
 
-    /// Enable debug logging (redirects output to log file)
-    #[arg(long)]
-    debug: bool,
+    /// Files to open (use `--` before arguments meant for Binary Ninja)
+    #[arg(requires = "profile", add = ArgValueCompleter::new(completions::launch_file_completer))]
+    args: Vec<OsString>,
+
+    /// Enable debug logging (redirects output to log file)
+    #[arg(long)]
+    debug: bool,
+
+    /// With --debug, write Binary Ninja's debug log to this file (default:
+    /// bn-loader-debug.log in the config directory)
+    #[arg(long, value_name = "FILE")]
+    bn_log_file: Option<PathBuf>,
+
+    /// Wait for Binary Ninja to exit and record its exit status
+    #[arg(long)]
+    wait: bool,
+
+    /// Detach Binary Ninja from the terminal, logging its output to a file
+    #[arg(long, conflicts_with = "wait")]
+    detach: bool,
+
+    /// Launch Binary Ninja as administrator (Windows UAC prompt)
+    #[arg(long)]
+    elevated: bool,
+
+    /// Re-open the files from the profile's last launch
+    #[arg(long, requires = "profile")]
+    resume: bool,
+
+    /// Print the command that would be run without launching
+    #[arg(long)]
+    dry_run: bool,
+
+    /// Check for updates and exit
+    #[arg(long, hide = !cfg!(feature = "updates"))]
+    check_update: bool,
+}
+
+#[derive(Subcommand)]
+enum Commands {
+    /// Create a new profile from a template
+    Init {
+        /// Name for the new profile
+        name: String,
+
+        /// Source profile for license and `install_dir`
+        #[arg(long, add = ArgValueCandidates::new(profile_completer))]
+        template: String,
+
+        /// Directory for new profile's config
+        #[arg(long)]
+        config_dir: PathBuf,
+    },
+
+    /// Sync config between profiles
+    Sync {
+        /// Source profile to sync from
+        #[arg(long, add = ArgValueCandidates::new(profile_completer))]
+        from: String,
+
+        /// Target profile (default: all other profiles)
+        #[arg(long, add = ArgValueCandidates::new(profile_completer))]
+        to: Option<String>,
+
+        /// Pull changes made in --from back into the --into profile
+        #[arg(long, requires = "into", conflicts_with_all = ["to", "link"])]
+        pull: bool,
+
+        /// Profile to pull into (with --pull)
+        #[arg(long, requires = "pull", add = ArgValueCandidates::new(profile_completer))]
+        into: Option<String>,
+
+        /// Additional exclusion pattern (can be repeated)
+        #[arg(long, action = clap::ArgAction::Append)]
+        exclude: Vec<String>,
+
+        /// Take items, exclusions and targets from `[sync.presets.<name>]`
+        #[arg(long, add = ArgValueCandidates::new(preset_completer))]
+        preset: Option<String>,
+
+        /// Only sync this item, e.g. `themes` or `keybindings.json` (can be repeated)
+        #[arg(long, action = clap::ArgAction::Append, add = ArgValueCandidates::new(item_completer))]
+        only: Vec<String>,
+
+        /// Show what would be synced without changes
+        #[arg(long)]
+        dry_run: bool,
+
+        /// Skip confirmation prompt
+        #[arg(long, short)]
+        yes: bool,
+
+        /// Overwrite target files that are newer than the source's
+        #[arg(long)]
+        force: bool,
+
+        /// Make targets exact mirrors: remove items the source doesn't have
+        #[arg(long)]
+        delete: bool,
+
+        /// Link directory items to the source's instead of copying them
+        #[arg(long)]
+        link: bool,
+
+        /// How much of repositories/ to transfer
+        #[arg(long, value_enum, default_value = "full")]
+        repositories_mode: RepositoriesMode,
+
+        /// Print a machine-readable report of what was synced to stdout
+        /// (other output moves to stderr)
+        #[arg(long, value_enum)]
+        report: Option<ReportFormat>,
+
+        /// Write the report to this file instead of stdout
+        #[arg(long, requires = "report", value_name = "FILE")]
+        report_file: Option<PathBuf>,
+
+        /// Shell command to run after each target is synced (overrides
+        /// `sync.post_hook`)
+        #[arg(long, value_name = "COMMAND")]
+        hook: Option<String>,
+
+        /// Compare the source with the targets file by file (hashing
+        /// contents) and report drift without copying anything; exits with
+        /// status 3 if anything differs
+        #[arg(long, conflicts_with_all = ["pull", "dry_run", "yes", "force", "link", "report", "hook"])]
+        verify: bool,
+    },
+
+    /// Restore a profile's items from one of its sync backups
+    Restore {
+        /// Profile to restore
+        #[arg(add = ArgValueCandidates::new(profile_completer))]
+        profile: String,
+
+        /// Timestamp of the backup (default: pick from a list)
+        #[arg(conflicts_with = "latest")]
+        backup: Option<u64>,
+
+        /// Restore the most recent backup
+        #[arg(long)]
+        latest: bool,
+
+        /// Only restore this item (can be repeated)
+        #[arg(long, action = clap::ArgAction::Append, add = ArgValueCandidates::new(item_completer))]
+        only: Vec<String>,
+
+        /// Show what would be restored without changes
+        #[arg(long)]
+        dry_run: bool,
+
+        /// Skip confirmation prompt
+        #[arg(long, short)]
+        yes: bool,
+    },
+
+    /// List or prune sync backups across profiles
+    Backups {
+        #[command(subcommand)]
+        action: BackupsAction,
+    },
+
+    /// List plugins for a profile, or share a profile's plugin list
+    #[command(args_conflicts_with_subcommands = true, subcommand_negates_reqs = true)]
+    Plugins {
+        #[command(subcommand)]
+        action: Option<PluginsAction>,
+
+        /// Profile name
+        #[arg(
+            required_unless_present = "all",
+            add = ArgValueCandidates::new(profile_completer)
+        )]
+        profile: Option<String>,
+
+        /// Show a plugin x profile table of every profile instead
+        #[arg(long, conflicts_with = "profile")]
+        all: bool,
+
+        /// Only plugins from this source
+        #[arg(long, value_enum)]
+        source: Option<PluginSource>,
+
+        /// Only plugins whose name, directory or author contains this
+        #[arg(long)]
+        search: Option<String>,
+
+        /// Order within each source
+        #[arg(long, value_enum, default_value = "name", conflicts_with = "all")]
+        sort: PluginSort,
+    },
+
+    /// Install and manage individual plugins
+    Plugin {
+        #[command(subcommand)]
+        action: PluginAction,
+    },
+
+    /// List, add, remove and copy a profile's snippets
+    Snippets {
+        #[command(subcommand)]
+        action: SnippetsAction,
+    },
+
+    /// Compare two profiles (or sync backups, as `profile@timestamp`)
+    Diff {
+        /// First profile (with --all, the baseline; default: the default profile)
+        #[arg(required_unless_present = "all", add = ArgValueCandidates::new(profile_completer))]
+        profile1: Option<String>,
+
+        /// Second profile
+        #[arg(required_unless_present_any = ["all", "defaults"], add = ArgValueCandidates::new(profile_completer))]
+        profile2: Option<String>,
+
+        /// Summarize how every profile differs from the baseline instead
+        #[arg(long, conflicts_with_all = ["profile2", "python", "apply", "base"])]
+        all: bool,
+
+        /// Compare the profile's settings with Binary Ninja's defaults instead,
+        /// showing only the keys it overrides
+        #[arg(long, conflicts_with_all = ["profile2", "all", "python", "apply", "base", "only", "skip", "output"])]
+        defaults: bool,
+
+        /// Only compare this section, e.g. `settings` or `plugins` (can be repeated)
+        #[arg(long, action = clap::ArgAction::Append, conflicts_with_all = ["all", "base"], add = ArgValueCandidates::new(diff_section_completer))]
+        only: Vec<String>,
+
+        /// Show every difference with complete values, not the first 20
+        /// with long values shortened
+        #[arg(long)]
+        full: bool,
+
+        /// Leave this section out (can be repeated)
+        #[arg(long, action = clap::ArgAction::Append, conflicts_with_all = ["all", "base"], add = ArgValueCandidates::new(diff_section_completer))]
+        skip: Vec<String>,
+
+        /// Also compare the Python packages each profile's environment sees
+        #[arg(long)]
+        python: bool,
+
+        /// Afterwards, offer to copy each differing setting, plugin and key
+        /// binding from one profile to the other
+        #[arg(long)]
+        apply: bool,
+
+        /// Compare both profiles against this common base instead, showing
+        /// which one changed what
+        #[arg(long, value_name = "PROFILE", conflicts_with_all = ["python", "apply"], add = ArgValueCandidates::new(profile_completer))]
+        base: Option<String>,
+
+        /// Write every difference to an HTML or Markdown report (.html or
+        /// .md) instead of printing them
+        #[arg(long, short, conflicts_with_all = ["all", "base", "apply"])]
+        output: Option<PathBuf>,
+    },
+
+    /// Open .bndb files (and optionally binaries) through a profile
+    Associate {
+        /// Profile that should handle the files
+        #[arg(long, add = ArgValueCandidates::new(profile_completer))]
+        profile: String,
+
+        /// Also register as a handler for executables and libraries
+        #[arg(long)]
+        binaries: bool,
+    },
+
+    /// Show recent launches and per-profile usage
+    History {
+        /// Only show launches of this profile
+        #[arg(add = ArgValueCandidates::new(profile_completer))]
+        profile: Option<String>,
+
+        /// Number of recent launches to show
+        #[arg(long, short = 'n', default_value_t = 20)]
+        limit: usize,
+    },
+
+    /// Manage Windows shortcuts for profiles
+    Shortcuts {
+        #[command(subcommand)]
+        action: ShortcutAction,
+    },
+
+    /// Import key bindings from IDA or Ghidra into a profile
+    Migrate {
+        /// Tool to migrate from
+        #[arg(long, value_enum)]
+        from: MigrateSource,
+
+        /// Config file or directory of the other tool (e.g. ~/.idapro, ~/.ghidra)
+        path: PathBuf,
+
+        /// Profile to write keybindings.json for
+        #[arg(long, add = ArgValueCandidates::new(profile_completer))]
+        profile: String,
+
+        /// Show the mapping without writing anything
+        #[arg(long)]
+        dry_run: bool,
+    },
+
+    /// Manage Binary Ninja installations shared by profiles
+    Bn {
+        #[command(subcommand)]
+        action: BnAction,
+    },
+
+    /// Manage a `binaryninja` command that launches a profile
+    Shim {
+        #[command(subcommand)]
+        action: ShimAction,
+    },
+
+    /// Browse profiles, plugins and backups, and launch, sync or diff from
+    /// the keyboard
+    #[command(hide = !cfg!(feature = "tui"))]
+    Tui,
+
+    /// Generate shell completions
+    Completions {
+        /// Shell type
+        #[arg(value_enum)]
+        shell: ShellType,
+    },
+}
+
+#[derive(Subcommand)]
+enum ShortcutAction {
+    /// Create .lnk shortcuts that launch profiles
+    Create {
+        /// Only create a shortcut for this profile (default: all profiles)
+        #[arg(long, add = ArgValueCandidates::new(profile_completer))]
+        profile: Option<String>,
+
+        /// Directory to write shortcuts to (default: Desktop)
+        #[arg(long)]
+        dir: Option<PathBuf>,
+
+        /// Also add entries to the Start Menu
+        #[arg(long)]
+        start_menu: bool,
+    },
+}
+
+#[derive(Subcommand)]
+enum BnAction {
+    /// Point an installation at a new path, updating every profile that uses it
+    Relocate {
+        /// Installation ID from `[installations]`
+        #[arg(add = ArgValueCandidates::new(installation_completer))]
+        id: String,
+
+        /// Directory Binary Ninja now lives in
+        new_path: PathBuf,
+    },
+}
+
+#[derive(Subcommand)]
+enum PluginsAction {
+    /// Write the plugins a profile has to a list others can apply
+    Export {
+        /// Profile to export
+        #[arg(add = ArgValueCandidates::new(profile_completer))]
+        profile: String,
+
+        /// File to write (default: print to stdout)
+        #[arg(long, short)]
+        output: Option<PathBuf>,
+    },
+
+    /// Show everything known about one plugin of a profile
+    Show {
+        /// Plugin name or directory name
+        name: String,
+
+        /// Profile the plugin is in
+        #[arg(long, add = ArgValueCandidates::new(profile_completer))]
+        profile: String,
+    },
+
+    /// Show each plugin's size on disk and when it last changed, largest first
+    Du {
+        /// Profile to report on
+        #[arg(add = ArgValueCandidates::new(profile_completer))]
+        profile: String,
+    },
+
+    /// Flag plugins that need a newer or older Binary Ninja than the profile runs
+    Compat {
+        /// Profile to check
+        #[arg(add = ArgValueCandidates::new(profile_completer))]
+        profile: String,
+
+        /// Check against this build number instead of the installed version
+        #[arg(long)]
+        build: Option<u64>,
+    },
+
+    /// Find plugins installed twice, sharing a name or registering the same command
+    Conflicts {
+        /// Profile to check (default: every profile)
+        #[arg(add = ArgValueCandidates::new(profile_completer))]
+        profile: Option<String>,
+    },
+
+    /// Install the plugins of an exported list into a profile
+    Apply {
+        /// Plugin list to apply
+        #[arg(default_value = PLUGIN_LIST_FILE)]
+        file: PathBuf,
+
+        /// Profile to apply it to
+        #[arg(long, add = ArgValueCandidates::new(profile_completer))]
+        profile: String,
+
+        /// Also remove plugins the list doesn't have
+        #[arg(long)]
+        prune: bool,
+
+        /// Show what would change without changes
+        #[arg(long)]
+        dry_run: bool,
+
+        /// Skip confirmation prompt
+        #[arg(long, short)]
+        yes: bool,
+    },
+}
+
+#[derive(Subcommand)]
+enum PluginAction {
+    /// Download a plugin from the profile's plugin repositories and install it
+    #[command(hide = !cfg!(feature = "plugin-index"))]
+    Install {
+        /// Plugin name (or directory name) from the repository listing
+        name: String,
+
+        /// Profile to install into
+        #[arg(long, add = ArgValueCandidates::new(profile_completer))]
+        profile: String,
+
+        /// Unpack into plugins/ instead, outside Binary Ninja's plugin manager
+        #[arg(long)]
+        manual: bool,
+
+        /// Reinstall a plugin that is already installed
+        #[arg(long)]
+        force: bool,
+    },
+
+    /// Delete an installed plugin, backing it up first
+    Remove {
+        /// Plugin name or directory name
+        name: String,
+
+        /// Profile to remove it from
+        #[arg(long, add = ArgValueCandidates::new(profile_completer))]
+        profile: String,
+
+        /// Skip confirmation prompt
+        #[arg(long, short)]
+        yes: bool,
+    },
+
+    /// Copy one installed plugin to another profile, backing up the target
+    Copy {
+        /// Plugin name or directory name
+        name: String,
+
+        /// Profile to copy from
+        #[arg(long, add = ArgValueCandidates::new(profile_completer))]
+        from: String,
+
+        /// Profile to copy into
+        #[arg(long, add = ArgValueCandidates::new(profile_completer))]
+        to: String,
+
+        /// Replace the plugin if the target already has it
+        #[arg(long)]
+        force: bool,
+
+        /// Skip confirmation prompt
+        #[arg(long, short)]
+        yes: bool,
+    },
+
+    /// Turn repository plugins back on
+    Enable {
+        /// Plugin names or directory names
+        #[arg(required = true)]
+        names: Vec<String>,
+
+        /// Profile the plugins are installed in
+        #[arg(long, add = ArgValueCandidates::new(profile_completer))]
+        profile: String,
+    },
+
+    /// Turn repository plugins off without uninstalling them
+    Disable {
+        /// Plugin names or directory names
+        #[arg(required = true)]
+        names: Vec<String>,
+
+        /// Profile the plugins are installed in
+        #[arg(long, add = ArgValueCandidates::new(profile_completer))]
+        profile: String,
+    },
+
+    /// Search the official and community repositories
+    #[command(hide = !cfg!(feature = "plugin-index"))]
+    Search {
+        /// Text to look for in plugin names, descriptions and authors
+        term: String,
+
+        /// Mark the plugins this profile already has
+        #[arg(long, add = ArgValueCandidates::new(profile_completer))]
+        profile: Option<String>,
+    },
+
+    /// List repository plugins with newer versions upstream
+    #[command(hide = !cfg!(feature = "plugin-index"))]
+    Outdated {
+        /// Only check this profile (default: every profile)
+        #[arg(long, add = ArgValueCandidates::new(profile_completer))]
+        profile: Option<String>,
+    },
+
+    /// Download newer versions of a profile's repository plugins
+    #[command(hide = !cfg!(feature = "plugin-index"))]
+    Update {
+        /// Plugins to update, by name or directory name
+        #[arg(required_unless_present = "all", conflicts_with = "all")]
+        names: Vec<String>,
+
+        /// Update every repository plugin with a newer version upstream
+        #[arg(long)]
+        all: bool,
+
+        /// Profile the plugins are installed in
+        #[arg(long, add = ArgValueCandidates::new(profile_completer))]
+        profile: String,
+
+        /// Show what would be updated without changes
+        #[arg(long)]
+        dry_run: bool,
+
+        /// Skip confirmation prompt
+        #[arg(long, short)]
+        yes: bool,
+    },
+
+    /// Pin every plugin of a profile, with versions and commits, in a lock file
+    Lock {
+        /// Profile to lock
+        #[arg(long, add = ArgValueCandidates::new(profile_completer))]
+        profile: String,
+
+        /// Lock file to write
+        #[arg(long, short, default_value = LOCK_FILE)]
+        output: PathBuf,
+    },
+
+    /// Install the exact plugins of a lock file into a profile
+    Apply {
+        /// Lock file to apply
+        #[arg(default_value = LOCK_FILE)]
+        lock_file: PathBuf,
+
+        /// Profile to apply it to
+        #[arg(long, add = ArgValueCandidates::new(profile_completer))]
+        profile: String,
+
+        /// Show what would change without changes
+        #[arg(long)]
+        dry_run: bool,
+
+        /// Skip confirmation prompt
+        #[arg(long, short)]
+        yes: bool,
+    },
+
+    /// List the Python packages installed plugins need but the profile lacks
+    Deps {
+        /// Profile to check
+        #[arg(add = ArgValueCandidates::new(profile_completer))]
+        profile: String,
+
+        /// Only this plugin (default: every installed plugin)
+        name: Option<String>,
+
+        /// Install the missing packages with pip
+        #[arg(long)]
+        install: bool,
+
+        /// Skip confirmation prompt
+        #[arg(long, short)]
+        yes: bool,
+    },
+
+    /// Link a plugin checkout into a profile's plugins/ for development
+    Link {
+        /// The plugin's directory
+        path: PathBuf,
+
+        /// Profile to link it into
+        #[arg(long, add = ArgValueCandidates::new(profile_completer))]
+        profile: String,
+
+        /// Name in plugins/ (default: the directory's name)
+        #[arg(long)]
+        name: Option<String>,
+
+        /// Replace a plugin the profile already has under that name
+        #[arg(long)]
+        force: bool,
+    },
+
+    /// Remove a plugin linked with `plugin link`, leaving its checkout alone
+    Unlink {
+        /// Name of the linked plugin in plugins/
+        name: String,
+
+        /// Profile it is linked into
+        #[arg(long, add = ArgValueCandidates::new(profile_completer))]
+        profile: String,
+    },
+
+    /// Report plugins whose files changed since bn-loader installed them
+    Verify {
+        /// Profile to check
+        #[arg(add = ArgValueCandidates::new(profile_completer))]
+        profile: String,
+
+        /// Trust the current contents of the changed plugins from now on
+        #[arg(long)]
+        accept: bool,
+    },
+
+    /// Manage the shared plugin store (`plugin_store` in [global])
+    Store {
+        #[command(subcommand)]
+        action: StoreAction,
+    },
+}
 
-    /// Write debug output to file
-    #[arg(long)]
-    log_file: Option<PathBuf>,
+#[derive(Subcommand)]
+enum StoreAction {
+    /// Move the plugins in profiles' plugins/ into the store, linking identical copies to one
+    Dedupe {
+        /// Only this profile (default: every profile)
+        #[arg(long, add = ArgValueCandidates::new(profile_completer))]
+        profile: Option<String>,
 
-    /// Check for updates and exit
-    #[arg(long)]
-    check_update: bool,
+        /// Show what would be moved without changes
+        #[arg(long)]
+        dry_run: bool,
+
+        /// Skip confirmation prompt
+        #[arg(long, short)]
+        yes: bool,
+    },
+    /// Remove plugins from the store that no profile or backup links to
+    Gc {
+        /// Show what would be removed without changes
+        #[arg(long)]
+        dry_run: bool,
+    },
 }
 
 #[derive(Subcommand)]
-enum Commands {
-    /// Create a new profile from a template
-    Init {
-        /// Name for the new profile
-        name: String,
+enum SnippetsAction {
+    /// Show a profile's snippets with their key bindings and descriptions
+    List {
+        /// Profile to list
+        #[arg(add = ArgValueCandidates::new(profile_completer))]
+        profile: String,
+    },
+    /// Copy a snippet file into a profile
+    Add {
+        /// The snippet's .py file
+        file: PathBuf,
 
-        /// Source profile for license and `install_dir`
+        /// Profile to add it to
         #[arg(long, add = ArgValueCandidates::new(profile_completer))]
-        template: String,
+        profile: String,
 
-        /// Directory for new profile's config
+        /// Name in snippets/, e.g. `analysis/xrefs` (default: the file's name)
         #[arg(long)]
-        config_dir: PathBuf,
+        name: Option<String>,
+
+        /// Replace a snippet the profile already has under that name
+        #[arg(long)]
+        force: bool,
     },
+    /// Delete a snippet, after a backup
+    Remove {
+        /// Snippet name, as `snippets list` shows it
+        name: String,
 
-    /// Sync config between profiles
-    Sync {
-        /// Source profile to sync from
+        /// Profile to remove it from
+        #[arg(long, add = ArgValueCandidates::new(profile_completer))]
+        profile: String,
+
+        /// Skip confirmation prompt
+        #[arg(long, short)]
+        yes: bool,
+    },
+    /// Copy one snippet to another profile
+    Copy {
+        /// Snippet name, as `snippets list` shows it
+        name: String,
+
+        /// Profile to copy from
         #[arg(long, add = ArgValueCandidates::new(profile_completer))]
         from: String,
 
-        /// Target profile (default: all other profiles)
+        /// Profile to copy to
         #[arg(long, add = ArgValueCandidates::new(profile_completer))]
-        to: Option<String>,
+        to: String,
 
-        /// Additional exclusion pattern (can be repeated)
-        #[arg(long, action = clap::ArgAction::Append)]
-        exclude: Vec<String>,
+        /// Replace a snippet the target already has under that name
+        #[arg(long)]
+        force: bool,
 
-        /// Show what would be synced without changes
+        /// Skip confirmation prompt
+        #[arg(long, short)]
+        yes: bool,
+    },
+}
+
+#[derive(Subcommand)]
+enum BackupsAction {
+    /// Show every sync backup with its age and size
+    List {
+        /// Only this profile's backups
+        #[arg(add = ArgValueCandidates::new(profile_completer))]
+        profile: Option<String>,
+    },
+    /// Remove old sync backups
+    Prune {
+        /// Only this profile's backups
+        #[arg(add = ArgValueCandidates::new(profile_completer))]
+        profile: Option<String>,
+
+        /// Keep this many of the newest backups per profile
+        #[arg(long, required_unless_present = "older_than")]
+        keep: Option<usize>,
+
+        /// Only remove backups older than this, e.g. `30d`, `12h`, `2w`
+        #[arg(long, value_parser = parse_age)]
+        older_than: Option<Duration>,
+
+        /// Show what would be removed without changes
         #[arg(long)]
         dry_run: bool,
 
@@ -98,30 +986,30 @@ enum Commands {
         #[arg(long, short)]
         yes: bool,
     },
+}
 
-    /// List plugins for a profile
-    Plugins {
-        /// Profile name
+#[derive(Subcommand)]
+enum ShimAction {
+    /// Put a `binaryninja` shim on PATH that runs `bn-loader <profile> -- ...`
+    Install {
+        /// Profile the shim launches
         #[arg(add = ArgValueCandidates::new(profile_completer))]
         profile: String,
-    },
 
-    /// Compare two profiles
-    Diff {
-        /// First profile
-        #[arg(add = ArgValueCandidates::new(profile_completer))]
-        profile1: String,
+        /// Directory to install the shim into (default: ~/.local/bin)
+        #[arg(long)]
+        dir: Option<PathBuf>,
 
-        /// Second profile
-        #[arg(add = ArgValueCandidates::new(profile_completer))]
-        profile2: String,
+        /// Replace an existing `binaryninja` that bn-loader didn't create
+        #[arg(long)]
+        force: bool,
     },
 
-    /// Generate shell completions
-    Completions {
-        /// Shell type
-        #[arg(value_enum)]
-        shell: ShellType,
+    /// Remove a previously installed shim
+    Remove {
+        /// Directory the shim was installed into (default: ~/.local/bin)
+        #[arg(long)]
+        dir: Option<PathBuf>,
     },
 }
 
@@ -133,18 +1021,285 @@ pub enum ShellType {
     Fish,
 }
 
-fn list_profiles_cmd(config: &Config) {
-    println!("Available profiles:");
+impl Cli {
+    /// Command name recorded in the result file
+    fn command_name(&self) -> &'static str {
+        match &self.command {
+            Some(Commands::Init { .. }) => "init",
+            Some(Commands::Sync { .. }) => "sync",
+            Some(Commands::Restore { .. }) => "restore",
+            Some(Commands::Backups { .. }) => "backups",
+            Some(Commands::Plugins { .. }) => "plugins",
+            Some(Commands::Plugin { .. }) => "plugin",
+            Some(Commands::Snippets { .. }) => "snippets",
+            Some(Commands::Diff { .. }) => "diff",
+            Some(Commands::Associate { .. }) => "associate",
+            Some(Commands::History { .. }) => "history",
+            Some(Commands::Shortcuts { .. }) => "shortcuts",
+            Some(Commands::Migrate { .. }) => "migrate",
+            Some(Commands::Bn { .. }) => "bn",
+            Some(Commands::Shim { .. }) => "shim",
+            Some(Commands::Tui) => "tui",
+            Some(Commands::Completions { .. }) => "completions",
+            None if self.list => "list",
+            None if self.check_update => "check-update",
+            None => "launch",
+        }
+    }
+}
+
+impl PluginAction {
+    /// Whether the command reads the plugin repositories' index
+    fn downloads(&self) -> bool {
+        matches!(
+            self,
+            PluginAction::Install { .. }
+                | PluginAction::Search { .. }
+                | PluginAction::Outdated { .. }
+                | PluginAction::Update { .. }
+        )
+    }
+}
+
+fn list_profiles_cmd(config: &Config, json: bool, porcelain: bool, long: bool) {
+    // Profiles often share an install, and detection reads its core library
+    let mut versions = HashMap::new();
+    if porcelain {
+        let mut names: Vec<&String> = config.profiles.keys().collect();
+        names.sort_unstable();
+        let default = config.global.default_profile.as_ref();
+        for name in names {
+            let profile = &config.profiles[name];
+            let version = versions
+                .entry(&profile.install_dir)
+                .or_insert_with(|| detect_version(&profile.install_dir));
+            // Scripts rely on these columns: never reorder or remove them
+            let fields = [
+                name.clone(),
+                profile.install_dir.display().to_string(),
+                profile.config_dir.display().to_string(),
+                profile.executable.clone(),
+                version
+                    .as_ref()
+                    .map(ToString::to_string)
+                    .unwrap_or_default(),
+                if default == Some(name) { "default" } else { "" }.to_string(),
+            ];
+            let line: Vec<String> = fields
+                .iter()
+                .map(|field| field.replace(['\t', '\n', '\r'], " "))
+                .collect();
+            dataln!("{}", line.join("\t"));
+        }
+        return;
+    }
+    let last_launches = if long {
+        history::last_launches()
+    } else {
+        BTreeMap::new()
+    };
+    if json {
+        let profiles: Vec<_> = config
+            .profiles
+            .iter()
+            .map(|(name, profile)| {
+                let version = versions
+                    .entry(&profile.install_dir)
+                    .or_insert_with(|| detect_version(&profile.install_dir));
+                let mut entry = serde_json::json!({
+                    "name": name,
+                    "install_dir": profile.install_dir,
+                    "config_dir": profile.config_dir,
+                    "bn_version": version.as_ref().map(ToString::to_string),
+                });
+                if long {
+                    let details = ProfileDetails::read(profile);
+                    entry["default"] =
+                        (config.global.default_profile.as_ref() == Some(name)).into();
+                    entry["plugins"] = details.plugins.into();
+                    entry["config_size"] = details.config_size.into();
+                    entry["last_launch"] = last_launches.get(name).copied().into();
+                }
+                entry
+            })
+            .collect();
+        if let Err(e) = report::print_json(&profiles) {
+            report::error(&e);
+            report::exit(1);
+        }
+        return;
+    }
+
+    if long {
+        print_profile_table(config, &mut versions, &last_launches);
+        return;
+    }
+
+    dataln!("Available profiles:");
     for (name, profile) in &config.profiles {
-        println!("  {} -> {}", name, profile.install_dir.display());
+        let version = versions
+            .entry(&profile.install_dir)
+            .or_insert_with(|| detect_version(&profile.install_dir));
+        match version {
+            Some(version) => dataln!(
+                "  {} -> {} (Binary Ninja {version})",
+                name,
+                profile.install_dir.display()
+            ),
+            None => dataln!("  {} -> {}", name, profile.install_dir.display()),
+        }
+    }
+}
+
+/// What `--list --long` shows beyond the install
+struct ProfileDetails {
+    /// `None` if the plugins can't be read
+    plugins: Option<usize>,
+    /// `None` if the config directory doesn't exist
+    config_size: Option<u64>,
+}
+
+impl ProfileDetails {
+    fn read(profile: &Profile) -> Self {
+        ProfileDetails {
+            plugins: list_plugins(profile).ok().map(|plugins| plugins.len()),
+            config_size: tree_size(&profile.config_dir).ok(),
+        }
+    }
+}
+
+/// `--list --long`: one row per profile, sorted by name, with its columns
+/// aligned
+fn print_profile_table<'a>(
+    config: &'a Config,
+    versions: &mut HashMap<&'a PathBuf, Option<BnVersion>>,
+    last_launches: &BTreeMap<String, u64>,
+) {
+    let mut names: Vec<&String> = config.profiles.keys().collect();
+    names.sort_unstable();
+    let now = history::now();
+    let mut rows = vec![[
+        "PROFILE".to_string(),
+        "VERSION".to_string(),
+        "PLUGINS".to_string(),
+        "SIZE".to_string(),
+        "LAST LAUNCH".to_string(),
+    ]];
+    for name in names {
+        let profile = &config.profiles[name];
+        let version = versions
+            .entry(&profile.install_dir)
+            .or_insert_with(|| detect_version(&profile.install_dir));
+        let details = ProfileDetails::read(profile);
+        let default = if config.global.default_profile.as_ref() == Some(name) {
+            "*"
+        } else {
+            " "
+        };
+        rows.push([
+            format!("{default} {name}"),
+            version
+                .as_ref()
+                .map_or_else(|| "-".to_string(), ToString::to_string),
+            details
+                .plugins
+                .map_or_else(|| "?".to_string(), |count| count.to_string()),
+            details
+                .config_size
+                .map_or_else(|| "-".to_string(), format_size),
+            last_launches.get(name).map_or_else(
+                || "never".to_string(),
+                |&timestamp| history::format_age(now.saturating_sub(timestamp)),
+            ),
+        ]);
+    }
+    rows[0][0] = format!("  {}", rows[0][0]);
+
+    let mut widths = [0; 5];
+    for row in &rows {
+        for (width, cell) in widths.iter_mut().zip(row) {
+            *width = (*width).max(cell.chars().count());
+        }
+    }
+    for row in &rows {
+        // Names and versions read left to right, counts and sizes line up on
+        // the right
+        let line = format!(
+            "{:<w0$}  {:<w1$}  {:>w2$}  {:>w3$}  {:<w4$}",
+            row[0],
+            row[1],
+            row[2],
+            row[3],
+            row[4],
+            w0 = widths[0],
+            w1 = widths[1],
+            w2 = widths[2],
+            w3 = widths[3],
+            w4 = widths[4],
+        );
+        dataln!("{}", line.trim_end());
+    }
+    if config.global.default_profile.is_some() {
+        dataln!("\n* default profile");
+    }
+}
+
+#[cfg(feature = "updates")]
+fn check_update_cmd(json: bool) {
+    if json {
+        let info = update::check_for_updates_forced();
+        let status = serde_json::json!({
+            "current": env!("CARGO_PKG_VERSION"),
+            "latest": info.as_ref().map(|info| &info.latest),
+            "update_available": info.is_some(),
+            "url": info.as_ref().map(|info| &info.url),
+        });
+        if let Err(e) = report::print_json(&status) {
+            report::error(&e);
+            report::exit(1);
+        }
+        return;
     }
+    outln!("Checking for updates...");
+    dataln!("Current version: {}", env!("CARGO_PKG_VERSION"));
+    match update::check_for_updates_forced() {
+        Some(info) => {
+            dataln!("Update available: v{} -> v{}", info.current, info.latest);
+            dataln!("Download: {}", info.url);
+        }
+        None => {
+            dataln!("You're on the latest version.");
+        }
+    }
+}
+
+#[cfg(not(feature = "updates"))]
+fn check_update_cmd(_json: bool) {
+    report::error(
+        "This build of bn-loader has no update checks (built without the `updates` feature)",
+    );
+    report::exit(1);
+}
+
+#[cfg(feature = "tui")]
+fn tui_cmd(config: &Config, allow_suspicious_dirs: bool) {
+    if let Err(e) = tui::run_tui(config, allow_suspicious_dirs) {
+        report::error(&e);
+        report::exit(1);
+    }
+}
+
+#[cfg(not(feature = "tui"))]
+fn tui_cmd(_config: &Config, _allow_suspicious_dirs: bool) {
+    report::error("This build of bn-loader has no TUI (built without the `tui` feature)");
+    report::exit(1);
 }
 
 fn load_config_or_exit(custom_config: Option<&Path>) -> (PathBuf, Config) {
-    let config_path = if let Some(p) = find_config_file(custom_config.and_then(|p| p.to_str())) {
+    let config_path = if let Some(p) = find_config_file(custom_config) {
         p
     } else {
-        eprintln!("Error: No config file found.");
+        report::error("No config file found.");
         eprintln!("Searched locations:");
         // Show preferred location first
         if let Some(home) = env::var("HOME")
@@ -164,52 +1319,80 @@ fn load_config_or_exit(custom_config: Option<&Path>) -> (PathBuf, Config) {
         {
             eprintln!("  - {}", exe_dir.join(CONFIG_FILE_NAME).display());
         }
-        process::exit(1);
+        report::exit(1);
     };
 
     let config = match load_config(&config_path) {
         Ok(c) => c,
         Err(e) => {
-            eprintln!("Error: {e}");
-            process::exit(1);
+            report::error(&e);
+            report::exit(1);
         }
     };
 
     (config_path, config)
 }
 
+/// Exit as `diff` does: 0 without differences, 1 with, 2 on error
+fn exit_for_diff(result: Result<usize, String>) {
+    match result {
+        Ok(0) => {}
+        Ok(_) => report::exit(DIFFERENCES_EXIT_CODE),
+        Err(e) => {
+            report::error(&e);
+            report::exit(DIFF_ERROR_EXIT_CODE);
+        }
+    }
+}
+
 fn main() {
     // Handle dynamic shell completions (intercepts COMPLETE=<shell> env var)
     CompleteEnv::with_factory(Cli::command).complete();
 
-    let cli = Cli::parse();
+    let mut cli = Cli::parse();
+    // Before --bn-log-file, `--debug --log-file` named Binary Ninja's debug log
+    let legacy_log_file = cli.debug && cli.bn_log_file.is_none() && cli.log_file.is_some();
+    if legacy_log_file {
+        cli.bn_log_file = cli.log_file.take();
+    }
+
+    report::start(cli.result_file.as_deref(), cli.command_name());
+    if let Err(e) = logging::init(cli.verbose, cli.quiet, cli.log_file.as_deref()) {
+        report::error(&e);
+        report::exit(1);
+    }
+    if legacy_log_file {
+        report::warn(
+            "--debug --log-file sending Binary Ninja's debug log is deprecated; use --bn-log-file",
+        );
+    }
+    if cli.json || cli.porcelain {
+        report::reserve_stdout();
+    }
+    if cli.no_progress {
+        progress::disable();
+    }
 
     // Handle completions subcommand (prints registration instructions)
     if let Some(Commands::Completions { shell }) = &cli.command {
         completions::print_instructions(shell);
+        report::finish();
         return;
     }
 
     // Manual update check (doesn't require config)
     if cli.check_update {
-        println!("Checking for updates...");
-        println!("Current version: {}", env!("CARGO_PKG_VERSION"));
-        match update::check_for_updates_forced() {
-            Some(info) => {
-                println!("Update available: v{} -> v{}", info.current, info.latest);
-                println!("Download: {}", info.url);
-            }
-            None => {
-                println!("You're on the latest version.");
-            }
-        }
+        check_update_cmd(cli.json);
+        report::finish();
         return;
     }
 
     // All other commands need config
     let (config_path, config) = load_config_or_exit(cli.config.as_deref());
+    colors::init(cli.color.unwrap_or(config.global.color));
 
     // Check for updates (non-blocking, silent on error)
+    #[cfg(feature = "updates")]
     if config.global.check_updates
         && let Some(update_info) = update::check_for_updates()
     {
@@ -217,7 +1400,8 @@ fn main() {
     }
 
     if cli.list {
-        list_profiles_cmd(&config);
+        list_profiles_cmd(&config, cli.json, cli.porcelain, cli.long);
+        report::finish();
         return;
     }
 
@@ -241,69 +1425,638 @@ fn main() {
                 config_dir: &expanded_config_dir,
             };
             if let Err(e) = run_init(&config, &config_path, &options) {
-                eprintln!("Error: {e}");
-                process::exit(1);
+                report::error(&e);
+                report::exit(1);
             }
         }
 
         Some(Commands::Sync {
             from,
             to,
+            pull,
+            into,
             exclude,
+            preset,
+            only,
             dry_run,
             yes,
+            force,
+            delete,
+            repositories_mode,
+            link,
+            report: report_format,
+            report_file,
+            hook,
+            verify,
         }) => {
+            report::summarize_after(
+                Duration::from_secs(config.global.summary_after_secs),
+                config.global.notify,
+            );
             let extra_exclusions: Vec<&str> =
                 exclude.iter().map(std::string::String::as_str).collect();
             let options = SyncOptions {
                 from: &from,
-                to: to.as_deref(),
+                to: to.as_deref().or(into.as_deref()),
+                pull,
                 extra_exclusions,
+                preset: preset.as_deref(),
+                only: only.iter().map(String::as_str).collect(),
                 dry_run,
                 yes,
+                force,
+                delete,
                 backup_retention: config.global.backup_retention,
+                repositories_mode,
+                link,
+                allow_suspicious_dirs: cli.i_know_what_im_doing,
+                report: report_format.or(cli.json.then_some(ReportFormat::Json)),
+                report_file: report_file.as_deref(),
+                hook: hook.as_deref(),
+                verify,
+                only_changed: false,
+            };
+            if verify {
+                match run_verify(&config, &options) {
+                    Ok(true) => {}
+                    Ok(false) => report::exit(DRIFT_EXIT_CODE),
+                    Err(e) => {
+                        report::error(&e);
+                        report::exit(1);
+                    }
+                }
+            } else if let Err(e) = run_sync(&config, &options) {
+                report::error(&e);
+                report::exit(1);
+            }
+        }
+
+        Some(Commands::Plugins {
+            action: Some(action),
+            ..
+        }) => {
+            let result = match action {
+                PluginsAction::Export { profile, output } => {
+                    run_export(&config, &profile, output.as_deref())
+                }
+                PluginsAction::Show { name, profile } => run_show(&config, &profile, &name),
+                PluginsAction::Du { profile } => run_du(&config, &profile),
+                PluginsAction::Compat { profile, build } => run_compat(&config, &profile, build),
+                PluginsAction::Conflicts { profile } => run_conflicts(&config, profile.as_deref()),
+                PluginsAction::Apply {
+                    file,
+                    profile,
+                    prune,
+                    dry_run,
+                    yes,
+                } => run_list_apply(
+                    &config,
+                    &ListApplyOptions {
+                        file: &file,
+                        profile: &profile,
+                        prune,
+                        dry_run,
+                        yes,
+                        allow_suspicious_dirs: cli.i_know_what_im_doing,
+                    },
+                ),
             };
-            if let Err(e) = run_sync(&config, &options) {
-                eprintln!("Error: {e}");
-                process::exit(1);
+            if let Err(e) = result {
+                report::error(&e);
+                report::exit(1);
+            }
+        }
+
+        Some(Commands::Plugins {
+            action: None,
+            all: true,
+            source,
+            search,
+            ..
+        }) => {
+            pager::start(!cli.no_pager);
+            if let Err(e) = run_matrix(&config, source.as_ref(), search.as_deref(), cli.json) {
+                report::error(&e);
+                report::exit(1);
             }
         }
 
-        Some(Commands::Plugins { profile }) => {
+        Some(Commands::Plugins {
+            action: None,
+            profile,
+            source,
+            search,
+            sort,
+            ..
+        }) => {
+            pager::start(!cli.no_pager);
+            // clap requires the profile without a subcommand or --all
+            let profile = profile.unwrap_or_default();
             let prof = if let Some(p) = config.profiles.get(&profile) {
                 p
             } else {
-                eprintln!("Error: Profile '{profile}' not found.");
-                process::exit(1);
+                report::error(&format!("Profile '{profile}' not found."));
+                report::exit(1);
             };
             match list_plugins(prof) {
-                Ok(plugins) => print_plugins(&profile, &plugins),
+                Ok(mut plugins) if cli.json => {
+                    filter_plugins(&mut plugins, source.as_ref(), search.as_deref());
+                    sort_plugins(&mut plugins, sort);
+                    let sizes = matches!(sort, PluginSort::Size);
+                    if let Err(e) = print_plugins_json(&profile, &plugins, sizes) {
+                        report::error(&e);
+                        report::exit(1);
+                    }
+                }
+                Ok(mut plugins) => {
+                    let total = plugins.len();
+                    filter_plugins(&mut plugins, source.as_ref(), search.as_deref());
+                    if plugins.is_empty() && total > 0 {
+                        dataln!("None of the {total} plugins of profile '{profile}' match.");
+                    } else {
+                        sort_plugins(&mut plugins, sort);
+                        print_plugins(&profile, &plugins, matches!(sort, PluginSort::Size));
+                    }
+                }
                 Err(e) => {
-                    eprintln!("Error: {e}");
-                    process::exit(1);
+                    report::error(&e);
+                    report::exit(1);
                 }
             }
         }
 
-        Some(Commands::Diff { profile1, profile2 }) => {
-            let prof1 = if let Some(p) = config.profiles.get(&profile1) {
-                p
-            } else {
-                eprintln!("Error: Profile '{profile1}' not found.");
-                process::exit(1);
+        Some(Commands::Plugin { action }) => {
+            if action.downloads() && !cfg!(feature = "plugin-index") {
+                report::error(
+                    "This build of bn-loader can't download plugins (built without the `plugin-index` feature)",
+                );
+                report::exit(1);
+            }
+            let result = match action {
+                PluginAction::Install {
+                    name,
+                    profile,
+                    manual,
+                    force,
+                } => run_install(
+                    &config,
+                    &InstallOptions {
+                        name: &name,
+                        profile: &profile,
+                        manual,
+                        force,
+                        allow_suspicious_dirs: cli.i_know_what_im_doing,
+                    },
+                ),
+                PluginAction::Remove { name, profile, yes } => run_remove(
+                    &config,
+                    &RemoveOptions {
+                        name: &name,
+                        profile: &profile,
+                        yes,
+                        allow_suspicious_dirs: cli.i_know_what_im_doing,
+                    },
+                ),
+                PluginAction::Copy {
+                    name,
+                    from,
+                    to,
+                    force,
+                    yes,
+                } => run_copy(
+                    &config,
+                    &CopyOptions {
+                        name: &name,
+                        from: &from,
+                        to: &to,
+                        force,
+                        yes,
+                        allow_suspicious_dirs: cli.i_know_what_im_doing,
+                    },
+                ),
+                PluginAction::Enable { names, profile } => run_toggle(
+                    &config,
+                    &ToggleOptions {
+                        names: &names,
+                        profile: &profile,
+                        enable: true,
+                        allow_suspicious_dirs: cli.i_know_what_im_doing,
+                    },
+                ),
+                PluginAction::Disable { names, profile } => run_toggle(
+                    &config,
+                    &ToggleOptions {
+                        names: &names,
+                        profile: &profile,
+                        enable: false,
+                        allow_suspicious_dirs: cli.i_know_what_im_doing,
+                    },
+                ),
+                PluginAction::Search { term, profile } => {
+                    run_search(&config, &term, profile.as_deref())
+                }
+                PluginAction::Outdated { profile } => run_outdated(&config, profile.as_deref()),
+                PluginAction::Update {
+                    names,
+                    all,
+                    profile,
+                    dry_run,
+                    yes,
+                } => run_update(
+                    &config,
+                    &UpdateOptions {
+                        names: &names,
+                        all,
+                        profile: &profile,
+                        dry_run,
+                        yes,
+                        allow_suspicious_dirs: cli.i_know_what_im_doing,
+                    },
+                ),
+                PluginAction::Lock { profile, output } => run_lock(&config, &profile, &output),
+                PluginAction::Apply {
+                    lock_file,
+                    profile,
+                    dry_run,
+                    yes,
+                } => run_apply(
+                    &config,
+                    &ApplyOptions {
+                        lock_file: &lock_file,
+                        profile: &profile,
+                        dry_run,
+                        yes,
+                        allow_suspicious_dirs: cli.i_know_what_im_doing,
+                    },
+                ),
+                PluginAction::Deps {
+                    profile,
+                    name,
+                    install,
+                    yes,
+                } => run_deps(
+                    &config,
+                    &DepsOptions {
+                        profile: &profile,
+                        name: name.as_deref(),
+                        install,
+                        yes,
+                    },
+                ),
+                PluginAction::Link {
+                    path,
+                    profile,
+                    name,
+                    force,
+                } => run_link(
+                    &config,
+                    &LinkOptions {
+                        path: &path,
+                        profile: &profile,
+                        name: name.as_deref(),
+                        force,
+                        allow_suspicious_dirs: cli.i_know_what_im_doing,
+                    },
+                ),
+                PluginAction::Unlink { name, profile } => run_unlink(&config, &profile, &name),
+                PluginAction::Verify { profile, accept } => plugin_verify::run_verify(
+                    &config,
+                    &VerifyOptions {
+                        profile: &profile,
+                        accept,
+                    },
+                )
+                .map(|intact| {
+                    if !intact {
+                        report::exit(DRIFT_EXIT_CODE);
+                    }
+                }),
+                PluginAction::Store {
+                    action:
+                        StoreAction::Dedupe {
+                            profile,
+                            dry_run,
+                            yes,
+                        },
+                } => run_dedupe(
+                    &config,
+                    &DedupeOptions {
+                        profile: profile.as_deref(),
+                        dry_run,
+                        yes,
+                    },
+                ),
+                PluginAction::Store {
+                    action: StoreAction::Gc { dry_run },
+                } => run_gc(&config, dry_run),
             };
-            let prof2 = if let Some(p) = config.profiles.get(&profile2) {
-                p
-            } else {
-                eprintln!("Error: Profile '{profile2}' not found.");
-                process::exit(1);
+            if let Err(e) = result {
+                report::error(&e);
+                report::exit(1);
+            }
+        }
+
+        Some(Commands::Restore {
+            profile,
+            backup,
+            latest,
+            only,
+            dry_run,
+            yes,
+        }) => {
+            let options = RestoreOptions {
+                profile: &profile,
+                timestamp: backup,
+                latest,
+                only: only.iter().map(String::as_str).collect(),
+                dry_run,
+                yes,
+                allow_suspicious_dirs: cli.i_know_what_im_doing,
+            };
+            if let Err(e) = run_restore(&config, &options) {
+                report::error(&e);
+                report::exit(1);
+            }
+        }
+
+        Some(Commands::Snippets { action }) => {
+            let result = match action {
+                SnippetsAction::List { profile } => snippets::run_list(&config, &profile),
+                SnippetsAction::Add {
+                    file,
+                    profile,
+                    name,
+                    force,
+                } => snippets::run_add(
+                    &config,
+                    &SnippetAddOptions {
+                        file: &file,
+                        profile: &profile,
+                        name: name.as_deref(),
+                        force,
+                        allow_suspicious_dirs: cli.i_know_what_im_doing,
+                    },
+                ),
+                SnippetsAction::Remove { name, profile, yes } => snippets::run_remove(
+                    &config,
+                    &SnippetRemoveOptions {
+                        name: &name,
+                        profile: &profile,
+                        yes,
+                        allow_suspicious_dirs: cli.i_know_what_im_doing,
+                    },
+                ),
+                SnippetsAction::Copy {
+                    name,
+                    from,
+                    to,
+                    force,
+                    yes,
+                } => snippets::run_copy(
+                    &config,
+                    &SnippetCopyOptions {
+                        name: &name,
+                        from: &from,
+                        to: &to,
+                        force,
+                        yes,
+                        allow_suspicious_dirs: cli.i_know_what_im_doing,
+                    },
+                ),
+            };
+            if let Err(e) = result {
+                report::error(&e);
+                report::exit(1);
+            }
+        }
+
+        Some(Commands::Backups { action }) => {
+            let result = match action {
+                BackupsAction::List { profile } => print_backups(&config, profile.as_deref()),
+                BackupsAction::Prune {
+                    profile,
+                    keep,
+                    older_than,
+                    dry_run,
+                    yes,
+                } => {
+                    let options = PruneOptions {
+                        profile: profile.as_deref(),
+                        keep,
+                        older_than,
+                        dry_run,
+                        yes,
+                    };
+                    run_prune(&config, &options)
+                }
+            };
+            if let Err(e) = result {
+                report::error(&e);
+                report::exit(1);
+            }
+        }
+
+        Some(Commands::Diff {
+            all: true,
+            profile1,
+            ..
+        }) => {
+            pager::start(!cli.no_pager);
+            exit_for_diff(run_drift_summary(&config, profile1.as_deref(), cli.json));
+        }
+
+        Some(Commands::Diff {
+            defaults: true,
+            profile1: Some(profile1),
+            full,
+            ..
+        }) => {
+            if cli.json {
+                report::error("--json can't be used with --defaults");
+                report::exit(DIFF_ERROR_EXIT_CODE);
+            }
+            pager::start(!cli.no_pager);
+            exit_for_diff(resolve_diff_side(&config, &profile1).and_then(|profile| {
+                let options = DiffOptions {
+                    full,
+                    ignore_keys: build_glob_set(&config.diff.ignore_keys)?,
+                    ..DiffOptions::default()
+                };
+                diff_defaults(&profile1, &profile, &options)
+            }));
+        }
+
+        Some(Commands::Diff {
+            profile1: Some(profile1),
+            profile2: Some(profile2),
+            python,
+            apply,
+            base,
+            only,
+            skip,
+            full,
+            output,
+            ..
+        }) => {
+            if cli.json && (apply || base.is_some() || output.is_some()) {
+                report::error("--json can't be used with --apply, --base or --output");
+                report::exit(DIFF_ERROR_EXIT_CODE);
+            }
+            let (prof1, prof2) = match (
+                resolve_diff_side(&config, &profile1),
+                resolve_diff_side(&config, &profile2),
+            ) {
+                (Ok(p1), Ok(p2)) => (p1, p2),
+                (Err(e), _) | (_, Err(e)) => {
+                    report::error(&e);
+                    report::exit(DIFF_ERROR_EXIT_CODE);
+                }
+            };
+            // --apply prompts, and a report leaves little to print
+            if !apply && output.is_none() {
+                pager::start(!cli.no_pager);
+            }
+            let left = DiffSide {
+                name: &profile1,
+                profile: &prof1,
+            };
+            let right = DiffSide {
+                name: &profile2,
+                profile: &prof2,
+            };
+
+            let result = match base {
+                Some(base) => resolve_diff_side(&config, &base).and_then(|base_profile| {
+                    let base = DiffSide {
+                        name: &base,
+                        profile: &base_profile,
+                    };
+                    diff_three_way(&base, &left, &right)
+                }),
+                None => build_glob_set(&config.diff.ignore_keys)
+                    .and_then(|ignore_keys| {
+                        let options = DiffOptions {
+                            python,
+                            only: only.iter().map(String::as_str).collect(),
+                            skip: skip.iter().map(String::as_str).collect(),
+                            // A report is read later, so nothing is left out
+                            full: full || output.is_some(),
+                            ignore_keys,
+                            output: output.as_deref(),
+                            json: cli.json,
+                        };
+                        diff_profiles(&profile1, &prof1, &profile2, &prof2, &options)
+                    })
+                    .and_then(|differences| {
+                        if apply {
+                            let options = DiffApplyOptions {
+                                left: &left,
+                                right: &right,
+                                allow_suspicious_dirs: cli.i_know_what_im_doing,
+                            };
+                            run_diff_apply(&config, &options)?;
+                        }
+                        Ok(differences)
+                    }),
+            };
+            exit_for_diff(result);
+        }
+
+        Some(Commands::Diff { .. }) => unreachable!("clap requires both profiles without --all"),
+
+        Some(Commands::Associate { profile, binaries }) => {
+            let options = AssociateOptions {
+                profile: &profile,
+                binaries,
+                config_path: &config_path,
+            };
+            if let Err(e) = run_associate(&config, &options) {
+                report::error(&e);
+                report::exit(1);
+            }
+        }
+
+        Some(Commands::History { profile, limit }) => {
+            history::print_history(profile.as_deref(), limit);
+        }
+
+        Some(Commands::Shortcuts {
+            action:
+                ShortcutAction::Create {
+                    profile,
+                    dir,
+                    start_menu,
+                },
+        }) => {
+            let options = ShortcutOptions {
+                profile: profile.as_deref(),
+                dir: dir.as_deref(),
+                start_menu,
+                config_path: &config_path,
+            };
+            if let Err(e) = create_shortcuts(&config, &options) {
+                report::error(&e);
+                report::exit(1);
+            }
+        }
+
+        Some(Commands::Migrate {
+            from,
+            path,
+            profile,
+            dry_run,
+        }) => {
+            let options = MigrateOptions {
+                source: from,
+                path: &path,
+                profile: &profile,
+                dry_run,
+                allow_suspicious_dirs: cli.i_know_what_im_doing,
+            };
+            if let Err(e) = run_migrate(&config, &options) {
+                report::error(&e);
+                report::exit(1);
+            }
+        }
+
+        Some(Commands::Bn { action }) => {
+            let BnAction::Relocate { id, new_path } = action;
+            let options = RelocateOptions {
+                id: &id,
+                new_path: &new_path,
+                config_path: &config_path,
+            };
+            if let Err(e) = relocate(&config, &options) {
+                report::error(&e);
+                report::exit(1);
+            }
+        }
+
+        Some(Commands::Shim { action }) => {
+            let result = match action {
+                ShimAction::Install {
+                    profile,
+                    dir,
+                    force,
+                } => {
+                    let options = ShimOptions {
+                        profile: &profile,
+                        dir: dir.as_deref(),
+                        force,
+                        config_path: &config_path,
+                    };
+                    install_shim(&config, &options)
+                }
+                ShimAction::Remove { dir } => remove_shim(dir.as_deref()),
             };
-            if let Err(e) = diff_profiles(&profile1, prof1, &profile2, prof2) {
-                eprintln!("Error: {e}");
-                process::exit(1);
+            if let Err(e) = result {
+                report::error(&e);
+                report::exit(1);
             }
         }
 
+        Some(Commands::Tui) => tui_cmd(&config, cli.i_know_what_im_doing),
+
         Some(Commands::Completions { .. }) => {
             // Already handled above
             unreachable!()
@@ -312,18 +2065,40 @@ fn main() {
         None => {
             // Launch profile mode
             let name = match cli.profile {
-                Some(n) => n,
+                Some(query) => match picker::resolve_profile(&config, &query) {
+                    Ok(n) => n,
+                    Err(e) => {
+                        report::error(&e);
+                        eprintln!("Use --list to see available profiles.");
+                        report::exit(1);
+                    }
+                },
                 None => {
                     // Try default profile from global config
                     if let Some(default) = &config.global.default_profile {
                         default.clone()
-                    } else {
-                        eprintln!("Error: No profile specified.");
+                    } else if config.profiles.is_empty() {
+                        report::error("No profile specified.");
                         eprintln!("Use --list to see available profiles, or --help for usage.");
                         eprintln!(
                             "Tip: Set global.default_profile in config to launch without arguments."
                         );
-                        process::exit(1);
+                        report::exit(1);
+                    } else {
+                        let mut names: Vec<&str> =
+                            config.profiles.keys().map(String::as_str).collect();
+                        names.sort_unstable();
+                        match picker::pick_profile("Select a profile to launch", &names) {
+                            Ok(n) => n,
+                            Err(e) => {
+                                report::error("No profile specified.");
+                                eprintln!("{e}");
+                                eprintln!(
+                                    "Tip: Set global.default_profile in config to launch without arguments."
+                                );
+                                report::exit(1);
+                            }
+                        }
                     }
                 }
             };
@@ -331,22 +2106,56 @@ fn main() {
             let profile = if let Some(p) = config.profiles.get(&name) {
                 p
             } else {
-                eprintln!("Error: Profile '{name}' not found.");
+                report::error(&format!("Profile '{name}' not found."));
                 eprintln!("Use --list to see available profiles.");
-                process::exit(1);
+                report::exit(1);
             };
 
+            let mut args = cli.args.clone();
+            if cli.resume {
+                let Some(saved) = session::load_session(&name) else {
+                    report::error(&format!("No saved session for profile '{name}'."));
+                    report::exit(1);
+                };
+                for file in saved.files {
+                    if file.exists() {
+                        args.push(file.into_os_string());
+                    } else {
+                        report::warn(&format!("Skipping missing file: {}", file.display()));
+                    }
+                }
+            }
+
+            if let Some(source) = &profile.auto_sync_from {
+                if cli.dry_run {
+                    outln!("Would auto-sync from '{source}' first");
+                } else if let Err(e) = auto_sync(&config, &name, profile, cli.i_know_what_im_doing)
+                {
+                    // The profile still works with what it had
+                    report::warn(&format!("{e}; launching anyway"));
+                }
+            }
+
             // Combine CLI debug flag with global debug setting
             let use_debug = cli.debug || config.global.debug;
 
             let options = LaunchOptions {
                 debug: use_debug,
-                log_file: cli.log_file.as_ref(),
+                bn_log_file: cli.bn_log_file.as_ref(),
+                log_file: cli.log_file.as_deref(),
+                dry_run: cli.dry_run,
+                args: &args,
+                wait: cli.wait,
+                detach: cli.detach,
+                elevated: cli.elevated,
+                allow_suspicious_dirs: cli.i_know_what_im_doing,
             };
             if let Err(e) = launch_profile(&name, profile, &options) {
-                eprintln!("Error: {e}");
-                process::exit(1);
+                report::error(&e);
+                report::exit(1);
             }
         }
     }
+
+    report::finish();
 }
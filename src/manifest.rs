@@ -0,0 +1,216 @@
+use crate::config::cache_dir;
+use globset::GlobSet;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::BTreeMap;
+use std::fs;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+const MANIFESTS_DIR: &str = "manifests";
+
+/// Content hashes of a profile's synced items, recorded after each
+/// bn-loader operation that writes to the profile
+#[derive(Serialize, Deserialize, Default)]
+pub(crate) struct Manifest {
+    pub timestamp: u64,
+    /// Relative path (with `/` separators) -> SHA-256 hex digest
+    pub files: BTreeMap<String, String>,
+}
+
+pub(crate) enum LocalChange {
+    Added(String),
+    Modified(String),
+    Removed(String),
+}
+
+impl LocalChange {
+    pub(crate) fn describe(&self) -> String {
+        match self {
+            LocalChange::Added(path) => format!("+ {path} (new)"),
+            LocalChange::Modified(path) => format!("~ {path} (modified)"),
+            LocalChange::Removed(path) => format!("- {path} (deleted)"),
+        }
+    }
+}
+
+/// Hash every file under the given items of a config dir
+pub(crate) fn build_manifest(
+    config_dir: &Path,
+    items: &[&str],
+    exclusions: &GlobSet,
+) -> Result<Manifest, String> {
+    let mut files = BTreeMap::new();
+    for item in items {
+        let path = config_dir.join(item);
+        if path.exists() {
+            hash_tree(&path, item, exclusions, &mut files)?;
+        }
+    }
+
+    let timestamp = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0);
+
+    Ok(Manifest { timestamp, files })
+}
+
+fn hash_tree(
+    path: &Path,
+    rel: &str,
+    exclusions: &GlobSet,
+    files: &mut BTreeMap<String, String>,
+) -> Result<(), String> {
+    if is_excluded(exclusions, rel, path.is_dir()) {
+        return Ok(());
+    }
+
+    if path.is_dir() {
+        let entries = fs::read_dir(path)
+            .map_err(|e| format!("Failed to read directory {}: {e}", path.display()))?;
+        for entry in entries {
+            let entry = entry.map_err(|e| format!("Failed to read entry: {e}"))?;
+            let child_rel = format!("{rel}/{}", entry.file_name().to_string_lossy());
+            hash_tree(&entry.path(), &child_rel, exclusions, files)?;
+        }
+    } else {
+        files.insert(rel.to_string(), hash_file(path)?);
+    }
+
+    Ok(())
+}
+
+/// One digest for a whole directory (or a single file), covering every
+/// file's relative path and contents
+pub(crate) fn hash_dir(dir: &Path, exclusions: &GlobSet) -> Result<String, String> {
+    let files = hash_files(dir, exclusions)?;
+    let mut hasher = Sha256::new();
+    for (path, hash) in &files {
+        hasher.update(path.as_bytes());
+        hasher.update(b"\0");
+        hasher.update(hash.as_bytes());
+        hasher.update(b"\n");
+    }
+    Ok(hasher
+        .finalize()
+        .iter()
+        .map(|b| format!("{b:02x}"))
+        .collect())
+}
+
+/// The digest of every file in a directory (or of a single file), by path
+/// relative to it
+pub(crate) fn hash_files(
+    dir: &Path,
+    exclusions: &GlobSet,
+) -> Result<BTreeMap<String, String>, String> {
+    let mut files = BTreeMap::new();
+    if !dir.is_dir() {
+        // A single-file plugin
+        let rel = dir
+            .file_name()
+            .map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or_default();
+        hash_tree(dir, &rel, exclusions, &mut files)?;
+    } else {
+        let entries = fs::read_dir(dir)
+            .map_err(|e| format!("Failed to read directory {}: {e}", dir.display()))?;
+        for entry in entries {
+            let entry = entry.map_err(|e| format!("Failed to read entry: {e}"))?;
+            let rel = entry.file_name().to_string_lossy().into_owned();
+            hash_tree(&entry.path(), &rel, exclusions, &mut files)?;
+        }
+    }
+    Ok(files)
+}
+
+/// Match exclusion globs against the relative path and the entry's own name,
+/// so `__pycache__/` and `*.pyc` apply at any depth during a tree walk
+pub(crate) fn is_excluded(exclusions: &GlobSet, rel: &str, is_dir: bool) -> bool {
+    if exclusions.is_match(rel) {
+        return true;
+    }
+    let name = rel.rsplit('/').next().unwrap_or(rel);
+    exclusions.is_match(name) || (is_dir && exclusions.is_match(format!("{name}/")))
+}
+
+pub(crate) fn hash_file(path: &Path) -> Result<String, String> {
+    let mut file =
+        fs::File::open(path).map_err(|e| format!("Failed to open {}: {e}", path.display()))?;
+    let mut hasher = Sha256::new();
+    let mut buf = [0u8; 64 * 1024];
+    loop {
+        let n = file
+            .read(&mut buf)
+            .map_err(|e| format!("Failed to read {}: {e}", path.display()))?;
+        if n == 0 {
+            break;
+        }
+        hasher.update(&buf[..n]);
+    }
+    Ok(hasher
+        .finalize()
+        .iter()
+        .map(|b| format!("{b:02x}"))
+        .collect())
+}
+
+/// Files that differ between the stored manifest and the current state,
+/// limited to paths under the given items
+pub(crate) fn local_changes(
+    stored: &Manifest,
+    current: &Manifest,
+    items: &[&str],
+) -> Vec<LocalChange> {
+    let in_items = |path: &str| {
+        items
+            .iter()
+            .any(|item| path == *item || path.starts_with(&format!("{item}/")))
+    };
+    file_changes(&stored.files, &current.files, in_items)
+}
+
+/// Files that differ between two sets of digests, limited to paths `keep`
+/// accepts
+pub(crate) fn file_changes(
+    stored: &BTreeMap<String, String>,
+    current: &BTreeMap<String, String>,
+    keep: impl Fn(&str) -> bool,
+) -> Vec<LocalChange> {
+    let mut changes = Vec::new();
+    for (path, hash) in current.iter().filter(|(p, _)| keep(p)) {
+        match stored.get(path) {
+            None => changes.push(LocalChange::Added(path.clone())),
+            Some(old) if old != hash => changes.push(LocalChange::Modified(path.clone())),
+            Some(_) => {}
+        }
+    }
+    for path in stored.keys().filter(|p| keep(p)) {
+        if !current.contains_key(path) {
+            changes.push(LocalChange::Removed(path.clone()));
+        }
+    }
+    changes
+}
+
+fn manifest_path(profile: &str) -> Option<PathBuf> {
+    cache_dir().map(|dir| dir.join(MANIFESTS_DIR).join(format!("{profile}.json")))
+}
+
+pub(crate) fn load_manifest(profile: &str) -> Option<Manifest> {
+    let content = fs::read_to_string(manifest_path(profile)?).ok()?;
+    serde_json::from_str(&content).ok()
+}
+
+pub(crate) fn save_manifest(profile: &str, manifest: &Manifest) -> Result<(), String> {
+    let path = manifest_path(profile).ok_or("Could not determine cache directory")?;
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)
+            .map_err(|e| format!("Failed to create manifest directory: {e}"))?;
+    }
+    let json = serde_json::to_string(manifest)
+        .map_err(|e| format!("Failed to serialize manifest: {e}"))?;
+    fs::write(&path, json).map_err(|e| format!("Failed to write manifest: {e}"))
+}
@@ -0,0 +1,341 @@
+use crate::config::Config;
+use crate::plugin_manager::back_up;
+use crate::report::outln;
+use crate::safety::guard_config_dir;
+use crate::settings::{KEYBINDINGS_FILE, write_bn_json};
+use clap::ValueEnum;
+use serde_json::{Map, Value};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Tool to migrate from
+#[derive(Clone, Copy, ValueEnum)]
+pub(crate) enum MigrateSource {
+    Ida,
+    Ghidra,
+}
+
+// Action names differ between tools; these are the ones with a clear
+// Binary Ninja counterpart. Anything else is reported as unmapped.
+const IDA_ACTIONS: &[(&str, &str)] = &[
+    ("JumpAsk", "Go To Address..."),
+    ("JumpName", "Go To Address..."),
+    ("MakeName", "Rename Symbol..."),
+    ("Rename", "Rename Symbol..."),
+    ("MakeComment", "Comment..."),
+    ("SetType", "Change Type..."),
+    ("JumpOpXref", "Cross References..."),
+    ("JumpXref", "Cross References..."),
+    ("MakeFunction", "Create Function"),
+    ("Return", "Back"),
+    ("UndoReturn", "Forward"),
+    ("Undo", "Undo"),
+    ("Redo", "Redo"),
+];
+
+const GHIDRA_ACTIONS: &[(&str, &str)] = &[
+    ("Go To Address/Label", "Go To Address..."),
+    ("Rename Function", "Rename Symbol..."),
+    ("Rename Variable", "Rename Symbol..."),
+    ("Edit Label", "Rename Symbol..."),
+    ("Set EOL Comment", "Comment..."),
+    ("Set Comment", "Comment..."),
+    ("Retype Variable", "Change Type..."),
+    ("Edit Function Signature", "Change Type..."),
+    ("Show References to", "Cross References..."),
+    ("Find References to", "Cross References..."),
+    ("Create Function", "Create Function"),
+    ("Previous Location in History", "Back"),
+    ("Next Location in History", "Forward"),
+    ("Undo", "Undo"),
+    ("Redo", "Redo"),
+];
+
+pub(crate) struct MigrateOptions<'a> {
+    pub source: MigrateSource,
+    pub path: &'a Path,
+    pub profile: &'a str,
+    pub dry_run: bool,
+    pub allow_suspicious_dirs: bool,
+}
+
+/// A key binding read from the other tool
+struct Binding {
+    action: String,
+    keys: String,
+}
+
+pub(crate) fn run_migrate(config: &Config, options: &MigrateOptions) -> Result<(), String> {
+    let profile = config
+        .profiles
+        .get(options.profile)
+        .ok_or_else(|| format!("Profile '{}' not found", options.profile))?;
+
+    let (bindings, table, not_migrated) = match options.source {
+        MigrateSource::Ida => (
+            read_ida(options.path)?,
+            IDA_ACTIONS,
+            "ida.cfg options and colors",
+        ),
+        MigrateSource::Ghidra => (
+            read_ghidra(options.path)?,
+            GHIDRA_ACTIONS,
+            "Ghidra preferences and tool options",
+        ),
+    };
+
+    if bindings.is_empty() {
+        return Err(format!(
+            "No key bindings found under {}",
+            options.path.display()
+        ));
+    }
+
+    let mut mapped = Map::new();
+    let mut unmapped = Vec::new();
+    for binding in &bindings {
+        match table.iter().find(|(from, _)| *from == binding.action) {
+            Some((_, to)) => {
+                mapped.insert(
+                    (*to).to_string(),
+                    Value::Array(vec![Value::String(binding.keys.clone())]),
+                );
+            }
+            None => unmapped.push(binding),
+        }
+    }
+
+    outln!(
+        "Migrating key bindings into profile '{}'...",
+        options.profile
+    );
+    for (action, keys) in &mapped {
+        outln!("  {action:<24} {}", keys[0].as_str().unwrap_or_default());
+    }
+
+    if !unmapped.is_empty() {
+        outln!("\nNo Binary Ninja equivalent ({}):", unmapped.len());
+        for binding in &unmapped {
+            outln!("  {:<32} {}", binding.action, binding.keys);
+        }
+    }
+    outln!("\nNot migrated: {not_migrated} (no sensible mapping to Binary Ninja settings)");
+
+    if mapped.is_empty() {
+        outln!("\nNothing to write.");
+        return Ok(());
+    }
+
+    guard_config_dir(
+        options.profile,
+        &profile.config_dir,
+        options.allow_suspicious_dirs || options.dry_run,
+    )?;
+
+    let path = profile.config_dir.join(KEYBINDINGS_FILE);
+    if options.dry_run {
+        outln!("\n[Dry run] {} was not changed.", path.display());
+        return Ok(());
+    }
+
+    // Overlay onto the profile's own bindings
+    let mut keybindings = match fs::read_to_string(&path) {
+        Ok(content) => serde_json::from_str(&content)
+            .map_err(|e| format!("Failed to parse {}: {e}", path.display()))?,
+        Err(_) => Value::Object(Map::new()),
+    };
+    let Some(existing) = keybindings.as_object_mut() else {
+        return Err(format!("{} is not a JSON object", path.display()));
+    };
+    existing.extend(mapped);
+    back_up(config, options.profile, profile, &[KEYBINDINGS_FILE])?;
+    write_bn_json(&path, &keybindings)?;
+
+    outln!("\nUpdated {}", path.display());
+    Ok(())
+}
+
+/// Read `"Action" = "Ctrl-Shift-X"` hotkey lines from IDA .cfg files
+/// (idagui.cfg, shortcuts.cfg) in a file or directory
+fn read_ida(path: &Path) -> Result<Vec<Binding>, String> {
+    let mut bindings = Vec::new();
+    for file in files_with_extension(path, "cfg")? {
+        let content = read_lossy(&file)?;
+        for line in content.lines() {
+            let line = line.split("//").next().unwrap_or_default().trim();
+            let Some((action, keys)) = line.split_once('=') else {
+                continue;
+            };
+            let (Some(action), Some(keys)) = (unquote(action), unquote(keys)) else {
+                continue;
+            };
+            if keys.is_empty() {
+                continue;
+            }
+            bindings.push(Binding {
+                action: action.to_string(),
+                keys: normalize_keys(&keys.replace('-', " ")),
+            });
+        }
+    }
+    Ok(bindings)
+}
+
+/// Read `<ACTION NAME=".." OWNER=".." KEY_STROKE="ctrl shift G" />` entries
+/// from Ghidra tool files (.tcd/.tool) in a file or directory
+fn read_ghidra(path: &Path) -> Result<Vec<Binding>, String> {
+    let mut bindings = Vec::new();
+    for extension in ["tcd", "tool"] {
+        for file in files_with_extension(path, extension)? {
+            let content = read_lossy(&file)?;
+            for element in content.split('<').filter(|e| e.starts_with("ACTION ")) {
+                let (Some(name), Some(keys)) =
+                    (xml_attr(element, "NAME"), xml_attr(element, "KEY_STROKE"))
+                else {
+                    continue;
+                };
+                if keys.is_empty() {
+                    continue;
+                }
+                bindings.push(Binding {
+                    action: name.to_string(),
+                    keys: normalize_keys(keys),
+                });
+            }
+        }
+    }
+    Ok(bindings)
+}
+
+/// Turn `ctrl shift g` into Qt's `Ctrl+Shift+G`
+fn normalize_keys(keys: &str) -> String {
+    keys.split_whitespace()
+        .map(|part| match part.to_ascii_lowercase().as_str() {
+            "ctrl" | "control" => "Ctrl".to_string(),
+            "shift" => "Shift".to_string(),
+            "alt" => "Alt".to_string(),
+            "meta" | "cmd" => "Meta".to_string(),
+            "semicolon" => ";".to_string(),
+            "escape" | "esc" => "Esc".to_string(),
+            "enter" | "return" => "Return".to_string(),
+            _ if part.chars().count() == 1 => part.to_uppercase(),
+            _ => {
+                let mut chars = part.chars();
+                chars.next().map_or_else(String::new, |first| {
+                    first
+                        .to_uppercase()
+                        .chain(chars.flat_map(char::to_lowercase))
+                        .collect()
+                })
+            }
+        })
+        .collect::<Vec<_>>()
+        .join("+")
+}
+
+fn unquote(s: &str) -> Option<&str> {
+    s.trim().strip_prefix('"')?.strip_suffix('"')
+}
+
+fn xml_attr<'a>(element: &'a str, name: &str) -> Option<&'a str> {
+    let start = element.find(&format!(" {name}=\""))? + name.len() + 3;
+    let len = element[start..].find('"')?;
+    Some(&element[start..start + len])
+}
+
+fn read_lossy(path: &Path) -> Result<String, String> {
+    fs::read(path)
+        .map(|bytes| String::from_utf8_lossy(&bytes).into_owned())
+        .map_err(|e| format!("Failed to read {}: {e}", path.display()))
+}
+
+/// The file itself, or every file with the extension below a directory
+fn files_with_extension(path: &Path, extension: &str) -> Result<Vec<PathBuf>, String> {
+    if path.is_file() {
+        let matches = path.extension().is_some_and(|e| e == extension);
+        return Ok(if matches {
+            vec![path.to_path_buf()]
+        } else {
+            Vec::new()
+        });
+    }
+
+    let entries =
+        fs::read_dir(path).map_err(|e| format!("Failed to read {}: {e}", path.display()))?;
+    let mut files = Vec::new();
+    for entry in entries {
+        let entry = entry.map_err(|e| format!("Failed to read entry: {e}"))?;
+        files.extend(files_with_extension(&entry.path(), extension)?);
+    }
+    files.sort();
+    Ok(files)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn actions(bindings: &[Binding]) -> Vec<(&str, &str)> {
+        bindings
+            .iter()
+            .map(|b| (b.action.as_str(), b.keys.as_str()))
+            .collect()
+    }
+
+    #[test]
+    fn ida_hotkeys_are_read_from_cfg_files() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(
+            dir.path().join("idagui.cfg"),
+            "// Hotkeys\n\
+             \"JumpAsk\"        =    \"g\"\n\
+             \"MakeName\"       = \"Shift-N\"  // rename\n\
+             \"SetType\" = \"\"\n\
+             // \"Undo\" = \"Ctrl-Z\"\n\
+             DISPLAY_PATCH_SUBMENU = YES\n",
+        )
+        .unwrap();
+        fs::write(dir.path().join("notes.txt"), "\"Redo\" = \"Ctrl-Y\"\n").unwrap();
+
+        let bindings = read_ida(dir.path()).unwrap();
+        assert_eq!(
+            actions(&bindings),
+            [("JumpAsk", "G"), ("MakeName", "Shift+N")]
+        );
+    }
+
+    #[test]
+    fn ghidra_key_strokes_are_read_from_tool_files() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(
+            dir.path().join("CodeBrowser.tcd"),
+            r#"<TOOL_CONFIG>
+    <ACTION NAME="Go To Address/Label" OWNER="GoToAddressLabelPlugin" KEY_STROKE="G" />
+    <ACTION NAME="Rename Variable" OWNER="DecompilePlugin" KEY_STROKE="ctrl shift L" />
+    <ACTION NAME="Set Comment" OWNER="CommentsPlugin" KEY_STROKE="" />
+    <ACTION NAME="Undo" OWNER="Tool" />
+</TOOL_CONFIG>
+"#,
+        )
+        .unwrap();
+
+        let bindings = read_ghidra(dir.path()).unwrap();
+        assert_eq!(
+            actions(&bindings),
+            [
+                ("Go To Address/Label", "G"),
+                ("Rename Variable", "Ctrl+Shift+L")
+            ]
+        );
+    }
+
+    #[test]
+    fn a_single_file_is_read_only_with_the_right_extension() {
+        let dir = tempfile::tempdir().unwrap();
+        let cfg = dir.path().join("shortcuts.cfg");
+        fs::write(&cfg, "\"Undo\" = \"Ctrl-Z\"\n").unwrap();
+
+        assert_eq!(actions(&read_ida(&cfg).unwrap()), [("Undo", "Ctrl+Z")]);
+        assert!(read_ghidra(&cfg).unwrap().is_empty());
+    }
+}
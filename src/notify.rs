@@ -0,0 +1,58 @@
+use crate::report;
+use std::process::{Command, Stdio};
+
+/// Show a desktop notification with the platform's own tooling. Failing to
+/// notify is only worth a warning; the summary was already printed.
+pub(crate) fn send(title: &str, body: &str) {
+    let mut command = notify_command(title, body);
+    let result = command
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .status();
+    match result {
+        Ok(status) if status.success() => {}
+        Ok(status) => report::warn(&format!("Desktop notification failed ({status})")),
+        Err(e) => report::warn(&format!("Desktop notification failed: {e}")),
+    }
+}
+
+#[cfg(windows)]
+fn notify_command(title: &str, body: &str) -> Command {
+    use crate::shortcuts::ps_quote;
+
+    // Toasts through the WinRT API that ships with Windows PowerShell
+    let script = format!(
+        "[Windows.UI.Notifications.ToastNotificationManager, Windows.UI.Notifications, ContentType = WindowsRuntime] > $null; \
+         $xml = [Windows.UI.Notifications.ToastNotificationManager]::GetTemplateContent([Windows.UI.Notifications.ToastTemplateType]::ToastText02); \
+         $text = $xml.GetElementsByTagName('text'); \
+         $text.Item(0).AppendChild($xml.CreateTextNode({})) > $null; \
+         $text.Item(1).AppendChild($xml.CreateTextNode({})) > $null; \
+         [Windows.UI.Notifications.ToastNotificationManager]::CreateToastNotifier('bn-loader').Show([Windows.UI.Notifications.ToastNotification]::new($xml))",
+        ps_quote(title),
+        ps_quote(body)
+    );
+    let mut command = Command::new("powershell");
+    command.args(["-NoProfile", "-NonInteractive", "-Command", &script]);
+    command
+}
+
+#[cfg(target_os = "macos")]
+fn notify_command(title: &str, body: &str) -> Command {
+    // AppleScript string literals escape backslashes and quotes
+    let quote = |s: &str| format!("\"{}\"", s.replace('\\', "\\\\").replace('"', "\\\""));
+    let mut command = Command::new("osascript");
+    command.arg("-e").arg(format!(
+        "display notification {} with title {}",
+        quote(body),
+        quote(title)
+    ));
+    command
+}
+
+#[cfg(not(any(windows, target_os = "macos")))]
+fn notify_command(title: &str, body: &str) -> Command {
+    let mut command = Command::new("notify-send");
+    command.args(["--app-name=bn-loader", title, body]);
+    command
+}
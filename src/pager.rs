@@ -0,0 +1,94 @@
+#[cfg(unix)]
+use std::sync::Mutex;
+
+/// Used when `$PAGER` isn't set
+#[cfg(unix)]
+const DEFAULT_PAGER: &str = "less";
+/// `LESS` when unset, as git sets it: quit if the output fits on one screen,
+/// pass colors through, and leave the output on screen
+#[cfg(unix)]
+const DEFAULT_LESS: &str = "FRX";
+
+/// The pager stdout is piped into, and the terminal to restore afterwards
+#[cfg(unix)]
+struct Paging {
+    child: std::process::Child,
+    terminal: i32,
+}
+
+#[cfg(unix)]
+static PAGER: Mutex<Option<Paging>> = Mutex::new(None);
+
+/// Pipe the rest of stdout through `$PAGER` (or `less`) when it is a
+/// terminal, as git does. Short output is shown directly, since less quits
+/// when it fits on one screen.
+#[cfg(unix)]
+pub(crate) fn start(enabled: bool) {
+    use std::env;
+    use std::io::{self, IsTerminal, Write};
+    use std::os::fd::AsRawFd;
+    use std::process::{Command, Stdio};
+
+    if !enabled || !io::stdout().is_terminal() || crate::report::stdout_is_data() {
+        return;
+    }
+    let pager = env::var("PAGER").unwrap_or_else(|_| DEFAULT_PAGER.to_string());
+    if pager.trim().is_empty() || pager.trim() == "cat" {
+        return;
+    }
+    let mut command = Command::new("sh");
+    command.args(["-c", &pager]).stdin(Stdio::piped());
+    if env::var_os("LESS").is_none() {
+        command.env("LESS", DEFAULT_LESS);
+    }
+    // Without a pager the output still goes to the terminal
+    let Ok(mut child) = command.spawn() else {
+        return;
+    };
+    let Some(input) = child.stdin.take() else {
+        return;
+    };
+
+    let _ = io::stdout().flush();
+    // SAFETY: dup and dup2 only duplicate descriptors this process owns
+    let terminal = unsafe { libc::dup(libc::STDOUT_FILENO) };
+    if terminal < 0 || unsafe { libc::dup2(input.as_raw_fd(), libc::STDOUT_FILENO) } < 0 {
+        drop(input);
+        let _ = child.wait();
+        return;
+    }
+    // Quitting the pager early ends bn-loader quietly, rather than with an
+    // error on its next write
+    // SAFETY: restores the default disposition; no handler runs
+    unsafe {
+        libc::signal(libc::SIGPIPE, libc::SIG_DFL);
+    }
+    if let Ok(mut guard) = PAGER.lock() {
+        *guard = Some(Paging { child, terminal });
+    }
+}
+
+/// Windows has no pager to pipe into by default
+#[cfg(not(unix))]
+pub(crate) fn start(_enabled: bool) {}
+
+/// Close the pager's input and wait for it to quit. Stdout goes to the
+/// terminal again afterwards.
+#[cfg(unix)]
+pub(crate) fn finish() {
+    use std::io::Write;
+
+    let Some(mut paging) = PAGER.lock().ok().and_then(|mut guard| guard.take()) else {
+        return;
+    };
+    let _ = std::io::stdout().flush();
+    // SAFETY: puts back the descriptor saved by `start`, closing the pipe
+    unsafe {
+        libc::dup2(paging.terminal, libc::STDOUT_FILENO);
+        libc::close(paging.terminal);
+    }
+    let _ = paging.child.wait();
+}
+
+#[cfg(not(unix))]
+pub(crate) fn finish() {}
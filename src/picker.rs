@@ -0,0 +1,81 @@
+use crate::config::Config;
+use crate::report::outln;
+use dialoguer::Select;
+use std::io::{self, IsTerminal};
+
+/// Find profiles matching a (possibly partial) name.
+///
+/// Matching is tiered: exact, case-insensitive exact, prefix, substring, then
+/// subsequence. The first tier with any hits wins, sorted by name.
+pub(crate) fn match_profiles<'a>(config: &'a Config, query: &str) -> Vec<&'a str> {
+    if let Some((name, _)) = config.profiles.get_key_value(query) {
+        return vec![name.as_str()];
+    }
+
+    let query = query.to_lowercase();
+    let mut names: Vec<(&str, String)> = config
+        .profiles
+        .keys()
+        .map(|name| (name.as_str(), name.to_lowercase()))
+        .collect();
+    names.sort_unstable();
+
+    let tiers: [&dyn Fn(&str) -> bool; 4] = [
+        &|name| name == query,
+        &|name| name.starts_with(&query),
+        &|name| name.contains(&query),
+        &|name| is_subsequence(&query, name),
+    ];
+
+    for tier in tiers {
+        let matches: Vec<&str> = names
+            .iter()
+            .filter(|(_, lower)| tier(lower))
+            .map(|(name, _)| *name)
+            .collect();
+        if !matches.is_empty() {
+            return matches;
+        }
+    }
+
+    Vec::new()
+}
+
+fn is_subsequence(needle: &str, haystack: &str) -> bool {
+    let mut chars = haystack.chars();
+    needle.chars().all(|c| chars.any(|h| h == c))
+}
+
+/// Resolve a profile argument to a configured profile name, prompting when ambiguous
+pub(crate) fn resolve_profile(config: &Config, query: &str) -> Result<String, String> {
+    let matches = match_profiles(config, query);
+
+    match matches.as_slice() {
+        [] => Err(format!("Profile '{query}' not found.")),
+        [name] => {
+            if *name != query {
+                outln!("Matched '{query}' to profile '{name}'.");
+            }
+            Ok((*name).to_string())
+        }
+        _ => pick_profile(&format!("Multiple profiles match '{query}'"), &matches),
+    }
+}
+
+/// Show an interactive selector over the given profile names
+pub(crate) fn pick_profile(prompt: &str, names: &[&str]) -> Result<String, String> {
+    if !io::stdin().is_terminal() || !io::stderr().is_terminal() {
+        return Err(format!("{prompt}: {}", names.join(", ")));
+    }
+
+    let selection = Select::new()
+        .with_prompt(prompt)
+        .items(names)
+        .default(0)
+        .interact_opt()
+        .map_err(|e| format!("Failed to read selection: {e}"))?;
+
+    selection
+        .map(|idx| names[idx].to_string())
+        .ok_or_else(|| "No profile selected.".to_string())
+}
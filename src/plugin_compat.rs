@@ -0,0 +1,112 @@
+use crate::bn_version::{BnVersion, detect_version};
+use crate::config::Config;
+use crate::plugin_manager::find_profile;
+use crate::plugins::{PLUGIN_METADATA_FILE, PluginStatus, list_plugins};
+use crate::report::dataln;
+use serde_json::Value;
+use std::fs;
+
+/// Check every plugin of a profile against the Binary Ninja build it runs
+/// (or `build`), flagging plugins that need a newer build, only support
+/// older ones or were written for Python 2
+pub(crate) fn run_compat(
+    config: &Config,
+    profile_name: &str,
+    build: Option<u64>,
+) -> Result<(), String> {
+    let profile = find_profile(config, profile_name)?;
+    let version = match build {
+        Some(build) => BnVersion {
+            version: format!("build {build}"),
+            build,
+        },
+        None => detect_version(&profile.install_dir).ok_or_else(|| {
+            format!(
+                "Could not determine the Binary Ninja version in {}; pass --build",
+                profile.install_dir.display()
+            )
+        })?,
+    };
+    let plugins = list_plugins(profile)?;
+    if plugins.is_empty() {
+        dataln!("No plugins installed for profile '{profile_name}'");
+        return Ok(());
+    }
+    let status = PluginStatus::load_if_present(&profile.config_dir)?;
+
+    dataln!(
+        "Checking {} plugin(s) of '{profile_name}' against Binary Ninja {version}:\n",
+        plugins.len()
+    );
+    let (mut problems, mut unknown) = (0, 0);
+    for plugin in &plugins {
+        let local = fs::read_to_string(plugin.path.join(PLUGIN_METADATA_FILE))
+            .ok()
+            .and_then(|content| serde_json::from_str::<Value>(&content).ok());
+        let listing = status.as_ref().and_then(|status| {
+            status
+                .installed()
+                .find(|&entry| status.install_dir(entry).as_ref() == Ok(&plugin.path))
+                .map(|entry| status.plugin(entry).clone())
+        });
+        let metadata: Vec<Value> = local.into_iter().chain(listing).collect();
+        if metadata.is_empty() {
+            unknown += 1;
+            continue;
+        }
+        if let Some(problem) = incompatibility(&metadata, version.build) {
+            problems += 1;
+            let name = plugin.name.as_deref().unwrap_or(&plugin.dir_name);
+            dataln!("  {name:<32} {problem}");
+        }
+    }
+
+    if problems == 0 {
+        dataln!("  Every plugin supports this build.");
+    }
+    let mut summary = format!("\n{problems} of {} plugin(s) may not load", plugins.len());
+    if unknown > 0 {
+        summary.push_str(&format!("; {unknown} have no metadata to check"));
+    }
+    dataln!("{summary}.");
+    Ok(())
+}
+
+/// Why a plugin with this metadata (plugin.json first, then its repository
+/// listing) won't run on `build`, if it won't
+pub(crate) fn incompatibility(metadata: &[Value], build: u64) -> Option<String> {
+    let field = |keys: &[&str]| {
+        metadata
+            .iter()
+            .flat_map(|meta| keys.iter().map(move |key| &meta[*key]))
+            .find(|value| !value.is_null())
+    };
+
+    let minimum =
+        field(&["minimumbinaryninjaversion", "minimumBinaryNinjaVersion"]).and_then(build_number);
+    if let Some(minimum) = minimum.filter(|&minimum| minimum > build) {
+        return Some(format!("needs build {minimum} or later"));
+    }
+    let maximum =
+        field(&["maximumbinaryninjaversion", "maximumBinaryNinjaVersion"]).and_then(build_number);
+    if let Some(maximum) = maximum.filter(|&maximum| maximum < build) {
+        return Some(format!("only supports up to build {maximum}"));
+    }
+
+    let apis: Vec<&str> = match field(&["api", "apis"]) {
+        Some(Value::Array(list)) => list.iter().filter_map(Value::as_str).collect(),
+        Some(Value::String(api)) => vec![api.as_str()],
+        _ => Vec::new(),
+    };
+    if !apis.is_empty() && apis.iter().all(|api| api.starts_with("python2")) {
+        return Some("written for Python 2, which Binary Ninja no longer runs".to_string());
+    }
+    None
+}
+
+/// A build number given as a number or a numeric string
+fn build_number(value: &Value) -> Option<u64> {
+    value
+        .as_u64()
+        .or_else(|| value.as_str().and_then(|s| s.trim().parse().ok()))
+}
@@ -0,0 +1,192 @@
+use crate::config::{Config, Profile};
+use crate::plugin_manager::profile_groups;
+use crate::plugins::{PluginInfo, PluginSource, list_plugins};
+use crate::report::dataln;
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// How Python plugins add menu commands
+const REGISTER_CALL: &str = "PluginCommand.register";
+/// Python files read per plugin when looking for commands
+const MAX_SOURCE_FILES: usize = 200;
+
+/// Plugins that get in each other's way when Binary Ninja loads them
+enum Conflict<'a> {
+    /// The same plugin both in `plugins/` and from a repository
+    InstalledTwice(Vec<&'a PluginInfo>),
+    /// Different directories claiming the same name
+    SameName(String, Vec<&'a PluginInfo>),
+    /// A menu command registered by more than one plugin
+    SameCommand(String, Vec<&'a PluginInfo>),
+}
+
+/// Report plugins installed twice, sharing a name, or registering the same
+/// command, for one profile or every profile
+pub(crate) fn run_conflicts(config: &Config, profile: Option<&str>) -> Result<(), String> {
+    let groups = profile_groups(config, profile)?;
+    let mut total = 0;
+    for (index, (names, profile)) in groups.iter().enumerate() {
+        if index > 0 {
+            dataln!();
+        }
+        total += report_profile(&names.join("/"), profile)?;
+    }
+    if groups.len() > 1 {
+        dataln!("\n{total} conflict(s) across {} profile(s).", groups.len());
+    }
+    Ok(())
+}
+
+fn report_profile(label: &str, profile: &Profile) -> Result<usize, String> {
+    // Disabled plugins don't load, so they can't conflict
+    let plugins: Vec<PluginInfo> = list_plugins(profile)?
+        .into_iter()
+        .filter(|plugin| !plugin.is_disabled())
+        .collect();
+    let conflicts = find_conflicts(&plugins);
+    if conflicts.is_empty() {
+        dataln!("No plugin conflicts in '{label}'.");
+        return Ok(0);
+    }
+
+    dataln!("Plugin conflicts in '{label}':");
+    let describe = |plugins: &[&PluginInfo]| {
+        plugins
+            .iter()
+            .map(|plugin| {
+                let rel = plugin
+                    .path
+                    .strip_prefix(&profile.config_dir)
+                    .unwrap_or(&plugin.path);
+                format!("{} ({})", rel.display(), plugin.source.label())
+            })
+            .collect::<Vec<_>>()
+            .join(", ")
+    };
+    for conflict in &conflicts {
+        match conflict {
+            Conflict::InstalledTwice(plugins) => {
+                let name = plugins[0].name.as_deref().unwrap_or(&plugins[0].dir_name);
+                dataln!("  {name} is installed both manually and from a repository:");
+                dataln!("      {}", describe(plugins));
+            }
+            Conflict::SameName(name, plugins) => {
+                dataln!("  {} plugins are named '{name}':", plugins.len());
+                dataln!("      {}", describe(plugins));
+            }
+            Conflict::SameCommand(command, plugins) => {
+                dataln!("  Command \"{command}\" is registered by:");
+                dataln!("      {}", describe(plugins));
+            }
+        }
+    }
+    dataln!("{} conflict(s).", conflicts.len());
+    Ok(conflicts.len())
+}
+
+fn find_conflicts(plugins: &[PluginInfo]) -> Vec<Conflict<'_>> {
+    let mut conflicts = Vec::new();
+
+    // Matched by plugin.json name, or by directory where there is none
+    let mut by_name: BTreeMap<String, Vec<&PluginInfo>> = BTreeMap::new();
+    for plugin in plugins {
+        let name = plugin.name.as_deref().unwrap_or(&plugin.dir_name);
+        by_name.entry(name.to_lowercase()).or_default().push(plugin);
+    }
+    for (name, group) in by_name {
+        if group.len() < 2 {
+            continue;
+        }
+        let manual = group
+            .iter()
+            .any(|p| matches!(p.source, PluginSource::Manual));
+        let repository = group
+            .iter()
+            .any(|p| !matches!(p.source, PluginSource::Manual));
+        if manual && repository {
+            conflicts.push(Conflict::InstalledTwice(group));
+        } else {
+            let name = group[0].name.clone().unwrap_or(name);
+            conflicts.push(Conflict::SameName(name, group));
+        }
+    }
+
+    let mut by_command: BTreeMap<String, Vec<&PluginInfo>> = BTreeMap::new();
+    for plugin in plugins {
+        for command in registered_commands(&plugin.path) {
+            let users = by_command.entry(command).or_default();
+            if !users.iter().any(|p| p.path == plugin.path) {
+                users.push(plugin);
+            }
+        }
+    }
+    conflicts.extend(
+        by_command
+            .into_iter()
+            .filter(|(_, users)| users.len() > 1)
+            .map(|(command, users)| Conflict::SameCommand(command, users)),
+    );
+    conflicts
+}
+
+/// Names of the commands a plugin registers with `PluginCommand.register*`,
+/// where the name is a string literal
+fn registered_commands(path: &Path) -> Vec<String> {
+    let mut files = Vec::new();
+    collect_python_files(path, &mut files);
+    let mut commands: Vec<String> = files
+        .iter()
+        .filter_map(|file| fs::read_to_string(file).ok())
+        .flat_map(|source| command_names(&source))
+        .collect();
+    commands.sort();
+    commands.dedup();
+    commands
+}
+
+fn collect_python_files(path: &Path, files: &mut Vec<PathBuf>) {
+    if files.len() >= MAX_SOURCE_FILES {
+        return;
+    }
+    if path.is_file() {
+        if path.extension().is_some_and(|ext| ext == "py") {
+            files.push(path.to_path_buf());
+        }
+        return;
+    }
+    let Ok(entries) = fs::read_dir(path) else {
+        return;
+    };
+    for entry in entries.filter_map(|e| e.ok()) {
+        let name = entry.file_name();
+        if name == "__pycache__" || name.to_string_lossy().starts_with('.') {
+            continue;
+        }
+        collect_python_files(&entry.path(), files);
+    }
+}
+
+/// `PluginCommand.register_for_address("Tools\\Find", ...)` -> `Tools\Find`
+fn command_names(source: &str) -> Vec<String> {
+    let mut names = Vec::new();
+    let mut rest = source;
+    while let Some(start) = rest.find(REGISTER_CALL) {
+        rest = &rest[start + REGISTER_CALL.len()..];
+        // Skip the `_for_address` and similar suffixes
+        let Some(open) = rest.find('(') else {
+            break;
+        };
+        if rest[..open].contains(|c: char| !(c.is_alphanumeric() || c == '_')) {
+            continue;
+        }
+        let args = rest[open + 1..].trim_start();
+        let Some(quote) = args.chars().next().filter(|c| *c == '"' || *c == '\'') else {
+            continue;
+        };
+        if let Some(end) = args[1..].find(quote) {
+            names.push(args[1..=end].replace("\\\\", "\\"));
+        }
+    }
+    names
+}
@@ -0,0 +1,265 @@
+use crate::config::{Config, Profile};
+use crate::plugin_manager::find_profile;
+use crate::plugins::{PLUGIN_METADATA_FILE, PLUGINS_DIR, PluginStatus, read_plugin_metadata};
+use crate::pyenv::{normalize_name, pip_interpreter, python_packages};
+use crate::report::{self, dataln, outln};
+use crate::sync::confirm;
+use serde_json::Value;
+use std::fs;
+use std::io::{self, IsTerminal};
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+const REQUIREMENTS_FILE: &str = "requirements.txt";
+
+/// A plugin whose Python requirements the profile's environment lacks
+pub(crate) struct MissingDeps {
+    pub plugin: String,
+    pub requirements: Vec<String>,
+}
+
+pub(crate) struct DepsOptions<'a> {
+    pub profile: &'a str,
+    /// Only this plugin (default: every installed plugin)
+    pub name: Option<&'a str>,
+    /// Install what is missing instead of only listing it
+    pub install: bool,
+    pub yes: bool,
+}
+
+/// List the Python packages the profile's plugins need but its environment
+/// doesn't have, and optionally install them
+pub(crate) fn run_deps(config: &Config, options: &DepsOptions) -> Result<(), String> {
+    let profile = find_profile(config, options.profile)?;
+    let mut plugins = installed_plugin_dirs(profile)?;
+    if let Some(name) = options.name {
+        plugins.retain(|(plugin, dir)| {
+            plugin.eq_ignore_ascii_case(name) || dir.file_name().is_some_and(|n| n == name)
+        });
+        if plugins.is_empty() {
+            return Err(format!(
+                "Plugin '{name}' isn't installed in profile '{}'",
+                options.profile
+            ));
+        }
+    }
+
+    let missing = missing_dependencies(profile, &plugins);
+    if missing.is_empty() {
+        dataln!(
+            "Every Python dependency of profile '{}' is installed.",
+            options.profile
+        );
+        return Ok(());
+    }
+    print_missing(&missing);
+    if !options.install {
+        outln!(
+            "\nRun `bn-loader plugin deps {} --install` to install them.",
+            options.profile
+        );
+        return Ok(());
+    }
+
+    let python = pip_interpreter(profile).ok_or_else(no_interpreter)?;
+    if !options.yes && !confirm(&format!("\nInstall them with {}?", python.display()))? {
+        outln!("Aborted.");
+        return Ok(());
+    }
+    install(profile, &python, &missing)
+}
+
+/// After plugins were installed or synced into a profile, report the Python
+/// packages they need that its environment lacks and, on a terminal when
+/// `ask` is set, offer to install them. Problems are only warned about; the
+/// plugins themselves are already in place.
+pub(crate) fn offer_install(
+    profile_name: &str,
+    profile: &Profile,
+    dirs: &[(String, PathBuf)],
+    ask: bool,
+) {
+    let missing = missing_dependencies(profile, dirs);
+    if missing.is_empty() {
+        return;
+    }
+    outln!();
+    print_missing(&missing);
+    let Some(python) = pip_interpreter(profile) else {
+        report::warn(&no_interpreter());
+        return;
+    };
+    let install_now = ask
+        && io::stdin().is_terminal()
+        && confirm(&format!("Install them with {}?", python.display())).unwrap_or(false);
+    if !install_now {
+        outln!("Run `bn-loader plugin deps {profile_name} --install` to install them.");
+        return;
+    }
+    if let Err(e) = install(profile, &python, &missing) {
+        report::warn(&e);
+    }
+}
+
+/// Every installed plugin of a profile, by name, with its directory
+pub(crate) fn installed_plugin_dirs(profile: &Profile) -> Result<Vec<(String, PathBuf)>, String> {
+    let mut plugins = Vec::new();
+    if let Some(status) = PluginStatus::load_if_present(&profile.config_dir)? {
+        for entry in status.installed() {
+            if let Some(dir) = status.install_dir(entry).ok().filter(|dir| dir.is_dir()) {
+                let plugin = status.plugin(entry);
+                let name = plugin["name"].as_str().or(plugin["path"].as_str());
+                plugins.push((name.unwrap_or("?").to_string(), dir));
+            }
+        }
+    }
+
+    let manual = profile.config_dir.join(PLUGINS_DIR);
+    if manual.is_dir() {
+        for entry in fs::read_dir(&manual)
+            .map_err(|e| format!("Failed to read directory {}: {e}", manual.display()))?
+        {
+            let entry = entry.map_err(|e| format!("Failed to read entry: {e}"))?;
+            let path = entry.path();
+            if path.is_dir() {
+                let dir_name = entry.file_name().to_string_lossy().into_owned();
+                let info = read_plugin_metadata(&path, &dir_name);
+                plugins.push((info.name.unwrap_or(dir_name), path));
+            }
+        }
+    }
+    plugins.sort_by_key(|(name, _)| name.to_lowercase());
+    Ok(plugins)
+}
+
+/// The plugins among `dirs` that need packages the profile's Python doesn't
+/// have
+pub(crate) fn missing_dependencies(
+    profile: &Profile,
+    dirs: &[(String, PathBuf)],
+) -> Vec<MissingDeps> {
+    let wanted: Vec<(&String, Vec<String>)> = dirs
+        .iter()
+        .map(|(name, dir)| (name, plugin_requirements(dir)))
+        .filter(|(_, requirements)| !requirements.is_empty())
+        .collect();
+    // Looking at site-packages may run the interpreter, so skip it when no
+    // plugin needs anything
+    if wanted.is_empty() {
+        return Vec::new();
+    }
+
+    let installed = python_packages(profile).packages;
+    wanted
+        .into_iter()
+        .filter_map(|(name, requirements)| {
+            let requirements: Vec<String> = requirements
+                .into_iter()
+                // Paths and URLs without a name can't be looked up
+                .filter(|req| {
+                    let name = requirement_name(req);
+                    !name.is_empty() && !installed.contains_key(&normalize_name(name))
+                })
+                .collect();
+            (!requirements.is_empty()).then(|| MissingDeps {
+                plugin: name.clone(),
+                requirements,
+            })
+        })
+        .collect()
+}
+
+/// The Python requirements a plugin declares, in its requirements.txt and
+/// in the `dependencies` of its plugin.json
+pub(crate) fn plugin_requirements(dir: &Path) -> Vec<String> {
+    let mut requirements = Vec::new();
+
+    if let Ok(content) = fs::read_to_string(dir.join(REQUIREMENTS_FILE)) {
+        for line in content.lines() {
+            let line = line.split(" #").next().unwrap_or(line).trim();
+            // Options such as `-r other.txt` or `--index-url` aren't packages
+            if !line.is_empty() && !line.starts_with('#') && !line.starts_with('-') {
+                requirements.push(line.to_string());
+            }
+        }
+    }
+
+    if let Ok(content) = fs::read_to_string(dir.join(PLUGIN_METADATA_FILE))
+        && let Ok(meta) = serde_json::from_str::<Value>(&content)
+    {
+        // Binary Ninja's format is {"pip": [...], "apt": [...], ...}; accept a
+        // bare list of pip requirements too
+        let pip = match &meta["dependencies"] {
+            Value::Object(deps) => deps.get("pip").and_then(Value::as_array),
+            Value::Array(list) => Some(list),
+            _ => None,
+        };
+        for req in pip.into_iter().flatten().filter_map(Value::as_str) {
+            let req = req.trim();
+            if !req.is_empty()
+                && !requirements.iter().any(|r| {
+                    normalize_name(requirement_name(r)) == normalize_name(requirement_name(req))
+                })
+            {
+                requirements.push(req.to_string());
+            }
+        }
+    }
+    requirements
+}
+
+/// `requests[socks]>=2.0; python_version > "3.8"` -> `requests`
+fn requirement_name(requirement: &str) -> &str {
+    let end = requirement
+        .find(|c: char| !(c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.')))
+        .unwrap_or(requirement.len());
+    &requirement[..end]
+}
+
+fn print_missing(missing: &[MissingDeps]) {
+    dataln!(
+        "{} plugin(s) need Python packages the profile's environment lacks:",
+        missing.len()
+    );
+    for deps in missing {
+        dataln!("  {:<32} {}", deps.plugin, deps.requirements.join(", "));
+    }
+}
+
+fn no_interpreter() -> String {
+    "No Python interpreter to install with; set python_interpreter or python_virtualenv for the profile".to_string()
+}
+
+/// Run pip for every missing requirement, then report which are now present
+fn install(profile: &Profile, python: &Path, missing: &[MissingDeps]) -> Result<(), String> {
+    let mut requirements: Vec<&str> = missing
+        .iter()
+        .flat_map(|deps| deps.requirements.iter().map(String::as_str))
+        .collect();
+    requirements.sort_unstable();
+    requirements.dedup();
+
+    let status = Command::new(python)
+        .args(["-m", "pip", "install"])
+        .args(&requirements)
+        .status()
+        .map_err(|e| format!("Failed to run {}: {e}", python.display()))?;
+    if !status.success() {
+        return Err(format!("pip failed ({status})"));
+    }
+
+    let packages = python_packages(profile).packages;
+    let (installed, still_missing): (Vec<&str>, Vec<&str>) = requirements
+        .iter()
+        .partition(|req| packages.contains_key(&normalize_name(requirement_name(req))));
+    if !installed.is_empty() {
+        outln!("Installed {}", installed.join(", "));
+    }
+    if !still_missing.is_empty() {
+        return Err(format!(
+            "pip ran, but the profile's Python still doesn't see {}",
+            still_missing.join(", ")
+        ));
+    }
+    Ok(())
+}
@@ -0,0 +1,74 @@
+use crate::download::fetch;
+use serde_json::Value;
+
+/// Upstream listings of Binary Ninja's plugin repositories, with the
+/// directory each repository lives in under `repositories/`
+const REPOSITORIES: &[(&str, &str)] = &[
+    (
+        "official",
+        "https://raw.githubusercontent.com/Vector35/official-plugins/master/plugins.json",
+    ),
+    (
+        "community",
+        "https://raw.githubusercontent.com/Vector35/community-plugins/master/plugins.json",
+    ),
+];
+
+/// A plugin as its upstream repository lists it. The listing has the same
+/// fields as the plugin's entry in plugin_status.json.
+pub(crate) struct IndexPlugin {
+    pub repo: &'static str,
+    pub listing: Value,
+}
+
+impl IndexPlugin {
+    pub(crate) fn name(&self) -> &str {
+        self.listing["name"].as_str().unwrap_or("?")
+    }
+
+    pub(crate) fn path(&self) -> Option<&str> {
+        self.listing["path"].as_str()
+    }
+
+    pub(crate) fn version(&self) -> Option<&str> {
+        self.listing["version"].as_str()
+    }
+
+    pub(crate) fn author(&self) -> Option<&str> {
+        self.listing["author"].as_str()
+    }
+
+    pub(crate) fn description(&self) -> Option<&str> {
+        self.listing["description"].as_str()
+    }
+
+    /// Whether `name` is the plugin's name (ignoring case) or directory name
+    pub(crate) fn is_named(&self, name: &str) -> bool {
+        self.name().eq_ignore_ascii_case(name) || self.path() == Some(name)
+    }
+
+    /// Whether the name, description or author contains `term`, ignoring case
+    pub(crate) fn matches(&self, term: &str) -> bool {
+        let term = term.to_lowercase();
+        [Some(self.name()), self.description(), self.author()]
+            .into_iter()
+            .flatten()
+            .any(|field| field.to_lowercase().contains(&term))
+    }
+}
+
+/// Download the current listing of every repository
+pub(crate) fn fetch_index() -> Result<Vec<IndexPlugin>, String> {
+    let mut plugins = Vec::new();
+    for &(repo, url) in REPOSITORIES {
+        let data = fetch(url)?;
+        let listings: Vec<Value> = serde_json::from_slice(&data)
+            .map_err(|e| format!("Failed to parse the {repo} plugin listing: {e}"))?;
+        plugins.extend(
+            listings
+                .into_iter()
+                .map(|listing| IndexPlugin { repo, listing }),
+        );
+    }
+    Ok(plugins)
+}
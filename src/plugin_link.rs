@@ -0,0 +1,171 @@
+use crate::config::{Config, cache_dir};
+use crate::copy::read_link;
+use crate::plugin_manager::{back_up, find_profile};
+use crate::plugins::PLUGINS_DIR;
+use crate::report::{self, outln};
+use crate::safety::guard_config_dir;
+use crate::sync::{create_dir_link, remove_path};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+const DEV_LINKS_FILE: &str = "dev-links.json";
+
+/// Plugins linked in from a local checkout: config dir -> plugin directory
+/// name -> checkout. Sync leaves these alone in every profile using the
+/// config dir.
+type DevLinks = BTreeMap<String, BTreeMap<String, PathBuf>>;
+
+fn dev_links_path() -> Result<PathBuf, String> {
+    cache_dir()
+        .map(|dir| dir.join(DEV_LINKS_FILE))
+        .ok_or_else(|| "Could not determine cache directory".to_string())
+}
+
+fn load_dev_links() -> Result<DevLinks, String> {
+    let path = dev_links_path()?;
+    match fs::read_to_string(&path) {
+        Ok(content) => serde_json::from_str(&content)
+            .map_err(|e| format!("Failed to parse {}: {e}", path.display())),
+        Err(_) => Ok(DevLinks::new()),
+    }
+}
+
+fn save_dev_links(links: &DevLinks) -> Result<(), String> {
+    let path = dev_links_path()?;
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)
+            .map_err(|e| format!("Failed to create directory {}: {e}", parent.display()))?;
+    }
+    let json = serde_json::to_string_pretty(links)
+        .map_err(|e| format!("Failed to serialize linked plugins: {e}"))?;
+    fs::write(&path, json).map_err(|e| format!("Failed to write {}: {e}", path.display()))
+}
+
+fn dir_key(config_dir: &Path) -> String {
+    config_dir.to_string_lossy().into_owned()
+}
+
+/// Exclusion patterns for the plugins linked into a config dir with
+/// `plugin link`, so sync neither copies nor replaces them
+pub(crate) fn dev_link_exclusions(config_dir: &Path) -> Vec<String> {
+    let Ok(links) = load_dev_links() else {
+        return Vec::new();
+    };
+    links
+        .get(&dir_key(config_dir))
+        .into_iter()
+        .flat_map(|plugins| plugins.keys())
+        .filter(|name| read_link(&config_dir.join(PLUGINS_DIR).join(name)).is_ok())
+        .map(|name| format!("{PLUGINS_DIR}/{}", globset::escape(name)))
+        .collect()
+}
+
+pub(crate) struct LinkOptions<'a> {
+    /// The plugin's checkout
+    pub path: &'a Path,
+    pub profile: &'a str,
+    /// Directory name in `plugins/` (default: the checkout's name)
+    pub name: Option<&'a str>,
+    /// Replace a plugin the profile already has under that name
+    pub force: bool,
+    pub allow_suspicious_dirs: bool,
+}
+
+/// Link a plugin under development into a profile's `plugins/`, so edits in
+/// the checkout show up on the next start of Binary Ninja
+pub(crate) fn run_link(config: &Config, options: &LinkOptions) -> Result<(), String> {
+    let profile = find_profile(config, options.profile)?;
+    let checkout = fs::canonicalize(options.path)
+        .map_err(|e| format!("Failed to resolve {}: {e}", options.path.display()))?;
+    if !checkout.is_dir() {
+        return Err(format!(
+            "{} isn't a directory; only plugin directories can be linked",
+            checkout.display()
+        ));
+    }
+    let name = match options.name {
+        Some(name) => name.to_string(),
+        None => checkout
+            .file_name()
+            .map(|n| n.to_string_lossy().into_owned())
+            .ok_or_else(|| format!("{} has no directory name", checkout.display()))?,
+    };
+    guard_config_dir(
+        options.profile,
+        &profile.config_dir,
+        options.allow_suspicious_dirs,
+    )?;
+
+    let dst = profile.config_dir.join(PLUGINS_DIR).join(&name);
+    if fs::symlink_metadata(&dst).is_ok() {
+        if read_link(&dst).is_ok_and(|target| target == checkout) {
+            outln!(
+                "{} is already linked to {}",
+                dst.display(),
+                checkout.display()
+            );
+            return Ok(());
+        }
+        if !options.force {
+            return Err(format!(
+                "Profile '{}' already has a plugin '{name}' (use --force to replace it)",
+                options.profile
+            ));
+        }
+        back_up(config, options.profile, profile, &[PLUGINS_DIR])?;
+        remove_path(&dst)?;
+    }
+    fs::create_dir_all(profile.config_dir.join(PLUGINS_DIR))
+        .map_err(|e| format!("Failed to create directory: {e}"))?;
+    create_dir_link(&checkout, &dst)?;
+    report::changed(&dst);
+
+    let mut links = load_dev_links()?;
+    links
+        .entry(dir_key(&profile.config_dir))
+        .or_default()
+        .insert(name.clone(), checkout.clone());
+    save_dev_links(&links)?;
+
+    outln!("Linked {} -> {}", dst.display(), checkout.display());
+    outln!(
+        "Sync leaves plugins/{name} alone until `bn-loader plugin unlink {name} --profile {}`.",
+        options.profile
+    );
+    Ok(())
+}
+
+/// Remove a link `plugin link` made. The checkout itself is left alone.
+pub(crate) fn run_unlink(config: &Config, profile_name: &str, name: &str) -> Result<(), String> {
+    let profile = find_profile(config, profile_name)?;
+    let mut links = load_dev_links()?;
+    let key = dir_key(&profile.config_dir);
+    let checkout = links
+        .get_mut(&key)
+        .and_then(|plugins| plugins.remove(name))
+        .ok_or_else(|| {
+            format!("Profile '{profile_name}' has no plugin '{name}' linked by bn-loader")
+        })?;
+
+    let dst = profile.config_dir.join(PLUGINS_DIR).join(name);
+    // Only ever remove the link, never a directory that replaced it
+    if read_link(&dst).is_ok() {
+        remove_path(&dst)?;
+        report::changed(&dst);
+    } else if fs::symlink_metadata(&dst).is_ok() {
+        report::warn(&format!(
+            "{} is no longer a link; leaving it in place",
+            dst.display()
+        ));
+    }
+    if links.get(&key).is_some_and(BTreeMap::is_empty) {
+        links.remove(&key);
+    }
+    save_dev_links(&links)?;
+    outln!(
+        "Unlinked {name} ({}) from '{profile_name}'",
+        checkout.display()
+    );
+    Ok(())
+}
@@ -0,0 +1,311 @@
+use crate::colors::paint;
+use crate::config::Config;
+use crate::plugin_deps::offer_install;
+use crate::plugin_index::fetch_index;
+use crate::plugin_manager::{
+    back_up, download_plugin, find_profile, mark_installed, settle_plugin,
+};
+use crate::plugin_verify::forget;
+use crate::plugins::{
+    ENABLED_BIT, INSTALLED_BIT, PLUGINS_DIR, PluginSource, PluginStatus, REPOSITORIES_DIR,
+    StatusEntry, is_installed, is_plugin_entry, list_plugins,
+};
+use crate::report::{self, outln};
+use crate::safety::guard_config_dir;
+use crate::sync::{confirm, remove_path};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use termcolor::Color;
+
+pub(crate) const PLUGIN_LIST_FILE: &str = "plugins.toml";
+const LIST_HEADER: &str = "\
+# Written by `bn-loader plugins export`; apply with `bn-loader plugins apply`.
+# Repository plugins are listed by name under their repository, plugins in
+# plugins/ by directory or file name under `manual`.
+
+";
+/// Key of plugins in `plugins/`, which no repository can install
+const MANUAL_KEY: &str = "manual";
+
+/// Plugins a profile should have: repository directory (or `manual`) ->
+/// plugin names
+type PluginList = BTreeMap<String, Vec<String>>;
+
+/// Write the plugins a profile has, as a list to share and apply elsewhere
+pub(crate) fn run_export(
+    config: &Config,
+    profile_name: &str,
+    output: Option<&Path>,
+) -> Result<(), String> {
+    let profile = find_profile(config, profile_name)?;
+    let mut list = PluginList::new();
+
+    if let Some(status) = PluginStatus::load_if_present(&profile.config_dir)? {
+        for entry in status.installed() {
+            let plugin = status.plugin(entry);
+            let Some(name) = plugin["name"].as_str().or(plugin["path"].as_str()) else {
+                continue;
+            };
+            list.entry(status.repo_dir(entry))
+                .or_default()
+                .push(name.to_string());
+        }
+    }
+    for plugin in list_plugins(profile)? {
+        if matches!(plugin.source, PluginSource::Manual) {
+            list.entry(MANUAL_KEY.to_string())
+                .or_default()
+                .push(plugin.dir_name);
+        }
+    }
+    for names in list.values_mut() {
+        names.sort_by_key(|name| name.to_lowercase());
+    }
+
+    let text = toml::to_string(&list).map_err(|e| format!("Failed to serialize plugins: {e}"))?;
+    let count: usize = list.values().map(Vec::len).sum();
+    match output {
+        Some(path) => {
+            fs::write(path, format!("{LIST_HEADER}{text}"))
+                .map_err(|e| format!("Failed to write {}: {e}", path.display()))?;
+            report::changed(path);
+            outln!(
+                "Exported {count} plugin(s) of profile '{profile_name}' to {}",
+                path.display()
+            );
+        }
+        None => print!("{LIST_HEADER}{text}"),
+    }
+    Ok(())
+}
+
+pub(crate) struct ListApplyOptions<'a> {
+    pub file: &'a Path,
+    pub profile: &'a str,
+    /// Also remove plugins the list doesn't have
+    pub prune: bool,
+    pub dry_run: bool,
+    pub yes: bool,
+    pub allow_suspicious_dirs: bool,
+}
+
+/// What applying the list does to one plugin
+enum Action {
+    Install(StatusEntry),
+    Remove(PathBuf, Option<StatusEntry>),
+    ManualMissing,
+    NotFound,
+}
+
+/// Make a profile have the plugins of a list: missing repository plugins
+/// are installed at their current version and, with `prune`, plugins the
+/// list doesn't have are removed
+pub(crate) fn run_list_apply(config: &Config, options: &ListApplyOptions) -> Result<(), String> {
+    let content = fs::read_to_string(options.file)
+        .map_err(|e| format!("Failed to read {}: {e}", options.file.display()))?;
+    let list: PluginList = toml::from_str(&content)
+        .map_err(|e| format!("Failed to parse {}: {e}", options.file.display()))?;
+
+    let profile = find_profile(config, options.profile)?;
+    let config_dir = &profile.config_dir;
+    let mut status = PluginStatus::load_if_present(config_dir)?
+        .unwrap_or_else(|| PluginStatus::empty(config_dir));
+
+    let mut actions: Vec<(String, Action)> = Vec::new();
+    let mut unlisted = Vec::new();
+    for (repo, names) in &list {
+        for name in names {
+            if repo == MANUAL_KEY {
+                if !is_plugin_entry(&config_dir.join(PLUGINS_DIR).join(name)) {
+                    actions.push((name.clone(), Action::ManualMissing));
+                }
+                continue;
+            }
+            match status.find_named(repo, name) {
+                Some(entry) if is_present(&status, entry) => {}
+                Some(entry) => actions.push((name.clone(), Action::Install(entry))),
+                None => unlisted.push((repo.as_str(), name.as_str())),
+            }
+        }
+    }
+
+    // Plugins this profile has never seen listed; take their listings
+    // from upstream
+    if !unlisted.is_empty() {
+        outln!("Fetching plugin repository listings...");
+        let index = fetch_index()?;
+        for (repo, name) in unlisted {
+            let action = match index.iter().find(|p| p.repo == repo && p.is_named(name)) {
+                Some(plugin) => Action::Install(status.upsert(repo, plugin.listing.clone())),
+                None => Action::NotFound,
+            };
+            actions.push((name.to_string(), action));
+        }
+    }
+
+    if options.prune {
+        actions.extend(unwanted(&list, &status, config_dir)?);
+    }
+
+    outln!(
+        "Applying {} to profile '{}':",
+        options.file.display(),
+        options.profile
+    );
+    if actions.is_empty() {
+        outln!("  Nothing to do; the profile has every listed plugin.");
+        return Ok(());
+    }
+    for (name, action) in &actions {
+        let (what, color) = match action {
+            Action::Install(_) => ("install", Color::Green),
+            Action::Remove(..) => ("remove (not in the list)", Color::Red),
+            Action::ManualMissing => (
+                "missing; manual plugins must be copied by hand",
+                Color::Yellow,
+            ),
+            Action::NotFound => ("not found in the repository listings", Color::Yellow),
+        };
+        outln!("  {name:<32} {}", paint(what, color));
+    }
+    let changes = actions
+        .iter()
+        .filter(|(_, a)| matches!(a, Action::Install(_) | Action::Remove(..)))
+        .count();
+    if changes == 0 {
+        return Ok(());
+    }
+    if options.dry_run {
+        outln!("\n{}", paint("[Dry run] No changes made.", Color::Yellow));
+        return Ok(());
+    }
+
+    guard_config_dir(options.profile, config_dir, options.allow_suspicious_dirs)?;
+    if !options.yes && !confirm("\nProceed?")? {
+        outln!("Aborted.");
+        return Ok(());
+    }
+    let mut items = Vec::new();
+    if actions
+        .iter()
+        .any(|(_, a)| matches!(a, Action::Install(_) | Action::Remove(_, Some(_))))
+    {
+        items.push(REPOSITORIES_DIR);
+    }
+    if actions
+        .iter()
+        .any(|(_, a)| matches!(a, Action::Remove(_, None)))
+    {
+        items.push(PLUGINS_DIR);
+    }
+    back_up(config, options.profile, profile, &items)?;
+
+    let mut failed = 0;
+    let mut installed = Vec::new();
+    for (name, action) in &actions {
+        let result = match action {
+            Action::Install(entry) => {
+                install(config, options.profile, config_dir, &mut status, *entry)
+            }
+            Action::Remove(dir, entry) => remove(&mut status, dir, *entry)
+                .and_then(|()| forget(options.profile, config_dir, dir)),
+            _ => continue,
+        };
+        match (result, action) {
+            (Err(e), _) => {
+                report::warn(&format!("{name}: {e}"));
+                failed += 1;
+            }
+            (Ok(()), Action::Install(entry)) => {
+                installed.extend(
+                    status
+                        .install_dir(*entry)
+                        .ok()
+                        .map(|dir| (name.clone(), dir)),
+                );
+            }
+            _ => {}
+        }
+    }
+    status.save()?;
+    report::changed(&config_dir.join(REPOSITORIES_DIR));
+    offer_install(options.profile, profile, &installed, !options.yes);
+
+    if failed > 0 {
+        return Err(format!("{failed} plugin(s) failed; the rest were applied"));
+    }
+    let summary = format!("Applied {changes} change(s) to '{}'.", options.profile);
+    outln!("\n{}", paint(&summary, Color::Green));
+    Ok(())
+}
+
+/// Installed, with its code in place
+fn is_present(status: &PluginStatus, entry: StatusEntry) -> bool {
+    is_installed(status.status(entry)) && status.install_dir(entry).is_ok_and(|dir| dir.is_dir())
+}
+
+fn install(
+    config: &Config,
+    profile_name: &str,
+    config_dir: &Path,
+    status: &mut PluginStatus,
+    entry: StatusEntry,
+) -> Result<(), String> {
+    let dir = status.install_dir(entry)?;
+    download_plugin(status.plugin(entry), &dir)?;
+    settle_plugin(config, profile_name, config_dir, &dir)?;
+    mark_installed(status, entry);
+    Ok(())
+}
+
+fn remove(status: &mut PluginStatus, dir: &Path, entry: Option<StatusEntry>) -> Result<(), String> {
+    if fs::symlink_metadata(dir).is_ok() {
+        remove_path(dir)?;
+    }
+    report::changed(dir);
+    if let Some(entry) = entry {
+        let bits = status.status(entry) & !(INSTALLED_BIT | ENABLED_BIT);
+        status.set_status(entry, bits);
+    }
+    Ok(())
+}
+
+/// Installed plugins the list doesn't have
+fn unwanted(
+    list: &PluginList,
+    status: &PluginStatus,
+    config_dir: &Path,
+) -> Result<Vec<(String, Action)>, String> {
+    let mut unwanted = Vec::new();
+    for entry in status.installed() {
+        let repo = status.repo_dir(entry);
+        let listed = list
+            .get(&repo)
+            .is_some_and(|names| names.iter().any(|name| status.is_named(entry, name)));
+        if let (false, Ok(dir)) = (listed, status.install_dir(entry)) {
+            let plugin = status.plugin(entry);
+            let name = plugin["name"].as_str().or(plugin["path"].as_str());
+            unwanted.push((
+                name.unwrap_or("?").to_string(),
+                Action::Remove(dir, Some(entry)),
+            ));
+        }
+    }
+
+    let manual = config_dir.join(PLUGINS_DIR);
+    if manual.is_dir() {
+        let wanted = list.get(MANUAL_KEY);
+        for entry in fs::read_dir(&manual)
+            .map_err(|e| format!("Failed to read directory {}: {e}", manual.display()))?
+        {
+            let entry = entry.map_err(|e| format!("Failed to read entry: {e}"))?;
+            let name = entry.file_name().to_string_lossy().into_owned();
+            if is_plugin_entry(&entry.path()) && !wanted.is_some_and(|names| names.contains(&name))
+            {
+                unwanted.push((name, Action::Remove(entry.path(), None)));
+            }
+        }
+    }
+    Ok(unwanted)
+}
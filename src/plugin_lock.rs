@@ -0,0 +1,438 @@
+use crate::colors::paint;
+use crate::config::{Config, default_exclusions};
+use crate::download::fetch;
+use crate::manifest::hash_dir;
+use crate::plugin_deps::offer_install;
+use crate::plugin_manager::{back_up, find_profile, package_url, settle_plugin, unpack};
+use crate::plugins::{
+    ENABLED_BIT, INSTALLED_BIT, PLUGINS_DIR, PluginSource, PluginStatus, REPOSITORIES_DIR,
+    is_installed, is_plugin_entry, list_plugins, read_plugin_metadata,
+};
+use crate::report::{self, outln};
+use crate::safety::guard_config_dir;
+use crate::sync::{build_glob_set, confirm};
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::fs;
+use std::path::{Path, PathBuf};
+use termcolor::Color;
+
+pub(crate) const LOCK_FILE: &str = "bn-plugins.lock";
+const LOCK_VERSION: u32 = 1;
+const LOCK_HEADER: &str =
+    "# Written by `bn-loader plugin lock`; apply with `bn-loader plugin apply`.\n\n";
+/// `source` of plugins in `plugins/`, which no repository can reinstall
+const MANUAL_SOURCE: &str = "manual";
+
+/// Every plugin of a profile, pinned to the exact code installed
+#[derive(Serialize, Deserialize)]
+struct LockFile {
+    version: u32,
+    profile: String,
+    #[serde(default, rename = "plugin")]
+    plugins: Vec<LockedPlugin>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct LockedPlugin {
+    name: String,
+    /// Directory name under the repository's `plugins/` (or `plugins/`)
+    path: String,
+    /// Repository directory (`official`, `community`), or `manual`
+    source: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    version: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    commit: Option<String>,
+    /// Archive of exactly this commit
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    url: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    subdir: Option<String>,
+    /// SHA-256 over the plugin's files, for manual plugins
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    hash: Option<String>,
+    #[serde(default = "default_true")]
+    enabled: bool,
+}
+
+fn default_true() -> bool {
+    true
+}
+
+impl LockedPlugin {
+    fn is_manual(&self) -> bool {
+        self.source == MANUAL_SOURCE
+    }
+
+    fn dir(&self, config_dir: &Path) -> PathBuf {
+        if self.is_manual() {
+            config_dir.join(PLUGINS_DIR).join(&self.path)
+        } else {
+            config_dir
+                .join(REPOSITORIES_DIR)
+                .join(&self.source)
+                .join(PLUGINS_DIR)
+                .join(&self.path)
+        }
+    }
+
+    /// The plugin_status.json entry to write for the plugin
+    fn listing(&self) -> Value {
+        let mut fields = Map::new();
+        fields.insert("name".into(), self.name.clone().into());
+        fields.insert("path".into(), self.path.clone().into());
+        for (key, value) in [
+            ("version", &self.version),
+            ("commit", &self.commit),
+            ("packageUrl", &self.url),
+            ("subdir", &self.subdir),
+        ] {
+            if let Some(value) = value {
+                fields.insert(key.into(), value.clone().into());
+            }
+        }
+        Value::Object(fields)
+    }
+}
+
+/// Write a lock file pinning every plugin of a profile
+pub(crate) fn run_lock(config: &Config, profile_name: &str, output: &Path) -> Result<(), String> {
+    let profile = find_profile(config, profile_name)?;
+    let mut plugins = Vec::new();
+
+    if let Some(status) = PluginStatus::load_if_present(&profile.config_dir)? {
+        for entry in status.installed() {
+            let plugin = status.plugin(entry);
+            let Some(path) = plugin["path"].as_str() else {
+                continue;
+            };
+            let on_disk = status
+                .install_dir(entry)
+                .ok()
+                .and_then(|dir| read_plugin_metadata(&dir, path).version);
+            let text = |key: &str| plugin[key].as_str().map(str::to_string);
+            plugins.push(LockedPlugin {
+                name: text("name").unwrap_or_else(|| path.to_string()),
+                path: path.to_string(),
+                source: status.repo_dir(entry),
+                version: on_disk.or_else(|| text("version")),
+                commit: text("commit"),
+                url: package_url(plugin),
+                subdir: text("subdir").filter(|s| !s.is_empty()),
+                hash: None,
+                enabled: status.status(entry) & ENABLED_BIT != 0,
+            });
+        }
+    }
+
+    let exclusions = build_glob_set(&default_exclusions())?;
+    for plugin in list_plugins(profile)? {
+        if !matches!(plugin.source, PluginSource::Manual) {
+            continue;
+        }
+        let dir = profile.config_dir.join(PLUGINS_DIR).join(&plugin.dir_name);
+        plugins.push(LockedPlugin {
+            name: plugin.name.unwrap_or_else(|| plugin.dir_name.clone()),
+            path: plugin.dir_name,
+            source: MANUAL_SOURCE.to_string(),
+            version: plugin.version,
+            commit: None,
+            url: None,
+            subdir: None,
+            hash: Some(hash_dir(&dir, &exclusions)?),
+            enabled: true,
+        });
+    }
+    plugins.sort_by(|a, b| (&a.source, &a.path).cmp(&(&b.source, &b.path)));
+
+    let lock = LockFile {
+        version: LOCK_VERSION,
+        profile: profile_name.to_string(),
+        plugins,
+    };
+    let text = toml::to_string(&lock).map_err(|e| format!("Failed to serialize lock file: {e}"))?;
+    fs::write(output, format!("{LOCK_HEADER}{text}"))
+        .map_err(|e| format!("Failed to write {}: {e}", output.display()))?;
+    report::changed(output);
+
+    let unpinned = lock
+        .plugins
+        .iter()
+        .filter(|p| !p.is_manual() && (p.commit.is_none() || p.url.is_none()))
+        .count();
+    outln!(
+        "Locked {} plugin(s) of profile '{profile_name}' in {}",
+        lock.plugins.len(),
+        output.display()
+    );
+    if unpinned > 0 {
+        report::warn(&format!(
+            "{unpinned} repository plugin(s) have no commit or download URL and can't be reinstalled exactly"
+        ));
+    }
+    Ok(())
+}
+
+pub(crate) struct ApplyOptions<'a> {
+    pub lock_file: &'a Path,
+    pub profile: &'a str,
+    pub dry_run: bool,
+    pub yes: bool,
+    pub allow_suspicious_dirs: bool,
+}
+
+/// What applying the lock file does to one plugin
+enum Step {
+    UpToDate,
+    /// Installed at the locked commit, but enabled or disabled differently
+    SetEnabled,
+    Install,
+    Reinstall,
+    /// Manual plugins can only be checked
+    ManualMissing,
+    ManualDiffers,
+    /// Needs installing, but the lock file has no archive to install from
+    Unavailable,
+}
+
+/// Make a profile's plugins match a lock file: repository plugins that are
+/// missing or at another commit are downloaded, manual ones are checked
+pub(crate) fn run_apply(config: &Config, options: &ApplyOptions) -> Result<(), String> {
+    let content = fs::read_to_string(options.lock_file)
+        .map_err(|e| format!("Failed to read {}: {e}", options.lock_file.display()))?;
+    let lock: LockFile = toml::from_str(&content)
+        .map_err(|e| format!("Failed to parse {}: {e}", options.lock_file.display()))?;
+    if lock.version > LOCK_VERSION {
+        return Err(format!(
+            "{} is lock file version {}; this bn-loader reads up to {LOCK_VERSION}",
+            options.lock_file.display(),
+            lock.version
+        ));
+    }
+
+    let profile = find_profile(config, options.profile)?;
+    let config_dir = &profile.config_dir;
+    let mut status = PluginStatus::load_if_present(config_dir)?
+        .unwrap_or_else(|| PluginStatus::empty(config_dir));
+    let exclusions = build_glob_set(&default_exclusions())?;
+
+    let mut steps = Vec::new();
+    for plugin in &lock.plugins {
+        let dir = plugin.dir(config_dir);
+        let step = if plugin.is_manual() {
+            if !is_plugin_entry(&dir) {
+                Step::ManualMissing
+            } else if plugin.hash.is_some() && hash_dir(&dir, &exclusions).ok() != plugin.hash {
+                Step::ManualDiffers
+            } else {
+                Step::UpToDate
+            }
+        } else {
+            let entry = status
+                .find_in(&plugin.source, &plugin.path)
+                .filter(|&e| is_installed(status.status(e)));
+            match entry {
+                Some(entry) if dir.is_dir() => {
+                    let version = read_plugin_metadata(&dir, &plugin.path)
+                        .version
+                        .or_else(|| status.plugin(entry)["version"].as_str().map(str::to_string));
+                    if status.plugin(entry)["commit"].as_str() == plugin.commit.as_deref()
+                        && version == plugin.version
+                    {
+                        if (status.status(entry) & ENABLED_BIT != 0) == plugin.enabled {
+                            Step::UpToDate
+                        } else {
+                            Step::SetEnabled
+                        }
+                    } else {
+                        Step::Reinstall
+                    }
+                }
+                _ => Step::Install,
+            }
+        };
+        let step = match step {
+            Step::Install | Step::Reinstall if plugin.url.is_none() => Step::Unavailable,
+            step => step,
+        };
+        steps.push((plugin, step));
+    }
+
+    outln!(
+        "Applying {} (from profile '{}') to profile '{}':",
+        options.lock_file.display(),
+        lock.profile,
+        options.profile
+    );
+    let mut downloads = 0;
+    for (plugin, step) in &steps {
+        let version = plugin
+            .version
+            .as_deref()
+            .map(|v| format!(" v{v}"))
+            .unwrap_or_default();
+        let (what, color) = match step {
+            Step::UpToDate => ("up to date", Color::Green),
+            Step::SetEnabled if plugin.enabled => ("enable", Color::Yellow),
+            Step::SetEnabled => ("disable", Color::Yellow),
+            Step::Install => ("install", Color::Green),
+            Step::Reinstall => ("reinstall at the locked commit", Color::Yellow),
+            Step::ManualMissing => ("missing; manual plugins must be copied by hand", Color::Red),
+            Step::ManualDiffers => (
+                "differs from the locked copy; manual plugins are left alone",
+                Color::Red,
+            ),
+            Step::Unavailable => (
+                "not installed; the lock file has no download URL for it",
+                Color::Red,
+            ),
+        };
+        if matches!(step, Step::Install | Step::Reinstall) {
+            downloads += 1;
+        }
+        outln!(
+            "  {:<32} {:<10} {}",
+            format!("{}{version}", plugin.name),
+            plugin.source,
+            paint(what, color)
+        );
+    }
+    for extra in extra_plugins(&lock, &status, config_dir)? {
+        outln!("  {extra:<32} {:<10} not in the lock file (left alone)", "");
+    }
+
+    let toggles = steps
+        .iter()
+        .filter(|(_, step)| matches!(step, Step::SetEnabled))
+        .count();
+    if downloads == 0 && toggles == 0 {
+        outln!(
+            "\n{}",
+            paint(
+                "Repository plugins already match the lock file.",
+                Color::Green
+            )
+        );
+        return Ok(());
+    }
+    if options.dry_run {
+        outln!("\n{}", paint("[Dry run] No changes made.", Color::Yellow));
+        return Ok(());
+    }
+
+    guard_config_dir(options.profile, config_dir, options.allow_suspicious_dirs)?;
+    if !options.yes && !confirm("\nProceed?")? {
+        outln!("Aborted.");
+        return Ok(());
+    }
+    if downloads > 0 {
+        back_up(config, options.profile, profile, &[REPOSITORIES_DIR])?;
+    }
+
+    let mut failed = 0;
+    let mut installed = Vec::new();
+    for (plugin, step) in &steps {
+        if plugin.is_manual() || matches!(step, Step::Unavailable) {
+            continue;
+        }
+        if matches!(step, Step::Install | Step::Reinstall) {
+            if let Err(e) = install_locked(config, options.profile, plugin, config_dir) {
+                report::warn(&format!("{}: {e}", plugin.name));
+                failed += 1;
+                continue;
+            }
+            installed.push((plugin.name.clone(), plugin.dir(config_dir)));
+        }
+        let entry = status.upsert(&plugin.source, plugin.listing());
+        let mut bits = status.status(entry) | INSTALLED_BIT;
+        if plugin.enabled {
+            bits |= ENABLED_BIT;
+        } else {
+            bits &= !ENABLED_BIT;
+        }
+        status.set_status(entry, bits);
+    }
+    status.save()?;
+    report::changed(&config_dir.join(REPOSITORIES_DIR));
+    offer_install(options.profile, profile, &installed, !options.yes);
+
+    if failed > 0 {
+        return Err(format!(
+            "{failed} plugin(s) failed to install; the rest match the lock file"
+        ));
+    }
+    let by_hand = steps
+        .iter()
+        .filter(|(_, step)| {
+            matches!(
+                step,
+                Step::ManualMissing | Step::ManualDiffers | Step::Unavailable
+            )
+        })
+        .count();
+    if by_hand > 0 {
+        let summary = format!(
+            "Applied the lock file to '{}'; {by_hand} plugin(s) listed above need attention by hand.",
+            options.profile
+        );
+        outln!("\n{}", paint(&summary, Color::Yellow));
+    } else {
+        let summary = format!("Profile '{}' matches the lock file.", options.profile);
+        outln!("\n{}", paint(&summary, Color::Green));
+    }
+    Ok(())
+}
+
+fn install_locked(
+    config: &Config,
+    profile_name: &str,
+    plugin: &LockedPlugin,
+    config_dir: &Path,
+) -> Result<(), String> {
+    let url = plugin
+        .url
+        .as_deref()
+        .ok_or("no download URL in the lock file")?;
+    outln!("  Downloading {} from {url}...", plugin.name);
+    let data = fetch(url)?;
+    let dir = plugin.dir(config_dir);
+    unpack(&data, &dir, plugin.subdir.as_deref())?;
+    report::changed(&dir);
+    settle_plugin(config, profile_name, config_dir, &dir)
+}
+
+/// Installed plugins the lock file doesn't mention
+fn extra_plugins(
+    lock: &LockFile,
+    status: &PluginStatus,
+    config_dir: &Path,
+) -> Result<Vec<String>, String> {
+    let locked = |source: &str, path: &str| {
+        lock.plugins
+            .iter()
+            .any(|p| p.source == source && p.path == path)
+    };
+    let mut extras: Vec<String> = status
+        .installed()
+        .filter_map(|entry| {
+            let path = status.plugin(entry)["path"].as_str()?;
+            (!locked(&status.repo_dir(entry), path)).then(|| path.to_string())
+        })
+        .collect();
+
+    let manual = config_dir.join(PLUGINS_DIR);
+    if manual.is_dir() {
+        for entry in fs::read_dir(&manual)
+            .map_err(|e| format!("Failed to read directory {}: {e}", manual.display()))?
+        {
+            let entry = entry.map_err(|e| format!("Failed to read entry: {e}"))?;
+            let name = entry.file_name().to_string_lossy().into_owned();
+            if is_plugin_entry(&entry.path()) && !locked(MANUAL_SOURCE, &name) {
+                extras.push(name);
+            }
+        }
+    }
+    extras.sort();
+    Ok(extras)
+}
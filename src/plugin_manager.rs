@@ -0,0 +1,986 @@
+use crate::backups::BackupStore;
+use crate::bn_version::detect_version;
+use crate::colors::paint;
+use crate::config::{Config, Profile};
+use crate::copy::copy_item;
+use crate::download::{extract_zip, fetch};
+use crate::history::format_age;
+use crate::items::HANDLERS;
+use crate::plugin_compat::incompatibility;
+use crate::plugin_deps::offer_install;
+use crate::plugin_index::{IndexPlugin, fetch_index};
+use crate::plugin_store::share;
+use crate::plugin_verify::{forget, record};
+use crate::plugins::{
+    ENABLED_BIT, INSTALLED_BIT, PLUGIN_METADATA_FILE, PLUGINS_DIR, PluginSource, PluginStatus,
+    REPOSITORIES_DIR, StatusEntry, describe_status, enclosed_name, is_installed, list_plugins,
+    read_plugin_metadata, read_script_metadata,
+};
+use crate::report::{self, dataln, outln};
+use crate::repositories::{format_size, tree_size};
+use crate::safety::guard_config_dir;
+use crate::sync::{confirm, remove_path};
+use serde_json::Value;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+use termcolor::Color;
+
+pub(crate) struct InstallOptions<'a> {
+    pub name: &'a str,
+    pub profile: &'a str,
+    /// Unpack into `plugins/`, outside Binary Ninja's plugin manager
+    pub manual: bool,
+    /// Reinstall a plugin that is already installed
+    pub force: bool,
+    pub allow_suspicious_dirs: bool,
+}
+
+/// Download a plugin listed in the profile's plugin repositories and install
+/// it the way Binary Ninja's plugin manager does
+pub(crate) fn run_install(config: &Config, options: &InstallOptions) -> Result<(), String> {
+    let profile = find_profile(config, options.profile)?;
+    guard_config_dir(
+        options.profile,
+        &profile.config_dir,
+        options.allow_suspicious_dirs,
+    )?;
+
+    let mut status = PluginStatus::load(&profile.config_dir)?;
+    let entry = status.find(options.name).ok_or_else(|| {
+        format!(
+            "Plugin '{}' isn't in the repository listings of profile '{}'",
+            options.name, options.profile
+        )
+    })?;
+    let plugin = status.plugin(entry);
+    let name = plugin["name"].as_str().unwrap_or(options.name).to_string();
+    let version = plugin["version"].as_str().unwrap_or("?").to_string();
+
+    let dst = if options.manual {
+        let dir = plugin["path"].as_str().unwrap_or(&name);
+        let dir = enclosed_name(dir).map_err(|e| format!("Plugin '{name}': {e}"))?;
+        profile.config_dir.join(PLUGINS_DIR).join(dir)
+    } else {
+        status
+            .install_dir(entry)
+            .map_err(|e| format!("Plugin '{name}': {e}"))?
+    };
+    let installed = options.manual || status.status(entry) & INSTALLED_BIT != 0;
+    if installed && dst.exists() && !options.force {
+        return Err(format!(
+            "'{name}' is already installed in profile '{}' (use --force to reinstall)",
+            options.profile
+        ));
+    }
+    if installed && dst.exists() {
+        // --force replaces the installed copy, which `restore` can bring back
+        let item = if options.manual {
+            PLUGINS_DIR
+        } else {
+            REPOSITORIES_DIR
+        };
+        back_up(config, options.profile, profile, &[item])?;
+    }
+
+    let files = download_plugin(plugin, &dst)?;
+    settle_plugin(config, options.profile, &profile.config_dir, &dst)?;
+    if !options.manual {
+        mark_installed(&mut status, entry);
+        status.save()?;
+    }
+    outln!(
+        "Installed {name} v{version} into profile '{}' ({files} files, {})",
+        options.profile,
+        dst.display()
+    );
+    offer_install(options.profile, profile, &[(name, dst)], true);
+    Ok(())
+}
+
+pub(crate) struct RemoveOptions<'a> {
+    pub name: &'a str,
+    pub profile: &'a str,
+    pub yes: bool,
+    pub allow_suspicious_dirs: bool,
+}
+
+/// An installed plugin and where its code lives
+struct Installed {
+    name: String,
+    version: Option<String>,
+    dir: PathBuf,
+    /// Set for plugins Binary Ninja's plugin manager installed
+    entry: Option<StatusEntry>,
+}
+
+impl Installed {
+    /// The sync item the plugin belongs to, which is what gets backed up
+    fn item(&self) -> &'static str {
+        if self.entry.is_some() {
+            REPOSITORIES_DIR
+        } else {
+            PLUGINS_DIR
+        }
+    }
+}
+
+/// Delete an installed plugin, repository-managed or manual, after backing
+/// up the item it lives in
+pub(crate) fn run_remove(config: &Config, options: &RemoveOptions) -> Result<(), String> {
+    let profile = find_profile(config, options.profile)?;
+    let mut status = PluginStatus::load_if_present(&profile.config_dir)?;
+    let plugin = find_installed(profile, status.as_ref(), options.name)?.ok_or_else(|| {
+        format!(
+            "No plugin '{}' is installed in profile '{}'",
+            options.name, options.profile
+        )
+    })?;
+
+    let version = plugin
+        .version
+        .as_deref()
+        .map(|v| format!(" v{v}"))
+        .unwrap_or_default();
+    outln!(
+        "Removing {}{version} from profile '{}':",
+        plugin.name,
+        options.profile
+    );
+    outln!("  {}", plugin.dir.display());
+
+    guard_config_dir(
+        options.profile,
+        &profile.config_dir,
+        options.allow_suspicious_dirs,
+    )?;
+    if !options.yes && !confirm("\nProceed?")? {
+        outln!("Aborted.");
+        return Ok(());
+    }
+
+    back_up(config, options.profile, profile, &[plugin.item()])?;
+    if fs::symlink_metadata(&plugin.dir).is_ok() {
+        remove_path(&plugin.dir)?;
+    }
+    report::changed(&plugin.dir);
+    forget(options.profile, &profile.config_dir, &plugin.dir)?;
+    if let (Some(status), Some(entry)) = (status.as_mut(), plugin.entry) {
+        let bits = status.status(entry) & !(INSTALLED_BIT | ENABLED_BIT);
+        status.set_status(entry, bits);
+        status.save()?;
+    }
+
+    outln!(
+        "Removed {}. Restore it with `bn-loader restore {} --latest --only {}`.",
+        plugin.name,
+        options.profile,
+        plugin.item()
+    );
+    Ok(())
+}
+
+pub(crate) struct CopyOptions<'a> {
+    pub name: &'a str,
+    pub from: &'a str,
+    pub to: &'a str,
+    /// Replace the plugin if the target already has it
+    pub force: bool,
+    pub yes: bool,
+    pub allow_suspicious_dirs: bool,
+}
+
+/// Copy one installed plugin to another profile, along with its entry in
+/// plugin_status.json for a repository plugin, after backing up the item it
+/// goes into
+pub(crate) fn run_copy(config: &Config, options: &CopyOptions) -> Result<(), String> {
+    let source = find_profile(config, options.from)?;
+    let target = find_profile(config, options.to)?;
+    if source.config_dir == target.config_dir {
+        return Err(format!(
+            "'{}' and '{}' share a config directory; they already have the same plugins",
+            options.from, options.to
+        ));
+    }
+
+    let source_status = PluginStatus::load_if_present(&source.config_dir)?;
+    let plugin =
+        find_installed(source, source_status.as_ref(), options.name)?.ok_or_else(|| {
+            format!(
+                "No plugin '{}' is installed in profile '{}'",
+                options.name, options.from
+            )
+        })?;
+    let mut target_status = match plugin.entry {
+        Some(_) => Some(
+            PluginStatus::load_if_present(&target.config_dir)?
+                .unwrap_or_else(|| PluginStatus::empty(&target.config_dir)),
+        ),
+        None => None,
+    };
+    let rel = plugin
+        .dir
+        .strip_prefix(&source.config_dir)
+        .map_err(|_| format!("{} is outside '{}'", plugin.dir.display(), options.from))?;
+    let dst = target.config_dir.join(rel);
+    // A linked `plugins` or `repositories` puts both at the same place, and
+    // replacing the copy would delete the original
+    if let (Ok(src), Ok(existing)) = (fs::canonicalize(&plugin.dir), fs::canonicalize(&dst))
+        && src == existing
+    {
+        return Err(format!(
+            "'{}' already shares {} with '{}' through a linked directory",
+            options.to, plugin.name, options.from
+        ));
+    }
+
+    if fs::symlink_metadata(&dst).is_ok() && !options.force {
+        return Err(format!(
+            "'{}' already has {} (use --force to replace it)",
+            options.to, plugin.name
+        ));
+    }
+    outln!(
+        "Copying {} from '{}' to '{}':",
+        plugin.name,
+        options.from,
+        options.to
+    );
+    outln!("  {} -> {}", plugin.dir.display(), dst.display());
+
+    guard_config_dir(
+        options.to,
+        &target.config_dir,
+        options.allow_suspicious_dirs,
+    )?;
+    if !options.yes && !confirm("\nProceed?")? {
+        outln!("Aborted.");
+        return Ok(());
+    }
+
+    back_up(config, options.to, target, &[plugin.item()])?;
+    if fs::symlink_metadata(&dst).is_ok() {
+        remove_path(&dst)?;
+    }
+    copy_item(&plugin.dir, &dst)?;
+    report::changed(&dst);
+    settle_plugin(config, options.to, &target.config_dir, &dst)?;
+
+    if let (Some(source_status), Some(entry), Some(target_status)) =
+        (&source_status, plugin.entry, target_status.as_mut())
+    {
+        let copied = target_status.upsert(
+            &source_status.repo_dir(entry),
+            source_status.plugin(entry).clone(),
+        );
+        target_status.set_status(copied, source_status.status(entry));
+        target_status.save()?;
+    }
+
+    outln!(
+        "Copied {} to '{}'. Restore the previous state with `bn-loader restore {} --latest --only {}`.",
+        plugin.name,
+        options.to,
+        options.to,
+        plugin.item()
+    );
+    offer_install(options.to, target, &[(plugin.name, dst)], !options.yes);
+    Ok(())
+}
+
+pub(crate) struct ToggleOptions<'a> {
+    pub names: &'a [String],
+    pub profile: &'a str,
+    pub enable: bool,
+    pub allow_suspicious_dirs: bool,
+}
+
+/// Enable or disable repository plugins without uninstalling them. Takes
+/// effect the next time Binary Ninja starts.
+pub(crate) fn run_toggle(config: &Config, options: &ToggleOptions) -> Result<(), String> {
+    let profile = find_profile(config, options.profile)?;
+    guard_config_dir(
+        options.profile,
+        &profile.config_dir,
+        options.allow_suspicious_dirs,
+    )?;
+    let mut status = PluginStatus::load(&profile.config_dir)?;
+
+    // Resolve every name before changing anything
+    let mut entries = Vec::new();
+    for name in options.names {
+        match status.find(name) {
+            Some(entry) if is_installed(status.status(entry)) => entries.push(entry),
+            _ if find_installed(profile, None, name)?.is_some() => {
+                return Err(format!(
+                    "'{name}' is a manual plugin; only repository plugins can be enabled or disabled"
+                ));
+            }
+            _ => {
+                return Err(format!(
+                    "No repository plugin '{name}' is installed in profile '{}'",
+                    options.profile
+                ));
+            }
+        }
+    }
+
+    let (verb, done) = if options.enable {
+        ("enabled", "Enabled")
+    } else {
+        ("disabled", "Disabled")
+    };
+    let mut changed = 0;
+    for entry in entries {
+        let bits = status.status(entry);
+        let name = status.plugin(entry)["name"]
+            .as_str()
+            .unwrap_or("?")
+            .to_string();
+        if (bits & ENABLED_BIT != 0) == options.enable {
+            outln!("  {name} is already {verb}");
+            continue;
+        }
+        let bits = if options.enable {
+            bits | INSTALLED_BIT | ENABLED_BIT
+        } else {
+            bits & !ENABLED_BIT
+        };
+        status.set_status(entry, bits);
+        outln!("  {done} {name}");
+        changed += 1;
+    }
+
+    if changed > 0 {
+        status.save()?;
+        report::changed(&profile.config_dir.join(REPOSITORIES_DIR));
+        outln!("Restart Binary Ninja for the change to take effect.");
+    }
+    Ok(())
+}
+
+/// Print everything known about one plugin of a profile: its metadata, where
+/// it lives, its size and its status in Binary Ninja's plugin manager
+pub(crate) fn run_show(config: &Config, profile_name: &str, name: &str) -> Result<(), String> {
+    let profile = find_profile(config, profile_name)?;
+    let status = PluginStatus::load_if_present(&profile.config_dir)?;
+    let (dir, entry) = match find_installed(profile, status.as_ref(), name)? {
+        Some(plugin) => (Some(plugin.dir), plugin.entry),
+        // Listed in a repository but not installed
+        None => match status.as_ref().and_then(|s| s.find(name)) {
+            Some(entry) => (None, Some(entry)),
+            None => {
+                return Err(format!(
+                    "No plugin '{name}' in profile '{profile_name}' or its repository listings"
+                ));
+            }
+        },
+    };
+
+    // plugin.json on disk wins over the repository listing
+    let local = match &dir {
+        Some(file) if file.is_file() => {
+            let info = read_script_metadata(file, "");
+            serde_json::json!({ "name": info.name, "version": info.version, "author": info.author })
+        }
+        Some(dir) => fs::read_to_string(dir.join(PLUGIN_METADATA_FILE))
+            .ok()
+            .and_then(|content| serde_json::from_str::<Value>(&content).ok())
+            .unwrap_or(Value::Null),
+        None => Value::Null,
+    };
+    let listing = match (&status, entry) {
+        (Some(status), Some(entry)) => status.plugin(entry).clone(),
+        _ => Value::Null,
+    };
+    let field = |keys: &[&str]| {
+        [&local, &listing]
+            .into_iter()
+            .flat_map(|meta| keys.iter().map(move |key| &meta[*key]))
+            .find(|value| !value.is_null())
+    };
+    let text = |keys: &[&str]| field(keys).and_then(|v| v.as_str()).map(str::to_string);
+
+    let title = text(&["name"]).unwrap_or_else(|| name.to_string());
+    match text(&["version"]) {
+        Some(version) => dataln!("{title} v{version}"),
+        None => dataln!("{title}"),
+    }
+    if let Some(description) = text(&["description"]) {
+        dataln!("  {}", description.trim());
+    }
+    dataln!();
+
+    if let Some(author) = text(&["author"]) {
+        dataln!("  Author:      {author}");
+    }
+    let apis = field(&["api", "apis"]).map(|api| match api {
+        Value::Array(list) => list
+            .iter()
+            .filter_map(Value::as_str)
+            .collect::<Vec<_>>()
+            .join(", "),
+        other => other.as_str().unwrap_or_default().to_string(),
+    });
+    if let Some(apis) = apis.filter(|a| !a.is_empty()) {
+        dataln!("  API:         {apis}");
+    }
+    if let Some(min) = field(&["minimumbinaryninjaversion", "minimumBinaryNinjaVersion"]) {
+        let min = min.as_str().map_or_else(|| min.to_string(), str::to_string);
+        dataln!("  Requires:    Binary Ninja build {min} or later");
+    }
+    if let Some(version) = detect_version(&profile.install_dir)
+        && let Some(problem) = incompatibility(&[local.clone(), listing.clone()], version.build)
+    {
+        dataln!("  Warning:     {problem}; the profile runs {version}");
+    }
+    let license = field(&["license"]).and_then(|license| match license {
+        Value::Object(l) => l.get("name").and_then(Value::as_str),
+        other => other.as_str(),
+    });
+    if let Some(license) = license {
+        dataln!("  License:     {license}");
+    }
+    if let Some(url) = text(&["projectUrl"]) {
+        dataln!("  Project:     {url}");
+    }
+    if let Some(commit) = text(&["commit"]) {
+        dataln!("  Commit:      {commit}");
+    }
+
+    let source = match (&status, entry) {
+        (Some(status), Some(entry)) => status.repo_dir(entry),
+        _ => PluginSource::Manual.label().to_string(),
+    };
+    dataln!("  Source:      {source}");
+    match &dir {
+        Some(dir) => {
+            dataln!("  Path:        {}", dir.display());
+            dataln!("  Size:        {}", format_size(tree_size(dir)?));
+            if let Some(modified) = last_modified(dir) {
+                let age = SystemTime::now()
+                    .duration_since(modified)
+                    .unwrap_or_default()
+                    .as_secs();
+                dataln!("  Modified:    {}", format_age(age));
+            }
+        }
+        None => dataln!("  Path:        (not on disk)"),
+    }
+    if let (Some(status), Some(entry)) = (&status, entry) {
+        let bits = status.status(entry);
+        dataln!("  Status:      {} ({bits:#x})", describe_status(bits));
+    }
+    Ok(())
+}
+
+/// The newest modification time of anything in `path`
+fn last_modified(path: &Path) -> Option<SystemTime> {
+    let metadata = fs::symlink_metadata(path).ok()?;
+    let mut newest = metadata.modified().ok();
+    if metadata.is_dir() {
+        for entry in fs::read_dir(path).ok()?.filter_map(|e| e.ok()) {
+            newest = newest.max(last_modified(&entry.path()));
+        }
+    }
+    newest
+}
+
+/// List a profile's plugins by disk usage, largest first, with when each was
+/// last modified
+pub(crate) fn run_du(config: &Config, profile_name: &str) -> Result<(), String> {
+    let profile = find_profile(config, profile_name)?;
+    let plugins = list_plugins(profile)?;
+    if plugins.is_empty() {
+        dataln!("No plugins installed for profile '{profile_name}'");
+        return Ok(());
+    }
+
+    let now = SystemTime::now();
+    let mut rows = Vec::new();
+    for plugin in &plugins {
+        // Measure what a link (e.g. into the plugin store) points to
+        let linked = fs::symlink_metadata(&plugin.path).is_ok_and(|m| m.file_type().is_symlink());
+        let path = fs::canonicalize(&plugin.path).unwrap_or_else(|_| plugin.path.clone());
+        let size = tree_size(&path)?;
+        let age = last_modified(&path)
+            .map(|modified| now.duration_since(modified).unwrap_or_default().as_secs());
+        rows.push((plugin, size, age, linked));
+    }
+    rows.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| b.2.cmp(&a.2)));
+
+    dataln!("Disk usage of plugins in profile '{profile_name}':\n");
+    dataln!("  {:>10}  {:<14} Plugin", "Size", "Modified");
+    for (plugin, size, age, linked) in &rows {
+        let name = plugin.name.as_deref().unwrap_or(&plugin.dir_name);
+        let age = age.map_or_else(|| "?".to_string(), format_age);
+        let link = if *linked { ", linked" } else { "" };
+        dataln!(
+            "  {:>10}  {age:<14} {name} ({}{link})",
+            format_size(*size),
+            plugin.source.label()
+        );
+    }
+    let total: u64 = rows.iter().map(|(_, size, ..)| size).sum();
+    dataln!(
+        "\nTotal: {} in {} plugin(s).",
+        format_size(total),
+        rows.len()
+    );
+    Ok(())
+}
+
+/// Longest description shown in search results
+const MAX_DESCRIPTION_WIDTH: usize = 76;
+
+/// Search the upstream repository listings. With a profile, results it
+/// already has are marked.
+pub(crate) fn run_search(config: &Config, term: &str, profile: Option<&str>) -> Result<(), String> {
+    let installed: Vec<String> = match profile {
+        Some(name) => list_plugins(find_profile(config, name)?)?
+            .into_iter()
+            .map(|p| p.dir_name)
+            .collect(),
+        None => Vec::new(),
+    };
+    outln!("Fetching plugin repository listings...");
+    let index = fetch_index()?;
+
+    let mut results: Vec<&IndexPlugin> = index.iter().filter(|p| p.matches(term)).collect();
+    if results.is_empty() {
+        dataln!("No plugins match '{term}'.");
+        return Ok(());
+    }
+    results.sort_by_key(|p| p.name().to_lowercase());
+
+    dataln!("{} plugin(s) match '{term}':", results.len());
+    for plugin in results {
+        let version = plugin
+            .version()
+            .map(|v| format!(" v{v}"))
+            .unwrap_or_default();
+        let author = plugin
+            .author()
+            .map(|a| format!(" by {a}"))
+            .unwrap_or_default();
+        let marker = if plugin
+            .path()
+            .is_some_and(|p| installed.iter().any(|i| i == p))
+        {
+            " [installed]"
+        } else {
+            ""
+        };
+        dataln!(
+            "\n  {}{version}{author} ({}){marker}",
+            plugin.name(),
+            plugin.repo
+        );
+        if let Some(description) = plugin.description() {
+            dataln!(
+                "      {}",
+                truncate(description.trim(), MAX_DESCRIPTION_WIDTH)
+            );
+        }
+    }
+    Ok(())
+}
+
+/// The first line of `text`, cut to `width` characters
+fn truncate(text: &str, width: usize) -> String {
+    let line = text.lines().next().unwrap_or_default();
+    if line.chars().count() <= width {
+        return line.to_string();
+    }
+    let cut: String = line.chars().take(width - 3).collect();
+    format!("{}...", cut.trim_end())
+}
+
+/// A repository plugin whose upstream listing has moved on
+pub(crate) struct Update<'a> {
+    pub entry: StatusEntry,
+    pub name: String,
+    /// Installed version, or commit when there are no versions
+    pub installed: String,
+    pub latest: String,
+    pub upstream: &'a IndexPlugin,
+}
+
+/// List the repository plugins with updates, for one profile or all of them
+pub(crate) fn run_outdated(config: &Config, profile: Option<&str>) -> Result<(), String> {
+    let groups = profile_groups(config, profile)?;
+    outln!("Fetching plugin repository listings...");
+    let index = fetch_index()?;
+
+    for (names, profile) in groups {
+        let label = names.join("', '");
+        let Some(status) = PluginStatus::load_if_present(&profile.config_dir)? else {
+            dataln!("\n'{label}': no repository plugins");
+            continue;
+        };
+        let installed = status.installed().count();
+        let updates = find_updates(&status, &index);
+        if updates.is_empty() {
+            dataln!("\n'{label}': {installed} repository plugins, all up to date");
+            continue;
+        }
+        dataln!(
+            "\n'{label}': {} of {installed} repository plugins have updates:",
+            updates.len()
+        );
+        for update in &updates {
+            dataln!(
+                "    {:<32} {} -> {}  ({})",
+                update.name,
+                update.installed,
+                update.latest,
+                update.upstream.repo
+            );
+        }
+    }
+    Ok(())
+}
+
+pub(crate) struct UpdateOptions<'a> {
+    /// Plugins to update, by name or directory name
+    pub names: &'a [String],
+    /// Update every repository plugin with a newer version instead
+    pub all: bool,
+    pub profile: &'a str,
+    pub dry_run: bool,
+    pub yes: bool,
+    pub allow_suspicious_dirs: bool,
+}
+
+/// Download the newer upstream version of a profile's repository plugins,
+/// as `plugin outdated` finds them, after backing up `repositories`
+pub(crate) fn run_update(config: &Config, options: &UpdateOptions) -> Result<(), String> {
+    let profile = find_profile(config, options.profile)?;
+    let mut status = PluginStatus::load(&profile.config_dir)?;
+    for name in options.names {
+        if !status
+            .find(name)
+            .is_some_and(|entry| is_installed(status.status(entry)))
+        {
+            return Err(format!(
+                "No repository plugin '{name}' is installed in profile '{}'",
+                options.profile
+            ));
+        }
+    }
+
+    outln!("Fetching plugin repository listings...");
+    let index = fetch_index()?;
+    let mut updates = find_updates(&status, &index);
+    if !options.all {
+        for name in options.names {
+            if !updates.iter().any(|u| status.is_named(u.entry, name)) {
+                outln!("  {name} is up to date");
+            }
+        }
+        updates.retain(|u| {
+            options
+                .names
+                .iter()
+                .any(|name| status.is_named(u.entry, name))
+        });
+    }
+    if updates.is_empty() {
+        outln!("Nothing to update in profile '{}'.", options.profile);
+        return Ok(());
+    }
+
+    outln!(
+        "{} plugin(s) to update in profile '{}':",
+        updates.len(),
+        options.profile
+    );
+    for update in &updates {
+        outln!(
+            "    {:<32} {} -> {}",
+            update.name,
+            update.installed,
+            update.latest
+        );
+    }
+    if options.dry_run {
+        outln!("\n{}", paint("[Dry run] No changes made.", Color::Yellow));
+        return Ok(());
+    }
+
+    guard_config_dir(
+        options.profile,
+        &profile.config_dir,
+        options.allow_suspicious_dirs,
+    )?;
+    if !options.yes && !confirm("\nProceed?")? {
+        outln!("Aborted.");
+        return Ok(());
+    }
+    back_up(config, options.profile, profile, &[REPOSITORIES_DIR])?;
+
+    let mut updated = Vec::new();
+    let mut failed = Vec::new();
+    for update in &updates {
+        let result = status.install_dir(update.entry).and_then(|dir| {
+            download_plugin(&update.upstream.listing, &dir)?;
+            settle_plugin(config, options.profile, &profile.config_dir, &dir)?;
+            Ok(dir)
+        });
+        match result {
+            Ok(dir) => {
+                // The listing only changes once the new version is in place
+                status.upsert(update.upstream.repo, update.upstream.listing.clone());
+                updated.push((update, dir));
+            }
+            Err(e) => failed.push((update, e)),
+        }
+    }
+    status.save()?;
+    report::changed(&profile.config_dir.join(REPOSITORIES_DIR));
+
+    outln!();
+    for (update, _) in &updated {
+        outln!(
+            "  Updated {} {} -> {}",
+            update.name,
+            update.installed,
+            update.latest
+        );
+    }
+    for (update, e) in &failed {
+        report::warn(&format!("Failed to update {}: {e}", update.name));
+    }
+    let dirs: Vec<(String, PathBuf)> = updated
+        .into_iter()
+        .map(|(update, dir)| (update.name.clone(), dir))
+        .collect();
+    offer_install(options.profile, profile, &dirs, !options.yes);
+
+    if !failed.is_empty() {
+        return Err(format!(
+            "{} of {} plugin(s) failed to update",
+            failed.len(),
+            updates.len()
+        ));
+    }
+    outln!("Restart Binary Ninja to load the new versions.");
+    Ok(())
+}
+
+/// Installed repository plugins whose version (or, without versions, commit)
+/// differs from the upstream listing
+pub(crate) fn find_updates<'a>(status: &PluginStatus, index: &'a [IndexPlugin]) -> Vec<Update<'a>> {
+    status
+        .installed()
+        .filter_map(|entry| {
+            let plugin = status.plugin(entry);
+            let repo = status.repo_dir(entry);
+            let path = plugin["path"].as_str()?;
+            let upstream = index
+                .iter()
+                .find(|p| p.repo == repo && p.path() == Some(path))?;
+
+            // Binary Ninja refreshes the entry whenever it fetches the
+            // listings, so the plugin's own plugin.json knows best what is
+            // actually installed
+            let installed = status
+                .install_dir(entry)
+                .ok()
+                .and_then(|dir| read_plugin_metadata(&dir, path).version)
+                .or_else(|| plugin["version"].as_str().map(str::to_string));
+            let (installed, latest) = match (installed, upstream.version()) {
+                (Some(installed), Some(latest)) => (installed, latest.to_string()),
+                _ => (
+                    short_commit(&plugin["commit"])?,
+                    short_commit(&upstream.listing["commit"])?,
+                ),
+            };
+            (installed != latest).then(|| Update {
+                entry,
+                name: plugin["name"].as_str().unwrap_or(path).to_string(),
+                installed,
+                latest,
+                upstream,
+            })
+        })
+        .collect()
+}
+
+fn short_commit(commit: &Value) -> Option<String> {
+    commit.as_str().map(|c| c.chars().take(10).collect())
+}
+
+/// The selected profile, or every profile; profiles sharing a config dir are
+/// grouped, since they have the same plugins
+pub(crate) fn profile_groups<'a>(
+    config: &'a Config,
+    profile: Option<&str>,
+) -> Result<Vec<(Vec<&'a str>, &'a Profile)>, String> {
+    if let Some(name) = profile {
+        let (name, profile) = config
+            .profiles
+            .get_key_value(name)
+            .ok_or_else(|| format!("Profile '{name}' not found."))?;
+        return Ok(vec![(vec![name.as_str()], profile)]);
+    }
+
+    let mut names: Vec<&String> = config.profiles.keys().collect();
+    names.sort_unstable();
+    let mut groups: Vec<(Vec<&str>, &Profile)> = Vec::new();
+    for name in names {
+        let profile = &config.profiles[name];
+        match groups
+            .iter_mut()
+            .find(|(_, p)| p.config_dir == profile.config_dir)
+        {
+            Some((names, _)) => names.push(name),
+            None => groups.push((vec![name], profile)),
+        }
+    }
+    Ok(groups)
+}
+
+/// Back up sync items of a profile before bn-loader changes them, the same
+/// way a sync does
+pub(crate) fn back_up(
+    config: &Config,
+    name: &str,
+    profile: &Profile,
+    items: &[&str],
+) -> Result<(), String> {
+    let store = BackupStore::new(
+        config.global.backup_dir.as_deref(),
+        name,
+        &profile.config_dir,
+    );
+    let items: Vec<_> = HANDLERS
+        .iter()
+        .copied()
+        .filter(|handler| items.contains(&handler.name()))
+        .collect();
+    if let Some(backup) = store.create(&items)? {
+        outln!("  Backup created: {}", backup.display());
+        report::changed(&backup);
+    }
+    if config.global.backup_retention > 0 {
+        store.cleanup(config.global.backup_retention)?;
+    }
+    Ok(())
+}
+
+/// Find an installed plugin by name (ignoring case) or directory name
+fn find_installed(
+    profile: &Profile,
+    status: Option<&PluginStatus>,
+    name: &str,
+) -> Result<Option<Installed>, String> {
+    if let Some(status) = status
+        && let Some(entry) = status.find(name)
+        && is_installed(status.status(entry))
+        && let Ok(dir) = status.install_dir(entry)
+    {
+        let plugin = status.plugin(entry);
+        return Ok(Some(Installed {
+            name: plugin["name"].as_str().unwrap_or(name).to_string(),
+            version: plugin["version"].as_str().map(str::to_string),
+            dir,
+            entry: Some(entry),
+        }));
+    }
+
+    Ok(list_plugins(profile)?
+        .into_iter()
+        .filter(|p| matches!(p.source, PluginSource::Manual))
+        .find(|p| {
+            p.dir_name == name
+                || p.name
+                    .as_deref()
+                    .is_some_and(|n| n.eq_ignore_ascii_case(name))
+        })
+        .map(|p| Installed {
+            name: p.name.unwrap_or_else(|| p.dir_name.clone()),
+            version: p.version,
+            dir: profile.config_dir.join(PLUGINS_DIR).join(&p.dir_name),
+            entry: None,
+        }))
+}
+
+pub(crate) fn find_profile<'a>(config: &'a Config, name: &str) -> Result<&'a Profile, String> {
+    config
+        .profiles
+        .get(name)
+        .ok_or_else(|| format!("Profile '{name}' not found."))
+}
+
+/// The archive to download: `packageUrl`, or the project's archive at the
+/// listed commit
+pub(crate) fn package_url(plugin: &Value) -> Option<String> {
+    if let Some(url) = plugin["packageUrl"].as_str().filter(|u| !u.is_empty()) {
+        return Some(url.to_string());
+    }
+    let project = plugin["projectUrl"].as_str()?.trim_end_matches('/');
+    let commit = plugin["commit"].as_str()?;
+    Some(format!("{project}/archive/{commit}.zip"))
+}
+
+/// Unpack next to `dst` first, so a failed download or extraction leaves
+/// what was installed before untouched
+pub(crate) fn unpack(data: &[u8], dst: &Path, subdir: Option<&str>) -> Result<usize, String> {
+    let dir_name = dst
+        .file_name()
+        .map(|n| n.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    let staging = dst.with_file_name(format!(".{dir_name}.bn-loader-partial"));
+    if fs::symlink_metadata(&staging).is_ok() {
+        remove_path(&staging)?;
+    }
+
+    let files = match extract_zip(data, &staging, subdir) {
+        Ok(files) => files,
+        Err(e) => {
+            let _ = remove_path(&staging);
+            return Err(e);
+        }
+    };
+    if fs::symlink_metadata(dst).is_ok() {
+        remove_path(dst)?;
+    }
+    fs::rename(&staging, dst)
+        .map_err(|e| format!("Failed to move plugin into place at {}: {e}", dst.display()))?;
+    Ok(files)
+}
+
+/// Bookkeeping once bn-loader has put a plugin into a profile: record its
+/// hashes for `plugin verify` and, with `plugin_store` on, move a manual
+/// plugin into the shared store
+pub(crate) fn settle_plugin(
+    config: &Config,
+    profile_name: &str,
+    config_dir: &Path,
+    dir: &Path,
+) -> Result<(), String> {
+    record(profile_name, config_dir, dir)?;
+    share(config, config_dir, dir)
+}
+
+/// Download the archive a plugin's listing points at and unpack it into
+/// `dst`. Returns the number of files.
+pub(crate) fn download_plugin(plugin: &Value, dst: &Path) -> Result<usize, String> {
+    let name = plugin["name"].as_str().unwrap_or("?");
+    let version = plugin["version"].as_str().unwrap_or("?");
+    let url = package_url(plugin)
+        .ok_or_else(|| format!("Plugin '{name}' has no download URL in its listing"))?;
+    outln!("Downloading {name} v{version} from {url}...");
+    let data = fetch(&url)?;
+    let files = unpack(&data, dst, plugin["subdir"].as_str())?;
+    report::changed(dst);
+    Ok(files)
+}
+
+/// Mark the plugin installed and enabled, as Binary Ninja does after an
+/// install
+pub(crate) fn mark_installed(status: &mut PluginStatus, entry: StatusEntry) {
+    let bits = status.status(entry) | INSTALLED_BIT | ENABLED_BIT;
+    status.set_status(entry, bits);
+}
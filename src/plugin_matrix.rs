@@ -0,0 +1,163 @@
+use crate::colors::{stdout, writeln_bold, writeln_colored};
+use crate::config::Config;
+use crate::plugin_manager::profile_groups;
+use crate::plugins::{PluginSource, filter_plugins, list_plugins};
+use crate::report::{self, dataln};
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::io::Write;
+use termcolor::Color;
+
+/// Longest plugin name shown before it is cut
+const MAX_NAME_WIDTH: usize = 40;
+const MISSING: &str = "-";
+
+/// A plugin as installed in one column's profile
+#[derive(Clone, PartialEq, Serialize)]
+struct Cell {
+    /// `?` without one
+    version: String,
+    disabled: bool,
+}
+
+impl Cell {
+    fn text(&self) -> String {
+        if self.disabled {
+            format!("{} (disabled)", self.version)
+        } else {
+            self.version.clone()
+        }
+    }
+}
+
+/// One plugin across profiles: its name and, per column, how it is installed
+#[derive(Serialize)]
+struct Row {
+    name: String,
+    dir_name: String,
+    cells: Vec<Option<Cell>>,
+}
+
+impl Row {
+    /// Missing from some profiles, at different versions or disabled in some
+    fn differs(&self) -> bool {
+        self.cells.windows(2).any(|pair| pair[0] != pair[1])
+    }
+}
+
+/// Print a plugin x profile table of what is installed where, with the rows
+/// that differ between profiles highlighted. Profiles sharing a config
+/// directory make one column.
+pub(crate) fn run_matrix(
+    config: &Config,
+    source: Option<&PluginSource>,
+    search: Option<&str>,
+    json: bool,
+) -> Result<(), String> {
+    let groups = profile_groups(config, None)?;
+    if groups.is_empty() && !json {
+        dataln!("No profiles configured.");
+        return Ok(());
+    }
+
+    // Plugins are matched across profiles by directory name, as `diff` does
+    let mut rows: BTreeMap<String, Row> = BTreeMap::new();
+    for (column, (_, profile)) in groups.iter().enumerate() {
+        let mut plugins = list_plugins(profile)?;
+        filter_plugins(&mut plugins, source, search);
+        for plugin in plugins {
+            let cell = Cell {
+                version: plugin.version.clone().unwrap_or_else(|| "?".to_string()),
+                disabled: plugin.is_disabled(),
+            };
+            let row = rows.entry(plugin.dir_name.clone()).or_insert_with(|| Row {
+                name: plugin.name.unwrap_or_else(|| plugin.dir_name.clone()),
+                dir_name: plugin.dir_name,
+                cells: vec![None; groups.len()],
+            });
+            row.cells[column] = Some(cell);
+        }
+    }
+    let mut rows: Vec<Row> = rows.into_values().collect();
+    rows.sort_by_key(|row| row.name.to_lowercase());
+    let labels: Vec<String> = groups.iter().map(|(names, _)| names.join("/")).collect();
+
+    if json {
+        // Columns are listed once; each row's cells follow their order
+        return report::print_json(&serde_json::json!({
+            "profiles": labels,
+            "plugins": rows,
+        }));
+    }
+    if rows.is_empty() {
+        dataln!("No plugins installed in any profile.");
+        return Ok(());
+    }
+
+    let name_width = rows
+        .iter()
+        .map(|row| row.name.chars().count())
+        .max()
+        .unwrap_or(0)
+        .clamp("Plugin".len(), MAX_NAME_WIDTH);
+    let widths: Vec<usize> = labels
+        .iter()
+        .enumerate()
+        .map(|(column, label)| {
+            rows.iter()
+                .filter_map(|row| row.cells[column].as_ref())
+                .map(|cell| cell.text().len())
+                .chain([label.len()])
+                .max()
+                .unwrap_or(0)
+        })
+        .collect();
+
+    let line = |name: &str, cells: Vec<&str>| {
+        let mut text = format!("  {:<name_width$}", cut(name, name_width));
+        for (cell, width) in cells.into_iter().zip(&widths) {
+            text.push_str(&format!("  {cell:<width$}"));
+        }
+        text.trim_end().to_string()
+    };
+
+    let mut out = stdout();
+    let header = line("Plugin", labels.iter().map(String::as_str).collect());
+    writeln_bold(&mut out, &header).map_err(|e| e.to_string())?;
+    let mut differing = 0;
+    for row in &rows {
+        let cells: Vec<String> = row
+            .cells
+            .iter()
+            .map(|cell| {
+                cell.as_ref()
+                    .map_or_else(|| MISSING.to_string(), Cell::text)
+            })
+            .collect();
+        let text = line(&row.name, cells.iter().map(String::as_str).collect());
+        if row.differs() {
+            differing += 1;
+            writeln_colored(&mut out, &text, Color::Yellow)
+        } else {
+            writeln!(out, "{text}")
+        }
+        .map_err(|e| e.to_string())?;
+    }
+    writeln!(
+        out,
+        "\n{} plugin(s) across {} profile(s); {differing} differ between them.",
+        rows.len(),
+        labels.len()
+    )
+    .map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+/// `name` cut to `width` characters
+fn cut(name: &str, width: usize) -> String {
+    if name.chars().count() <= width {
+        return name.to_string();
+    }
+    let cut: String = name.chars().take(width - 3).collect();
+    format!("{cut}...")
+}
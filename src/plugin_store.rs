@@ -0,0 +1,277 @@
+use crate::backups::BackupStore;
+use crate::colors::paint;
+use crate::config::{Config, cache_dir, default_exclusions};
+use crate::copy::{copy_item, read_link};
+use crate::manifest::hash_dir;
+use crate::plugin_manager::profile_groups;
+use crate::plugins::PLUGINS_DIR;
+use crate::report::{self, outln};
+use crate::repositories::{format_size, tree_size};
+use crate::sync::{build_glob_set, confirm, create_dir_link, remove_path};
+use std::collections::HashSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+use termcolor::Color;
+
+/// Directory of the shared store under the cache dir
+const STORE_DIR: &str = "plugin-store";
+
+/// What adding a plugin to the store did
+enum Stored {
+    /// First copy of this content; it now lives in the store
+    Moved,
+    /// The store already had it, so the plugin's own copy was dropped
+    Deduplicated(u64),
+    /// Already a link into the store
+    Linked,
+}
+
+pub(crate) fn store_dir() -> Result<PathBuf, String> {
+    cache_dir()
+        .map(|dir| dir.join(STORE_DIR))
+        .ok_or_else(|| "Could not determine the cache directory".to_string())
+}
+
+/// With `plugin_store` on, move a plugin that was just installed or copied
+/// into `plugins/` into the store and leave a link in its place
+pub(crate) fn share(config: &Config, config_dir: &Path, dir: &Path) -> Result<(), String> {
+    if !config.global.plugin_store || !is_manual_plugin(config_dir, dir) || !dir.is_dir() {
+        return Ok(());
+    }
+    store(&store_dir()?, dir).map(|_| ())
+}
+
+/// Only `plugins/` is shared. Binary Ninja's plugin manager updates the
+/// repository plugins under `repositories/` in place, which through a link
+/// would change every profile sharing the copy and leave it under a stale
+/// hash.
+fn is_manual_plugin(config_dir: &Path, dir: &Path) -> bool {
+    dir.parent() == Some(config_dir.join(PLUGINS_DIR).as_path())
+}
+
+/// Move a plugin directory into the store, keyed by the hash of its
+/// contents, and link it back. Identical plugins end up as links to one
+/// copy.
+fn store(store: &Path, dir: &Path) -> Result<Stored, String> {
+    if let Ok(target) = read_link(dir) {
+        if target.starts_with(store) {
+            return Ok(Stored::Linked);
+        }
+        return Err(format!(
+            "{} is a link to {}, outside the plugin store",
+            dir.display(),
+            target.display()
+        ));
+    }
+
+    let exclusions = build_glob_set(&default_exclusions())?;
+    let object = store.join(hash_dir(dir, &exclusions)?);
+    let stored = if object.is_dir() {
+        let size = tree_size(dir)?;
+        remove_path(dir)?;
+        Stored::Deduplicated(size)
+    } else {
+        fs::create_dir_all(store)
+            .map_err(|e| format!("Failed to create directory {}: {e}", store.display()))?;
+        // The cache dir may be on another filesystem
+        if fs::rename(dir, &object).is_err() {
+            copy_item(dir, &object)?;
+            remove_path(dir)?;
+        }
+        Stored::Moved
+    };
+    create_dir_link(&object, dir)?;
+    report::changed(dir);
+    Ok(stored)
+}
+
+/// The plugin directories of a profile the store takes: `plugins/*`
+fn plugin_dirs(config_dir: &Path) -> Vec<PathBuf> {
+    let Ok(entries) = fs::read_dir(config_dir.join(PLUGINS_DIR)) else {
+        return Vec::new();
+    };
+    let mut dirs: Vec<PathBuf> = entries
+        .filter_map(|e| e.ok())
+        .map(|e| e.path())
+        .filter(|path| path.is_dir())
+        .collect();
+    dirs.sort();
+    dirs
+}
+
+pub(crate) struct DedupeOptions<'a> {
+    /// Only this profile (default: every profile)
+    pub profile: Option<&'a str>,
+    pub dry_run: bool,
+    pub yes: bool,
+}
+
+/// Move the plugins profiles already have into the store, so identical
+/// copies share one directory
+pub(crate) fn run_dedupe(config: &Config, options: &DedupeOptions) -> Result<(), String> {
+    let store_path = store_dir()?;
+    let groups = profile_groups(config, options.profile)?;
+    let mut candidates: Vec<(String, PathBuf)> = Vec::new();
+    for (names, profile) in &groups {
+        for dir in plugin_dirs(&profile.config_dir) {
+            if fs::symlink_metadata(&dir).is_ok_and(|m| !m.file_type().is_symlink()) {
+                candidates.push((names.join("/"), dir));
+            }
+        }
+    }
+    if candidates.is_empty() {
+        outln!("Every plugin is already in the store.");
+        return Ok(());
+    }
+
+    outln!(
+        "{} plugin(s) to move into {}:",
+        candidates.len(),
+        store_path.display()
+    );
+    for (label, dir) in &candidates {
+        outln!(
+            "    {} {}",
+            paint(&format!("{label:<16}"), Color::Cyan),
+            dir.display()
+        );
+    }
+    if options.dry_run {
+        outln!("\n{}", paint("[Dry run] No changes made.", Color::Yellow));
+        return Ok(());
+    }
+    if !options.yes && !confirm("\nProceed?")? {
+        outln!("Aborted.");
+        return Ok(());
+    }
+
+    let (mut moved, mut deduplicated, mut saved) = (0, 0, 0);
+    for (_, dir) in &candidates {
+        match store(&store_path, dir) {
+            Ok(Stored::Moved) => moved += 1,
+            Ok(Stored::Deduplicated(size)) => {
+                deduplicated += 1;
+                saved += size;
+            }
+            Ok(Stored::Linked) => {}
+            Err(e) => report::warn(&e),
+        }
+    }
+    let summary = format!(
+        "Moved {moved} plugin(s) into the store; {deduplicated} duplicate(s) became links ({} saved).",
+        format_size(saved)
+    );
+    outln!("\n{}", paint(&summary, Color::Green));
+    Ok(())
+}
+
+/// Remove store entries no profile (or backup of one) links to
+pub(crate) fn run_gc(config: &Config, dry_run: bool) -> Result<(), String> {
+    let store_path = store_dir()?;
+    let Ok(entries) = fs::read_dir(&store_path) else {
+        outln!("The plugin store is empty.");
+        return Ok(());
+    };
+
+    // Backups of plugins keep their links, so they count as users too
+    let mut used = HashSet::new();
+    for (name, profile) in &config.profiles {
+        let backups = BackupStore::new(
+            config.global.backup_dir.as_deref(),
+            name,
+            &profile.config_dir,
+        );
+        let mut roots = vec![profile.config_dir.clone()];
+        roots.extend(backups.list()?.into_iter().map(|(path, _)| path));
+        for dir in roots.iter().flat_map(|root| plugin_dirs(root)) {
+            if let Ok(target) = read_link(&dir) {
+                used.insert(target);
+            }
+        }
+    }
+
+    let mut unused = Vec::new();
+    for entry in entries.filter_map(|e| e.ok()) {
+        if !used.contains(&entry.path()) {
+            unused.push(entry.path());
+        }
+    }
+    if unused.is_empty() {
+        outln!("Every plugin in the store is in use.");
+        return Ok(());
+    }
+
+    let mut freed = 0;
+    for object in &unused {
+        freed += tree_size(object).unwrap_or(0);
+        if dry_run {
+            outln!(
+                "  {} {}",
+                paint("Would remove", Color::Red),
+                object.display()
+            );
+        } else {
+            remove_path(object)?;
+            report::changed(object);
+        }
+    }
+    let verb = if dry_run { "Would free" } else { "Freed" };
+    let summary = format!(
+        "{verb} {} from {} unused plugin(s).",
+        format_size(freed),
+        unused.len()
+    );
+    outln!("{}", paint(&summary, Color::Green));
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn plugin(config_dir: &Path, rel: &str, contents: &str) -> PathBuf {
+        let dir = config_dir.join(rel);
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(dir.join("__init__.py"), contents).unwrap();
+        dir
+    }
+
+    #[test]
+    fn only_manual_plugins_are_stored() {
+        let root = tempfile::tempdir().unwrap();
+        let config_dir = root.path().join("cfg");
+        let manual = plugin(&config_dir, "plugins/manual", "a");
+        let repo = plugin(&config_dir, "repositories/community/plugins/repo", "b");
+        plugin(&config_dir, "plugins/manual/nested", "c");
+
+        assert_eq!(plugin_dirs(&config_dir), std::slice::from_ref(&manual));
+        assert!(is_manual_plugin(&config_dir, &manual));
+        assert!(!is_manual_plugin(&config_dir, &repo));
+        assert!(!is_manual_plugin(&config_dir, &manual.join("nested")));
+    }
+
+    #[test]
+    fn identical_plugins_share_one_object() {
+        let root = tempfile::tempdir().unwrap();
+        let store_path = root.path().join("store");
+        let first = plugin(&root.path().join("a"), "plugins/foo", "same");
+        let second = plugin(&root.path().join("b"), "plugins/foo", "same");
+        let other = plugin(&root.path().join("c"), "plugins/foo", "other");
+
+        assert!(matches!(store(&store_path, &first), Ok(Stored::Moved)));
+        assert!(matches!(
+            store(&store_path, &second),
+            Ok(Stored::Deduplicated(4))
+        ));
+        assert!(matches!(store(&store_path, &first), Ok(Stored::Linked)));
+        assert!(matches!(store(&store_path, &other), Ok(Stored::Moved)));
+
+        assert_eq!(read_link(&first).unwrap(), read_link(&second).unwrap());
+        assert_ne!(read_link(&first).unwrap(), read_link(&other).unwrap());
+        assert_eq!(
+            fs::read_to_string(second.join("__init__.py")).unwrap(),
+            "same"
+        );
+        assert_eq!(fs::read_dir(&store_path).unwrap().count(), 2);
+    }
+}
@@ -0,0 +1,197 @@
+use crate::colors::paint;
+use crate::config::{Config, cache_dir, default_exclusions};
+use crate::manifest::{file_changes, hash_files};
+use crate::plugin_manager::find_profile;
+use crate::plugins::list_plugins;
+use crate::report::{dataln, outln};
+use crate::sync::build_glob_set;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+use termcolor::Color;
+
+const HASHES_DIR: &str = "plugin-hashes";
+/// Changed files listed per plugin before the rest are summarized
+const MAX_FILES_DISPLAY: usize = 10;
+
+/// Hashes of the plugins bn-loader installed into a profile, by path
+/// relative to its config dir. They live in the cache dir rather than the
+/// profile, so whoever changes a shared profile can't update them as well.
+type PluginHashes = BTreeMap<String, RecordedPlugin>;
+
+#[derive(Serialize, Deserialize)]
+struct RecordedPlugin {
+    /// When it was installed, or its changes accepted
+    timestamp: u64,
+    /// Relative path (with `/` separators) -> SHA-256 hex digest
+    files: BTreeMap<String, String>,
+}
+
+fn hashes_path(profile: &str) -> Result<PathBuf, String> {
+    cache_dir()
+        .map(|dir| dir.join(HASHES_DIR).join(format!("{profile}.json")))
+        .ok_or_else(|| "Could not determine cache directory".to_string())
+}
+
+fn load_hashes(profile: &str) -> Result<PluginHashes, String> {
+    let path = hashes_path(profile)?;
+    match fs::read_to_string(&path) {
+        Ok(content) => serde_json::from_str(&content)
+            .map_err(|e| format!("Failed to parse {}: {e}", path.display())),
+        Err(_) => Ok(PluginHashes::new()),
+    }
+}
+
+fn save_hashes(profile: &str, hashes: &PluginHashes) -> Result<(), String> {
+    let path = hashes_path(profile)?;
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)
+            .map_err(|e| format!("Failed to create directory {}: {e}", parent.display()))?;
+    }
+    let json = serde_json::to_string_pretty(hashes)
+        .map_err(|e| format!("Failed to serialize plugin hashes: {e}"))?;
+    fs::write(&path, json).map_err(|e| format!("Failed to write {}: {e}", path.display()))
+}
+
+/// `repositories/official/plugins/foo`
+fn plugin_key(config_dir: &Path, dir: &Path) -> String {
+    let rel = dir.strip_prefix(config_dir).unwrap_or(dir);
+    rel.components()
+        .map(|c| c.as_os_str().to_string_lossy())
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+fn current_files(dir: &Path) -> Result<BTreeMap<String, String>, String> {
+    hash_files(dir, &build_glob_set(&default_exclusions())?)
+}
+
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+/// Record the files of a plugin bn-loader just put into a profile, for
+/// `plugin verify` to check against later
+pub(crate) fn record(profile_name: &str, config_dir: &Path, dir: &Path) -> Result<(), String> {
+    let mut hashes = load_hashes(profile_name)?;
+    hashes.insert(
+        plugin_key(config_dir, dir),
+        RecordedPlugin {
+            timestamp: now(),
+            files: current_files(dir)?,
+        },
+    );
+    save_hashes(profile_name, &hashes)
+}
+
+/// Drop the hashes of a plugin bn-loader removed from a profile
+pub(crate) fn forget(profile_name: &str, config_dir: &Path, dir: &Path) -> Result<(), String> {
+    let mut hashes = load_hashes(profile_name)?;
+    if hashes.remove(&plugin_key(config_dir, dir)).is_some() {
+        save_hashes(profile_name, &hashes)?;
+    }
+    Ok(())
+}
+
+pub(crate) struct VerifyOptions<'a> {
+    pub profile: &'a str,
+    /// Record the current contents of changed plugins as the trusted ones
+    pub accept: bool,
+}
+
+/// Compare the plugins bn-loader installed into a profile with the hashes
+/// recorded then. Returns whether every plugin is unchanged (or the changes
+/// were accepted).
+pub(crate) fn run_verify(config: &Config, options: &VerifyOptions) -> Result<bool, String> {
+    let profile = find_profile(config, options.profile)?;
+    let mut hashes = load_hashes(options.profile)?;
+    let unrecorded = list_plugins(profile)?
+        .iter()
+        .filter(|plugin| !hashes.contains_key(&plugin_key(&profile.config_dir, &plugin.path)))
+        .count();
+    if hashes.is_empty() {
+        dataln!(
+            "bn-loader hasn't installed any plugin into '{}', so there are no hashes to verify against.",
+            options.profile
+        );
+        return Ok(true);
+    }
+
+    dataln!(
+        "Verifying plugins of '{}' against the hashes recorded at install:\n",
+        options.profile
+    );
+    let mut changed: Vec<(String, Option<BTreeMap<String, String>>)> = Vec::new();
+    for (key, recorded) in &hashes {
+        let dir = profile.config_dir.join(key);
+        if fs::symlink_metadata(&dir).is_err() {
+            dataln!("  {key}: {}", paint("removed", Color::Red));
+            changed.push((key.clone(), None));
+            continue;
+        }
+        let current = current_files(&dir)?;
+        let changes = file_changes(&recorded.files, &current, |_| true);
+        if changes.is_empty() {
+            continue;
+        }
+        let summary = format!("{} file(s) changed", changes.len());
+        dataln!("  {key}: {}", paint(&summary, Color::Yellow));
+        for change in changes.iter().take(MAX_FILES_DISPLAY) {
+            dataln!("      {}", change.describe());
+        }
+        if changes.len() > MAX_FILES_DISPLAY {
+            dataln!("      ... and {} more", changes.len() - MAX_FILES_DISPLAY);
+        }
+        changed.push((key.clone(), Some(current)));
+    }
+
+    if changed.is_empty() {
+        let summary = format!("Every plugin matches: {} unchanged.", hashes.len());
+        dataln!("  {}", paint(&summary, Color::Green));
+    } else {
+        let summary = format!(
+            "{} of {} plugin(s) changed since bn-loader installed them.",
+            changed.len(),
+            hashes.len()
+        );
+        dataln!("\n{}", paint(&summary, Color::Yellow));
+    }
+    if unrecorded > 0 {
+        dataln!("{unrecorded} plugin(s) weren't installed by bn-loader and have no hashes.");
+    }
+    if changed.is_empty() {
+        return Ok(true);
+    }
+    if !options.accept {
+        outln!(
+            "Run `bn-loader plugin verify {} --accept` to trust the current contents.",
+            options.profile
+        );
+        return Ok(false);
+    }
+
+    for (key, files) in changed {
+        match files {
+            Some(files) => {
+                hashes.insert(
+                    key,
+                    RecordedPlugin {
+                        timestamp: now(),
+                        files,
+                    },
+                );
+            }
+            None => {
+                hashes.remove(&key);
+            }
+        }
+    }
+    save_hashes(options.profile, &hashes)?;
+    outln!("{}", paint("Accepted the current contents.", Color::Green));
+    Ok(true)
+}
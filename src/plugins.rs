@@ -1,16 +1,38 @@
+use crate::colors::paint;
 use crate::config::Profile;
-use serde::Deserialize;
-use std::fs;
-use std::path::Path;
-
-// Bit 1 (value 2) indicates "installed" in pluginStatus
-const INSTALLED_BIT: u32 = 2;
+use crate::report::dataln;
+use crate::repositories::{format_size, tree_size};
+use clap::ValueEnum;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::borrow::Cow;
+use std::cmp::Reverse;
+use std::fs::{self, File};
+use std::io::{BufReader, BufWriter, Write};
+use std::path::{Component, Path, PathBuf};
+use termcolor::Color;
+
+// pluginStatus is Binary Ninja's PluginStatus bit set
+pub(crate) const INSTALLED_BIT: u32 = 0x1;
+pub(crate) const ENABLED_BIT: u32 = 0x2;
+const STATUS_BITS: &[(u32, &str)] = &[
+    (INSTALLED_BIT, "installed"),
+    (ENABLED_BIT, "enabled"),
+    (0x10, "update available"),
+    (0x20, "delete pending"),
+    (0x40, "update pending"),
+    (0x80, "disable pending"),
+    (0x200, "restart pending"),
+    (0x400, "being updated"),
+    (0x800, "being deleted"),
+    (0x1000, "installing dependencies"),
+];
 
 // Directory and file names
-const PLUGINS_DIR: &str = "plugins";
-const REPOSITORIES_DIR: &str = "repositories";
+pub(crate) const PLUGINS_DIR: &str = "plugins";
+pub(crate) const REPOSITORIES_DIR: &str = "repositories";
 const PLUGIN_STATUS_FILE: &str = "plugin_status.json";
-const PLUGIN_METADATA_FILE: &str = "plugin.json";
+pub(crate) const PLUGIN_METADATA_FILE: &str = "plugin.json";
 
 #[derive(Deserialize, Default)]
 struct PluginJson {
@@ -22,42 +44,78 @@ struct PluginJson {
     author: Option<String>,
 }
 
+/// plugin_status.json, borrowing the few fields `plugins` shows from the
+/// file's bytes (a string only gets copied when it has escapes)
 #[derive(Deserialize)]
-struct PluginStatusFile(Vec<Repository>);
+struct PluginStatusFile<'a>(#[serde(borrow)] Vec<Repository<'a>>);
 
 #[derive(Deserialize)]
-struct Repository {
-    #[serde(default)]
-    plugins: Vec<RepoPlugin>,
+struct Repository<'a> {
+    #[serde(default, borrow)]
+    path: Option<Cow<'a, str>>,
+    #[serde(default, borrow)]
+    plugins: Vec<RepoPlugin<'a>>,
 }
 
 #[derive(Deserialize)]
-struct RepoPlugin {
-    #[serde(default)]
-    name: Option<String>,
-    #[serde(default)]
-    version: Option<String>,
-    #[serde(default)]
-    author: Option<String>,
-    #[serde(default)]
-    path: Option<String>,
+struct RepoPlugin<'a> {
+    #[serde(default, borrow)]
+    name: Option<Cow<'a, str>>,
+    #[serde(default, borrow)]
+    version: Option<Cow<'a, str>>,
+    #[serde(default, borrow)]
+    author: Option<Cow<'a, str>>,
+    #[serde(default, borrow)]
+    path: Option<Cow<'a, str>>,
     #[serde(default, rename = "pluginStatus")]
     plugin_status: u32,
 }
 
-#[derive(Clone)]
+#[derive(Clone, PartialEq, Eq, ValueEnum)]
 pub(crate) enum PluginSource {
     Manual,
     Official,
     Community,
 }
 
+impl PluginSource {
+    pub(crate) fn label(&self) -> &'static str {
+        match self {
+            PluginSource::Manual => "manual",
+            PluginSource::Official => "official",
+            PluginSource::Community => "community",
+        }
+    }
+}
+
+/// Order of plugin listings
+#[derive(Clone, Copy, Default, ValueEnum)]
+pub(crate) enum PluginSort {
+    #[default]
+    Name,
+    /// Newest first
+    Version,
+    /// Largest first
+    Size,
+}
+
 pub(crate) struct PluginInfo {
     pub dir_name: String,
+    /// Where the plugin's code lives
+    pub path: PathBuf,
     pub name: Option<String>,
     pub version: Option<String>,
     pub author: Option<String>,
     pub source: PluginSource,
+    /// pluginStatus of a repository plugin
+    pub status: Option<u32>,
+}
+
+impl PluginInfo {
+    /// Installed, but switched off in Binary Ninja's plugin manager
+    pub(crate) fn is_disabled(&self) -> bool {
+        self.status.is_some_and(|status| status & ENABLED_BIT == 0)
+    }
 }
 
 pub(crate) fn list_plugins(profile: &Profile) -> Result<Vec<PluginInfo>, String> {
@@ -96,20 +154,19 @@ fn read_manual_plugins(plugins_dir: &Path) -> Result<Vec<PluginInfo>, String> {
     for entry in entries {
         let entry = entry.map_err(|e| format!("Failed to read entry: {e}"))?;
         let path = entry.path();
+        let dir_name = entry.file_name().to_string_lossy().to_string();
 
-        if !path.is_dir() {
-            continue;
+        if path.is_dir() {
+            plugins.push(read_plugin_metadata(&path, &dir_name));
+        } else if is_plugin_entry(&path) {
+            plugins.push(read_script_metadata(&path, &dir_name));
         }
-
-        let dir_name = entry.file_name().to_string_lossy().to_string();
-        let plugin_info = read_plugin_metadata(&path, &dir_name);
-        plugins.push(plugin_info);
     }
 
     Ok(plugins)
 }
 
-fn read_plugin_metadata(plugin_dir: &Path, dir_name: &str) -> PluginInfo {
+pub(crate) fn read_plugin_metadata(plugin_dir: &Path, dir_name: &str) -> PluginInfo {
     let plugin_json_path = plugin_dir.join(PLUGIN_METADATA_FILE);
 
     if plugin_json_path.exists()
@@ -118,29 +175,110 @@ fn read_plugin_metadata(plugin_dir: &Path, dir_name: &str) -> PluginInfo {
     {
         return PluginInfo {
             dir_name: dir_name.to_string(),
+            path: plugin_dir.to_path_buf(),
             name: meta.name,
             version: meta.version,
             author: meta.author,
             source: PluginSource::Manual,
+            status: None,
         };
     }
 
     PluginInfo {
         dir_name: dir_name.to_string(),
+        path: plugin_dir.to_path_buf(),
         name: None,
         version: None,
         author: None,
         source: PluginSource::Manual,
+        status: None,
     }
 }
 
-fn read_repo_plugins(status_file: &Path) -> Result<Vec<PluginInfo>, String> {
-    let content = fs::read_to_string(status_file)
-        .map_err(|e| format!("Failed to read plugin_status.json: {e}"))?;
+/// Whether an entry of `plugins/` is a plugin: a directory, or a single
+/// `.py` file Binary Ninja loads as a module
+pub(crate) fn is_plugin_entry(path: &Path) -> bool {
+    path.is_dir()
+        || (path.is_file()
+            && path
+                .extension()
+                .is_some_and(|ext| ext.eq_ignore_ascii_case("py")))
+}
+
+/// Metadata of a single-file plugin, from `key: value` lines in its module
+/// docstring:
+///
+/// ```text
+/// """
+/// name: Rename Helper
+/// version: 1.2
+/// author: Someone
+/// """
+/// ```
+pub(crate) fn read_script_metadata(path: &Path, file_name: &str) -> PluginInfo {
+    let mut info = PluginInfo {
+        dir_name: file_name.to_string(),
+        path: path.to_path_buf(),
+        name: None,
+        version: None,
+        author: None,
+        source: PluginSource::Manual,
+        status: None,
+    };
+    let Ok(content) = fs::read(path) else {
+        return info;
+    };
+    let content = String::from_utf8_lossy(&content);
+    let Some(docstring) = module_docstring(&content) else {
+        return info;
+    };
+    for line in docstring.lines() {
+        let Some((key, value)) = line.split_once(':') else {
+            continue;
+        };
+        let value = Some(value.trim().to_string()).filter(|v| !v.is_empty());
+        match key.trim().to_ascii_lowercase().as_str() {
+            "name" => info.name = value,
+            "version" => info.version = value,
+            "author" => info.author = value,
+            _ => {}
+        }
+    }
+    info
+}
+
+/// The module's docstring: the first statement, if it is a triple-quoted
+/// string
+fn module_docstring(source: &str) -> Option<&str> {
+    let mut rest = source.trim_start_matches('\u{feff}');
+    // Skip the shebang, encoding line, comments and blank lines before it
+    loop {
+        rest = rest.trim_start();
+        if rest.starts_with('#') {
+            rest = rest.split_once('\n').map_or("", |(_, after)| after);
+        } else {
+            break;
+        }
+    }
+    let rest = rest.trim_start_matches(['r', 'R', 'u', 'U']);
+    let quote = ["\"\"\"", "'''"]
+        .into_iter()
+        .find(|quote| rest.starts_with(quote))?;
+    let body = &rest[quote.len()..];
+    body.find(quote).map(|end| &body[..end])
+}
 
-    let repos: PluginStatusFile = serde_json::from_str(&content)
+fn read_repo_plugins(status_file: &Path) -> Result<Vec<PluginInfo>, String> {
+    // The file carries every repository plugin's full description and can
+    // run to tens of MB. Streaming it with from_reader kept the peak near
+    // 1 MB but walked those descriptions a byte at a time, about 8x slower;
+    // holding the bytes costs the file's size only while they are skipped.
+    let data =
+        fs::read(status_file).map_err(|e| format!("Failed to read plugin_status.json: {e}"))?;
+    let repos: PluginStatusFile = serde_json::from_slice(&data)
         .map_err(|e| format!("Failed to parse plugin_status.json: {e}"))?;
 
+    let repositories_dir = status_file.parent().unwrap_or(Path::new(""));
     let mut plugins = Vec::new();
 
     for (idx, repo) in repos.0.iter().enumerate() {
@@ -150,16 +288,22 @@ fn read_repo_plugins(status_file: &Path) -> Result<Vec<PluginInfo>, String> {
         } else {
             PluginSource::Community
         };
+        let repo_dir = repo.path.as_deref().unwrap_or(source.label());
 
         for plugin in &repo.plugins {
-            // Check if installed (bit 1 set)
-            if plugin.plugin_status & INSTALLED_BIT != 0 {
+            if is_installed(plugin.plugin_status) {
+                let dir_name = plugin.path.as_deref().unwrap_or_default().to_string();
                 plugins.push(PluginInfo {
-                    dir_name: plugin.path.clone().unwrap_or_default(),
-                    name: plugin.name.clone(),
-                    version: plugin.version.clone(),
-                    author: plugin.author.clone(),
+                    path: repositories_dir
+                        .join(repo_dir)
+                        .join(PLUGINS_DIR)
+                        .join(&dir_name),
+                    dir_name,
+                    name: plugin.name.as_deref().map(str::to_string),
+                    version: plugin.version.as_deref().map(str::to_string),
+                    author: plugin.author.as_deref().map(str::to_string),
                     source: source.clone(),
+                    status: Some(plugin.plugin_status),
                 });
             }
         }
@@ -168,9 +312,325 @@ fn read_repo_plugins(status_file: &Path) -> Result<Vec<PluginInfo>, String> {
     Ok(plugins)
 }
 
-pub(crate) fn print_plugins(profile_name: &str, plugins: &[PluginInfo]) {
+/// Enabled plugins always carry the installed bit too, but older status
+/// files only have the enabled one set
+pub(crate) fn is_installed(status: u32) -> bool {
+    status & (INSTALLED_BIT | ENABLED_BIT) != 0
+}
+
+/// pluginStatus spelled out, e.g. `installed, enabled, update available`
+pub(crate) fn describe_status(status: u32) -> String {
+    if status == 0 {
+        return "not installed".to_string();
+    }
+    status_names(status).join(", ")
+}
+
+/// The states in pluginStatus beyond installed and enabled, such as
+/// `disabled` or `update pending`, which listings point out
+pub(crate) fn notable_status(status: u32) -> Vec<String> {
+    status_names(status)
+        .into_iter()
+        .filter(|name| name != "installed" && name != "enabled")
+        .collect()
+}
+
+fn status_names(status: u32) -> Vec<String> {
+    let mut names: Vec<String> = STATUS_BITS
+        .iter()
+        .filter(|(bit, _)| status & bit != 0)
+        .map(|(_, name)| name.to_string())
+        .collect();
+    let unknown = STATUS_BITS
+        .iter()
+        .fold(status, |rest, (bit, _)| rest & !bit);
+    if unknown != 0 {
+        names.push(format!("{unknown:#x}"));
+    }
+    if status & ENABLED_BIT == 0 && is_installed(status) {
+        names.insert(1, "disabled".to_string());
+    }
+    names
+}
+
+/// A profile's plugin_status.json, kept as raw JSON so that every field
+/// Binary Ninja wrote survives a rewrite
+pub(crate) struct PluginStatus {
+    path: PathBuf,
+    repos: Vec<Value>,
+}
+
+/// Where a plugin sits in plugin_status.json: (repository, plugin) indices
+#[derive(Clone, Copy)]
+pub(crate) struct StatusEntry(usize, usize);
+
+impl PluginStatus {
+    pub(crate) fn load(config_dir: &Path) -> Result<Self, String> {
+        let path = config_dir.join(REPOSITORIES_DIR).join(PLUGIN_STATUS_FILE);
+        let file = File::open(&path).map_err(|e| {
+            format!(
+                "Failed to read {} (start Binary Ninja once so it fetches the plugin repositories): {e}",
+                path.display()
+            )
+        })?;
+        let repos: Vec<Value> = serde_json::from_reader(BufReader::new(file))
+            .map_err(|e| format!("Failed to parse plugin_status.json: {e}"))?;
+        Ok(Self { path, repos })
+    }
+
+    /// A profile that has never fetched the plugin repositories
+    pub(crate) fn empty(config_dir: &Path) -> Self {
+        Self {
+            path: config_dir.join(REPOSITORIES_DIR).join(PLUGIN_STATUS_FILE),
+            repos: Vec::new(),
+        }
+    }
+
+    /// Like [`PluginStatus::load`], but a profile without the file just has
+    /// no repository plugins
+    pub(crate) fn load_if_present(config_dir: &Path) -> Result<Option<Self>, String> {
+        let path = config_dir.join(REPOSITORIES_DIR).join(PLUGIN_STATUS_FILE);
+        if !path.exists() {
+            return Ok(None);
+        }
+        Self::load(config_dir).map(Some)
+    }
+
+    /// Find a repository plugin by name (ignoring case) or directory name
+    pub(crate) fn find(&self, name: &str) -> Option<StatusEntry> {
+        self.entries().find(|&entry| self.is_named(entry, name))
+    }
+
+    /// Like [`PluginStatus::find`], within repository `repo` only
+    pub(crate) fn find_named(&self, repo: &str, name: &str) -> Option<StatusEntry> {
+        self.entries()
+            .find(|&entry| self.repo_dir(entry) == repo && self.is_named(entry, name))
+    }
+
+    /// Whether `name` is the plugin's name (ignoring case) or directory name
+    pub(crate) fn is_named(&self, entry: StatusEntry, name: &str) -> bool {
+        let plugin = self.plugin(entry);
+        plugin["name"]
+            .as_str()
+            .is_some_and(|n| n.eq_ignore_ascii_case(name))
+            || plugin["path"].as_str() == Some(name)
+    }
+
+    /// The plugin in directory `path` of repository `repo`
+    pub(crate) fn find_in(&self, repo: &str, path: &str) -> Option<StatusEntry> {
+        self.entries()
+            .find(|&entry| self.repo_dir(entry) == repo && self.plugin(entry)["path"] == path)
+    }
+
+    /// Add a plugin's listing to repository `repo`, or update the fields of
+    /// the one already there. Its status is kept.
+    pub(crate) fn upsert(&mut self, repo: &str, listing: Value) -> StatusEntry {
+        if let Some(path) = listing["path"].as_str()
+            && let Some(entry) = self.find_in(repo, path)
+        {
+            let plugin = &mut self.repos[entry.0]["plugins"][entry.1];
+            if let (Some(plugin), Value::Object(fields)) = (plugin.as_object_mut(), listing) {
+                plugin.extend(fields);
+            }
+            return entry;
+        }
+
+        let repo_idx =
+            match (0..self.repos.len()).find(|&idx| self.repo_dir(StatusEntry(idx, 0)) == repo) {
+                Some(idx) => idx,
+                None => {
+                    self.repos
+                        .push(serde_json::json!({ "path": repo, "plugins": [] }));
+                    self.repos.len() - 1
+                }
+            };
+        let plugins = &mut self.repos[repo_idx]["plugins"];
+        if !plugins.is_array() {
+            *plugins = Value::Array(Vec::new());
+        }
+        let list = plugins.as_array_mut().expect("just made an array");
+        list.push(listing);
+        StatusEntry(repo_idx, list.len() - 1)
+    }
+
+    /// Every plugin marked installed
+    pub(crate) fn installed(&self) -> impl Iterator<Item = StatusEntry> + '_ {
+        self.entries()
+            .filter(|&entry| is_installed(self.status(entry)))
+    }
+
+    fn entries(&self) -> impl Iterator<Item = StatusEntry> + '_ {
+        self.repos.iter().enumerate().flat_map(|(repo, value)| {
+            let count = value["plugins"].as_array().map_or(0, Vec::len);
+            (0..count).map(move |plugin| StatusEntry(repo, plugin))
+        })
+    }
+
+    pub(crate) fn plugin(&self, entry: StatusEntry) -> &Value {
+        &self.repos[entry.0]["plugins"][entry.1]
+    }
+
+    /// Directory under `repositories/` the plugin's repository lives in
+    pub(crate) fn repo_dir(&self, entry: StatusEntry) -> String {
+        match self.repos[entry.0]["path"].as_str() {
+            Some(path) => path.to_string(),
+            // Same guess as the listing: official first, community after
+            None if entry.0 == 0 => "official".to_string(),
+            None => "community".to_string(),
+        }
+    }
+
+    /// Where the plugin's code goes once installed. The listing's paths
+    /// must be plain directory names, so it can't point outside the profile.
+    pub(crate) fn install_dir(&self, entry: StatusEntry) -> Result<PathBuf, String> {
+        let dir = self.plugin(entry)["path"]
+            .as_str()
+            .ok_or("no path in its listing")?;
+        let repo = self.repo_dir(entry);
+        let repositories = self
+            .path
+            .parent()
+            .ok_or("plugin_status.json has no parent directory")?;
+        Ok(repositories
+            .join(enclosed_name(&repo)?)
+            .join(PLUGINS_DIR)
+            .join(enclosed_name(dir)?))
+    }
+
+    pub(crate) fn status(&self, entry: StatusEntry) -> u32 {
+        self.plugin(entry)["pluginStatus"]
+            .as_u64()
+            .and_then(|s| u32::try_from(s).ok())
+            .unwrap_or(0)
+    }
+
+    pub(crate) fn set_status(&mut self, entry: StatusEntry, status: u32) {
+        self.repos[entry.0]["plugins"][entry.1]["pluginStatus"] = Value::from(status);
+    }
+
+    /// Write the file back, through a temporary file so Binary Ninja never
+    /// sees half of it
+    pub(crate) fn save(&self) -> Result<(), String> {
+        if let Some(parent) = self.path.parent() {
+            fs::create_dir_all(parent)
+                .map_err(|e| format!("Failed to create directory {}: {e}", parent.display()))?;
+        }
+        let temp = self.path.with_extension("json.tmp");
+        let file =
+            File::create(&temp).map_err(|e| format!("Failed to write {}: {e}", temp.display()))?;
+        let mut writer = BufWriter::new(file);
+        serde_json::to_writer(&mut writer, &self.repos)
+            .map_err(|e| format!("Failed to write plugin_status.json: {e}"))?;
+        writer
+            .flush()
+            .map_err(|e| format!("Failed to write plugin_status.json: {e}"))?;
+        fs::rename(&temp, &self.path)
+            .map_err(|e| format!("Failed to replace {}: {e}", self.path.display()))
+    }
+}
+
+/// Keep the plugins from `source` whose name, directory or author contains
+/// `search` (ignoring case)
+pub(crate) fn filter_plugins(
+    plugins: &mut Vec<PluginInfo>,
+    source: Option<&PluginSource>,
+    search: Option<&str>,
+) {
+    let search = search.map(str::to_lowercase);
+    plugins.retain(|plugin| {
+        source.is_none_or(|source| plugin.source == *source)
+            && search.as_deref().is_none_or(|term| {
+                [
+                    Some(&plugin.dir_name),
+                    plugin.name.as_ref(),
+                    plugin.author.as_ref(),
+                ]
+                .into_iter()
+                .flatten()
+                .any(|field| field.to_lowercase().contains(term))
+            })
+    });
+}
+
+/// Sort plugins for listing; [`list_plugins`] already returns them by name
+pub(crate) fn sort_plugins(plugins: &mut [PluginInfo], sort: PluginSort) {
+    match sort {
+        PluginSort::Name => {}
+        // Stable, so plugins without a version stay by name at the end
+        PluginSort::Version => {
+            plugins.sort_by_key(|p| Reverse(p.version.as_deref().map(version_key)))
+        }
+        PluginSort::Size => {
+            plugins.sort_by_cached_key(|p| Reverse(tree_size(&p.path).unwrap_or(0)))
+        }
+    }
+}
+
+/// `1.10.2-beta` -> [1, 10, 2], so versions compare by their numbers
+fn version_key(version: &str) -> Vec<u64> {
+    version
+        .split(|c: char| !c.is_ascii_digit())
+        .filter(|part| !part.is_empty())
+        .filter_map(|part| part.parse().ok())
+        .collect()
+}
+
+/// A plugin as `plugins --json` prints it
+#[derive(Serialize)]
+struct PluginOutput<'a> {
+    name: &'a str,
+    dir_name: &'a str,
+    version: Option<&'a str>,
+    author: Option<&'a str>,
+    source: &'static str,
+    path: &'a Path,
+    enabled: bool,
+    /// Notable plugin manager states, such as `update available`
+    status: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    size: Option<u64>,
+}
+
+/// [`print_plugins`] as JSON
+pub(crate) fn print_plugins_json(
+    profile_name: &str,
+    plugins: &[PluginInfo],
+    sizes: bool,
+) -> Result<(), String> {
+    let plugins: Vec<PluginOutput> = plugins
+        .iter()
+        .map(|plugin| PluginOutput {
+            name: plugin.name.as_deref().unwrap_or(&plugin.dir_name),
+            dir_name: &plugin.dir_name,
+            version: plugin.version.as_deref(),
+            author: plugin.author.as_deref(),
+            source: plugin.source.label(),
+            path: &plugin.path,
+            enabled: !plugin.is_disabled(),
+            status: plugin.status.map(notable_status).unwrap_or_default(),
+            size: sizes.then(|| tree_size(&plugin.path).unwrap_or(0)),
+        })
+        .collect();
+    crate::report::print_json(&serde_json::json!({
+        "profile": profile_name,
+        "plugins": plugins,
+    }))
+}
+
+/// `name` if it is a single plain path component, so joining it onto a
+/// directory stays inside that directory (no `..`, `/` or drive prefixes)
+pub(crate) fn enclosed_name(name: &str) -> Result<&str, String> {
+    let mut components = Path::new(name).components();
+    match (components.next(), components.next()) {
+        (Some(Component::Normal(_)), None) => Ok(name),
+        _ => Err(format!("'{name}' isn't a plain directory name")),
+    }
+}
+
+/// With `sizes`, each plugin's size on disk is shown
+pub(crate) fn print_plugins(profile_name: &str, plugins: &[PluginInfo], sizes: bool) {
     if plugins.is_empty() {
-        println!("No plugins installed for profile '{profile_name}'");
+        dataln!("No plugins installed for profile '{profile_name}'");
         return;
     }
 
@@ -187,35 +647,43 @@ pub(crate) fn print_plugins(profile_name: &str, plugins: &[PluginInfo]) {
         .filter(|p| matches!(p.source, PluginSource::Community))
         .collect();
 
-    println!(
+    dataln!(
         "Plugins for profile '{}' ({} total):",
         profile_name,
         plugins.len()
     );
 
     if !official.is_empty() {
-        println!("\n  [Official Repository] ({}):", official.len());
+        dataln!(
+            "\n  {} ({}):",
+            paint("[Official Repository]", Color::Cyan),
+            official.len()
+        );
         for plugin in &official {
-            print_plugin_line(plugin);
+            print_plugin_line(plugin, sizes);
         }
     }
 
     if !community.is_empty() {
-        println!("\n  [Community Repository] ({}):", community.len());
+        dataln!(
+            "\n  {} ({}):",
+            paint("[Community Repository]", Color::Cyan),
+            community.len()
+        );
         for plugin in &community {
-            print_plugin_line(plugin);
+            print_plugin_line(plugin, sizes);
         }
     }
 
     if !manual.is_empty() {
-        println!("\n  [Manual] ({}):", manual.len());
+        dataln!("\n  {} ({}):", paint("[Manual]", Color::Cyan), manual.len());
         for plugin in &manual {
-            print_plugin_line(plugin);
+            print_plugin_line(plugin, sizes);
         }
     }
 }
 
-fn print_plugin_line(plugin: &PluginInfo) {
+fn print_plugin_line(plugin: &PluginInfo, sizes: bool) {
     let display_name = plugin.name.as_deref().unwrap_or(&plugin.dir_name);
     let version = plugin.version.as_deref().unwrap_or("?");
     let author = plugin
@@ -224,5 +692,198 @@ fn print_plugin_line(plugin: &PluginInfo) {
         .map(|a| format!(" by {a}"))
         .unwrap_or_default();
 
-    println!("    {display_name} v{version}{author}");
+    let size = if sizes {
+        format!(" ({})", format_size(tree_size(&plugin.path).unwrap_or(0)))
+    } else {
+        String::new()
+    };
+    let flags = plugin
+        .status
+        .map(notable_status)
+        .filter(|flags| !flags.is_empty())
+        .map(|flags| {
+            format!(
+                " {}",
+                paint(&format!("[{}]", flags.join(", ")), Color::Yellow)
+            )
+        })
+        .unwrap_or_default();
+
+    dataln!("    {display_name} v{version}{author}{size}{flags}");
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::peak_memory::measure;
+    use serde_json::json;
+
+    fn status_with(repo_path: &str, plugin_path: &str) -> PluginStatus {
+        PluginStatus {
+            path: Path::new("cfg")
+                .join(REPOSITORIES_DIR)
+                .join(PLUGIN_STATUS_FILE),
+            repos: vec![json!({
+                "path": repo_path,
+                "plugins": [{ "name": "Example", "path": plugin_path }],
+            })],
+        }
+    }
+
+    #[test]
+    fn enclosed_name_takes_plain_names() {
+        assert_eq!(enclosed_name("Vector35_snippets"), Ok("Vector35_snippets"));
+        assert_eq!(enclosed_name("ünïcode plugin"), Ok("ünïcode plugin"));
+    }
+
+    #[test]
+    fn enclosed_name_rejects_escapes() {
+        for name in ["", ".", "..", "../x", "a/b", "/etc", "./x"] {
+            assert!(enclosed_name(name).is_err(), "{name:?}");
+        }
+        #[cfg(windows)]
+        for name in [r"C:\x", r"..\x", r"a\b", "C:"] {
+            assert!(enclosed_name(name).is_err(), "{name:?}");
+        }
+    }
+
+    #[test]
+    fn install_dir_stays_in_the_repository() {
+        let status = status_with("community", "author_plugin");
+        assert_eq!(
+            status.install_dir(StatusEntry(0, 0)),
+            Ok(Path::new("cfg")
+                .join(REPOSITORIES_DIR)
+                .join("community")
+                .join(PLUGINS_DIR)
+                .join("author_plugin"))
+        );
+    }
+
+    #[test]
+    fn install_dir_rejects_paths_leaving_the_profile() {
+        for (repo, plugin) in [
+            ("community", "../../../../home/me"),
+            ("community", "/tmp/evil"),
+            ("../..", "plugin"),
+            ("/", "plugin"),
+        ] {
+            let status = status_with(repo, plugin);
+            assert!(
+                status.install_dir(StatusEntry(0, 0)).is_err(),
+                "{repo:?}/{plugin:?}"
+            );
+        }
+    }
+
+    /// A plugin_status.json with `per_repo` plugins in each of two
+    /// repositories, every other one installed, each with a long description
+    fn write_status_file(dir: &Path, per_repo: usize, description_len: usize) -> PathBuf {
+        let repos: Vec<_> = ["official", "community"]
+            .iter()
+            .map(|repo| {
+                let plugins: Vec<_> = (0..per_repo)
+                    .map(|i| {
+                        json!({
+                            "name": format!("Plugin {i}"),
+                            "version": "1.0.0",
+                            "author": "Someone",
+                            "path": format!("author_plugin{i}"),
+                            "pluginStatus": if i % 2 == 0 { 2 } else { 0 },
+                            "description": "x".repeat(description_len),
+                            "longdescription": "y".repeat(description_len),
+                        })
+                    })
+                    .collect();
+                json!({ "path": repo, "plugins": plugins })
+            })
+            .collect();
+        let path = dir.join(PLUGIN_STATUS_FILE);
+        fs::write(&path, serde_json::to_string(&repos).unwrap()).unwrap();
+        path
+    }
+
+    #[test]
+    fn read_repo_plugins_keeps_installed_plugins() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = write_status_file(dir.path(), 3, 10);
+        let plugins = read_repo_plugins(&path).unwrap();
+        let names: Vec<_> = plugins.iter().map(|p| p.dir_name.as_str()).collect();
+        assert_eq!(
+            names,
+            [
+                "author_plugin0",
+                "author_plugin2",
+                "author_plugin0",
+                "author_plugin2"
+            ]
+        );
+        assert_eq!(
+            plugins[2].path,
+            dir.path()
+                .join("community")
+                .join(PLUGINS_DIR)
+                .join("author_plugin0")
+        );
+    }
+
+    /// What read_repo_plugins streamed plugin_status.json into before
+    #[derive(Deserialize)]
+    struct StreamedRepository {
+        #[serde(default)]
+        plugins: Vec<StreamedPlugin>,
+    }
+
+    #[derive(Deserialize)]
+    struct StreamedPlugin {
+        #[serde(default)]
+        name: Option<String>,
+        #[serde(default)]
+        version: Option<String>,
+        #[serde(default)]
+        author: Option<String>,
+        #[serde(default)]
+        path: Option<String>,
+        #[serde(default, rename = "pluginStatus")]
+        plugin_status: u32,
+    }
+
+    /// Parsing plugin_status.json's bytes in place against streaming it, as
+    /// `plugins` did before
+    #[test]
+    #[ignore = "benchmark; run with --release -- --ignored --test-threads=1 --nocapture"]
+    fn bench_read_repo_plugins() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = write_status_file(dir.path(), 2000, 3000);
+        let size = fs::metadata(&path).unwrap().len();
+
+        let (old, old_usage) = measure(|| {
+            let file = BufReader::new(File::open(&path).unwrap());
+            serde_json::from_reader::<_, Vec<StreamedRepository>>(file).unwrap()
+        });
+        let (new, new_usage) = measure(|| read_repo_plugins(&path).unwrap());
+
+        println!("plugin_status.json, {:.1} MB", size as f64 / 1_000_000.0);
+        println!("  from_reader:           {old_usage}");
+        println!("  fs::read + from_slice: {new_usage}");
+        let old: Vec<_> = old
+            .iter()
+            .flat_map(|repo| &repo.plugins)
+            .filter(|p| is_installed(p.plugin_status))
+            .map(|p| {
+                (
+                    &p.name,
+                    &p.version,
+                    &p.author,
+                    p.path.clone().unwrap_or_default(),
+                )
+            })
+            .collect();
+        let new: Vec<_> = new
+            .iter()
+            .map(|p| (&p.name, &p.version, &p.author, p.dir_name.clone()))
+            .collect();
+        assert_eq!(old, new);
+        assert!(new_usage.elapsed < old_usage.elapsed);
+    }
 }
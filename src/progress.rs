@@ -0,0 +1,120 @@
+use crate::report;
+use indicatif::{ProgressBar, ProgressStyle};
+use std::fs;
+use std::io::IsTerminal;
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+static DISABLED: AtomicBool = AtomicBool::new(false);
+
+/// Turn progress bars off for the rest of the run (`--no-progress`)
+pub(crate) fn disable() {
+    DISABLED.store(true, Ordering::Relaxed);
+}
+
+/// Byte and file-count progress through a tree being copied. Drawn on stderr,
+/// and only when stderr is a terminal, so piped output stays clean; when
+/// hidden, nothing is measured up front.
+pub(crate) struct Progress {
+    bar: Option<ProgressBar>,
+    files: u64,
+    total_files: u64,
+}
+
+impl Progress {
+    /// A bar for copying `src`. `leave_out` is given each path (relative, as
+    /// `rel` is) the copy will skip, so the totals match.
+    pub(crate) fn for_tree(
+        label: &str,
+        src: &Path,
+        rel: &str,
+        leave_out: &dyn Fn(&str, &fs::Metadata) -> bool,
+    ) -> Self {
+        if DISABLED.load(Ordering::Relaxed)
+            || report::quiet()
+            || !std::io::stderr().is_terminal()
+            || !src.is_dir()
+        {
+            return Self::hidden();
+        }
+
+        let (total_files, total_bytes) = measure(src, rel, leave_out);
+        let bar = ProgressBar::new(total_bytes);
+        bar.set_style(
+            ProgressStyle::with_template(
+                "  {prefix} [{bar:30}] {bytes}/{total_bytes} {msg} ({eta})",
+            )
+            .unwrap_or_else(|_| ProgressStyle::default_bar())
+            .progress_chars("=> "),
+        );
+        bar.set_prefix(label.to_string());
+        let progress = Self {
+            bar: Some(bar),
+            files: 0,
+            total_files,
+        };
+        progress.update_message();
+        progress
+    }
+
+    pub(crate) fn hidden() -> Self {
+        Self {
+            bar: None,
+            files: 0,
+            total_files: 0,
+        }
+    }
+
+    /// Count one file of `bytes` as done, whether it was copied or skipped
+    pub(crate) fn file_done(&mut self, bytes: u64) {
+        if let Some(bar) = &self.bar {
+            self.files += 1;
+            bar.inc(bytes);
+            self.update_message();
+        }
+    }
+
+    fn update_message(&self) {
+        if let Some(bar) = &self.bar {
+            bar.set_message(format!("{}/{} files", self.files, self.total_files));
+        }
+    }
+}
+
+impl Drop for Progress {
+    fn drop(&mut self) {
+        // The line printed after the copy says what happened
+        if let Some(bar) = &self.bar {
+            bar.finish_and_clear();
+        }
+    }
+}
+
+/// Files and bytes under `dir`, walked the way the copy walks it
+fn measure(dir: &Path, rel: &str, leave_out: &dyn Fn(&str, &fs::Metadata) -> bool) -> (u64, u64) {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return (0, 0);
+    };
+    let mut totals = (0, 0);
+    for entry in entries.filter_map(|e| e.ok()) {
+        let Ok(metadata) = fs::symlink_metadata(entry.path()) else {
+            continue;
+        };
+        let child_rel = format!("{rel}/{}", entry.file_name().to_string_lossy());
+        if leave_out(&child_rel, &metadata) {
+            continue;
+        }
+        if metadata.is_dir() {
+            let (files, bytes) = measure(&entry.path(), &child_rel, leave_out);
+            totals.0 += files;
+            totals.1 += bytes;
+        } else if metadata.file_type().is_symlink() {
+            // Recreated as a link; nothing to copy
+            totals.0 += 1;
+        } else {
+            totals.0 += 1;
+            totals.1 += metadata.len();
+        }
+    }
+    totals
+}
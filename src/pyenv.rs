@@ -0,0 +1,196 @@
+use crate::config::Profile;
+use crate::settings::read_settings;
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+/// Prints the interpreter's own site-packages directories, one per line
+const SITE_DIRS_SCRIPT: &str =
+    "import site; print('\\n'.join(site.getsitepackages() + [site.getusersitepackages()]))";
+
+/// Python packages visible to a profile
+pub(crate) struct PythonPackages {
+    /// site-packages directories that were inspected
+    pub site_dirs: Vec<PathBuf>,
+    /// Normalized name -> (name as installed, version)
+    pub packages: BTreeMap<String, (String, String)>,
+}
+
+/// Collect the packages in every site-packages directory a profile's Python
+/// sees: its virtualenv, the directory Binary Ninja's plugin manager installs
+/// into, and the configured interpreter's own.
+///
+/// Packages are read from `.dist-info`/`.egg-info` names rather than by
+/// running pip, so a broken or missing pip doesn't hide anything.
+pub(crate) fn python_packages(profile: &Profile) -> PythonPackages {
+    let site_dirs = site_dirs(profile);
+
+    let mut packages = BTreeMap::new();
+    for dir in &site_dirs {
+        let Ok(entries) = fs::read_dir(dir) else {
+            continue;
+        };
+        for entry in entries.filter_map(|e| e.ok()) {
+            let file_name = entry.file_name().to_string_lossy().into_owned();
+            if let Some((name, version)) = parse_dist_info(&file_name) {
+                // Earlier directories shadow later ones on sys.path
+                packages
+                    .entry(normalize_name(&name))
+                    .or_insert((name, version));
+            }
+        }
+    }
+
+    PythonPackages {
+        site_dirs,
+        packages,
+    }
+}
+
+/// The interpreter to run pip with for a profile: its virtualenv's own, else
+/// the configured interpreter
+pub(crate) fn pip_interpreter(profile: &Profile) -> Option<PathBuf> {
+    let (venv, interpreter) = configured_python(profile);
+    if let Some(root) = venv.as_deref().and_then(|venv| {
+        venv.ancestors()
+            .take(4)
+            .find(|dir| dir.join("pyvenv.cfg").exists())
+    }) {
+        let candidates = [
+            root.join("bin").join("python3"),
+            root.join("bin").join("python"),
+            root.join("Scripts").join("python.exe"),
+        ];
+        if let Some(python) = candidates.into_iter().find(|path| path.is_file()) {
+            return Some(python);
+        }
+    }
+    interpreter.filter(|path| is_python_executable(path))
+}
+
+/// The virtualenv and interpreter a profile's Python is set up with
+fn configured_python(profile: &Profile) -> (Option<PathBuf>, Option<PathBuf>) {
+    // bn-loader writes the profile's values into settings.json at launch, but
+    // they may also have been set from Binary Ninja itself
+    let settings = read_settings(&profile.config_dir).ok();
+    let setting = |key: &str| {
+        settings
+            .as_ref()
+            .and_then(|s| s.get(key))
+            .and_then(|v| v.as_str())
+            .filter(|s| !s.is_empty())
+            .map(PathBuf::from)
+    };
+    (
+        profile
+            .python_virtualenv
+            .clone()
+            .or_else(|| setting("python.virtualenv")),
+        profile
+            .python_interpreter
+            .clone()
+            .or_else(|| setting("python.interpreter")),
+    )
+}
+
+fn site_dirs(profile: &Profile) -> Vec<PathBuf> {
+    let (venv, interpreter) = configured_python(profile);
+    let mut dirs = Vec::new();
+
+    if let Some(venv) = venv {
+        dirs.extend(venv_site_packages(&venv));
+    }
+
+    dirs.extend(
+        subdirs(&profile.config_dir)
+            .into_iter()
+            .filter(|dir| file_name_starts_with(dir, "python"))
+            .map(|dir| dir.join("site-packages"))
+            .filter(|dir| dir.is_dir()),
+    );
+
+    if let Some(interpreter) = interpreter
+        && is_python_executable(&interpreter)
+        && let Ok(output) = Command::new(&interpreter)
+            .args(["-c", SITE_DIRS_SCRIPT])
+            .output()
+        && output.status.success()
+    {
+        dirs.extend(
+            String::from_utf8_lossy(&output.stdout)
+                .lines()
+                .map(PathBuf::from)
+                .filter(|dir| dir.is_dir()),
+        );
+    }
+
+    let mut unique = Vec::new();
+    for dir in dirs {
+        if !unique.contains(&dir) {
+            unique.push(dir);
+        }
+    }
+    unique
+}
+
+/// `python.virtualenv` normally points at site-packages, but accept the
+/// virtualenv root too
+fn venv_site_packages(venv: &Path) -> Vec<PathBuf> {
+    if !venv.join("pyvenv.cfg").exists() {
+        return vec![venv.to_path_buf()];
+    }
+    let mut dirs: Vec<PathBuf> = subdirs(&venv.join("lib"))
+        .into_iter()
+        .filter(|dir| file_name_starts_with(dir, "python"))
+        .map(|dir| dir.join("site-packages"))
+        .collect();
+    // Windows virtualenvs
+    dirs.push(venv.join("Lib").join("site-packages"));
+    dirs.retain(|dir| dir.is_dir());
+    dirs
+}
+
+/// An interpreter we can run, as opposed to a libpython/python3x.dll
+fn is_python_executable(path: &Path) -> bool {
+    path.is_file()
+        && file_name_starts_with(path, "python")
+        && path
+            .extension()
+            .is_none_or(|ext| ext.eq_ignore_ascii_case("exe"))
+}
+
+/// `requests-2.31.0.dist-info` -> ("requests", "2.31.0")
+fn parse_dist_info(file_name: &str) -> Option<(String, String)> {
+    let stem = file_name
+        .strip_suffix(".dist-info")
+        .or_else(|| file_name.strip_suffix(".egg-info"))?;
+    let (name, version) = stem.split_once('-')?;
+    // Egg names can carry a -pyX.Y tag after the version
+    let version = version.split('-').next().unwrap_or(version);
+    Some((name.to_string(), version.to_string()))
+}
+
+/// PEP 503 normalization, so `Foo_Bar` and `foo-bar` compare equal
+pub(crate) fn normalize_name(name: &str) -> String {
+    name.to_ascii_lowercase().replace(['_', '.'], "-")
+}
+
+fn subdirs(dir: &Path) -> Vec<PathBuf> {
+    let mut dirs: Vec<PathBuf> = fs::read_dir(dir)
+        .map(|entries| {
+            entries
+                .filter_map(|e| e.ok())
+                .map(|e| e.path())
+                .filter(|p| p.is_dir())
+                .collect()
+        })
+        .unwrap_or_default();
+    dirs.sort();
+    dirs
+}
+
+fn file_name_starts_with(path: &Path, prefix: &str) -> bool {
+    path.file_name()
+        .is_some_and(|n| n.to_string_lossy().to_ascii_lowercase().starts_with(prefix))
+}
@@ -0,0 +1,256 @@
+use crate::history::format_duration;
+use crate::notify;
+use crate::pager;
+use crate::repositories::format_size;
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::fmt;
+use std::fs;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::process;
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+/// Structured outcome of a run, written to `--result-file` at exit
+#[derive(Serialize)]
+struct Report {
+    command: String,
+    success: bool,
+    error: Option<String>,
+    warnings: Vec<String>,
+    changed_paths: Vec<PathBuf>,
+    /// Items (such as sync items) transferred, and their size
+    items_transferred: usize,
+    bytes_transferred: u64,
+    started_at: u64,
+    duration_ms: u128,
+    /// Named phases (such as one sync target) -> milliseconds
+    timings: BTreeMap<String, u128>,
+    #[serde(skip)]
+    path: Option<PathBuf>,
+    #[serde(skip)]
+    started: Instant,
+    #[serde(skip)]
+    summary: Option<Summary>,
+}
+
+/// When to wrap a long run up with a summary block
+struct Summary {
+    after: Duration,
+    notify: bool,
+}
+
+static REPORT: Mutex<Option<Report>> = Mutex::new(None);
+
+/// Set when stdout carries machine-readable output, such as `sync --report
+/// json`; human-readable text then goes to stderr
+static STDOUT_IS_DATA: AtomicBool = AtomicBool::new(false);
+
+/// Keep stdout for data: from now on [`outln!`] writes to stderr
+pub(crate) fn reserve_stdout() {
+    STDOUT_IS_DATA.store(true, Ordering::Relaxed);
+}
+
+pub(crate) fn stdout_is_data() -> bool {
+    STDOUT_IS_DATA.load(Ordering::Relaxed)
+}
+
+/// Set while a step runs in the background of another command, such as the
+/// sync before a launch; warnings and errors still print
+static QUIET: AtomicBool = AtomicBool::new(false);
+
+/// Silence (or restore) [`outln!`] output. Returns the previous setting.
+pub(crate) fn set_quiet(quiet: bool) -> bool {
+    QUIET.swap(quiet, Ordering::Relaxed)
+}
+
+pub(crate) fn quiet() -> bool {
+    QUIET.load(Ordering::Relaxed)
+}
+
+/// `println!` for progress and information, logged at info level: it moves
+/// to stderr when stdout is reserved for data, and `-q` hides it
+macro_rules! outln {
+    () => {
+        ::tracing::info!("")
+    };
+    ($($arg:tt)*) => {
+        ::tracing::info!($($arg)*)
+    };
+}
+pub(crate) use outln;
+
+/// `println!` for what a command exists to print, such as a listing or a
+/// table: unlike [`outln!`], `-q` doesn't hide it. It still moves to stderr
+/// when stdout is reserved for data.
+macro_rules! dataln {
+    () => {
+        $crate::report::write_data(format_args!(""))
+    };
+    ($($arg:tt)*) => {
+        $crate::report::write_data(format_args!($($arg)*))
+    };
+}
+pub(crate) use dataln;
+
+pub(crate) fn write_data(line: fmt::Arguments) {
+    // A closed pipe (`bn-loader plugins | head`) isn't worth a panic
+    let _ = if stdout_is_data() {
+        writeln!(io::stderr(), "{line}")
+    } else {
+        writeln!(io::stdout(), "{line}")
+    };
+}
+
+/// Print `value` to stdout as JSON, for `--json`
+pub(crate) fn print_json(value: &impl Serialize) -> Result<(), String> {
+    let json = serde_json::to_string_pretty(value)
+        .map_err(|e| format!("Failed to serialize output: {e}"))?;
+    writeln!(io::stdout(), "{json}").map_err(|e| format!("Failed to write output: {e}"))
+}
+
+fn with_report(f: impl FnOnce(&mut Report)) {
+    if let Ok(mut guard) = REPORT.lock()
+        && let Some(report) = guard.as_mut()
+    {
+        f(report);
+    }
+}
+
+/// Start collecting the outcome of `command`, to be written to `path` (the
+/// `--result-file`) at exit if given
+pub(crate) fn start(path: Option<&Path>, command: &str) {
+    let started_at = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0);
+    if let Ok(mut guard) = REPORT.lock() {
+        *guard = Some(Report {
+            command: command.to_string(),
+            success: true,
+            error: None,
+            warnings: Vec::new(),
+            changed_paths: Vec::new(),
+            items_transferred: 0,
+            bytes_transferred: 0,
+            started_at,
+            duration_ms: 0,
+            timings: BTreeMap::new(),
+            path: path.map(Path::to_path_buf),
+            started: Instant::now(),
+            summary: None,
+        });
+    }
+}
+
+/// Print an error and mark the run as failed
+pub(crate) fn error(message: &str) {
+    tracing::error!("{message}");
+    with_report(|report| {
+        report.success = false;
+        report.error.get_or_insert_with(|| message.to_string());
+    });
+}
+
+/// Print a warning and record it
+pub(crate) fn warn(message: &str) {
+    tracing::warn!("{message}");
+    with_report(|report| report.warnings.push(message.to_string()));
+}
+
+/// Record a file or directory bn-loader created, modified or removed
+pub(crate) fn changed(path: &Path) {
+    with_report(|report| report.changed_paths.push(path.to_path_buf()));
+}
+
+/// Record an item bn-loader copied or linked, and its size
+pub(crate) fn transferred(bytes: u64) {
+    with_report(|report| {
+        report.items_transferred += 1;
+        report.bytes_transferred += bytes;
+    });
+}
+
+/// Print a summary at exit if the run took at least `after`, and with
+/// `notify`, also raise a desktop notification
+pub(crate) fn summarize_after(after: Duration, notify: bool) {
+    with_report(|report| report.summary = Some(Summary { after, notify }));
+}
+
+pub(crate) fn timing(label: &str, elapsed: Duration) {
+    with_report(|report| {
+        report
+            .timings
+            .insert(label.to_string(), elapsed.as_millis());
+    });
+}
+
+/// Print the wrap-up summary and write the result file, as requested
+pub(crate) fn finish() {
+    pager::finish();
+    let Some(mut report) = REPORT.lock().ok().and_then(|mut guard| guard.take()) else {
+        return;
+    };
+    let elapsed = report.started.elapsed();
+    report.duration_ms = elapsed.as_millis();
+
+    if let Some(summary) = &report.summary
+        && elapsed >= summary.after
+    {
+        print_summary(&report, elapsed);
+        if summary.notify {
+            notify::send(&format!("bn-loader {}", report.command), &outcome(&report));
+        }
+    }
+
+    let Some(path) = &report.path else {
+        return;
+    };
+    let result = serde_json::to_string_pretty(&report)
+        .map_err(|e| e.to_string())
+        .and_then(|json| fs::write(path, json + "\n").map_err(|e| e.to_string()));
+    if let Err(e) = result {
+        tracing::warn!("Failed to write result file {}: {e}", path.display());
+    }
+}
+
+/// One line saying how the run ended
+fn outcome(report: &Report) -> String {
+    match &report.error {
+        Some(error) => format!("Failed: {error}"),
+        None if !report.success => "Failed".to_string(),
+        None if report.warnings.is_empty() => "Succeeded".to_string(),
+        None => format!("Succeeded with {} warning(s)", report.warnings.len()),
+    }
+}
+
+fn print_summary(report: &Report, elapsed: Duration) {
+    let _ = io::stdout().flush();
+    outln!(
+        "\n=== {} finished in {} ===",
+        report.command,
+        format_duration(elapsed.as_secs())
+    );
+    outln!(
+        "  Items:    {} ({})",
+        report.items_transferred,
+        format_size(report.bytes_transferred)
+    );
+    outln!("  Warnings: {}", report.warnings.len());
+    for warning in &report.warnings {
+        outln!("    - {warning}");
+    }
+    outln!("  Result:   {}", outcome(report));
+}
+
+/// Write the result file and exit
+pub(crate) fn exit(code: i32) -> ! {
+    if code != 0 {
+        with_report(|report| report.success = false);
+    }
+    finish();
+    let _ = io::stdout().flush();
+    process::exit(code);
+}
@@ -0,0 +1,265 @@
+use crate::config::SyncMode;
+use crate::items::MergeOptions;
+use crate::manifest::is_excluded;
+use crate::report::outln;
+use crate::sync::{FileAction, TransferStats, transfer_item};
+use clap::ValueEnum;
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+pub(crate) const REPOSITORIES_DIR: &str = "repositories";
+const PLUGIN_STATUS_FILE: &str = "plugin_status.json";
+const PLUGINS_DIR: &str = "plugins";
+const MAX_PLUGINS_DISPLAY: usize = 10;
+
+/// How much of `repositories/` a sync transfers
+#[derive(Clone, Copy, Default, PartialEq, Eq, ValueEnum)]
+pub(crate) enum RepositoriesMode {
+    /// Everything, including repository listings and caches
+    #[default]
+    Full,
+    /// Plugin code and plugin_status.json, without caches
+    CodeOnly,
+    /// Only plugin_status.json
+    StatusOnly,
+}
+
+impl RepositoriesMode {
+    fn label(self) -> &'static str {
+        match self {
+            RepositoriesMode::Full => "full",
+            RepositoriesMode::CodeOnly => "code-only",
+            RepositoriesMode::StatusOnly => "status-only",
+        }
+    }
+}
+
+/// Sizes of the parts of a `repositories/` tree
+#[derive(Default)]
+struct Breakdown {
+    /// Plugin checkouts under `<repo>/plugins/`
+    code: u64,
+    /// Repository listings and anything else Binary Ninja can re-fetch
+    cache: u64,
+    /// plugin_status.json, which records what is installed and enabled
+    state: u64,
+    /// `<repo>/<plugin>` -> size
+    plugins: BTreeMap<String, u64>,
+}
+
+impl Breakdown {
+    fn transferred(&self, mode: RepositoriesMode) -> u64 {
+        match mode {
+            RepositoriesMode::Full => self.code + self.cache + self.state,
+            RepositoriesMode::CodeOnly => self.code + self.state,
+            RepositoriesMode::StatusOnly => self.state,
+        }
+    }
+}
+
+/// Print what the repositories item is made of and how much a sync will copy
+pub(crate) fn print_breakdown(config_dir: &Path, mode: RepositoriesMode) -> Result<(), String> {
+    let breakdown = breakdown(&config_dir.join(REPOSITORIES_DIR))?;
+
+    outln!("\nRepositories breakdown:");
+    outln!(
+        "    code   {:>10}  ({} plugins)",
+        format_size(breakdown.code),
+        breakdown.plugins.len()
+    );
+    outln!("    cache  {:>10}", format_size(breakdown.cache));
+    outln!("    state  {:>10}", format_size(breakdown.state));
+
+    let mut largest: Vec<(&String, &u64)> = breakdown.plugins.iter().collect();
+    largest.sort_by_key(|(_, size)| std::cmp::Reverse(**size));
+    if !largest.is_empty() {
+        outln!("  Largest plugins:");
+        for (plugin, size) in largest.iter().take(MAX_PLUGINS_DISPLAY) {
+            outln!("    {plugin:<40} {:>10}", format_size(**size));
+        }
+        if largest.len() > MAX_PLUGINS_DISPLAY {
+            outln!("    ... and {} more", largest.len() - MAX_PLUGINS_DISPLAY);
+        }
+    }
+
+    outln!(
+        "  Transferred with --repositories-mode {}: {}",
+        mode.label(),
+        format_size(breakdown.transferred(mode))
+    );
+    Ok(())
+}
+
+fn breakdown(repos_dir: &Path) -> Result<Breakdown, String> {
+    let mut breakdown = Breakdown::default();
+
+    for entry in read_dir(repos_dir)? {
+        let size = tree_size(&entry)?;
+        if file_name(&entry) == PLUGIN_STATUS_FILE {
+            breakdown.state += size;
+        } else {
+            breakdown.cache += size;
+        }
+    }
+
+    // Plugin checkouts were counted as cache above; move them over
+    for (repo, plugin, path) in plugin_dirs(repos_dir)? {
+        let size = tree_size(&path)?;
+        breakdown.cache -= size;
+        breakdown.code += size;
+        breakdown.plugins.insert(format!("{repo}/{plugin}"), size);
+    }
+
+    Ok(breakdown)
+}
+
+/// Every `<repo>/plugins/<plugin>` directory as (repo, plugin, path)
+fn plugin_dirs(repos_dir: &Path) -> Result<Vec<(String, String, PathBuf)>, String> {
+    let mut dirs = Vec::new();
+    for repo in read_dir(repos_dir)?.into_iter().filter(|p| p.is_dir()) {
+        let plugins = repo.join(PLUGINS_DIR);
+        if !plugins.is_dir() {
+            continue;
+        }
+        let repo_name = file_name(&repo);
+        for plugin in read_dir(&plugins)?.into_iter().filter(|p| p.is_dir()) {
+            dirs.push((repo_name.clone(), file_name(&plugin), plugin));
+        }
+    }
+    Ok(dirs)
+}
+
+/// Copy the parts of `repositories/` selected by the mode. Outside of full
+/// mode the target's own caches are left alone, so only full mode can link
+/// the whole tree.
+pub(crate) fn merge_repositories(
+    source_dir: &Path,
+    target_dir: &Path,
+    options: &MergeOptions,
+) -> Result<TransferStats, String> {
+    let source = source_dir.join(REPOSITORIES_DIR);
+    let target = target_dir.join(REPOSITORIES_DIR);
+    let mode = options.repositories_mode;
+
+    if mode == RepositoriesMode::Full {
+        return transfer_item(&source, &target, REPOSITORIES_DIR, options.mode, options);
+    }
+
+    // Copied even in symlink mode: the target keeps its own tree
+    let mut stats = TransferStats::default();
+    let status = source.join(PLUGIN_STATUS_FILE);
+    if status.exists() {
+        stats += transfer_item(
+            &status,
+            &target.join(PLUGIN_STATUS_FILE),
+            &format!("{REPOSITORIES_DIR}/{PLUGIN_STATUS_FILE}"),
+            SyncMode::Copy,
+            options,
+        )?;
+    }
+
+    if mode == RepositoriesMode::CodeOnly {
+        for (repo, plugin, path) in plugin_dirs(&source)? {
+            let plugin_target = target.join(&repo).join(PLUGINS_DIR).join(&plugin);
+            let rel = format!("{REPOSITORIES_DIR}/{repo}/{PLUGINS_DIR}/{plugin}");
+            if is_excluded(&options.exclusions, &rel, true) {
+                stats.excluded.push(rel);
+                continue;
+            }
+            stats += transfer_item(&path, &plugin_target, &rel, SyncMode::Copy, options)?;
+        }
+
+        // Plugins uninstalled in the source; the rest of the target's tree
+        // is its own cache
+        if options.delete && target.is_dir() {
+            let source_plugins: Vec<(String, String)> = plugin_dirs(&source)?
+                .into_iter()
+                .map(|(repo, plugin, _)| (repo, plugin))
+                .collect();
+            for (repo, plugin, path) in plugin_dirs(&target)? {
+                let rel = format!("{REPOSITORIES_DIR}/{repo}/{PLUGINS_DIR}/{plugin}");
+                if is_excluded(&options.exclusions, &rel, true)
+                    || source_plugins.contains(&(repo, plugin))
+                {
+                    continue;
+                }
+                if !options.dry_run {
+                    fs::remove_dir_all(&path)
+                        .map_err(|e| format!("Failed to remove {}: {e}", path.display()))?;
+                }
+                stats.record(&rel, FileAction::Removed);
+            }
+        }
+    }
+
+    Ok(stats)
+}
+
+fn read_dir(dir: &Path) -> Result<Vec<PathBuf>, String> {
+    fs::read_dir(dir)
+        .map_err(|e| format!("Failed to read directory {}: {e}", dir.display()))?
+        .map(|entry| {
+            entry
+                .map(|e| e.path())
+                .map_err(|e| format!("Failed to read entry: {e}"))
+        })
+        .collect()
+}
+
+fn file_name(path: &Path) -> String {
+    path.file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_default()
+}
+
+/// Total size of a file or directory, without following symlinks
+pub(crate) fn tree_size(path: &Path) -> Result<u64, String> {
+    let metadata = fs::symlink_metadata(path)
+        .map_err(|e| format!("Failed to read {}: {e}", path.display()))?;
+    if !metadata.is_dir() {
+        return Ok(metadata.len());
+    }
+    read_dir(path)?
+        .iter()
+        .try_fold(0, |total, child| Ok(total + tree_size(child)?))
+}
+
+/// Parse a size such as `500`, `100KB`, `1.5GB` (or `100M`)
+pub(crate) fn parse_size(value: &str) -> Result<u64, String> {
+    let trimmed = value.trim();
+    let split = trimmed
+        .find(|c: char| !c.is_ascii_digit() && c != '.')
+        .unwrap_or(trimmed.len());
+    let (number, unit) = trimmed.split_at(split);
+    let number: f64 = number
+        .parse()
+        .map_err(|_| format!("Invalid size '{value}'"))?;
+    let multiplier: u64 = match unit.trim().to_ascii_uppercase().as_str() {
+        "" | "B" => 1,
+        "K" | "KB" => 1 << 10,
+        "M" | "MB" => 1 << 20,
+        "G" | "GB" => 1 << 30,
+        "T" | "TB" => 1 << 40,
+        _ => {
+            return Err(format!(
+                "Unknown unit '{unit}' in '{value}' (use B, KB, MB, GB or TB)"
+            ));
+        }
+    };
+    Ok((number * multiplier as f64) as u64)
+}
+
+pub(crate) fn format_size(bytes: u64) -> String {
+    const UNITS: [&str; 4] = ["KB", "MB", "GB", "TB"];
+    if bytes < 1024 {
+        return format!("{bytes} B");
+    }
+    let mut size = bytes as f64 / 1024.0;
+    let mut unit = 0;
+    while size >= 1024.0 && unit < UNITS.len() - 1 {
+        size /= 1024.0;
+        unit += 1;
+    }
+    format!("{size:.1} {}", UNITS[unit])
+}
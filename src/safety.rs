@@ -0,0 +1,180 @@
+use crate::backups::BACKUP_PREFIX;
+use crate::config::home_dir;
+use crate::items::HANDLERS;
+use crate::launch::DEBUG_LOG_FILENAME;
+use crate::report;
+use crate::settings::TEMP_SUFFIX;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+/// Files Binary Ninja creates in a user directory besides the sync items
+const BN_MARKERS: &[&str] = &["license.dat", "license.txt", "user.id", "lastrun"];
+
+/// Entries that only show up in home directories, never in Binary Ninja's
+const FOREIGN_MARKERS: &[&str] = &[
+    ".bashrc",
+    ".bash_profile",
+    ".zshrc",
+    ".profile",
+    ".ssh",
+    ".gnupg",
+    ".local",
+    "Desktop",
+    "Documents",
+    "Downloads",
+    "NTUSER.DAT",
+];
+
+/// Why a directory doesn't look like a Binary Ninja user directory, if it
+/// doesn't. Directories that don't exist yet or are empty are fine.
+pub(crate) fn suspicious_config_dir(dir: &Path) -> Option<String> {
+    suspicious_dir(dir, home_dir().as_deref())
+}
+
+fn suspicious_dir(dir: &Path, home: Option<&Path>) -> Option<String> {
+    let canonical = match fs::canonicalize(dir) {
+        Ok(canonical) => canonical,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return None,
+        Err(e) => return Some(format!("can't be checked ({e})")),
+    };
+
+    if home.and_then(|home| fs::canonicalize(home).ok()) == Some(canonical.clone()) {
+        return Some("is your home directory".to_string());
+    }
+    if canonical.parent().is_none() {
+        return Some("is a filesystem root".to_string());
+    }
+
+    // Whatever can't be listed might be anything, so it isn't assumed fine
+    let names: Vec<String> =
+        match fs::read_dir(&canonical).and_then(|entries| entries.collect::<Result<Vec<_>, _>>()) {
+            Ok(entries) => entries
+                .iter()
+                .map(|entry| entry.file_name().to_string_lossy().into_owned())
+                .collect(),
+            Err(e) => return Some(format!("can't be listed ({e})")),
+        };
+
+    if let Some(foreign) = names
+        .iter()
+        .find(|name| FOREIGN_MARKERS.contains(&name.as_str()))
+    {
+        return Some(format!(
+            "contains {foreign}, which Binary Ninja never creates"
+        ));
+    }
+
+    let has_marker = HANDLERS.iter().any(|handler| handler.identify(&canonical))
+        || names
+            .iter()
+            .any(|name| BN_MARKERS.contains(&name.as_str()) || is_own_entry(name));
+    if !names.is_empty() && !has_marker {
+        return Some("has files, but none that Binary Ninja creates".to_string());
+    }
+
+    None
+}
+
+/// What bn-loader itself leaves in a config directory: sync backups, the
+/// debug log and interrupted settings writes
+fn is_own_entry(name: &str) -> bool {
+    name.starts_with(BACKUP_PREFIX) || name == DEBUG_LOG_FILENAME || name.ends_with(TEMP_SUFFIX)
+}
+
+/// Refuse to modify a profile's config_dir when it looks like the wrong
+/// directory. With `allow`, only warn.
+pub(crate) fn guard_config_dir(name: &str, dir: &Path, allow: bool) -> Result<(), String> {
+    let Some(reason) = suspicious_config_dir(dir) else {
+        return Ok(());
+    };
+    let message = format!(
+        "config_dir of profile '{name}' ({}) {reason}",
+        dir.display()
+    );
+    if allow {
+        report::warn(&message);
+        return Ok(());
+    }
+    Err(format!(
+        "{message}; refusing to modify it (pass --i-know-what-im-doing if this really is the right directory)"
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn dir_with(names: &[&str]) -> tempfile::TempDir {
+        let dir = tempfile::tempdir().unwrap();
+        for name in names {
+            fs::write(dir.path().join(name), "").unwrap();
+        }
+        dir
+    }
+
+    #[test]
+    fn missing_and_empty_dirs_are_fine() {
+        let dir = dir_with(&[]);
+        assert_eq!(suspicious_dir(dir.path(), None), None);
+        assert_eq!(suspicious_dir(&dir.path().join("new"), None), None);
+    }
+
+    #[test]
+    fn home_and_root_are_refused() {
+        let dir = dir_with(&["settings.json"]);
+        let reason = suspicious_dir(dir.path(), Some(dir.path()));
+        assert_eq!(reason.as_deref(), Some("is your home directory"));
+
+        let root = Path::new(if cfg!(windows) { r"C:\" } else { "/" });
+        let reason = suspicious_dir(root, None);
+        assert_eq!(reason.as_deref(), Some("is a filesystem root"));
+    }
+
+    #[test]
+    fn foreign_markers_are_refused_even_beside_binary_ninjas() {
+        let dir = dir_with(&["settings.json", ".bashrc"]);
+        let reason = suspicious_dir(dir.path(), None).unwrap();
+        assert!(reason.contains(".bashrc"), "{reason}");
+    }
+
+    #[test]
+    fn unrecognized_files_are_refused() {
+        let dir = dir_with(&["notes.txt"]);
+        assert!(suspicious_dir(dir.path(), None).is_some());
+    }
+
+    #[test]
+    fn binary_ninja_markers_are_fine() {
+        for marker in ["settings.json", "license.dat", "user.id", "lastrun"] {
+            let dir = dir_with(&["notes.txt", marker]);
+            assert_eq!(suspicious_dir(dir.path(), None), None, "{marker}");
+        }
+    }
+
+    #[test]
+    fn bn_loaders_own_entries_are_markers() {
+        let backup = format!("{BACKUP_PREFIX}1700000000");
+        let temp = format!("settings.json{TEMP_SUFFIX}");
+        for entry in [backup.as_str(), DEBUG_LOG_FILENAME, temp.as_str()] {
+            let dir = dir_with(&[entry]);
+            assert_eq!(suspicious_dir(dir.path(), None), None, "{entry}");
+        }
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn unreadable_dirs_are_refused() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = dir_with(&["settings.json"]);
+        fs::set_permissions(dir.path(), fs::Permissions::from_mode(0o300)).unwrap();
+        let readable = fs::read_dir(dir.path()).is_ok();
+        let reason = suspicious_dir(dir.path(), None);
+        fs::set_permissions(dir.path(), fs::Permissions::from_mode(0o700)).unwrap();
+        // Root reads directories regardless of their permissions
+        if !readable {
+            assert!(reason.unwrap().contains("can't be listed"));
+        }
+    }
+}
@@ -0,0 +1,53 @@
+use crate::config::cache_dir;
+use crate::report;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+const SESSIONS_DIR: &str = "sessions";
+
+/// Files opened by the most recent launch of a profile
+#[derive(Serialize, Deserialize, Default)]
+pub(crate) struct Session {
+    pub timestamp: u64,
+    pub files: Vec<PathBuf>,
+}
+
+fn session_path(profile: &str) -> Option<PathBuf> {
+    cache_dir().map(|dir| dir.join(SESSIONS_DIR).join(format!("{profile}.json")))
+}
+
+pub(crate) fn load_session(profile: &str) -> Option<Session> {
+    let path = session_path(profile)?;
+    let content = fs::read_to_string(path).ok()?;
+    serde_json::from_str(&content).ok()
+}
+
+/// Remember the files opened by a launch. Failures only warn; they must
+/// never stop Binary Ninja from launching.
+pub(crate) fn save_session(profile: &str, files: &[&Path]) {
+    let Some(path) = session_path(profile) else {
+        return;
+    };
+
+    let timestamp = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0);
+    let session = Session {
+        timestamp,
+        files: files.iter().map(|f| f.to_path_buf()).collect(),
+    };
+
+    let result = path
+        .parent()
+        .map_or(Ok(()), fs::create_dir_all)
+        .map_err(|e| e.to_string())
+        .and_then(|()| serde_json::to_string_pretty(&session).map_err(|e| e.to_string()))
+        .and_then(|json| fs::write(&path, json).map_err(|e| e.to_string()));
+
+    if let Err(e) = result {
+        report::warn(&format!("Failed to save session: {e}"));
+    }
+}
@@ -0,0 +1,431 @@
+use crate::report;
+use serde_json::{Map, Value};
+use std::fs::{self, File};
+use std::io::{BufReader, Write};
+use std::path::{Path, PathBuf};
+
+pub(crate) const SETTINGS_FILE: &str = "settings.json";
+pub(crate) const KEYBINDINGS_FILE: &str = "keybindings.json";
+pub(crate) const TEMP_SUFFIX: &str = ".bn-loader-tmp";
+
+// Binary Ninja writes settings with JsonCpp's styled writer: tab indentation,
+// `"key" : value`, and short scalar arrays kept on one line
+const INDENT: char = '\t';
+const RIGHT_MARGIN: usize = 74;
+
+/// Read a profile's settings.json, treating a missing file as empty
+pub(crate) fn read_settings(config_dir: &Path) -> Result<Value, String> {
+    let path = config_dir.join(SETTINGS_FILE);
+    if !path.exists() {
+        return Ok(Value::Object(Map::new()));
+    }
+    let file = File::open(&path).map_err(|e| format!("Failed to read {}: {e}", path.display()))?;
+    serde_json::from_reader(BufReader::new(file))
+        .map_err(|e| format!("Failed to parse {}: {e}", path.display()))
+}
+
+/// Apply changes to a profile's settings.json. The file is only rewritten if
+/// something actually changed. Returns whether it was.
+///
+/// Every settings.json write in bn-loader goes through here or
+/// [`write_settings`].
+pub(crate) fn update_settings(
+    config_dir: &Path,
+    apply: impl FnOnce(&mut Value),
+) -> Result<bool, String> {
+    let original = read_settings(config_dir)?;
+    let mut updated = original.clone();
+    apply(&mut updated);
+
+    if updated == original {
+        return Ok(false);
+    }
+
+    write_settings(config_dir, &updated)?;
+    Ok(true)
+}
+
+/// Replace a profile's settings.json atomically.
+///
+/// The output uses sorted keys and Binary Ninja's own layout, is re-parsed to
+/// make sure it round-trips, and is written to a temp file that is renamed
+/// over the original so a crash never leaves a truncated file behind.
+pub(crate) fn write_settings(config_dir: &Path, settings: &Value) -> Result<(), String> {
+    write_bn_json(&config_dir.join(SETTINGS_FILE), settings)
+}
+
+/// Atomically write any of Binary Ninja's JSON files (settings.json,
+/// keybindings.json) the way [`write_settings`] does
+pub(crate) fn write_bn_json(path: &Path, value: &Value) -> Result<(), String> {
+    let json = to_bn_json(value);
+
+    let reparsed: Value = serde_json::from_str(&json).map_err(|e| {
+        format!(
+            "Refusing to write {}: output did not re-parse: {e}",
+            path.display()
+        )
+    })?;
+    if &reparsed != value {
+        return Err(format!(
+            "Refusing to write {}: output did not round-trip",
+            path.display()
+        ));
+    }
+
+    let mut temp_path = path.as_os_str().to_owned();
+    temp_path.push(TEMP_SUFFIX);
+    let temp_path = PathBuf::from(temp_path);
+    let result = fs::File::create(&temp_path)
+        .and_then(|mut file| {
+            file.write_all(json.as_bytes())?;
+            file.sync_all()
+        })
+        .and_then(|()| fs::rename(&temp_path, path));
+
+    if let Err(e) = result {
+        let _ = fs::remove_file(&temp_path);
+        return Err(format!("Failed to write {}: {e}", path.display()));
+    }
+    report::changed(path);
+
+    Ok(())
+}
+
+/// Serialize like JsonCpp's `StyledStreamWriter`
+fn to_bn_json(value: &Value) -> String {
+    let mut writer = StyledWriter {
+        out: String::new(),
+        indent: String::new(),
+        // The root value starts at the beginning of the document
+        indented: true,
+    };
+    writer.write_value(value);
+    writer.out.push('\n');
+    writer.out
+}
+
+struct StyledWriter {
+    out: String,
+    indent: String,
+    indented: bool,
+}
+
+impl StyledWriter {
+    fn write_indent(&mut self) {
+        self.out.push('\n');
+        self.out.push_str(&self.indent);
+    }
+
+    fn write_with_indent(&mut self, text: &str) {
+        if !self.indented {
+            self.write_indent();
+        }
+        self.out.push_str(text);
+        self.indented = false;
+    }
+
+    fn write_value(&mut self, value: &Value) {
+        match value {
+            Value::Object(map) if !map.is_empty() => {
+                self.write_with_indent("{");
+                self.indent.push(INDENT);
+                let mut entries = map.iter().peekable();
+                while let Some((key, child)) = entries.next() {
+                    self.write_with_indent(&scalar(&Value::String(key.clone())));
+                    self.out.push_str(" : ");
+                    self.write_value(child);
+                    if entries.peek().is_some() {
+                        self.out.push(',');
+                    }
+                }
+                self.indent.pop();
+                self.write_with_indent("}");
+            }
+            Value::Array(items) if !items.is_empty() => {
+                if let Some(line) = single_line_array(items) {
+                    self.out.push_str(&line);
+                    return;
+                }
+                self.write_with_indent("[");
+                self.indent.push(INDENT);
+                for (idx, child) in items.iter().enumerate() {
+                    if !self.indented {
+                        self.write_indent();
+                    }
+                    self.indented = true;
+                    self.write_value(child);
+                    self.indented = false;
+                    if idx + 1 < items.len() {
+                        self.out.push(',');
+                    }
+                }
+                self.indent.pop();
+                self.write_with_indent("]");
+            }
+            _ => self.out.push_str(&scalar(value)),
+        }
+    }
+}
+
+/// Render an array as `[ a, b ]` if it only holds scalars and fits the margin
+fn single_line_array(items: &[Value]) -> Option<String> {
+    if items.len() * 3 >= RIGHT_MARGIN
+        || items.iter().any(|v| {
+            matches!(v, Value::Array(a) if !a.is_empty())
+                || matches!(v, Value::Object(o) if !o.is_empty())
+        })
+    {
+        return None;
+    }
+
+    let children: Vec<String> = items.iter().map(scalar).collect();
+    let line_length = 4 + (items.len() - 1) * 2 + children.iter().map(String::len).sum::<usize>();
+    (line_length < RIGHT_MARGIN).then(|| format!("[ {} ]", children.join(", ")))
+}
+
+/// Scalars and empty containers
+fn scalar(value: &Value) -> String {
+    match value {
+        Value::Object(_) => "{}".to_string(),
+        Value::Array(_) => "[]".to_string(),
+        _ => value.to_string(),
+    }
+}
+
+/// Overlay `source` onto `target`: objects are merged key by key, anything
+/// else in the source replaces the target's value. Keys only the target has
+/// (window geometry, per-machine paths) are kept.
+pub(crate) fn merge_settings(target: &mut Value, source: &Value) {
+    match (target, source) {
+        (Value::Object(target), Value::Object(source)) => {
+            for (key, value) in source {
+                match target.get_mut(key) {
+                    Some(existing) => merge_settings(existing, value),
+                    None => {
+                        target.insert(key.clone(), value.clone());
+                    }
+                }
+            }
+        }
+        (target, source) => *target = source.clone(),
+    }
+}
+
+/// Set a dotted key such as `python.interpreter`, creating parent objects
+pub(crate) fn set_key(settings: &mut Value, key: &str, value: Value) {
+    let (parents, leaf) = key.rsplit_once('.').unwrap_or(("", key));
+
+    let mut current = settings;
+    for part in parents.split('.').filter(|p| !p.is_empty()) {
+        current = as_object(current)
+            .entry(part)
+            .or_insert_with(|| Value::Object(Map::new()));
+    }
+    as_object(current).insert(leaf.to_string(), value);
+}
+
+/// Settings by dotted path (`ui.font.size`), down to the values that aren't
+/// objects. Empty objects are kept as values of their own.
+pub(crate) fn leaf_values(settings: &Value) -> Map<String, Value> {
+    fn walk(value: &Value, prefix: &str, leaves: &mut Map<String, Value>) {
+        match value {
+            Value::Object(map) if !map.is_empty() => {
+                for (key, child) in map {
+                    let path = if prefix.is_empty() {
+                        key.clone()
+                    } else {
+                        format!("{prefix}.{key}")
+                    };
+                    walk(child, &path, leaves);
+                }
+            }
+            _ if !prefix.is_empty() => {
+                leaves.insert(prefix.to_string(), value.clone());
+            }
+            _ => {}
+        }
+    }
+    let mut leaves = Map::new();
+    walk(settings, "", &mut leaves);
+    leaves
+}
+
+/// Borrow a value as an object, replacing it with an empty one if it isn't
+fn as_object(value: &mut Value) -> &mut Map<String, Value> {
+    if !value.is_object() {
+        *value = Value::Object(Map::new());
+    }
+    value
+        .as_object_mut()
+        .expect("value was just made an object")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::strategies::any_name;
+    use proptest::prelude::*;
+    use serde_json::json;
+
+    #[test]
+    fn to_bn_json_matches_binary_ninja() {
+        let settings = json!({
+            "analysis": { "mode": "full", "limits": {} },
+            "empty": [],
+            "long": ["a".repeat(40), "b".repeat(40)],
+            "nested": [{ "x": 1 }, [1, 2]],
+            "paths": ["/a", "b"],
+            "text": "ü\"🦀",
+        });
+        let expected = "{\n\
+            \t\"analysis\" : \n\
+            \t{\n\
+            \t\t\"limits\" : {},\n\
+            \t\t\"mode\" : \"full\"\n\
+            \t},\n\
+            \t\"empty\" : [],\n\
+            \t\"long\" : \n\
+            \t[\n\
+            \t\t\"aaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa\",\n\
+            \t\t\"bbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbb\"\n\
+            \t],\n\
+            \t\"nested\" : \n\
+            \t[\n\
+            \t\t{\n\
+            \t\t\t\"x\" : 1\n\
+            \t\t},\n\
+            \t\t[ 1, 2 ]\n\
+            \t],\n\
+            \t\"paths\" : [ \"/a\", \"b\" ],\n\
+            \t\"text\" : \"ü\\\"🦀\"\n\
+            }\n";
+        assert_eq!(to_bn_json(&settings), expected);
+    }
+
+    #[test]
+    fn to_bn_json_writes_bare_roots() {
+        assert_eq!(to_bn_json(&json!({})), "{}\n");
+        assert_eq!(to_bn_json(&json!([])), "[]\n");
+        assert_eq!(to_bn_json(&json!("x")), "\"x\"\n");
+    }
+
+    fn json_value() -> impl Strategy<Value = Value> {
+        let leaf = prop_oneof![
+            Just(Value::Null),
+            any::<bool>().prop_map(Value::from),
+            any::<i64>().prop_map(Value::from),
+            any::<f64>().prop_map(Value::from),
+            any_name().prop_map(Value::from),
+        ];
+        leaf.prop_recursive(4, 64, 8, |inner| {
+            prop_oneof![
+                prop::collection::vec(inner.clone(), 0..8).prop_map(Value::from),
+                prop::collection::btree_map(any_name(), inner, 0..8)
+                    .prop_map(|map| Value::Object(map.into_iter().collect())),
+            ]
+        })
+    }
+
+    proptest! {
+        #[test]
+        fn to_bn_json_round_trips(value in json_value()) {
+            let text = to_bn_json(&value);
+            prop_assert_eq!(serde_json::from_str::<Value>(&text).unwrap(), value, "{}", text);
+        }
+    }
+
+    #[test]
+    fn write_settings_keeps_floats_exact() {
+        let dir = tempfile::tempdir().unwrap();
+        let settings = json!({ "scale": 1.2957298721826642e113 });
+        write_settings(dir.path(), &settings).unwrap();
+        assert_eq!(read_settings(dir.path()).unwrap(), settings);
+    }
+
+    #[test]
+    fn set_key_creates_parents() {
+        let mut settings = json!({ "ui": { "theme": "dark" } });
+        set_key(
+            &mut settings,
+            "python.interpreter",
+            json!("/usr/bin/python3"),
+        );
+        set_key(&mut settings, "ui.font.size", json!(12));
+        set_key(&mut settings, "top", json!(true));
+        assert_eq!(
+            settings,
+            json!({
+                "python": { "interpreter": "/usr/bin/python3" },
+                "ui": { "theme": "dark", "font": { "size": 12 } },
+                "top": true,
+            })
+        );
+    }
+
+    #[test]
+    fn set_key_replaces_values_in_the_way() {
+        let mut settings = json!({ "python": "old", "ui": { "theme": "dark" } });
+        set_key(&mut settings, "python.interpreter", json!("/py"));
+        set_key(&mut settings, "ui.theme", json!("light"));
+        assert_eq!(
+            settings,
+            json!({ "python": { "interpreter": "/py" }, "ui": { "theme": "light" } })
+        );
+
+        let mut settings = json!(null);
+        set_key(&mut settings, "a.b", json!(1));
+        assert_eq!(settings, json!({ "a": { "b": 1 } }));
+    }
+
+    #[test]
+    fn leaf_values_are_dotted_paths() {
+        let settings = json!({
+            "ui": { "theme": "dark", "font": { "size": 12 }, "panes": {} },
+            "paths": ["/a"],
+            "top": null,
+        });
+        assert_eq!(
+            Value::Object(leaf_values(&settings)),
+            json!({
+                "ui.theme": "dark",
+                "ui.font.size": 12,
+                "ui.panes": {},
+                "paths": ["/a"],
+                "top": null,
+            })
+        );
+        assert!(leaf_values(&json!({})).is_empty());
+    }
+
+    #[test]
+    fn merge_settings_keeps_target_only_keys() {
+        let mut target = json!({ "ui": { "geometry": "100x100", "theme": "dark" }, "list": [1] });
+        merge_settings(
+            &mut target,
+            &json!({ "ui": { "theme": "light" }, "list": [2, 3], "new": 1 }),
+        );
+        assert_eq!(
+            target,
+            json!({ "ui": { "geometry": "100x100", "theme": "light" }, "list": [2, 3], "new": 1 })
+        );
+    }
+
+    #[test]
+    fn update_settings_only_writes_changes() {
+        let dir = tempfile::tempdir().unwrap();
+        assert!(!update_settings(dir.path(), |_| {}).unwrap());
+        assert!(!dir.path().join(SETTINGS_FILE).exists());
+
+        assert!(update_settings(dir.path(), |s| set_key(s, "a.b", json!(1))).unwrap());
+        assert!(!update_settings(dir.path(), |s| set_key(s, "a.b", json!(1))).unwrap());
+        assert_eq!(
+            read_settings(dir.path()).unwrap(),
+            json!({ "a": { "b": 1 } })
+        );
+        let names: Vec<_> = fs::read_dir(dir.path())
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name())
+            .collect();
+        assert_eq!(names, [SETTINGS_FILE]);
+    }
+}
@@ -0,0 +1,180 @@
+use crate::config::{Config, home_dir};
+use crate::report::{self, outln};
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Marker line identifying files written by bn-loader, so we never overwrite
+/// or delete a real Binary Ninja executable
+const SHIM_MARKER: &str = "bn-loader shim";
+
+#[cfg(windows)]
+const SHIM_NAME: &str = "binaryninja.cmd";
+
+#[cfg(not(windows))]
+const SHIM_NAME: &str = "binaryninja";
+
+/// Names that would resolve as `binaryninja` from a shell
+#[cfg(windows)]
+const COMMAND_NAMES: &[&str] = &["binaryninja.exe", "binaryninja.cmd", "binaryninja.bat"];
+
+#[cfg(not(windows))]
+const COMMAND_NAMES: &[&str] = &["binaryninja"];
+
+pub(crate) struct ShimOptions<'a> {
+    pub profile: &'a str,
+    pub dir: Option<&'a Path>,
+    pub force: bool,
+    pub config_path: &'a Path,
+}
+
+pub(crate) fn install_shim(config: &Config, options: &ShimOptions) -> Result<(), String> {
+    let name = options.profile;
+    if !config.profiles.contains_key(name) {
+        return Err(format!("Profile '{name}' not found"));
+    }
+
+    let dir = shim_dir(options.dir)?;
+    let shim_path = dir.join(SHIM_NAME);
+
+    // The shim execs bn-loader, which execs the profile's executable; replacing
+    // that executable would lose it and make the shim call itself
+    let canonical = fs::canonicalize(&shim_path).ok();
+    if let Some((owner, _)) = config.profiles.iter().find(|(_, profile)| {
+        canonical.is_some()
+            && fs::canonicalize(profile.install_dir.join(&profile.executable)).ok() == canonical
+    }) {
+        return Err(format!(
+            "{} is the Binary Ninja executable of profile '{owner}'; refusing to replace it",
+            shim_path.display()
+        ));
+    }
+
+    if shim_path.exists() && !is_shim(&shim_path) && !options.force {
+        return Err(format!(
+            "{} already exists and was not created by bn-loader (use --force to replace it)",
+            shim_path.display()
+        ));
+    }
+
+    let loader_exe =
+        env::current_exe().map_err(|e| format!("Failed to locate bn-loader executable: {e}"))?;
+    let config_path = std::path::absolute(options.config_path)
+        .map_err(|e| format!("Failed to resolve config path: {e}"))?;
+
+    fs::create_dir_all(&dir)
+        .map_err(|e| format!("Failed to create directory {}: {e}", dir.display()))?;
+    fs::write(&shim_path, shim_script(&loader_exe, &config_path, name))
+        .map_err(|e| format!("Failed to write {}: {e}", shim_path.display()))?;
+    make_executable(&shim_path)?;
+    report::changed(&shim_path);
+
+    outln!(
+        "Installed shim for profile '{name}': {}",
+        shim_path.display()
+    );
+    warn_about_path(&dir);
+    Ok(())
+}
+
+pub(crate) fn remove_shim(dir: Option<&Path>) -> Result<(), String> {
+    let dir = shim_dir(dir)?;
+    let shim_path = dir.join(SHIM_NAME);
+
+    if !shim_path.exists() {
+        return Err(format!("No shim found at {}", shim_path.display()));
+    }
+    if !is_shim(&shim_path) {
+        return Err(format!(
+            "{} was not created by bn-loader; refusing to remove it",
+            shim_path.display()
+        ));
+    }
+
+    fs::remove_file(&shim_path)
+        .map_err(|e| format!("Failed to remove {}: {e}", shim_path.display()))?;
+    outln!("Removed shim: {}", shim_path.display());
+    report::changed(&shim_path);
+    Ok(())
+}
+
+fn shim_dir(dir: Option<&Path>) -> Result<PathBuf, String> {
+    match dir {
+        Some(dir) => Ok(dir.to_path_buf()),
+        None => home_dir()
+            .map(|home| home.join(".local").join("bin"))
+            .ok_or_else(|| "Could not determine home directory".to_string()),
+    }
+}
+
+fn is_shim(path: &Path) -> bool {
+    fs::read_to_string(path).is_ok_and(|content| content.contains(SHIM_MARKER))
+}
+
+#[cfg(windows)]
+fn shim_script(loader_exe: &Path, config_path: &Path, name: &str) -> String {
+    // Batch files expand %, so literal ones must be doubled
+    let quote = |s: &str| format!("\"{}\"", s.replace('%', "%%"));
+    format!(
+        "@echo off\r\nrem {SHIM_MARKER} for profile '{name}'\r\n{} --config {} {} -- %*\r\n",
+        quote(&loader_exe.to_string_lossy()),
+        quote(&config_path.to_string_lossy()),
+        quote(name),
+    )
+}
+
+#[cfg(not(windows))]
+fn shim_script(loader_exe: &Path, config_path: &Path, name: &str) -> String {
+    let quote = |s: &str| format!("'{}'", s.replace('\'', r"'\''"));
+    format!(
+        "#!/bin/sh\n# {SHIM_MARKER} for profile '{name}'\nexec {} --config {} {} -- \"$@\"\n",
+        quote(&loader_exe.to_string_lossy()),
+        quote(&config_path.to_string_lossy()),
+        quote(name),
+    )
+}
+
+#[cfg(unix)]
+fn make_executable(path: &Path) -> Result<(), String> {
+    use std::os::unix::fs::PermissionsExt;
+    fs::set_permissions(path, fs::Permissions::from_mode(0o755))
+        .map_err(|e| format!("Failed to make {} executable: {e}", path.display()))
+}
+
+#[cfg(not(unix))]
+fn make_executable(_path: &Path) -> Result<(), String> {
+    Ok(())
+}
+
+/// Warn if the shim won't be what a shell finds for `binaryninja`
+fn warn_about_path(dir: &Path) {
+    let Some(paths) = env::var_os("PATH") else {
+        return;
+    };
+    let same_dir = |other: &Path| {
+        fs::canonicalize(other)
+            .ok()
+            .is_some_and(|other| fs::canonicalize(dir).is_ok_and(|dir| dir == other))
+    };
+
+    for entry in env::split_paths(&paths) {
+        if same_dir(&entry) {
+            return;
+        }
+        for command in COMMAND_NAMES {
+            let candidate = entry.join(command);
+            if candidate.is_file() {
+                report::warn(&format!(
+                    "{} comes earlier on PATH and will shadow the shim",
+                    candidate.display()
+                ));
+                return;
+            }
+        }
+    }
+
+    report::warn(&format!(
+        "{} is not on PATH; add it so `binaryninja` resolves to the shim",
+        dir.display()
+    ));
+}
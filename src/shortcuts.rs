@@ -0,0 +1,192 @@
+use crate::config::{Config, Profile};
+use crate::report::{self, outln};
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+const START_MENU_FOLDER: &str = "bn-loader";
+
+pub(crate) struct ShortcutOptions<'a> {
+    pub profile: Option<&'a str>,
+    pub dir: Option<&'a Path>,
+    pub start_menu: bool,
+    pub config_path: &'a Path,
+}
+
+pub(crate) fn create_shortcuts(config: &Config, options: &ShortcutOptions) -> Result<(), String> {
+    if !cfg!(windows) {
+        return Err("Shortcut creation is only supported on Windows".to_string());
+    }
+
+    let profiles: Vec<(&str, &Profile)> = if let Some(name) = options.profile {
+        let profile = config
+            .profiles
+            .get(name)
+            .ok_or_else(|| format!("Profile '{name}' not found"))?;
+        vec![(name, profile)]
+    } else {
+        let mut all: Vec<_> = config
+            .profiles
+            .iter()
+            .map(|(name, profile)| (name.as_str(), profile))
+            .collect();
+        all.sort_by_key(|(name, _)| *name);
+        all
+    };
+
+    if profiles.is_empty() {
+        return Err("No profiles to create shortcuts for".to_string());
+    }
+
+    let mut dirs = Vec::new();
+    if let Some(dir) = options.dir {
+        dirs.push(dir.to_path_buf());
+    } else {
+        dirs.push(desktop_dir()?);
+    }
+    if options.start_menu {
+        dirs.push(start_menu_dir()?);
+    }
+
+    let loader_exe =
+        env::current_exe().map_err(|e| format!("Failed to locate bn-loader executable: {e}"))?;
+    let config_path = std::path::absolute(options.config_path)
+        .map_err(|e| format!("Failed to resolve config path: {e}"))?;
+
+    for dir in &dirs {
+        fs::create_dir_all(dir)
+            .map_err(|e| format!("Failed to create directory {}: {e}", dir.display()))?;
+
+        for (name, profile) in &profiles {
+            let link_path = dir.join(format!("Binary Ninja ({name}).lnk"));
+            write_shortcut(&link_path, &loader_exe, name, profile, &config_path)?;
+            outln!("  Created: {}", link_path.display());
+            report::changed(&link_path);
+        }
+    }
+
+    Ok(())
+}
+
+fn desktop_dir() -> Result<PathBuf, String> {
+    env::var("USERPROFILE")
+        .map(|home| PathBuf::from(home).join("Desktop"))
+        .map_err(|_| "Could not determine Desktop directory (USERPROFILE not set)".to_string())
+}
+
+fn start_menu_dir() -> Result<PathBuf, String> {
+    env::var("APPDATA")
+        .map(|appdata| {
+            PathBuf::from(appdata)
+                .join("Microsoft")
+                .join("Windows")
+                .join("Start Menu")
+                .join("Programs")
+                .join(START_MENU_FOLDER)
+        })
+        .map_err(|_| "Could not determine Start Menu directory (APPDATA not set)".to_string())
+}
+
+/// Write a .lnk file through the `WScript.Shell` COM object
+fn write_shortcut(
+    link_path: &Path,
+    loader_exe: &Path,
+    name: &str,
+    profile: &Profile,
+    config_path: &Path,
+) -> Result<(), String> {
+    // Default to Binary Ninja's own icon so each shortcut is recognizable
+    let icon = profile
+        .icon
+        .clone()
+        .unwrap_or_else(|| profile.install_dir.join(&profile.executable));
+
+    // Pin the config file so the shortcut works regardless of environment
+    let arguments = format!(
+        "--config {} {}",
+        win_quote(&config_path.to_string_lossy()),
+        win_quote(name)
+    );
+
+    let script = format!(
+        "$s = (New-Object -ComObject WScript.Shell).CreateShortcut({link}); \
+         $s.TargetPath = {target}; \
+         $s.Arguments = {args}; \
+         $s.WorkingDirectory = {workdir}; \
+         $s.IconLocation = {icon}; \
+         $s.Description = {desc}; \
+         $s.Save()",
+        link = ps_quote(&link_path.to_string_lossy()),
+        target = ps_quote(&loader_exe.to_string_lossy()),
+        args = ps_quote(&arguments),
+        workdir = ps_quote(&profile.install_dir.to_string_lossy()),
+        icon = ps_quote(&icon.to_string_lossy()),
+        desc = ps_quote(&format!("Launch Binary Ninja with the '{name}' profile")),
+    );
+
+    let status = Command::new("powershell")
+        .args(["-NoProfile", "-NonInteractive", "-Command", &script])
+        .status()
+        .map_err(|e| format!("Failed to run PowerShell: {e}"))?;
+
+    if !status.success() {
+        return Err(format!(
+            "Failed to create shortcut {}: PowerShell exited with {status}",
+            link_path.display()
+        ));
+    }
+
+    Ok(())
+}
+
+/// Quote an argument for a Windows command line, as programs split it
+/// (`CommandLineToArgvW`): quotes are escaped, and backslashes doubled where
+/// they precede one
+pub(crate) fn win_quote(arg: &str) -> String {
+    let mut quoted = String::from("\"");
+    let mut backslashes = 0;
+    for c in arg.chars() {
+        match c {
+            '\\' => backslashes += 1,
+            '"' => {
+                quoted.push_str(&"\\".repeat(backslashes + 1));
+                backslashes = 0;
+            }
+            _ => backslashes = 0,
+        }
+        quoted.push(c);
+    }
+    // Before the closing quote too
+    quoted.push_str(&"\\".repeat(backslashes));
+    quoted.push('"');
+    quoted
+}
+
+/// Quote a string as a PowerShell single-quoted literal
+pub(crate) fn ps_quote(s: &str) -> String {
+    format!("'{}'", s.replace('\'', "''"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn win_quote_keeps_plain_paths() {
+        assert_eq!(win_quote(r"C:\Users\me"), r#""C:\Users\me""#);
+        assert_eq!(win_quote("my profile ü"), r#""my profile ü""#);
+    }
+
+    #[test]
+    fn win_quote_escapes_quotes_and_trailing_backslashes() {
+        assert_eq!(win_quote(r#"a"b"#), r#""a\"b""#);
+        assert_eq!(win_quote(r#"a\"b"#), r#""a\\\"b""#);
+        assert_eq!(win_quote(r"C:\dir\"), r#""C:\dir\\""#);
+    }
+
+    #[test]
+    fn ps_quote_doubles_single_quotes() {
+        assert_eq!(ps_quote("it's"), "'it''s'");
+    }
+}
@@ -0,0 +1,269 @@
+use crate::colors::paint;
+use crate::config::{Config, Profile};
+use crate::copy::copy_item;
+use crate::plugin_manager::{back_up, find_profile};
+use crate::report::{self, dataln, outln};
+use crate::safety::guard_config_dir;
+use crate::sync::{confirm, remove_path};
+use std::fs;
+use std::path::{Path, PathBuf};
+use termcolor::Color;
+
+pub(crate) const SNIPPETS_DIR: &str = "snippets";
+const SNIPPET_EXTENSION: &str = "py";
+
+/// A snippet file. The Snippets editor keeps its description and key
+/// binding as comments on the first two lines.
+pub(crate) struct Snippet {
+    /// Path under `snippets/` without `.py`, e.g. `analysis/find_xrefs`
+    pub name: String,
+    pub path: PathBuf,
+    pub description: Option<String>,
+    pub keybinding: Option<String>,
+}
+
+/// Every snippet of a profile, sorted by name. Folders group snippets in the
+/// editor, so their names include the folder.
+pub(crate) fn list_snippets(config_dir: &Path) -> Result<Vec<Snippet>, String> {
+    let mut snippets = Vec::new();
+    let dir = config_dir.join(SNIPPETS_DIR);
+    if dir.is_dir() {
+        collect_snippets(&dir, "", &mut snippets)?;
+    }
+    snippets.sort_by_key(|s| s.name.to_lowercase());
+    Ok(snippets)
+}
+
+fn collect_snippets(dir: &Path, prefix: &str, snippets: &mut Vec<Snippet>) -> Result<(), String> {
+    for entry in
+        fs::read_dir(dir).map_err(|e| format!("Failed to read directory {}: {e}", dir.display()))?
+    {
+        let entry = entry.map_err(|e| format!("Failed to read entry: {e}"))?;
+        let path = entry.path();
+        let file_name = entry.file_name().to_string_lossy().into_owned();
+        if file_name.starts_with('.') || file_name == "__pycache__" {
+            continue;
+        }
+        if path.is_dir() {
+            collect_snippets(&path, &format!("{prefix}{file_name}/"), snippets)?;
+        } else if let Some(stem) = file_name.strip_suffix(&format!(".{SNIPPET_EXTENSION}")) {
+            let (description, keybinding) = read_header(&path);
+            snippets.push(Snippet {
+                name: format!("{prefix}{stem}"),
+                path,
+                description,
+                keybinding,
+            });
+        }
+    }
+    Ok(())
+}
+
+/// `#Find every xref` / `#Ctrl+Shift+X` -> the description and key binding
+fn read_header(path: &Path) -> (Option<String>, Option<String>) {
+    let Ok(content) = fs::read_to_string(path) else {
+        return (None, None);
+    };
+    let mut lines = content.lines();
+    let mut comment = || {
+        lines
+            .next()
+            .and_then(|line| line.strip_prefix('#'))
+            .map(str::trim)
+            .filter(|text| !text.is_empty())
+            .map(str::to_string)
+    };
+    let description = comment();
+    (description, comment())
+}
+
+fn find_snippet(profile: &Profile, profile_name: &str, name: &str) -> Result<Snippet, String> {
+    let name = name.strip_suffix(".py").unwrap_or(name);
+    list_snippets(&profile.config_dir)?
+        .into_iter()
+        .find(|s| s.name == name)
+        .ok_or_else(|| format!("Profile '{profile_name}' has no snippet '{name}'"))
+}
+
+/// Where a snippet named `name` goes in a config dir
+fn snippet_path(config_dir: &Path, name: &str) -> PathBuf {
+    config_dir
+        .join(SNIPPETS_DIR)
+        .join(format!("{name}.{SNIPPET_EXTENSION}"))
+}
+
+/// List a profile's snippets with their key bindings and descriptions
+pub(crate) fn run_list(config: &Config, profile_name: &str) -> Result<(), String> {
+    let profile = find_profile(config, profile_name)?;
+    let snippets = list_snippets(&profile.config_dir)?;
+    if snippets.is_empty() {
+        dataln!("No snippets in profile '{profile_name}'");
+        return Ok(());
+    }
+
+    dataln!(
+        "Snippets for profile '{profile_name}' ({} total):\n",
+        snippets.len()
+    );
+    let width = snippets.iter().map(|s| s.name.len()).max().unwrap_or(0);
+    let key_width = snippets
+        .iter()
+        .filter_map(|s| s.keybinding.as_deref())
+        .map(str::len)
+        .max()
+        .unwrap_or(0);
+    for snippet in &snippets {
+        let mut line = format!(
+            "  {}",
+            paint(&format!("{:<width$}", snippet.name), Color::Cyan)
+        );
+        if key_width > 0 {
+            let key = snippet.keybinding.as_deref().unwrap_or("");
+            line.push_str(&format!("  {key:<key_width$}"));
+        }
+        if let Some(description) = &snippet.description {
+            line.push_str(&format!("  {description}"));
+        }
+        dataln!("{}", line.trim_end());
+    }
+    Ok(())
+}
+
+pub(crate) struct SnippetAddOptions<'a> {
+    /// The snippet's `.py` file
+    pub file: &'a Path,
+    pub profile: &'a str,
+    /// Name in `snippets/`, optionally under a folder (default: the file's name)
+    pub name: Option<&'a str>,
+    /// Replace a snippet the profile already has under that name
+    pub force: bool,
+    pub allow_suspicious_dirs: bool,
+}
+
+/// Copy a snippet file into a profile's `snippets/`
+pub(crate) fn run_add(config: &Config, options: &SnippetAddOptions) -> Result<(), String> {
+    let profile = find_profile(config, options.profile)?;
+    if !options.file.is_file() {
+        return Err(format!("{} isn't a file", options.file.display()));
+    }
+    let name = match options.name {
+        Some(name) => name.strip_suffix(".py").unwrap_or(name).to_string(),
+        None => options
+            .file
+            .file_stem()
+            .map(|stem| stem.to_string_lossy().into_owned())
+            .ok_or_else(|| format!("{} has no file name", options.file.display()))?,
+    };
+    let dst = snippet_path(&profile.config_dir, &name);
+    if dst.exists() && !options.force {
+        return Err(format!(
+            "Profile '{}' already has a snippet '{name}' (use --force to replace it)",
+            options.profile
+        ));
+    }
+
+    guard_config_dir(
+        options.profile,
+        &profile.config_dir,
+        options.allow_suspicious_dirs,
+    )?;
+    if dst.exists() {
+        back_up(config, options.profile, profile, &[SNIPPETS_DIR])?;
+    }
+    copy_item(options.file, &dst)?;
+    report::changed(&dst);
+    let summary = format!("Added snippet '{name}' to profile '{}'", options.profile);
+    outln!("{}", paint(&summary, Color::Green));
+    Ok(())
+}
+
+pub(crate) struct SnippetRemoveOptions<'a> {
+    pub name: &'a str,
+    pub profile: &'a str,
+    pub yes: bool,
+    pub allow_suspicious_dirs: bool,
+}
+
+/// Delete a snippet from a profile, after backing up `snippets/`
+pub(crate) fn run_remove(config: &Config, options: &SnippetRemoveOptions) -> Result<(), String> {
+    let profile_name = options.profile;
+    let profile = find_profile(config, profile_name)?;
+    let snippet = find_snippet(profile, profile_name, options.name)?;
+    guard_config_dir(
+        profile_name,
+        &profile.config_dir,
+        options.allow_suspicious_dirs,
+    )?;
+    if !options.yes
+        && !confirm(&format!(
+            "Remove snippet '{}' from profile '{profile_name}'?",
+            snippet.name
+        ))?
+    {
+        outln!("Aborted.");
+        return Ok(());
+    }
+
+    back_up(config, profile_name, profile, &[SNIPPETS_DIR])?;
+    remove_path(&snippet.path)?;
+    report::changed(&snippet.path);
+    outln!(
+        "Removed snippet '{}' from profile '{profile_name}'. Restore it with `bn-loader restore {profile_name} --latest --only {SNIPPETS_DIR}`.",
+        snippet.name
+    );
+    Ok(())
+}
+
+pub(crate) struct SnippetCopyOptions<'a> {
+    pub name: &'a str,
+    pub from: &'a str,
+    pub to: &'a str,
+    /// Replace a snippet the target already has under that name
+    pub force: bool,
+    pub yes: bool,
+    pub allow_suspicious_dirs: bool,
+}
+
+/// Copy one snippet from a profile to another
+pub(crate) fn run_copy(config: &Config, options: &SnippetCopyOptions) -> Result<(), String> {
+    let source = find_profile(config, options.from)?;
+    let target = find_profile(config, options.to)?;
+    if source.config_dir == target.config_dir {
+        return Err(format!(
+            "'{}' and '{}' share a config directory; there is nothing to copy",
+            options.from, options.to
+        ));
+    }
+    let snippet = find_snippet(source, options.from, options.name)?;
+    let dst = snippet_path(&target.config_dir, &snippet.name);
+    if dst.exists() && !options.force {
+        return Err(format!(
+            "'{}' already has a snippet '{}' (use --force to replace it)",
+            options.to, snippet.name
+        ));
+    }
+
+    outln!(
+        "Copying snippet '{}' from '{}' to '{}':",
+        snippet.name,
+        options.from,
+        options.to
+    );
+    outln!("  {} -> {}", snippet.path.display(), dst.display());
+    guard_config_dir(
+        options.to,
+        &target.config_dir,
+        options.allow_suspicious_dirs,
+    )?;
+    if !options.yes && !confirm("\nProceed?")? {
+        outln!("Aborted.");
+        return Ok(());
+    }
+
+    back_up(config, options.to, target, &[SNIPPETS_DIR])?;
+    copy_item(&snippet.path, &dst)?;
+    report::changed(&dst);
+    let summary = format!("Copied snippet '{}' to '{}'.", snippet.name, options.to);
+    outln!("{}", paint(&summary, Color::Green));
+    Ok(())
+}
@@ -0,0 +1,26 @@
+//! Proptest strategies for the names and paths users actually have: spaces,
+//! quotes, emoji and other non-ASCII text
+
+use proptest::prelude::*;
+use std::path::PathBuf;
+
+/// One path component, never `.` or `..`
+pub(crate) fn file_name() -> impl Strategy<Value = String> {
+    "[a-zA-Z0-9 '\"_()#&%$é日ß🦀-]{1,12}"
+}
+
+/// Any text at all, leaning towards what [`file_name`] generates
+pub(crate) fn any_name() -> impl Strategy<Value = String> {
+    prop_oneof![file_name(), any::<String>()]
+}
+
+pub(crate) fn relative_path() -> impl Strategy<Value = PathBuf> {
+    prop::collection::vec(file_name(), 1..4).prop_map(PathBuf::from_iter)
+}
+
+pub(crate) fn absolute_path() -> impl Strategy<Value = PathBuf> {
+    relative_path().prop_map(|path| {
+        let root = if cfg!(windows) { r"C:\" } else { "/" };
+        PathBuf::from(root).join(path)
+    })
+}
@@ -1,45 +1,712 @@
-use crate::config::{Config, Profile, default_exclusions};
+use crate::backups::{BackupStore, restore_item};
+use crate::colors::paint;
+use crate::config::{Config, Profile, SyncMode, default_exclusions};
+use crate::copy::{copy_file, copy_link, read_link, set_modified};
+use crate::history::format_age;
+use crate::items::{HANDLERS, ItemHandler, MergeOptions};
+use crate::manifest::{
+    build_manifest, hash_file, is_excluded, load_manifest, local_changes, save_manifest,
+};
+use crate::plugin_deps::{installed_plugin_dirs, offer_install};
+use crate::plugin_link::dev_link_exclusions;
+use crate::plugins::{PLUGINS_DIR, REPOSITORIES_DIR};
+use crate::progress::Progress;
+use crate::report::{self, outln};
+use crate::repositories::{RepositoriesMode, format_size, tree_size};
+use crate::safety::guard_config_dir;
+use crate::settings::SETTINGS_FILE;
+use crate::sync_report::{ItemReport, ReportFormat, SyncReport, TargetReport};
+use dialoguer::MultiSelect;
 use globset::{Glob, GlobSet, GlobSetBuilder};
+use serde::Serialize;
 use std::fs;
-use std::io::{self, Write};
-use std::path::{Path, PathBuf};
-use std::time::SystemTime;
-
-const SYNC_ITEMS: &[&str] = &[
-    "plugins",
-    "repositories",
-    "signatures",
-    "themes",
-    "snippets",
-    "types",
-    "settings.json",
-    "startup.py",
-    "keybindings.json",
-];
-
-const BACKUP_PREFIX: &str = ".bn-loader-backup-";
+use std::io::{self, IsTerminal, Write};
+use std::ops::AddAssign;
+use std::path::Path;
+use std::process::Command;
+use std::sync::OnceLock;
+use std::time::Instant;
+use termcolor::Color;
+use tracing::{debug, trace};
+
+const MAX_CHANGES_DISPLAY: usize = 20;
+
+/// Exit code of `sync --verify` when a target differs from the source
+pub(crate) const DRIFT_EXIT_CODE: i32 = 3;
 
 pub(crate) struct SyncOptions<'a> {
     pub from: &'a str,
     pub to: Option<&'a str>,
+    /// Pulling from a throwaway profile back into `to`: always copies, so
+    /// the target never ends up linked to a profile that may be deleted
+    pub pull: bool,
     pub extra_exclusions: Vec<&'a str>,
+    /// Name of a `[sync.presets.<name>]` section to take defaults from
+    pub preset: Option<&'a str>,
+    /// Only sync these items; overrides `sync.only` from the config
+    pub only: Vec<&'a str>,
     pub dry_run: bool,
     pub yes: bool,
+    /// Overwrite target files that are newer than the source's
+    pub force: bool,
+    /// Remove items the source doesn't have from the targets
+    pub delete: bool,
     pub backup_retention: usize,
+    pub repositories_mode: RepositoriesMode,
+    /// Link directory items instead of copying; overrides `sync.mode`
+    pub link: bool,
+    /// Sync into targets whose config_dir doesn't look like Binary Ninja's
+    pub allow_suspicious_dirs: bool,
+    /// Emit a machine-readable report of what was done
+    pub report: Option<ReportFormat>,
+    /// Write the report here instead of to stdout
+    pub report_file: Option<&'a Path>,
+    /// Command to run after each target; overrides `sync.post_hook`
+    pub hook: Option<&'a str>,
+    /// Compare the source with the targets instead of syncing
+    pub verify: bool,
+    /// Leave out items a target already has up to date, so they aren't
+    /// backed up, and targets with nothing to change entirely
+    pub only_changed: bool,
+}
+
+/// What a sync works on
+struct SyncPlan<'a> {
+    source: &'a Profile,
+    /// Exclusion patterns shared by every target
+    exclusions: Vec<String>,
+    only: Vec<&'a str>,
+    items: Vec<&'static dyn ItemHandler>,
+    targets: Vec<SyncTarget<'a>>,
+    merge_options: MergeOptions,
+}
+
+/// A target profile and the exclusions that apply when syncing into it
+struct SyncTarget<'a> {
+    name: &'a str,
+    profile: &'a Profile,
+    /// `sync.profile_exclusions` for this profile and the plugins linked into
+    /// it with `plugin link`, on top of the shared ones
+    own_exclusions: Vec<String>,
+    exclusions: GlobSet,
+    /// Items only the target has, removed with `--delete`
+    stale: Vec<&'static dyn ItemHandler>,
 }
 
 pub(crate) fn run_sync(config: &Config, options: &SyncOptions) -> Result<(), String> {
+    let mut sync_report = SyncReport::new(options.from, options.pull, options.dry_run);
+    let Some(format) = options.report else {
+        return sync(config, options, &mut sync_report);
+    };
+    if options.report_file.is_none() {
+        report::reserve_stdout();
+    }
+
+    let result = sync(config, options, &mut sync_report);
+    if let Err(e) = &result {
+        sync_report.success = false;
+        sync_report.error = Some(e.clone());
+    }
+    sync_report.write(format, options.report_file)?;
+    result
+}
+
+fn sync(
+    config: &Config,
+    options: &SyncOptions,
+    sync_report: &mut SyncReport,
+) -> Result<(), String> {
+    let SyncPlan {
+        source,
+        exclusions,
+        only,
+        mut items,
+        targets,
+        merge_options,
+    } = plan(config, options)?;
+    sync_report.exclusions.clone_from(&exclusions);
+
+    outln!(
+        "{}",
+        if options.pull {
+            "Pull Plan:"
+        } else {
+            "Sync Plan:"
+        }
+    );
+    if let Some(name) = options.preset {
+        outln!("  Preset: {name}");
+    }
+    outln!(
+        "  Source: {} ({})",
+        options.from,
+        source.config_dir.display()
+    );
+    outln!("  Targets:");
+    for target in &targets {
+        outln!(
+            "    - {} ({})",
+            target.name,
+            target.profile.config_dir.display()
+        );
+        if !target.own_exclusions.is_empty() {
+            outln!("        also excluding: {:?}", target.own_exclusions);
+        }
+    }
+    outln!("  Items to sync: {}", items.len());
+    outln!("  Exclusions: {exclusions:?}");
+    if !only.is_empty() {
+        outln!("  Only: {only:?}");
+    }
+    let hook = options.hook.or(config.sync.post_hook.as_deref());
+    if let Some(hook) = hook {
+        outln!("  Post-sync hook: {hook}");
+    }
+
+    if items.is_empty() && targets.iter().all(|t| t.stale.is_empty()) {
+        outln!("\nNo items to sync.");
+        return Ok(());
+    }
+
+    outln!("\nItems:");
+    for item in &items {
+        outln!("    {:<18} {}", item.name(), item.describe());
+    }
+    if targets.iter().any(|t| !t.stale.is_empty()) {
+        outln!("\nDelete (not in the source; backed up first):");
+        for target in targets.iter().filter(|t| !t.stale.is_empty()) {
+            let names: Vec<&str> = target.stale.iter().map(|item| item.name()).collect();
+            outln!("    {:<18} {}", target.name, names.join(", "));
+        }
+    }
+
+    if options.pull && config.sync.mode == SyncMode::Symlink {
+        outln!("\nMode: copy (pulls never link the target to the source)");
+    }
+    if merge_options.mode == SyncMode::Symlink {
+        outln!(
+            "\nMode: symlink (directory items will link to the source's; files are still copied)"
+        );
+        // A linked directory can't keep anything out
+        for target in targets.iter().filter(|t| !t.own_exclusions.is_empty()) {
+            outln!("  '{}' has its own exclusions and is copied", target.name);
+        }
+    }
+    for item in &items {
+        item.print_plan(&source.config_dir, &merge_options)?;
+    }
+
+    for item in &items {
+        item.validate(&source.config_dir)?;
+    }
+
+    let item_names: Vec<&str> = items.iter().map(|item| item.name()).collect();
+    print_local_changes(&targets, &item_names)?;
+
+    let conflicts = targets
+        .iter()
+        .map(|target| {
+            newer_in_target(
+                &source.config_dir,
+                target,
+                &items,
+                merge_options.max_file_size,
+            )
+        })
+        .collect::<Result<Vec<_>, String>>()?;
+    print_conflicts(&targets, &conflicts, options.force);
+
+    if options.dry_run {
+        for target in &targets {
+            let preview = MergeOptions {
+                dry_run: true,
+                ..target_options(target, &merge_options)
+            };
+            sync_report.targets.push(preview_target(
+                &source.config_dir,
+                target,
+                &items,
+                &preview,
+            )?);
+        }
+        outln!("\n{}", paint("[Dry run] No changes made.", Color::Yellow));
+        return Ok(());
+    }
+
+    if !options.yes && !confirm_items(&mut items)? {
+        outln!("Aborted.");
+        return Ok(());
+    }
+
+    // Settle every conflict before anything is written
+    let mut skipped: Vec<Vec<&str>> = Vec::new();
+    for (target, target_conflicts) in targets.iter().zip(&conflicts) {
+        let mut skip = Vec::new();
+        for (item, files) in target_conflicts {
+            if options.force || !items.iter().any(|i| i.name() == *item) {
+                continue;
+            }
+            let overwrite = !options.yes
+                && confirm(&format!(
+                    "Overwrite {} newer in '{}' with {}'s {item}?",
+                    plural(files.len(), "file"),
+                    target.name,
+                    options.from
+                ))?;
+            if !overwrite {
+                report::warn(&format!(
+                    "Skipping {item} for '{}': the target has newer files (use --force to overwrite)",
+                    target.name
+                ));
+                skip.push(*item);
+            }
+        }
+        skipped.push(skip);
+    }
+
+    for (target, skip) in targets.iter().zip(&skipped) {
+        let started = Instant::now();
+        let mut target_report = TargetReport::new(target.name, &target.profile.config_dir);
+        let mut target_items: Vec<&'static dyn ItemHandler> = Vec::new();
+        for item in &items {
+            if target.exclusions.is_match(item.name()) {
+                target_report.skip(item.name(), "excluded");
+            } else if skip.contains(&item.name()) {
+                target_report.skip(item.name(), "target has newer files");
+            } else {
+                target_items.push(*item);
+            }
+        }
+        let target_options = target_options(target, &merge_options);
+        if options.only_changed {
+            target_items =
+                changed_items(&source.config_dir, target, target_items, &target_options)?;
+            if target_items.is_empty() && target.stale.is_empty() {
+                outln!("\n'{}' is up to date.", target.name);
+                sync_report.targets.push(target_report);
+                continue;
+            }
+        }
+        let backups = BackupStore::new(
+            config.global.backup_dir.as_deref(),
+            target.name,
+            &target.profile.config_dir,
+        );
+        let result = sync_to_target(
+            &source.config_dir,
+            target,
+            &target_items,
+            &backups,
+            options.backup_retention,
+            &target_options,
+            &mut target_report,
+        );
+        sync_report.bytes_transferred += target_report
+            .items
+            .iter()
+            .map(|item| item.bytes)
+            .sum::<u64>();
+        sync_report.targets.push(target_report);
+        result?;
+        record_manifest(target.name, &target.profile.config_dir, &target.exclusions);
+        if let Some(hook) = hook {
+            let names: Vec<&str> = target_items.iter().map(|item| item.name()).collect();
+            run_post_hook(hook, options.from, target, &names);
+        }
+        if target_items
+            .iter()
+            .any(|item| [PLUGINS_DIR, REPOSITORIES_DIR].contains(&item.name()))
+            && !report::quiet()
+        {
+            check_plugin_dependencies(target, !options.yes);
+        }
+        report::timing(&format!("sync:{}", target.name), started.elapsed());
+    }
+
+    outln!("\n{}", paint("Sync complete.", Color::Green));
+    Ok(())
+}
+
+/// Report the Python packages the target's plugins need but its
+/// environment lacks, offering to install them
+fn check_plugin_dependencies(target: &SyncTarget, ask: bool) {
+    match installed_plugin_dirs(target.profile) {
+        Ok(plugins) => offer_install(target.name, target.profile, &plugins, ask),
+        Err(e) => report::warn(&format!(
+            "Couldn't check the Python dependencies of '{}': {e}",
+            target.name
+        )),
+    }
+}
+
+/// Merge options for one target: its own exclusions, and copies rather than
+/// links when it has any, since a linked directory can't keep anything out
+fn target_options(target: &SyncTarget, merge_options: &MergeOptions) -> MergeOptions {
+    MergeOptions {
+        mode: if target.own_exclusions.is_empty() {
+            merge_options.mode
+        } else {
+            SyncMode::Copy
+        },
+        exclusions: target.exclusions.clone(),
+        ..*merge_options
+    }
+}
+
+/// Print, file by file, what syncing into a target would do. `options` has
+/// `dry_run` set, so nothing is written.
+fn preview_target(
+    source_dir: &Path,
+    target: &SyncTarget,
+    items: &[&'static dyn ItemHandler],
+    options: &MergeOptions,
+) -> Result<TargetReport, String> {
+    let target_dir = &target.profile.config_dir;
+    let mut target_report = TargetReport::new(target.name, target_dir);
+    outln!(
+        "\nPreview for '{}' ({}):",
+        target.name,
+        target_dir.display()
+    );
+
+    for item in items {
+        if target.exclusions.is_match(item.name()) {
+            target_report.skip(item.name(), "excluded");
+            outln!("    {:<18} excluded", item.name());
+            continue;
+        }
+        let stats = item.merge(source_dir, target_dir, options)?;
+        if stats.linked > 0 {
+            outln!(
+                "    {:<18} link to {}",
+                item.name(),
+                source_dir.join(item.name()).display()
+            );
+        } else if stats.files.is_empty() && stats.skipped_files.is_empty() {
+            outln!(
+                "    {:<18} up to date ({})",
+                item.name(),
+                plural(stats.unchanged, "file")
+            );
+        } else {
+            let mut changes: Vec<(&str, &str, u64)> = stats
+                .files
+                .iter()
+                .map(|change| match change.action {
+                    FileAction::Removed => (
+                        "delete",
+                        change.path.as_str(),
+                        tree_size(&target_dir.join(&change.path)).unwrap_or(0),
+                    ),
+                    FileAction::Updated => (
+                        if target_dir.join(&change.path).exists() {
+                            "overwrite"
+                        } else {
+                            "create"
+                        },
+                        change.path.as_str(),
+                        fs::metadata(source_dir.join(&change.path)).map_or(0, |m| m.len()),
+                    ),
+                })
+                .collect();
+            changes.sort_by_key(|(_, path, _)| *path);
+            let count = |action: &str| changes.iter().filter(|(a, ..)| *a == action).count();
+            outln!(
+                "    {:<18} {} to create, {} to overwrite, {} to delete, {} unchanged ({})",
+                item.name(),
+                count("create"),
+                count("overwrite"),
+                count("delete"),
+                stats.unchanged,
+                format_size(stats.bytes)
+            );
+            for (action, path, size) in changes.iter().take(MAX_CHANGES_DISPLAY) {
+                outln!(
+                    "        {} {path} ({})",
+                    paint(&format!("{action:<9}"), action_color(action)),
+                    format_size(*size)
+                );
+            }
+            if changes.len() > MAX_CHANGES_DISPLAY {
+                outln!(
+                    "        ... and {} more (--report json lists them all)",
+                    changes.len() - MAX_CHANGES_DISPLAY
+                );
+            }
+            for file in &stats.skipped_files {
+                outln!(
+                    "        skip      {} ({}, {})",
+                    file.path,
+                    format_size(file.size),
+                    file.reason
+                );
+            }
+        }
+        target_report
+            .items
+            .push(ItemReport::new(item.name(), "planned", stats));
+    }
+
+    for item in &target.stale {
+        let size = tree_size(&target_dir.join(item.name())).unwrap_or(0);
+        outln!(
+            "    {:<18} {} ({})",
+            item.name(),
+            paint("delete", Color::Red),
+            format_size(size)
+        );
+        let mut stats = TransferStats::default();
+        stats.record(item.name(), FileAction::Removed);
+        target_report
+            .items
+            .push(ItemReport::new(item.name(), "planned", stats));
+    }
+    Ok(target_report)
+}
+
+/// Color of a file's action in a preview or verification: what a sync would
+/// add, change or remove
+fn action_color(action: &str) -> Color {
+    match action {
+        "create" | "missing" => Color::Green,
+        "delete" | "extra" => Color::Red,
+        _ => Color::Yellow,
+    }
+}
+
+/// The items that would change anything in the target
+fn changed_items<'a>(
+    source_dir: &Path,
+    target: &SyncTarget,
+    items: Vec<&'a dyn ItemHandler>,
+    options: &MergeOptions,
+) -> Result<Vec<&'a dyn ItemHandler>, String> {
+    let preview = MergeOptions {
+        exclusions: options.exclusions.clone(),
+        dry_run: true,
+        ..*options
+    };
+    let mut changed = Vec::new();
+    for item in items {
+        let stats = item.merge(source_dir, &target.profile.config_dir, &preview)?;
+        if stats.updated + stats.removed + stats.linked > 0 {
+            changed.push(item);
+        }
+    }
+    Ok(changed)
+}
+
+/// Quietly sync a profile from its `auto_sync_from` source before it
+/// launches, leaving out whatever is already up to date
+pub(crate) fn auto_sync(
+    config: &Config,
+    name: &str,
+    profile: &Profile,
+    allow_suspicious_dirs: bool,
+) -> Result<(), String> {
+    let Some(source) = profile.auto_sync_from.as_deref() else {
+        return Ok(());
+    };
+    let options = SyncOptions {
+        from: source,
+        to: Some(name),
+        pull: false,
+        extra_exclusions: Vec::new(),
+        preset: profile.auto_sync_preset.as_deref(),
+        only: Vec::new(),
+        dry_run: false,
+        yes: true,
+        force: false,
+        delete: false,
+        backup_retention: config.global.backup_retention,
+        repositories_mode: RepositoriesMode::default(),
+        link: false,
+        allow_suspicious_dirs,
+        report: None,
+        report_file: None,
+        hook: None,
+        verify: false,
+        only_changed: true,
+    };
+    let mut sync_report = SyncReport::new(source, false, false);
+    let was_quiet = report::set_quiet(true);
+    let result = sync(config, &options, &mut sync_report);
+    report::set_quiet(was_quiet);
+    result.map_err(|e| format!("Auto-sync from '{source}' failed: {e}"))?;
+
+    let items: Vec<&ItemReport> = sync_report
+        .targets
+        .iter()
+        .flat_map(|target| &target.items)
+        .collect();
+    if items.is_empty() {
+        outln!("Nothing to sync from '{source}'.");
+    } else {
+        let updated: usize = items.iter().map(|item| item.updated).sum();
+        let names: Vec<&str> = items.iter().map(|item| item.name.as_str()).collect();
+        outln!(
+            "Auto-synced from '{source}': {} updated ({})",
+            plural(updated, "file"),
+            names.join(", ")
+        );
+    }
+    Ok(())
+}
+
+/// Compare every target with the source file by file, hashing contents,
+/// and report what a sync with the same options would change. Nothing is
+/// written. Returns whether every target matches.
+pub(crate) fn run_verify(config: &Config, options: &SyncOptions) -> Result<bool, String> {
+    let SyncPlan {
+        source,
+        items,
+        targets,
+        merge_options,
+        ..
+    } = plan(config, options)?;
+
+    outln!(
+        "Verifying against '{}' ({})",
+        options.from,
+        source.config_dir.display()
+    );
+    let mut drifted = 0;
+    for target in &targets {
+        let check = MergeOptions {
+            mode: SyncMode::Copy,
+            exclusions: target.exclusions.clone(),
+            dry_run: true,
+            checksum: true,
+            ..merge_options
+        };
+        let mut drift = Vec::new();
+        for item in items
+            .iter()
+            .filter(|item| !target.exclusions.is_match(item.name()))
+        {
+            let stats = item.merge(&source.config_dir, &target.profile.config_dir, &check)?;
+            if stats.updated + stats.removed > 0 {
+                drift.push((item.name(), stats));
+            }
+        }
+        for item in &target.stale {
+            let mut stats = TransferStats::default();
+            stats.record(item.name(), FileAction::Removed);
+            drift.push((item.name(), stats));
+        }
+
+        if drift.is_empty() {
+            outln!("\n  '{}': {}", target.name, paint("in sync", Color::Green));
+            continue;
+        }
+        drifted += 1;
+        outln!(
+            "\n  '{}' ({}):",
+            target.name,
+            target.profile.config_dir.display()
+        );
+        for (name, stats) in &drift {
+            let mut labelled: Vec<(&str, &str)> = stats
+                .files
+                .iter()
+                .map(|change| {
+                    let label = match change.action {
+                        FileAction::Removed => "extra",
+                        FileAction::Updated
+                            if target.profile.config_dir.join(&change.path).exists() =>
+                        {
+                            "differs"
+                        }
+                        FileAction::Updated => "missing",
+                    };
+                    (label, change.path.as_str())
+                })
+                .collect();
+            labelled.sort_by_key(|(_, path)| *path);
+            let count = |label: &str| labelled.iter().filter(|(l, _)| *l == label).count();
+            outln!(
+                "    {name:<18} {} differ, {} missing, {} extra",
+                count("differs"),
+                count("missing"),
+                count("extra")
+            );
+            for (label, path) in labelled.iter().take(MAX_CHANGES_DISPLAY) {
+                outln!(
+                    "        {} {path}",
+                    paint(&format!("{label:<8}"), action_color(label))
+                );
+            }
+            if labelled.len() > MAX_CHANGES_DISPLAY {
+                outln!(
+                    "        ... and {} more",
+                    labelled.len() - MAX_CHANGES_DISPLAY
+                );
+            }
+        }
+    }
+
+    if drifted == 0 {
+        let summary = format!("All targets match '{}'.", options.from);
+        outln!("\n{}", paint(&summary, Color::Green));
+    } else {
+        let summary = format!(
+            "{} of {} targets differ from '{}'.",
+            drifted,
+            targets.len(),
+            options.from
+        );
+        outln!("\n{}", paint(&summary, Color::Yellow));
+    }
+    Ok(drifted == 0)
+}
+
+/// Work out what a sync (or verification) involves from the config and
+/// options
+fn plan<'a>(config: &'a Config, options: &SyncOptions<'a>) -> Result<SyncPlan<'a>, String> {
     let source = config
         .profiles
         .get(options.from)
         .ok_or_else(|| format!("Source profile '{}' not found", options.from))?;
 
+    let preset = options
+        .preset
+        .map(|name| {
+            config.sync.presets.get(name).ok_or_else(|| {
+                let mut known: Vec<&str> = config.sync.presets.keys().map(String::as_str).collect();
+                known.sort_unstable();
+                format!(
+                    "Sync preset '{name}' not found (available: {})",
+                    if known.is_empty() {
+                        "none".to_string()
+                    } else {
+                        known.join(", ")
+                    }
+                )
+            })
+        })
+        .transpose()?;
+
+    let preset_targets: Vec<&str> = preset
+        .map(|p| p.targets.iter().map(String::as_str).collect())
+        .unwrap_or_default();
     let targets: Vec<(&str, &Profile)> = if let Some(to) = options.to {
         let target = config
             .profiles
             .get(to)
             .ok_or_else(|| format!("Target profile '{to}' not found"))?;
         vec![(to, target)]
+    } else if !preset_targets.is_empty() {
+        preset_targets
+            .iter()
+            .map(|name| {
+                config
+                    .profiles
+                    .get(*name)
+                    .map(|profile| (*name, profile))
+                    .ok_or_else(|| format!("Target profile '{name}' not found"))
+            })
+            .filter(|target| !matches!(target, Ok((name, _)) if *name == options.from))
+            .collect::<Result<_, _>>()?
     } else {
         config
             .profiles
@@ -53,74 +720,358 @@ pub(crate) fn run_sync(config: &Config, options: &SyncOptions) -> Result<(), Str
         return Err("No target profiles to sync to".to_string());
     }
 
-    // Start with defaults, add config exclusions, then CLI exclusions
+    for (name, target) in &targets {
+        guard_config_dir(
+            name,
+            &target.config_dir,
+            options.allow_suspicious_dirs || options.dry_run || options.verify,
+        )?;
+    }
+
+    // Start with defaults, add config and preset exclusions, then CLI exclusions
     let mut exclusions = default_exclusions();
     exclusions.extend(config.sync.exclusions.iter().cloned());
+    if let Some(preset) = preset {
+        exclusions.extend(preset.exclusions.iter().cloned());
+    }
     for excl in &options.extra_exclusions {
         exclusions.push((*excl).to_string());
     }
+    // Plugins under development stay in the profile they're linked into
+    exclusions.extend(dev_link_exclusions(&source.config_dir));
+
+    let only: Vec<&str> = if !options.only.is_empty() {
+        options.only.clone()
+    } else if let Some(preset) = preset.filter(|p| !p.items.is_empty()) {
+        preset.items.iter().map(String::as_str).collect()
+    } else {
+        config.sync.only.iter().map(String::as_str).collect()
+    };
+    validate_item_names(&only)?;
 
     let glob_set = build_glob_set(&exclusions)?;
-    let items = collect_sync_items(&source.config_dir, &glob_set)?;
+    let items = collect_sync_items(&source.config_dir, &glob_set, &only)?;
 
-    println!("Sync Plan:");
-    println!(
-        "  Source: {} ({})",
-        options.from,
-        source.config_dir.display()
-    );
-    println!("  Targets:");
-    for (name, profile) in &targets {
-        println!("    - {} ({})", name, profile.config_dir.display());
+    let targets = targets
+        .into_iter()
+        .map(|(name, profile)| {
+            let mut own_exclusions = config
+                .sync
+                .profile_exclusions
+                .get(name)
+                .cloned()
+                .unwrap_or_default();
+            own_exclusions.extend(dev_link_exclusions(&profile.config_dir));
+            let exclusions = if own_exclusions.is_empty() {
+                glob_set.clone()
+            } else {
+                build_glob_set(&[exclusions.as_slice(), own_exclusions.as_slice()].concat())?
+            };
+            let stale = if options.delete {
+                stale_items(&source.config_dir, &profile.config_dir, &exclusions, &only)
+            } else {
+                Vec::new()
+            };
+            Ok(SyncTarget {
+                name,
+                profile,
+                own_exclusions,
+                exclusions,
+                stale,
+            })
+        })
+        .collect::<Result<Vec<_>, String>>()?;
+
+    let merge_options = MergeOptions {
+        repositories_mode: options.repositories_mode,
+        mode: if options.pull {
+            SyncMode::Copy
+        } else if options.link {
+            SyncMode::Symlink
+        } else {
+            config.sync.mode
+        },
+        exclusions: glob_set,
+        delete: options.delete,
+        dry_run: false,
+        checksum: false,
+        max_file_size: config.sync.max_file_size,
+    };
+
+    Ok(SyncPlan {
+        source,
+        exclusions,
+        only,
+        items,
+        targets,
+        merge_options,
+    })
+}
+
+/// Run the post-sync hook through the shell, from the target's config dir.
+/// The sync already happened, so a failing hook is only a warning.
+fn run_post_hook(hook: &str, source: &str, target: &SyncTarget, items: &[&str]) {
+    outln!("  Running post-sync hook...");
+    let mut command = shell_command(hook);
+    command
+        .current_dir(&target.profile.config_dir)
+        .env("BN_LOADER_SOURCE", source)
+        .env("BN_LOADER_TARGET", target.name)
+        .env("BN_LOADER_CONFIG_DIR", &target.profile.config_dir)
+        .env("BN_LOADER_ITEMS", items.join(","));
+    if report::stdout_is_data() {
+        command.stdout(io::stderr());
     }
-    println!("  Items to sync: {}", items.len());
-    println!("  Exclusions: {exclusions:?}");
+    debug!("Running {command:?}");
+    match command.status() {
+        Ok(status) if status.success() => {}
+        Ok(status) => report::warn(&format!(
+            "Post-sync hook for '{}' failed ({status})",
+            target.name
+        )),
+        Err(e) => report::warn(&format!(
+            "Failed to run post-sync hook for '{}': {e}",
+            target.name
+        )),
+    }
+}
 
-    if items.is_empty() {
-        println!("\nNo items to sync.");
+#[cfg(windows)]
+fn shell_command(script: &str) -> Command {
+    let mut command = Command::new("cmd");
+    command.arg("/C").arg(script);
+    command
+}
+
+#[cfg(not(windows))]
+fn shell_command(script: &str) -> Command {
+    let mut command = Command::new("sh");
+    command.arg("-c").arg(script);
+    command
+}
+
+/// Warn about files in the targets that changed since bn-loader last wrote
+/// them, since syncing will overwrite those edits
+fn print_local_changes(targets: &[SyncTarget], items: &[&str]) -> Result<(), String> {
+    let mut header_printed = false;
+
+    for target in targets {
+        let name = target.name;
+        let Some(stored) = load_manifest(name) else {
+            continue;
+        };
+        let current = build_manifest(&target.profile.config_dir, items, &target.exclusions)?;
+        let changes = local_changes(&stored, &current, items);
+        if changes.is_empty() {
+            continue;
+        }
+
+        if !header_printed {
+            outln!("\nTarget has local changes since last bn-loader operation:");
+            header_printed = true;
+        }
+        let age = current.timestamp.saturating_sub(stored.timestamp);
+        outln!("  '{name}' (last synced {}):", format_age(age));
+        for change in changes.iter().take(MAX_CHANGES_DISPLAY) {
+            outln!("      {}", change.describe());
+        }
+        if changes.len() > MAX_CHANGES_DISPLAY {
+            outln!("      ... and {} more", changes.len() - MAX_CHANGES_DISPLAY);
+        }
+    }
+
+    Ok(())
+}
+
+/// Files in a target that differ from the source's and were modified more
+/// recently, per item. Syncing would throw those edits away. settings.json
+/// is merged by key and rewritten at every launch, so it isn't checked.
+fn newer_in_target(
+    source_dir: &Path,
+    target: &SyncTarget,
+    items: &[&'static dyn ItemHandler],
+    max_file_size: Option<u64>,
+) -> Result<Vec<(&'static str, Vec<String>)>, String> {
+    let mut conflicts = Vec::new();
+    for item in items {
+        let name = item.name();
+        if name == SETTINGS_FILE || target.exclusions.is_match(name) {
+            continue;
+        }
+        let mut files = Vec::new();
+        collect_newer(
+            &source_dir.join(name),
+            &target.profile.config_dir.join(name),
+            name,
+            &target.exclusions,
+            max_file_size,
+            &mut files,
+        )?;
+        if !files.is_empty() {
+            conflicts.push((name, files));
+        }
+    }
+    Ok(conflicts)
+}
+
+fn collect_newer(
+    src: &Path,
+    dst: &Path,
+    rel: &str,
+    exclusions: &GlobSet,
+    max_file_size: Option<u64>,
+    files: &mut Vec<String>,
+) -> Result<(), String> {
+    // Nothing of the target's own is overwritten through a link
+    let Ok(dst_meta) = fs::symlink_metadata(dst) else {
+        return Ok(());
+    };
+    if dst_meta.file_type().is_symlink() {
         return Ok(());
     }
 
-    println!("\nItems:");
-    for item in &items {
-        println!("    {}", item.display());
+    if src.is_dir() {
+        if !dst_meta.is_dir() {
+            return Ok(());
+        }
+        for entry in fs::read_dir(src)
+            .map_err(|e| format!("Failed to read directory {}: {e}", src.display()))?
+        {
+            let entry = entry.map_err(|e| format!("Failed to read entry: {e}"))?;
+            let path = entry.path();
+            let child_rel = format!("{rel}/{}", entry.file_name().to_string_lossy());
+            if is_excluded(exclusions, &child_rel, path.is_dir()) {
+                continue;
+            }
+            collect_newer(
+                &path,
+                &dst.join(entry.file_name()),
+                &child_rel,
+                exclusions,
+                max_file_size,
+                files,
+            )?;
+        }
+        return Ok(());
     }
 
-    if options.dry_run {
-        println!("\n[Dry run] No changes made.");
+    if !dst_meta.is_file() {
+        return Ok(());
+    }
+    let src_meta =
+        fs::metadata(src).map_err(|e| format!("Failed to read {}: {e}", src.display()))?;
+    // Files the transfer leaves out are never overwritten
+    if rel.contains('/') && skip_reason(rel, src_meta.len(), max_file_size).is_some() {
+        return Ok(());
+    }
+    let (Ok(src_modified), Ok(dst_modified)) = (src_meta.modified(), dst_meta.modified()) else {
         return Ok(());
+    };
+    if dst_modified > src_modified
+        && (src_meta.len() != dst_meta.len() || hash_file(src)? != hash_file(dst)?)
+    {
+        files.push(rel.to_string());
+    }
+    Ok(())
+}
+
+fn print_conflicts(
+    targets: &[SyncTarget],
+    conflicts: &[Vec<(&'static str, Vec<String>)>],
+    force: bool,
+) {
+    if conflicts.iter().all(Vec::is_empty) {
+        return;
+    }
+
+    outln!("\nTarget files newer than the source's:");
+    for (target, target_conflicts) in targets.iter().zip(conflicts) {
+        let files: Vec<&String> = target_conflicts
+            .iter()
+            .flat_map(|(_, files)| files)
+            .collect();
+        if files.is_empty() {
+            continue;
+        }
+        outln!("  '{}':", target.name);
+        for file in files.iter().take(MAX_CHANGES_DISPLAY) {
+            outln!("      {file}");
+        }
+        if files.len() > MAX_CHANGES_DISPLAY {
+            outln!("      ... and {} more", files.len() - MAX_CHANGES_DISPLAY);
+        }
+    }
+    if force {
+        outln!("  --force: these will be overwritten");
     }
+}
 
-    if !options.yes {
-        print!("\nProceed? [y/N] ");
+pub(crate) fn confirm(prompt: &str) -> Result<bool, String> {
+    Ok(ask(&format!("{prompt} [y/N] "))?.eq_ignore_ascii_case("y"))
+}
+
+/// Ask to go ahead with the sync. On a terminal, the items can be picked
+/// from a checklist first; `items` is left holding the ones kept.
+fn confirm_items(items: &mut Vec<&'static dyn ItemHandler>) -> Result<bool, String> {
+    if items.is_empty() || !io::stdin().is_terminal() || !io::stderr().is_terminal() {
+        return confirm("\nProceed?");
+    }
+    let answer = ask("\nProceed? [y/N, s to select items] ")?;
+    if !answer.eq_ignore_ascii_case("s") {
+        return Ok(answer.eq_ignore_ascii_case("y"));
+    }
+
+    let labels: Vec<String> = items
+        .iter()
+        .map(|item| format!("{:<18} {}", item.name(), item.describe()))
+        .collect();
+    let chosen = MultiSelect::new()
+        .with_prompt("Items to sync (space toggles, enter accepts)")
+        .items(&labels)
+        .defaults(&vec![true; items.len()])
+        .interact_opt()
+        .map_err(|e| format!("Failed to read selection: {e}"))?;
+    // Escape keeps every item
+    if let Some(chosen) = chosen {
+        *items = chosen.into_iter().map(|idx| items[idx]).collect();
+    }
+    if items.is_empty() {
+        outln!("No items selected.");
+        return Ok(false);
+    }
+    let names: Vec<&str> = items.iter().map(|item| item.name()).collect();
+    outln!("Syncing: {}", names.join(", "));
+    confirm("Proceed?")
+}
+
+/// Print a prompt and read a trimmed line of input
+pub(crate) fn ask(prompt: &str) -> Result<String, String> {
+    if report::stdout_is_data() {
+        eprint!("{prompt}");
+    } else {
+        print!("{prompt}");
         io::stdout()
             .flush()
             .map_err(|e| format!("Failed to flush stdout: {e}"))?;
-        let mut input = String::new();
-        io::stdin()
-            .read_line(&mut input)
-            .map_err(|e| format!("Failed to read input: {e}"))?;
-        if !input.trim().eq_ignore_ascii_case("y") {
-            println!("Aborted.");
-            return Ok(());
-        }
     }
+    let mut input = String::new();
+    io::stdin()
+        .read_line(&mut input)
+        .map_err(|e| format!("Failed to read input: {e}"))?;
+    Ok(input.trim().to_string())
+}
 
-    for (name, target) in &targets {
-        sync_to_target(
-            &source.config_dir,
-            &target.config_dir,
-            &items,
-            name,
-            options.backup_retention,
-        )?;
+/// Remember what a target looks like right after bn-loader wrote to it
+fn record_manifest(name: &str, config_dir: &Path, exclusions: &GlobSet) {
+    let all_items: Vec<&str> = HANDLERS.iter().map(|h| h.name()).collect();
+    let result = build_manifest(config_dir, &all_items, exclusions)
+        .and_then(|manifest| save_manifest(name, &manifest));
+    if let Err(e) = result {
+        report::warn(&format!("Failed to record manifest for '{name}': {e}"));
     }
-
-    println!("\nSync complete.");
-    Ok(())
 }
 
-fn build_glob_set(patterns: &[String]) -> Result<GlobSet, String> {
+pub(crate) fn build_glob_set(patterns: &[String]) -> Result<GlobSet, String> {
     let mut builder = GlobSetBuilder::new();
     for pattern in patterns {
         let glob =
@@ -132,13 +1083,51 @@ fn build_glob_set(patterns: &[String]) -> Result<GlobSet, String> {
         .map_err(|e| format!("Failed to build glob set: {e}"))
 }
 
-fn collect_sync_items(source_dir: &Path, exclusions: &GlobSet) -> Result<Vec<PathBuf>, String> {
+fn validate_item_names(names: &[&str]) -> Result<(), String> {
+    let known: Vec<&str> = HANDLERS.iter().map(|h| h.name()).collect();
+    match names.iter().find(|name| !known.contains(name)) {
+        Some(unknown) => Err(format!(
+            "Unknown sync item '{unknown}' (expected one of: {})",
+            known.join(", ")
+        )),
+        None => Ok(()),
+    }
+}
+
+/// Items a target has that the source doesn't. settings.json is merged by
+/// key rather than mirrored, so it is never one of them.
+fn stale_items(
+    source_dir: &Path,
+    target_dir: &Path,
+    exclusions: &GlobSet,
+    only: &[&str],
+) -> Vec<&'static dyn ItemHandler> {
+    HANDLERS
+        .iter()
+        .copied()
+        .filter(|handler| {
+            (only.is_empty() || only.contains(&handler.name()))
+                && handler.name() != SETTINGS_FILE
+                && !exclusions.is_match(handler.name())
+                && !handler.identify(source_dir)
+                && handler.identify(target_dir)
+        })
+        .collect()
+}
+
+fn collect_sync_items(
+    source_dir: &Path,
+    exclusions: &GlobSet,
+    only: &[&str],
+) -> Result<Vec<&'static dyn ItemHandler>, String> {
     let mut items = Vec::new();
 
-    for item_name in SYNC_ITEMS {
-        let item_path = source_dir.join(item_name);
-        if item_path.exists() && !exclusions.is_match(item_name) {
-            items.push(PathBuf::from(item_name));
+    for handler in HANDLERS {
+        if (only.is_empty() || only.contains(&handler.name()))
+            && handler.identify(source_dir)
+            && !exclusions.is_match(handler.name())
+        {
+            items.push(*handler);
         }
     }
 
@@ -147,143 +1136,878 @@ fn collect_sync_items(source_dir: &Path, exclusions: &GlobSet) -> Result<Vec<Pat
 
 fn sync_to_target(
     source_dir: &Path,
-    target_dir: &Path,
-    items: &[PathBuf],
-    target_name: &str,
+    target: &SyncTarget,
+    items: &[&dyn ItemHandler],
+    backups: &BackupStore,
     backup_retention: usize,
+    merge_options: &MergeOptions,
+    target_report: &mut TargetReport,
 ) -> Result<(), String> {
-    println!("\nSyncing to '{target_name}'...");
+    let target_dir = &target.profile.config_dir;
+    outln!("\nSyncing to '{}'...", target.name);
 
-    let backup_dir = create_backup(target_dir, items)?;
+    let backup_dir = backups.create(&[items, &target.stale].concat())?;
     if let Some(ref backup) = backup_dir {
-        println!("  Backup created: {}", backup.display());
+        outln!("  Backup created: {}", backup.display());
+        report::changed(backup);
     }
+    target_report.backup = backup_dir;
 
     // Clean up old backups if retention is set
     if backup_retention > 0 {
-        cleanup_old_backups(target_dir, backup_retention)?;
+        debug!(
+            "Keeping the newest {backup_retention} backup(s) of '{}'",
+            target.name
+        );
+        backups.cleanup(backup_retention)?;
     }
 
-    for item in items {
-        let source_path = source_dir.join(item);
-        let target_path = target_dir.join(item);
+    // A target is never left half-synced: on failure, everything written so
+    // far is put back from the backup
+    let mut touched = Vec::new();
+    if let Err(e) = write_items(
+        source_dir,
+        target,
+        items,
+        merge_options,
+        target_report,
+        &mut touched,
+    ) {
+        let rolled_back = roll_back(target_report.backup.as_deref(), target_dir, &touched);
+        if !rolled_back.is_empty() {
+            outln!(
+                "  {} {}",
+                paint("Rolled back:", Color::Yellow),
+                rolled_back.join(", ")
+            );
+        }
+        let message = format!(
+            "{e} (rolled back {} in '{}')",
+            if rolled_back.is_empty() {
+                "nothing".to_string()
+            } else {
+                rolled_back.join(", ")
+            },
+            target.name
+        );
+        target_report.rolled_back = rolled_back;
+        return Err(message);
+    }
 
-        if source_path.is_dir() {
-            copy_dir_recursive(&source_path, &target_path)?;
-        } else {
-            if let Some(parent) = target_path.parent() {
-                fs::create_dir_all(parent)
-                    .map_err(|e| format!("Failed to create directory: {e}"))?;
-            }
-            fs::copy(&source_path, &target_path)
-                .map_err(|e| format!("Failed to copy {}: {}", item.display(), e))?;
+    Ok(())
+}
+
+/// Merge the items into the target and delete its stale ones, recording
+/// each item in `touched` before it is written
+fn write_items(
+    source_dir: &Path,
+    target: &SyncTarget,
+    items: &[&dyn ItemHandler],
+    merge_options: &MergeOptions,
+    target_report: &mut TargetReport,
+    touched: &mut Vec<&'static str>,
+) -> Result<(), String> {
+    let target_dir = &target.profile.config_dir;
+
+    let mut total = TransferStats::default();
+    let mut skipped = Vec::new();
+    for item in items {
+        touched.push(item.name());
+        debug!(
+            "Merging {} from {} into {}",
+            item.name(),
+            source_dir.display(),
+            target_dir.display()
+        );
+        let stats = item.merge(source_dir, target_dir, merge_options)?;
+        outln!(
+            "  {} {} ({})",
+            paint("Synced:", Color::Green),
+            item.name(),
+            stats.describe()
+        );
+        for file in &stats.files {
+            trace!("{:?} {}", file.action, file.path);
+        }
+        skipped.extend(
+            stats
+                .skipped_files
+                .iter()
+                .map(|file| (file.path.clone(), file.size, file.reason)),
+        );
+        if stats.updated + stats.removed + stats.linked > 0 {
+            report::changed(&target_dir.join(item.name()));
+        }
+        report::transferred(stats.bytes);
+        total.updated += stats.updated;
+        total.unchanged += stats.unchanged;
+        total.removed += stats.removed;
+        total.linked += stats.linked;
+        let action = if stats.linked > 0 { "linked" } else { "synced" };
+        target_report
+            .items
+            .push(ItemReport::new(item.name(), action, stats));
+    }
+    if items.len() > 1 {
+        outln!("  Total: {}", total.describe());
+    }
+    if !skipped.is_empty() {
+        outln!(
+            "  Skipped {} (Binary Ninja databases, or over sync.max_file_size):",
+            plural(skipped.len(), "file")
+        );
+        for (path, size, reason) in skipped.iter().take(MAX_CHANGES_DISPLAY) {
+            outln!("      {path} ({}, {reason})", format_size(*size));
+        }
+        if skipped.len() > MAX_CHANGES_DISPLAY {
+            outln!("      ... and {} more", skipped.len() - MAX_CHANGES_DISPLAY);
         }
-        println!("  Copied: {}", item.display());
+    }
+
+    for item in &target.stale {
+        touched.push(item.name());
+        let path = target_dir.join(item.name());
+        debug!("Removing {}", path.display());
+        remove_path(&path)?;
+        outln!("  {} {}", paint("Deleted:", Color::Red), item.name());
+        report::changed(&path);
+        target_report.items.push(ItemReport::new(
+            item.name(),
+            "deleted",
+            TransferStats::default(),
+        ));
     }
 
     Ok(())
 }
 
-fn create_backup(target_dir: &Path, items: &[PathBuf]) -> Result<Option<PathBuf>, String> {
-    let items_to_backup: Vec<&PathBuf> = items
-        .iter()
-        .filter(|item| target_dir.join(item).exists())
-        .collect();
+/// Put items back from the backup taken before the sync, newest first.
+/// Returns the items that were restored.
+fn roll_back(backup_dir: Option<&Path>, target_dir: &Path, touched: &[&str]) -> Vec<String> {
+    let mut rolled_back = Vec::new();
+    for name in touched.iter().rev() {
+        match restore_item(backup_dir, target_dir, name) {
+            Ok(()) => rolled_back.push((*name).to_string()),
+            Err(e) => report::warn(&format!(
+                "Failed to roll back {name} in {}: {e}",
+                target_dir.display()
+            )),
+        }
+    }
+    rolled_back.reverse();
+    rolled_back
+}
+
+/// Files a transfer touched
+#[derive(Default)]
+pub(crate) struct TransferStats {
+    pub updated: usize,
+    pub unchanged: usize,
+    pub removed: usize,
+    /// Directory items linked instead of copied
+    pub linked: usize,
+    /// Size of the updated files
+    pub bytes: u64,
+    /// Paths (relative to the config dir) written or removed
+    pub files: Vec<FileChange>,
+    /// Source paths left out by exclusions
+    pub excluded: Vec<String>,
+    /// Source files left out for their size or type
+    pub skipped_files: Vec<SkippedFile>,
+}
+
+/// A file a transfer left out even though no exclusion names it
+#[derive(Serialize)]
+pub(crate) struct SkippedFile {
+    pub path: String,
+    pub size: u64,
+    pub reason: &'static str,
+}
 
-    if items_to_backup.is_empty() {
-        return Ok(None);
+/// One path a transfer wrote or removed
+#[derive(Serialize)]
+pub(crate) struct FileChange {
+    pub path: String,
+    pub action: FileAction,
+}
+
+#[derive(Clone, Copy, Debug, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub(crate) enum FileAction {
+    Updated,
+    Removed,
+}
+
+impl AddAssign for TransferStats {
+    fn add_assign(&mut self, other: Self) {
+        self.updated += other.updated;
+        self.unchanged += other.unchanged;
+        self.removed += other.removed;
+        self.linked += other.linked;
+        self.bytes += other.bytes;
+        self.files.extend(other.files);
+        self.excluded.extend(other.excluded);
+        self.skipped_files.extend(other.skipped_files);
+    }
+}
+
+impl TransferStats {
+    pub(crate) fn record(&mut self, path: &str, action: FileAction) {
+        match action {
+            FileAction::Updated => self.updated += 1,
+            FileAction::Removed => self.removed += 1,
+        }
+        self.files.push(FileChange {
+            path: path.to_string(),
+            action,
+        });
+    }
+
+    pub(crate) fn describe(&self) -> String {
+        if self.linked > 0 && self.updated + self.unchanged + self.removed == 0 {
+            return "linked".to_string();
+        }
+        let mut parts = vec![
+            format!("{} updated", plural(self.updated, "file")),
+            format!("{} unchanged", self.unchanged),
+        ];
+        if self.removed > 0 {
+            parts.push(format!("{} removed", self.removed));
+        }
+        if self.linked > 0 {
+            parts.push(format!("{} linked", self.linked));
+        }
+        parts.join(", ")
     }
+}
 
-    let timestamp = SystemTime::now()
-        .duration_since(SystemTime::UNIX_EPOCH)
-        .map_err(|e| format!("System clock error: {e}"))?
-        .as_secs();
-    let backup_name = format!("{BACKUP_PREFIX}{timestamp}");
-    let backup_dir = target_dir.join(&backup_name);
+fn plural(count: usize, noun: &str) -> String {
+    if count == 1 {
+        format!("{count} {noun}")
+    } else {
+        format!("{count} {noun}s")
+    }
+}
 
-    fs::create_dir_all(&backup_dir)
-        .map_err(|e| format!("Failed to create backup directory: {e}"))?;
+/// Put a source item into the target: copied, or for directories in symlink
+/// mode, linked to the source. A link left by an earlier symlink-mode sync is
+/// replaced rather than written through. `rel` is the item's path inside the
+/// config dir, which exclusions are matched against. With `dry_run`, nothing
+/// is written and the stats say what would change.
+pub(crate) fn transfer_item(
+    src: &Path,
+    dst: &Path,
+    rel: &str,
+    mode: SyncMode,
+    options: &MergeOptions,
+) -> Result<TransferStats, String> {
+    let dst_is_link = fs::symlink_metadata(dst).is_ok_and(|m| m.file_type().is_symlink());
 
-    for item in items_to_backup {
-        let source = target_dir.join(item);
-        let dest = backup_dir.join(item);
+    // The source is (or is inside) a link to the target's real data; replacing
+    // the target would delete the source too. A target linked to the source
+    // has the same content, which is all a checksum comparison asks about.
+    if (!dst_is_link || options.checksum)
+        && let (Ok(src_real), Ok(dst_real)) = (fs::canonicalize(src), fs::canonicalize(dst))
+        && src_real == dst_real
+    {
+        return Ok(TransferStats::default());
+    }
 
-        if source.is_dir() {
-            copy_dir_recursive(&source, &dest)?;
-        } else {
-            if let Some(parent) = dest.parent() {
-                fs::create_dir_all(parent)
-                    .map_err(|e| format!("Failed to create backup subdirectory: {e}"))?;
-            }
-            fs::copy(&source, &dest)
-                .map_err(|e| format!("Failed to backup {}: {}", item.display(), e))?;
+    if mode == SyncMode::Symlink && src.is_dir() {
+        if !options.dry_run {
+            link_dir(src, dst)?;
         }
+        return Ok(TransferStats {
+            linked: 1,
+            ..TransferStats::default()
+        });
+    }
+
+    if dst_is_link && !options.dry_run {
+        remove_link(dst)?;
+    }
+    let mut transfer = Transfer {
+        exclusions: &options.exclusions,
+        dry_run: options.dry_run,
+        checksum: options.checksum,
+        max_file_size: options.max_file_size,
+        stats: TransferStats::default(),
+        progress: if options.dry_run {
+            Progress::hidden()
+        } else {
+            Progress::for_tree(rel, src, rel, &|rel, metadata| {
+                is_excluded(&options.exclusions, rel, metadata.is_dir())
+                    || (!metadata.is_dir()
+                        && skip_reason(rel, metadata.len(), options.max_file_size).is_some())
+            })
+        },
+    };
+    transfer.tree(src, dst, rel, dst_is_link)?;
+    Ok(transfer.stats)
+}
+
+/// Binary Ninja databases and their SQLite side files. They belong to an
+/// analysis rather than a profile and can run to gigabytes, so transfers
+/// never copy them out of a directory item.
+const DATABASE_PATTERNS: &[&str] = &["*.bndb", "*.bndb-*"];
+
+fn database_files() -> &'static GlobSet {
+    static DATABASES: OnceLock<GlobSet> = OnceLock::new();
+    DATABASES.get_or_init(|| {
+        let patterns: Vec<String> = DATABASE_PATTERNS.iter().map(|p| p.to_string()).collect();
+        build_glob_set(&patterns).unwrap_or_else(|_| GlobSet::empty())
+    })
+}
+
+/// Why a file inside a directory item is left out of transfers, if it is
+fn skip_reason(rel: &str, size: u64, max_file_size: Option<u64>) -> Option<&'static str> {
+    if database_files().is_match(rel) {
+        Some("database")
+    } else if max_file_size.is_some_and(|max| size > max) {
+        Some("over max_file_size")
+    } else {
+        None
     }
+}
 
-    Ok(Some(backup_dir))
+/// The walk behind [`transfer_item`]
+struct Transfer<'a> {
+    exclusions: &'a GlobSet,
+    /// Only work out what would change
+    dry_run: bool,
+    /// Hash files even when size and modification time match
+    checksum: bool,
+    max_file_size: Option<u64>,
+    stats: TransferStats,
+    progress: Progress,
 }
 
-fn cleanup_old_backups(target_dir: &Path, retention: usize) -> Result<(), String> {
-    let entries = fs::read_dir(target_dir)
-        .map_err(|e| format!("Failed to read directory for backup cleanup: {e}"))?;
+impl Transfer<'_> {
+    /// Bring `dst` in line with `src`, copying only files that changed and
+    /// removing what the source no longer has. Excluded paths are skipped in
+    /// the source and left alone in the target. `dst_gone` is set in a dry run
+    /// once `dst` would already have been removed.
+    fn tree(&mut self, src: &Path, dst: &Path, rel: &str, dst_gone: bool) -> Result<(), String> {
+        if !src.is_dir() {
+            return self.file(src, dst, rel, dst_gone);
+        }
 
-    // Collect all backup directories with their timestamps
-    let mut backups: Vec<(PathBuf, u64)> = entries
-        .filter_map(std::result::Result::ok)
-        .filter_map(|entry| {
+        let mut gone = dst_gone;
+        let mut removed = Vec::new();
+        match fs::symlink_metadata(dst) {
+            Ok(metadata) if !dst_gone && metadata.is_dir() => {
+                for entry in fs::read_dir(dst)
+                    .map_err(|e| format!("Failed to read directory {}: {e}", dst.display()))?
+                {
+                    let entry = entry.map_err(|e| format!("Failed to read entry: {e}"))?;
+                    let path = entry.path();
+                    let child_rel = format!("{rel}/{}", entry.file_name().to_string_lossy());
+                    if is_excluded(self.exclusions, &child_rel, path.is_dir()) {
+                        continue;
+                    }
+                    // Gone from the source, or a file where the source has a
+                    // directory or link (or any other mix)
+                    let source = EntryKind::of(&src.join(entry.file_name()));
+                    if source.is_none() || source != EntryKind::of(&path) {
+                        if !self.dry_run {
+                            remove_path(&path)?;
+                        }
+                        self.stats.record(&child_rel, FileAction::Removed);
+                        removed.push(entry.file_name());
+                    }
+                }
+            }
+            _ if self.dry_run => gone = true,
+            Ok(_) => {
+                remove_path(dst)?;
+                fs::create_dir_all(dst)
+                    .map_err(|e| format!("Failed to create directory {}: {e}", dst.display()))?;
+            }
+            Err(_) => {
+                fs::create_dir_all(dst)
+                    .map_err(|e| format!("Failed to create directory {}: {e}", dst.display()))?;
+            }
+        }
+
+        for entry in fs::read_dir(src)
+            .map_err(|e| format!("Failed to read directory {}: {e}", src.display()))?
+        {
+            let entry = entry.map_err(|e| format!("Failed to read entry: {e}"))?;
             let path = entry.path();
-            if !path.is_dir() {
-                return None;
+            let child_rel = format!("{rel}/{}", entry.file_name().to_string_lossy());
+            if is_excluded(self.exclusions, &child_rel, path.is_dir()) {
+                self.stats.excluded.push(child_rel);
+                continue;
             }
-            let name = entry.file_name().to_string_lossy().to_string();
-            if !name.starts_with(BACKUP_PREFIX) {
-                return None;
+            let metadata = fs::symlink_metadata(&path)
+                .map_err(|e| format!("Failed to read {}: {e}", path.display()))?;
+            let child_dst = dst.join(entry.file_name());
+            let child_gone = gone || removed.contains(&entry.file_name());
+            if metadata.file_type().is_symlink() {
+                self.link(&path, &child_dst, &child_rel, child_gone)?;
+                continue;
             }
-            // Extract timestamp from name
-            let timestamp: u64 = name.strip_prefix(BACKUP_PREFIX)?.parse().ok()?;
-            Some((path, timestamp))
-        })
-        .collect();
+            if metadata.is_file()
+                && let Some(reason) = skip_reason(&child_rel, metadata.len(), self.max_file_size)
+            {
+                self.stats.skipped_files.push(SkippedFile {
+                    path: child_rel,
+                    size: metadata.len(),
+                    reason,
+                });
+                continue;
+            }
+            self.tree(&path, &child_dst, &child_rel, child_gone)?;
+        }
 
-    // Sort by timestamp (newest first)
-    backups.sort_by(|a, b| b.1.cmp(&a.1));
+        Ok(())
+    }
 
-    // Remove old backups beyond retention limit
-    for (path, _) in backups.into_iter().skip(retention) {
-        if let Err(e) = fs::remove_dir_all(&path) {
-            eprintln!(
-                "  Warning: Failed to remove old backup {}: {e}",
-                path.display()
-            );
+    /// Copy a file unless the target already has the same content. Same size
+    /// and modification time counts as the same unless `checksum` is set;
+    /// otherwise the contents are hashed.
+    fn file(&mut self, src: &Path, dst: &Path, rel: &str, dst_gone: bool) -> Result<(), String> {
+        let src_meta =
+            fs::metadata(src).map_err(|e| format!("Failed to read {}: {e}", src.display()))?;
+        self.progress.file_done(src_meta.len());
+
+        if !dst_gone
+            && let Ok(dst_meta) = fs::symlink_metadata(dst)
+            && dst_meta.is_file()
+            && dst_meta.len() == src_meta.len()
+            && dst_meta.permissions() == src_meta.permissions()
+        {
+            let same_mtime = src_meta.modified().ok() == dst_meta.modified().ok();
+            if (same_mtime && !self.checksum) || hash_file(src)? == hash_file(dst)? {
+                if !same_mtime && !self.dry_run {
+                    // Cheap comparison next time
+                    set_modified(dst, &src_meta);
+                }
+                self.stats.unchanged += 1;
+                return Ok(());
+            }
+        }
+
+        if !self.dry_run {
+            if fs::symlink_metadata(dst).is_ok() {
+                remove_path(dst)?;
+            }
+            copy_file(src, dst, &src_meta)?;
+        }
+        self.stats.record(rel, FileAction::Updated);
+        self.stats.bytes += src_meta.len();
+        Ok(())
+    }
+
+    /// Recreate a link from inside the source unless the target already has
+    /// one pointing at the same place
+    fn link(&mut self, src: &Path, dst: &Path, rel: &str, dst_gone: bool) -> Result<(), String> {
+        let target = read_link(src)?;
+        self.progress.file_done(0);
+        if !dst_gone && fs::read_link(dst).is_ok_and(|existing| existing == target) {
+            self.stats.unchanged += 1;
+            return Ok(());
+        }
+
+        if !self.dry_run {
+            if fs::symlink_metadata(dst).is_ok() {
+                remove_path(dst)?;
+            }
+            copy_link(src, &target, dst)?;
+        }
+        self.stats.record(rel, FileAction::Updated);
+        Ok(())
+    }
+}
+
+/// What a path is, without following links
+#[derive(PartialEq)]
+enum EntryKind {
+    File,
+    Dir,
+    Link,
+}
+
+impl EntryKind {
+    fn of(path: &Path) -> Option<Self> {
+        let file_type = fs::symlink_metadata(path).ok()?.file_type();
+        Some(if file_type.is_symlink() {
+            Self::Link
+        } else if file_type.is_dir() {
+            Self::Dir
         } else {
-            println!("  Removed old backup: {}", path.display());
+            Self::File
+        })
+    }
+}
+
+/// Remove a file, directory tree or link without following links
+pub(crate) fn remove_path(path: &Path) -> Result<(), String> {
+    let metadata = fs::symlink_metadata(path)
+        .map_err(|e| format!("Failed to read {}: {e}", path.display()))?;
+    if metadata.file_type().is_symlink() {
+        return remove_link(path);
+    }
+    let result = if metadata.is_dir() {
+        fs::remove_dir_all(path)
+    } else {
+        fs::remove_file(path)
+    };
+    result.map_err(|e| format!("Failed to remove {}: {e}", path.display()))
+}
+
+fn link_dir(src: &Path, dst: &Path) -> Result<(), String> {
+    let src =
+        fs::canonicalize(src).map_err(|e| format!("Failed to resolve {}: {e}", src.display()))?;
+
+    match fs::symlink_metadata(dst) {
+        Ok(meta) if meta.file_type().is_symlink() => remove_link(dst)?,
+        Ok(meta) if meta.is_dir() => fs::remove_dir_all(dst)
+            .map_err(|e| format!("Failed to remove {}: {e}", dst.display()))?,
+        Ok(_) => {
+            fs::remove_file(dst).map_err(|e| format!("Failed to remove {}: {e}", dst.display()))?
         }
+        Err(_) => {}
     }
 
+    create_dir_link(&src, dst)
+}
+
+/// Remove a symlink or junction without touching what it points to
+fn remove_link(path: &Path) -> Result<(), String> {
+    // Directory links on Windows are removed like directories
+    fs::remove_file(path)
+        .or_else(|_| fs::remove_dir(path))
+        .map_err(|e| format!("Failed to remove link {}: {e}", path.display()))
+}
+
+#[cfg(unix)]
+pub(crate) fn create_dir_link(src: &Path, dst: &Path) -> Result<(), String> {
+    std::os::unix::fs::symlink(src, dst)
+        .map_err(|e| format!("Failed to link {} -> {}: {e}", dst.display(), src.display()))
+}
+
+/// Junctions work without the privilege Windows requires for symlinks
+#[cfg(windows)]
+pub(crate) fn create_dir_link(src: &Path, dst: &Path) -> Result<(), String> {
+    let status = Command::new("cmd")
+        .arg("/C")
+        .arg("mklink")
+        .arg("/J")
+        .arg(dst)
+        .arg(src)
+        .stdout(std::process::Stdio::null())
+        .status()
+        .map_err(|e| format!("Failed to run mklink: {e}"))?;
+    if !status.success() {
+        return Err(format!(
+            "Failed to create junction {} -> {}",
+            dst.display(),
+            src.display()
+        ));
+    }
     Ok(())
 }
 
-fn copy_dir_recursive(src: &Path, dst: &Path) -> Result<(), String> {
-    if dst.exists() {
-        fs::remove_dir_all(dst).map_err(|e| format!("Failed to remove existing directory: {e}"))?;
+#[cfg(not(any(unix, windows)))]
+pub(crate) fn create_dir_link(_src: &Path, _dst: &Path) -> Result<(), String> {
+    Err("Symlink sync is not supported on this platform".to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::strategies::file_name;
+    use proptest::prelude::*;
+    use std::time::{Duration, SystemTime};
+
+    fn copy(src: &Path, dst: &Path, rel: &str) -> TransferStats {
+        transfer_item(src, dst, rel, SyncMode::Copy, &MergeOptions::default()).unwrap()
     }
 
-    fs::create_dir_all(dst)
-        .map_err(|e| format!("Failed to create directory {}: {}", dst.display(), e))?;
+    fn changed(stats: &TransferStats) -> Vec<String> {
+        let mut paths: Vec<_> = stats.files.iter().map(|f| f.path.clone()).collect();
+        paths.sort();
+        paths
+    }
 
-    for entry in fs::read_dir(src)
-        .map_err(|e| format!("Failed to read directory {}: {}", src.display(), e))?
-    {
-        let entry = entry.map_err(|e| format!("Failed to read entry: {e}"))?;
-        let src_path = entry.path();
-        let dst_path = dst.join(entry.file_name());
+    fn write(path: &Path, contents: &str) {
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(path, contents).unwrap();
+    }
 
-        if src_path.is_dir() {
-            copy_dir_recursive(&src_path, &dst_path)?;
-        } else {
-            fs::copy(&src_path, &dst_path).map_err(|e| format!("Failed to copy file: {e}"))?;
+    fn touch(path: &Path, time: SystemTime) {
+        fs::File::options()
+            .write(true)
+            .open(path)
+            .unwrap()
+            .set_modified(time)
+            .unwrap();
+    }
+
+    #[test]
+    fn transfer_copies_only_what_changed() {
+        let root = tempfile::tempdir().unwrap();
+        let (src, dst) = (root.path().join("a/plugins"), root.path().join("b/plugins"));
+        write(&src.join("same.py"), "same");
+        write(&src.join("changed.py"), "before");
+        write(&src.join("sub/gone.py"), "gone");
+        copy(&src, &dst, "plugins");
+
+        write(&src.join("changed.py"), "after a change");
+        write(&src.join("new.py"), "new");
+        fs::remove_file(src.join("sub/gone.py")).unwrap();
+        let stats = copy(&src, &dst, "plugins");
+        assert_eq!((stats.updated, stats.unchanged, stats.removed), (2, 1, 1));
+        assert_eq!(
+            changed(&stats),
+            [
+                "plugins/changed.py",
+                "plugins/new.py",
+                "plugins/sub/gone.py"
+            ]
+        );
+        assert_eq!(stats.bytes, "after a change".len() as u64 + 3);
+        assert_eq!(
+            fs::read_to_string(dst.join("changed.py")).unwrap(),
+            "after a change"
+        );
+        assert!(!dst.join("sub/gone.py").exists());
+    }
+
+    #[test]
+    fn same_size_and_time_count_as_unchanged_without_checksum() {
+        let root = tempfile::tempdir().unwrap();
+        let (src, dst) = (root.path().join("a/types"), root.path().join("b/types"));
+        write(&src.join("t.bntl"), "abc");
+        write(&dst.join("t.bntl"), "xyz");
+        let time = SystemTime::UNIX_EPOCH + Duration::from_secs(1_700_000_000);
+        touch(&src.join("t.bntl"), time);
+        touch(&dst.join("t.bntl"), time);
+
+        assert_eq!(copy(&src, &dst, "types").unchanged, 1);
+        assert_eq!(fs::read_to_string(dst.join("t.bntl")).unwrap(), "xyz");
+
+        let options = MergeOptions {
+            checksum: true,
+            ..MergeOptions::default()
+        };
+        let stats = transfer_item(&src, &dst, "types", SyncMode::Copy, &options).unwrap();
+        assert_eq!(stats.updated, 1);
+        assert_eq!(fs::read_to_string(dst.join("t.bntl")).unwrap(), "abc");
+    }
+
+    #[test]
+    fn transfer_leaves_out_exclusions_and_databases() {
+        let root = tempfile::tempdir().unwrap();
+        let (src, dst) = (root.path().join("a/plugins"), root.path().join("b/plugins"));
+        write(&src.join("keep.py"), "keep");
+        write(&src.join("debug.log"), "source log");
+        write(&src.join("big.bndb"), "database");
+        write(&dst.join("own.log"), "target log");
+        let options = MergeOptions {
+            exclusions: build_glob_set(&["*.log".to_string()]).unwrap(),
+            ..MergeOptions::default()
+        };
+
+        let stats = transfer_item(&src, &dst, "plugins", SyncMode::Copy, &options).unwrap();
+        assert_eq!(changed(&stats), ["plugins/keep.py"]);
+        assert_eq!(stats.excluded, ["plugins/debug.log"]);
+        let skipped: Vec<_> = stats
+            .skipped_files
+            .iter()
+            .map(|f| (f.path.as_str(), f.reason))
+            .collect();
+        assert_eq!(skipped, [("plugins/big.bndb", "database")]);
+        assert!(!dst.join("debug.log").exists() && !dst.join("big.bndb").exists());
+        assert_eq!(
+            fs::read_to_string(dst.join("own.log")).unwrap(),
+            "target log"
+        );
+    }
+
+    #[test]
+    fn dry_run_writes_nothing() {
+        let root = tempfile::tempdir().unwrap();
+        let (src, dst) = (root.path().join("a/plugins"), root.path().join("b/plugins"));
+        write(&src.join("new.py"), "new");
+        write(&dst.join("old.py"), "old");
+        let options = MergeOptions {
+            dry_run: true,
+            ..MergeOptions::default()
+        };
+
+        let stats = transfer_item(&src, &dst, "plugins", SyncMode::Copy, &options).unwrap();
+        assert_eq!(changed(&stats), ["plugins/new.py", "plugins/old.py"]);
+        assert!(!dst.join("new.py").exists());
+        assert!(dst.join("old.py").exists());
+    }
+
+    /// Writes its item like any other, then fails
+    struct Failing;
+
+    impl ItemHandler for Failing {
+        fn name(&self) -> &'static str {
+            "broken"
+        }
+
+        fn describe(&self) -> &'static str {
+            "Fails after writing"
+        }
+
+        fn merge(
+            &self,
+            source_dir: &Path,
+            target_dir: &Path,
+            options: &MergeOptions,
+        ) -> Result<TransferStats, String> {
+            let src = source_dir.join(self.name());
+            transfer_item(
+                &src,
+                &target_dir.join(self.name()),
+                self.name(),
+                options.mode,
+                options,
+            )?;
+            Err("disk full".to_string())
         }
     }
 
-    Ok(())
+    fn item(name: &str) -> &'static dyn ItemHandler {
+        *HANDLERS.iter().find(|h| h.name() == name).unwrap()
+    }
+
+    #[test]
+    fn failed_sync_is_rolled_back() {
+        let root = tempfile::tempdir().unwrap();
+        let (source, target_dir) = (root.path().join("source"), root.path().join("target"));
+        write(&source.join("signatures/new.sig"), "source");
+        write(&source.join("types/t.bntl"), "source");
+        write(&source.join("broken/file"), "source");
+        write(&target_dir.join("types/t.bntl"), "target");
+        write(&target_dir.join("broken/file"), "target");
+
+        let profile = Profile {
+            config_dir: target_dir.clone(),
+            ..Profile::default()
+        };
+        let target = SyncTarget {
+            name: "work",
+            profile: &profile,
+            own_exclusions: Vec::new(),
+            exclusions: GlobSet::empty(),
+            stale: Vec::new(),
+        };
+        let items = [item("signatures"), item("types"), &Failing];
+        let backups = BackupStore::new(None, "work", &target_dir);
+        let mut report = TargetReport::new("work", &target_dir);
+        let error = sync_to_target(
+            &source,
+            &target,
+            &items,
+            &backups,
+            0,
+            &MergeOptions::default(),
+            &mut report,
+        )
+        .unwrap_err();
+
+        assert_eq!(
+            error,
+            "disk full (rolled back signatures, types, broken in 'work')"
+        );
+        assert_eq!(report.rolled_back, ["signatures", "types", "broken"]);
+        assert!(!target_dir.join("signatures").exists());
+        let read = |rel| fs::read_to_string(target_dir.join(rel)).unwrap();
+        assert_eq!(read("types/t.bntl"), "target");
+        assert_eq!(read("broken/file"), "target");
+        assert!(
+            report
+                .backup
+                .is_some_and(|backup| backup.starts_with(&target_dir))
+        );
+    }
+
+    #[test]
+    fn newer_target_files_are_conflicts() {
+        let root = tempfile::tempdir().unwrap();
+        let (src, dst) = (root.path().join("a/plugins"), root.path().join("b/plugins"));
+        let then = SystemTime::UNIX_EPOCH + Duration::from_secs(1_700_000_000);
+        let later = then + Duration::from_secs(60);
+        for (name, source, target, target_time) in [
+            ("edited.py", "source", "edited in target", later),
+            ("same.py", "same", "same", later),
+            (
+                "older.py",
+                "source",
+                "older target",
+                then - Duration::from_secs(60),
+            ),
+            ("sub/edited.py", "source", "edited in target", later),
+            ("skip.log", "source", "edited in target", later),
+        ] {
+            write(&src.join(name), source);
+            write(&dst.join(name), target);
+            touch(&src.join(name), then);
+            touch(&dst.join(name), target_time);
+        }
+        write(&dst.join("only_target.py"), "mine");
+
+        let mut files = Vec::new();
+        let exclusions = build_glob_set(&["*.log".to_string()]).unwrap();
+        collect_newer(&src, &dst, "plugins", &exclusions, None, &mut files).unwrap();
+        files.sort();
+        assert_eq!(files, ["plugins/edited.py", "plugins/sub/edited.py"]);
+    }
+
+    #[test]
+    fn links_in_the_target_are_not_conflicts() {
+        let root = tempfile::tempdir().unwrap();
+        let (src, dst) = (root.path().join("a/plugins"), root.path().join("b/plugins"));
+        write(&src.join("x.py"), "source");
+        write(&root.path().join("elsewhere/x.py"), "newer and different");
+        fs::create_dir_all(dst.parent().unwrap()).unwrap();
+        create_dir_link(&root.path().join("elsewhere"), &dst).unwrap();
+
+        let mut files = Vec::new();
+        collect_newer(&src, &dst, "plugins", &GlobSet::empty(), None, &mut files).unwrap();
+        assert!(files.is_empty());
+        assert!(dst.join("x.py").exists());
+    }
+
+    proptest! {
+        #![proptest_config(ProptestConfig::with_cases(32))]
+
+        #[test]
+        fn transfer_handles_odd_names(
+            item in file_name(),
+            files in prop::collection::btree_set(file_name(), 1..5),
+        ) {
+            let root = tempfile::tempdir().unwrap();
+            let src = root.path().join("from").join(&item);
+            let dst = root.path().join("to").join(&item);
+            fs::create_dir_all(&src).unwrap();
+            for file in &files {
+                fs::write(src.join(file), file).unwrap();
+            }
+            let rels: Vec<_> = files.iter().map(|file| format!("{item}/{file}")).collect();
+
+            let stats = copy(&src, &dst, &item);
+            prop_assert_eq!(changed(&stats), rels);
+            for file in &files {
+                prop_assert_eq!(fs::read_to_string(dst.join(file)).unwrap(), file.as_str());
+            }
+
+            let stats = copy(&src, &dst, &item);
+            prop_assert_eq!(stats.updated, 0);
+            prop_assert_eq!(stats.unchanged, files.len());
+
+            let gone = files.last().unwrap();
+            fs::remove_file(src.join(gone)).unwrap();
+            let stats = copy(&src, &dst, &item);
+            prop_assert_eq!(stats.removed, 1);
+            prop_assert_eq!(changed(&stats), [format!("{item}/{gone}")]);
+            prop_assert!(!dst.join(gone).exists());
+        }
+    }
 }
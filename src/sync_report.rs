@@ -0,0 +1,129 @@
+use crate::sync::{FileChange, SkippedFile, TransferStats};
+use clap::ValueEnum;
+use serde::Serialize;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Format of `sync --report`
+#[derive(Clone, Copy, ValueEnum)]
+pub(crate) enum ReportFormat {
+    Json,
+}
+
+/// Everything a sync did, for automation and audit tooling
+#[derive(Serialize)]
+pub(crate) struct SyncReport {
+    pub source: String,
+    pub pull: bool,
+    pub dry_run: bool,
+    pub success: bool,
+    pub error: Option<String>,
+    /// Exclusion patterns shared by every target
+    pub exclusions: Vec<String>,
+    pub targets: Vec<TargetReport>,
+    pub bytes_transferred: u64,
+}
+
+#[derive(Serialize)]
+pub(crate) struct TargetReport {
+    pub name: String,
+    pub config_dir: PathBuf,
+    /// Backup taken before anything was written
+    pub backup: Option<PathBuf>,
+    pub items: Vec<ItemReport>,
+    /// Items not synced into this target
+    pub skipped: Vec<SkippedItem>,
+    /// Items put back from the backup after the sync failed
+    pub rolled_back: Vec<String>,
+}
+
+#[derive(Serialize)]
+pub(crate) struct ItemReport {
+    pub name: String,
+    /// `synced`, `linked`, `deleted`, or `planned` in a dry run
+    pub action: &'static str,
+    pub updated: usize,
+    pub unchanged: usize,
+    pub removed: usize,
+    pub bytes: u64,
+    /// Files written or removed (or that would be, in a dry run)
+    pub files: Vec<FileChange>,
+    /// Paths left out by exclusion patterns
+    pub excluded: Vec<String>,
+    /// Files left out as Binary Ninja databases or for their size
+    pub skipped_files: Vec<SkippedFile>,
+}
+
+#[derive(Serialize)]
+pub(crate) struct SkippedItem {
+    pub name: String,
+    pub reason: String,
+}
+
+impl SyncReport {
+    pub(crate) fn new(source: &str, pull: bool, dry_run: bool) -> Self {
+        Self {
+            source: source.to_string(),
+            pull,
+            dry_run,
+            success: true,
+            error: None,
+            exclusions: Vec::new(),
+            targets: Vec::new(),
+            bytes_transferred: 0,
+        }
+    }
+
+    /// Write the report to `path`, or to stdout
+    pub(crate) fn write(&self, format: ReportFormat, path: Option<&Path>) -> Result<(), String> {
+        let text = match format {
+            ReportFormat::Json => serde_json::to_string_pretty(self)
+                .map_err(|e| format!("Failed to serialize sync report: {e}"))?,
+        };
+        match path {
+            Some(path) => fs::write(path, text + "\n")
+                .map_err(|e| format!("Failed to write sync report {}: {e}", path.display())),
+            None => {
+                // Not `dataln!`: this is what `--report json` reserved stdout for
+                println!("{text}");
+                Ok(())
+            }
+        }
+    }
+}
+
+impl TargetReport {
+    pub(crate) fn new(name: &str, config_dir: &Path) -> Self {
+        Self {
+            name: name.to_string(),
+            config_dir: config_dir.to_path_buf(),
+            backup: None,
+            items: Vec::new(),
+            skipped: Vec::new(),
+            rolled_back: Vec::new(),
+        }
+    }
+
+    pub(crate) fn skip(&mut self, name: &str, reason: &str) {
+        self.skipped.push(SkippedItem {
+            name: name.to_string(),
+            reason: reason.to_string(),
+        });
+    }
+}
+
+impl ItemReport {
+    pub(crate) fn new(name: &str, action: &'static str, stats: TransferStats) -> Self {
+        Self {
+            name: name.to_string(),
+            action,
+            updated: stats.updated,
+            unchanged: stats.unchanged,
+            removed: stats.removed,
+            bytes: stats.bytes,
+            files: stats.files,
+            excluded: stats.excluded,
+            skipped_files: stats.skipped_files,
+        }
+    }
+}
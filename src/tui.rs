@@ -0,0 +1,380 @@
+use crate::backups::describe_profile_backups;
+use crate::config::Config;
+use crate::diff::{DiffOptions, diff_profiles};
+use crate::launch::{LaunchOptions, launch_profile};
+use crate::pager;
+use crate::plugins::list_plugins;
+use crate::report;
+use crate::repositories::RepositoriesMode;
+use crate::sync::{SyncOptions, auto_sync, build_glob_set, run_sync};
+use ratatui::DefaultTerminal;
+use ratatui::Frame;
+use ratatui::crossterm::event::{self, Event, KeyCode, KeyEventKind};
+use ratatui::layout::{Constraint, Layout};
+use ratatui::style::{Modifier, Style};
+use ratatui::text::Line;
+use ratatui::widgets::{Block, List, ListItem, ListState, Paragraph};
+use std::io::{self, Write};
+
+/// Shown at the bottom of the screen
+const KEY_HELP: &str = "↑↓ move  Tab pane  Enter launch  Space mark  s sync marked → selected  d diff marked/selected  r reload  q quit";
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Pane {
+    Profiles,
+    Plugins,
+    Backups,
+}
+
+impl Pane {
+    fn next(self) -> Self {
+        match self {
+            Pane::Profiles => Pane::Plugins,
+            Pane::Plugins => Pane::Backups,
+            Pane::Backups => Pane::Profiles,
+        }
+    }
+
+    fn previous(self) -> Self {
+        match self {
+            Pane::Profiles => Pane::Backups,
+            Pane::Plugins => Pane::Profiles,
+            Pane::Backups => Pane::Plugins,
+        }
+    }
+}
+
+/// What runs with the TUI suspended
+enum Action {
+    Launch,
+    Sync,
+    Diff,
+}
+
+struct App<'a> {
+    config: &'a Config,
+    names: Vec<&'a str>,
+    profiles: ListState,
+    /// The other side of a sync or diff
+    marked: Option<usize>,
+    plugins: Vec<String>,
+    plugin_state: ListState,
+    backups: Vec<String>,
+    backup_state: ListState,
+    focus: Pane,
+    status: String,
+    allow_suspicious_dirs: bool,
+}
+
+/// Browse profiles, their plugins and their recent backups, and launch,
+/// sync or diff them from the keyboard
+pub(crate) fn run_tui(config: &Config, allow_suspicious_dirs: bool) -> Result<(), String> {
+    let mut names: Vec<&str> = config.profiles.keys().map(String::as_str).collect();
+    if names.is_empty() {
+        return Err("No profiles configured".to_string());
+    }
+    names.sort_unstable();
+    let selected = config
+        .global
+        .default_profile
+        .as_deref()
+        .and_then(|default| names.iter().position(|name| *name == default))
+        .unwrap_or(0);
+
+    let mut app = App {
+        config,
+        names,
+        profiles: ListState::default().with_selected(Some(selected)),
+        marked: None,
+        plugins: Vec::new(),
+        plugin_state: ListState::default(),
+        backups: Vec::new(),
+        backup_state: ListState::default(),
+        focus: Pane::Profiles,
+        status: String::new(),
+        allow_suspicious_dirs,
+    };
+    app.load_details();
+
+    let mut terminal = ratatui::try_init().map_err(|e| format!("Failed to start the TUI: {e}"))?;
+    let result = app.run(&mut terminal);
+    ratatui::restore();
+    result
+}
+
+impl<'a> App<'a> {
+    fn run(&mut self, terminal: &mut DefaultTerminal) -> Result<(), String> {
+        loop {
+            terminal
+                .draw(|frame| self.draw(frame))
+                .map_err(|e| format!("Failed to draw the TUI: {e}"))?;
+            let Event::Key(key) =
+                event::read().map_err(|e| format!("Failed to read input: {e}"))?
+            else {
+                continue;
+            };
+            if key.kind != KeyEventKind::Press {
+                continue;
+            }
+            match key.code {
+                KeyCode::Char('q') | KeyCode::Esc => return Ok(()),
+                KeyCode::Tab => self.focus = self.focus.next(),
+                KeyCode::BackTab => self.focus = self.focus.previous(),
+                KeyCode::Down | KeyCode::Char('j') => self.move_selection(1),
+                KeyCode::Up | KeyCode::Char('k') => self.move_selection(-1),
+                KeyCode::Char(' ') | KeyCode::Char('m') => self.toggle_mark(),
+                KeyCode::Char('r') => {
+                    self.load_details();
+                    self.status = "Reloaded".to_string();
+                }
+                KeyCode::Enter => self.suspend(terminal, Action::Launch)?,
+                KeyCode::Char('s') => self.suspend(terminal, Action::Sync)?,
+                KeyCode::Char('d') => self.suspend(terminal, Action::Diff)?,
+                _ => {}
+            }
+        }
+    }
+
+    fn selected(&self) -> &'a str {
+        self.names[self.profiles.selected().unwrap_or(0)]
+    }
+
+    /// The marked profile, if it isn't the selected one
+    fn other(&self) -> Option<&'a str> {
+        self.marked
+            .filter(|&marked| Some(marked) != self.profiles.selected())
+            .map(|marked| self.names[marked])
+    }
+
+    fn move_selection(&mut self, delta: isize) {
+        let (state, len) = match self.focus {
+            Pane::Profiles => (&mut self.profiles, self.names.len()),
+            Pane::Plugins => (&mut self.plugin_state, self.plugins.len()),
+            Pane::Backups => (&mut self.backup_state, self.backups.len()),
+        };
+        if len == 0 {
+            return;
+        }
+        let current = state.selected().unwrap_or(0);
+        state.select(Some(current.saturating_add_signed(delta).min(len - 1)));
+        if self.focus == Pane::Profiles {
+            self.load_details();
+        }
+    }
+
+    fn toggle_mark(&mut self) {
+        let selected = self.profiles.selected();
+        if self.marked == selected {
+            self.marked = None;
+            self.status = "Unmarked".to_string();
+        } else {
+            self.marked = selected;
+            self.status = format!(
+                "Marked '{}'; select another profile to sync or diff with",
+                self.selected()
+            );
+        }
+    }
+
+    /// Read the selected profile's plugins and backups
+    fn load_details(&mut self) {
+        let name = self.selected();
+        let profile = &self.config.profiles[name];
+        self.plugins = match list_plugins(profile) {
+            Ok(plugins) => plugins
+                .iter()
+                .map(|plugin| {
+                    let mut line = plugin
+                        .name
+                        .clone()
+                        .unwrap_or_else(|| plugin.dir_name.clone());
+                    if let Some(version) = &plugin.version {
+                        line.push_str(&format!(" {version}"));
+                    }
+                    if plugin.is_disabled() {
+                        line.push_str(" (disabled)");
+                    }
+                    line
+                })
+                .collect(),
+            Err(e) => vec![e],
+        };
+        self.backups = describe_profile_backups(self.config, name).unwrap_or_else(|e| vec![e]);
+        self.plugin_state
+            .select((!self.plugins.is_empty()).then_some(0));
+        self.backup_state
+            .select((!self.backups.is_empty()).then_some(0));
+    }
+
+    fn draw(&mut self, frame: &mut Frame) {
+        let [main, status, help] = Layout::vertical([
+            Constraint::Min(3),
+            Constraint::Length(1),
+            Constraint::Length(1),
+        ])
+        .areas(frame.area());
+        let [left, right] =
+            Layout::horizontal([Constraint::Percentage(30), Constraint::Percentage(70)])
+                .areas(main);
+        let [plugins, backups] =
+            Layout::vertical([Constraint::Percentage(60), Constraint::Percentage(40)]).areas(right);
+
+        let default = self.config.global.default_profile.as_deref();
+        let profile_items: Vec<ListItem> = self
+            .names
+            .iter()
+            .enumerate()
+            .map(|(index, name)| {
+                let mark = if self.marked == Some(index) {
+                    "* "
+                } else {
+                    "  "
+                };
+                let suffix = if default == Some(*name) {
+                    " (default)"
+                } else {
+                    ""
+                };
+                ListItem::new(format!("{mark}{name}{suffix}"))
+            })
+            .collect();
+        let name = self.selected();
+        let panes = [
+            (Pane::Profiles, "Profiles".to_string(), profile_items, left),
+            (
+                Pane::Plugins,
+                format!("Plugins of '{name}' ({})", self.plugins.len()),
+                self.plugins
+                    .iter()
+                    .map(|line| ListItem::new(line.as_str()))
+                    .collect(),
+                plugins,
+            ),
+            (
+                Pane::Backups,
+                format!("Backups of '{name}' ({})", self.backups.len()),
+                self.backups
+                    .iter()
+                    .map(|line| ListItem::new(line.as_str()))
+                    .collect(),
+                backups,
+            ),
+        ];
+        for (pane, title, items, area) in panes {
+            let mut block = Block::bordered().title(title);
+            if pane == self.focus {
+                block = block.border_style(Style::new().add_modifier(Modifier::BOLD));
+            }
+            let list = List::new(items)
+                .block(block)
+                .highlight_style(Style::new().add_modifier(Modifier::REVERSED));
+            let state = match pane {
+                Pane::Profiles => &mut self.profiles,
+                Pane::Plugins => &mut self.plugin_state,
+                Pane::Backups => &mut self.backup_state,
+            };
+            frame.render_stateful_widget(list, area, state);
+        }
+
+        frame.render_widget(Paragraph::new(self.status.as_str()), status);
+        frame.render_widget(
+            Paragraph::new(Line::from(KEY_HELP).style(Style::new().add_modifier(Modifier::DIM))),
+            help,
+        );
+    }
+
+    /// Leave the TUI to run an action on the terminal, then come back
+    fn suspend(&mut self, terminal: &mut DefaultTerminal, action: Action) -> Result<(), String> {
+        let selected = self.selected();
+        let other = match action {
+            Action::Launch => None,
+            Action::Sync | Action::Diff => match self.other() {
+                Some(other) => Some(other),
+                None => {
+                    self.status =
+                        "Mark a profile with Space, then select the other one".to_string();
+                    return Ok(());
+                }
+            },
+        };
+
+        ratatui::restore();
+        let result = match (action, other) {
+            (Action::Launch, _) => self.launch(selected),
+            (Action::Sync, Some(from)) => self.sync(from, selected),
+            (Action::Diff, Some(left)) => self.diff(left, selected),
+            _ => Ok(()),
+        };
+        self.status = match result {
+            Ok(()) => "Done".to_string(),
+            Err(e) => {
+                tracing::error!("{e}");
+                format!("Failed: {e}")
+            }
+        };
+        print!("\nPress Enter to return to bn-loader...");
+        let _ = io::stdout().flush();
+        let _ = io::stdin().read_line(&mut String::new());
+
+        *terminal = ratatui::try_init().map_err(|e| format!("Failed to start the TUI: {e}"))?;
+        self.load_details();
+        Ok(())
+    }
+
+    fn launch(&self, name: &str) -> Result<(), String> {
+        let profile = &self.config.profiles[name];
+        if let Err(e) = auto_sync(self.config, name, profile, self.allow_suspicious_dirs) {
+            // The profile still works with what it had
+            report::warn(&format!("{e}; launching anyway"));
+        }
+        let options = LaunchOptions {
+            debug: self.config.global.debug,
+            allow_suspicious_dirs: self.allow_suspicious_dirs,
+            ..LaunchOptions::default()
+        };
+        launch_profile(name, profile, &options)
+    }
+
+    /// Sync with the usual prompts, so the plan can be reviewed first
+    fn sync(&self, from: &str, to: &str) -> Result<(), String> {
+        let options = SyncOptions {
+            from,
+            to: Some(to),
+            pull: false,
+            extra_exclusions: Vec::new(),
+            preset: None,
+            only: Vec::new(),
+            dry_run: false,
+            yes: false,
+            force: false,
+            delete: false,
+            backup_retention: self.config.global.backup_retention,
+            repositories_mode: RepositoriesMode::default(),
+            link: false,
+            allow_suspicious_dirs: self.allow_suspicious_dirs,
+            report: None,
+            report_file: None,
+            hook: None,
+            verify: false,
+            only_changed: false,
+        };
+        run_sync(self.config, &options)
+    }
+
+    fn diff(&self, left: &str, right: &str) -> Result<(), String> {
+        let options = DiffOptions {
+            ignore_keys: build_glob_set(&self.config.diff.ignore_keys)?,
+            ..DiffOptions::default()
+        };
+        pager::start(true);
+        let result = diff_profiles(
+            left,
+            &self.config.profiles[left],
+            right,
+            &self.config.profiles[right],
+            &options,
+        );
+        pager::finish();
+        result.map(|_| ())
+    }
+}
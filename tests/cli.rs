@@ -0,0 +1,322 @@
+//! Runs the bn-loader binary against a throwaway home directory
+
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Output, Stdio};
+use tempfile::TempDir;
+
+/// A home directory with a config, two profiles sharing an install, a
+/// plugin and a sync backup
+struct Fixture {
+    home: TempDir,
+}
+
+impl Fixture {
+    fn new() -> Self {
+        let home = tempfile::tempdir().expect("create a temporary home");
+        let root = home.path();
+        let install = root.join("bn");
+        fs::create_dir_all(&install).unwrap();
+        fs::write(install.join("binaryninja"), "").unwrap();
+        fs::write(install.join("binaryninja.exe"), "").unwrap();
+        for name in ["work", "personal"] {
+            let config_dir = root.join("cfg").join(name);
+            fs::create_dir_all(config_dir.join("plugins").join("foo")).unwrap();
+            fs::write(config_dir.join("settings.json"), "{}\n").unwrap();
+        }
+        let backup = root.join("cfg/work/.bn-loader-backup-1700000000");
+        fs::create_dir_all(&backup).unwrap();
+        fs::write(backup.join("settings.json"), "{}\n").unwrap();
+
+        let config = format!(
+            "[global]\ncheck_updates = false\ncolor = \"always\"\ndefault_profile = \"work\"\n\n\
+             [profiles.work]\ninstall_dir = '{install}'\nconfig_dir = '{work}'\n\n\
+             [profiles.personal]\ninstall_dir = '{install}'\nconfig_dir = '{personal}'\n",
+            install = install.display(),
+            work = root.join("cfg").join("work").display(),
+            personal = root.join("cfg").join("personal").display(),
+        );
+        fs::write(root.join("bn-loader.toml"), config).unwrap();
+        Fixture { home }
+    }
+
+    fn path(&self) -> &Path {
+        self.home.path()
+    }
+
+    fn config(&self) -> PathBuf {
+        self.path().join("bn-loader.toml")
+    }
+
+    /// Run with the fixture's config and home, and without `NO_COLOR` from
+    /// the environment running the tests
+    fn command(&self) -> Command {
+        let mut command = Command::new(env!("CARGO_BIN_EXE_bn-loader"));
+        command
+            .env("HOME", self.path())
+            .env("USERPROFILE", self.path())
+            .env_remove("NO_COLOR")
+            .arg("--config")
+            .arg(self.config());
+        command
+    }
+
+    fn run(&self, args: &[&str]) -> Output {
+        self.command().args(args).output().expect("run bn-loader")
+    }
+}
+
+fn stdout(output: &Output) -> String {
+    String::from_utf8_lossy(&output.stdout).into_owned()
+}
+
+fn has_escapes(output: &Output) -> bool {
+    output.stdout.contains(&0x1b) || output.stderr.contains(&0x1b)
+}
+
+#[test]
+fn color_always_colors_listings() {
+    let fixture = Fixture::new();
+    for args in [&["plugins", "work"][..], &["backups", "list"]] {
+        let output = fixture.run(args);
+        assert!(output.status.success(), "{args:?}: {output:?}");
+        assert!(has_escapes(&output), "{args:?} printed no colors");
+    }
+}
+
+#[test]
+fn color_never_overrides_the_config() {
+    let fixture = Fixture::new();
+    for args in [&["plugins", "work"][..], &["backups", "list"]] {
+        let output = fixture.run(&[&["--color", "never"], args].concat());
+        assert!(output.status.success(), "{args:?}: {output:?}");
+        assert!(!has_escapes(&output), "{args:?}: {:?}", stdout(&output));
+        assert!(!stdout(&output).is_empty(), "{args:?} printed nothing");
+    }
+}
+
+#[test]
+fn no_color_leaves_auto_uncolored() {
+    let fixture = Fixture::new();
+    for args in [&["plugins", "work"][..], &["backups", "list"]] {
+        let output = fixture
+            .command()
+            .env("NO_COLOR", "1")
+            .args(["--color", "auto"])
+            .args(args)
+            .output()
+            .expect("run bn-loader");
+        assert!(output.status.success(), "{args:?}: {output:?}");
+        assert!(!has_escapes(&output), "{args:?}: {:?}", stdout(&output));
+    }
+}
+
+#[test]
+fn quiet_keeps_listings() {
+    let fixture = Fixture::new();
+    for args in [
+        &["plugins", "work"][..],
+        &["backups", "list"],
+        &["--list"],
+        &["plugins", "show", "foo", "--profile", "work"],
+    ] {
+        let output = fixture.run(&[&["-q"], args].concat());
+        assert!(output.status.success(), "{args:?}: {output:?}");
+        assert!(!stdout(&output).is_empty(), "{args:?} printed nothing");
+    }
+}
+
+#[test]
+fn quiet_silences_progress() {
+    let fixture = Fixture::new();
+    let args = ["backups", "prune", "--keep", "0", "--dry-run"];
+    let output = fixture.run(&args);
+    assert!(stdout(&output).contains("Backups to remove"), "{output:?}");
+
+    let output = fixture.run(&[&["-q"], &args[..]].concat());
+    assert!(output.status.success(), "{output:?}");
+    assert_eq!(stdout(&output), "");
+}
+
+#[test]
+fn json_output_is_only_json() {
+    let fixture = Fixture::new();
+    for args in [
+        &["--list"][..],
+        &["plugins", "work"],
+        &["plugins", "--all"],
+        &["diff", "work", "personal"],
+        &["sync", "--from", "work", "--to", "personal", "--dry-run"],
+    ] {
+        let output = fixture.run(&[&["--json"], args].concat());
+        let text = stdout(&output);
+        if let Err(e) = serde_json::from_str::<serde_json::Value>(&text) {
+            panic!("{args:?} printed more than JSON ({e}): {text}");
+        }
+    }
+}
+
+fn stderr(output: &Output) -> String {
+    String::from_utf8_lossy(&output.stderr).into_owned()
+}
+
+/// Whether `--help` output has an entry for `item`
+fn lists(help: &str, item: &str) -> bool {
+    help.lines()
+        .any(|line| line.trim_start().starts_with(&format!("{item} ")))
+}
+
+#[test]
+fn help_shows_only_compiled_in_commands() {
+    let fixture = Fixture::new();
+    let output = fixture.run(&["--help"]);
+    assert_eq!(stderr(&output), "");
+    let help = stdout(&output);
+    assert_eq!(lists(&help, "--check-update"), cfg!(feature = "updates"));
+    assert_eq!(lists(&help, "tui"), cfg!(feature = "tui"));
+
+    let help = stdout(&fixture.run(&["plugin", "--help"]));
+    for command in ["install", "search", "outdated", "update"] {
+        assert_eq!(
+            lists(&help, command),
+            cfg!(feature = "plugin-index"),
+            "{command}"
+        );
+    }
+    assert!(lists(&help, "remove"));
+}
+
+#[cfg(not(feature = "updates"))]
+#[test]
+fn check_update_names_the_missing_feature() {
+    let output = Fixture::new().run(&["--check-update"]);
+    assert!(!output.status.success());
+    assert!(stderr(&output).contains("`updates` feature"), "{output:?}");
+}
+
+#[cfg(not(feature = "plugin-index"))]
+#[test]
+fn plugin_downloads_name_the_missing_feature() {
+    let fixture = Fixture::new();
+    for args in [
+        &["plugin", "search", "snippets"][..],
+        &["plugin", "install", "snippets", "--profile", "work"],
+        &["plugin", "outdated"],
+    ] {
+        let output = fixture.run(args);
+        assert!(!output.status.success(), "{args:?}");
+        assert!(
+            stderr(&output).contains("`plugin-index` feature"),
+            "{args:?}: {output:?}"
+        );
+    }
+}
+
+#[cfg(not(feature = "tui"))]
+#[test]
+fn tui_names_the_missing_feature() {
+    let output = Fixture::new().run(&["tui"]);
+    assert!(!output.status.success());
+    assert!(stderr(&output).contains("`tui` feature"), "{output:?}");
+}
+
+#[test]
+fn diff_apply_copies_one_nested_setting() {
+    let fixture = Fixture::new();
+    let settings = |name| fixture.path().join("cfg").join(name).join("settings.json");
+    fs::write(settings("work"), r#"{"ui": {"theme": "dark"}}"#).unwrap();
+    fs::write(settings("personal"), r#"{"ui": {"font": {"size": 12}}}"#).unwrap();
+
+    // ui.font.size is only in 'personal', ui.theme only in 'work'
+    let mut child = fixture
+        .command()
+        .args(["diff", "work", "personal", "--apply"])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .expect("run bn-loader");
+    child.stdin.take().unwrap().write_all(b"<\n>\n").unwrap();
+    let output = child.wait_with_output().unwrap();
+    // Like diff(1), 1 means differences were found
+    assert_eq!(output.status.code(), Some(1), "{output:?}");
+    assert!(stdout(&output).contains("Applied 2 of 2"), "{output:?}");
+
+    for name in ["work", "personal"] {
+        let text = fs::read_to_string(settings(name)).unwrap();
+        let value: serde_json::Value = serde_json::from_str(&text).unwrap();
+        assert_eq!(
+            value,
+            serde_json::json!({ "ui": { "font": { "size": 12 }, "theme": "dark" } }),
+            "{name}"
+        );
+    }
+}
+
+#[test]
+fn debug_log_file_still_names_binary_ninjas_log() {
+    let fixture = Fixture::new();
+    let log = fixture.path().join("bn.log");
+    let log = log.to_str().unwrap();
+    let output = fixture.run(&["work", "--debug", "--log-file", log, "--dry-run"]);
+    assert!(output.status.success(), "{output:?}");
+    assert!(stdout(&output).contains(log), "{output:?}");
+    assert!(stderr(&output).contains("--bn-log-file"), "{output:?}");
+}
+
+#[test]
+fn migrate_backs_up_key_bindings_first() {
+    let fixture = Fixture::new();
+    let work = fixture.path().join("cfg/work");
+    fs::write(work.join("keybindings.json"), "{\"Undo\": [\"Ctrl+Z\"]}\n").unwrap();
+    let ida = fixture.path().join("idagui.cfg");
+    fs::write(&ida, "\"JumpAsk\" = \"g\"\n").unwrap();
+
+    let output = fixture.run(&[
+        "migrate",
+        "--from",
+        "ida",
+        "--profile",
+        "work",
+        ida.to_str().unwrap(),
+    ]);
+    assert!(output.status.success(), "{output:?}");
+
+    let backups: Vec<PathBuf> = fs::read_dir(&work)
+        .unwrap()
+        .map(|entry| entry.unwrap().path())
+        .filter(|path| {
+            let name = path.file_name().unwrap().to_string_lossy();
+            name.starts_with(".bn-loader-backup-") && !name.ends_with("1700000000")
+        })
+        .collect();
+    assert_eq!(backups.len(), 1, "{backups:?}");
+    let saved = fs::read_to_string(backups[0].join("keybindings.json")).unwrap();
+    assert_eq!(saved, "{\"Undo\": [\"Ctrl+Z\"]}\n");
+}
+
+#[test]
+fn failed_launch_checks_leave_settings_alone() {
+    let fixture = Fixture::new();
+    let mut config = fs::OpenOptions::new()
+        .append(true)
+        .open(fixture.config())
+        .unwrap();
+    // Appended to the last profile, personal
+    writeln!(config, "python_interpreter = '/usr/bin/python3'").unwrap();
+    let log = fixture.path().join("both.log");
+    let log = log.to_str().unwrap();
+
+    let output = fixture.run(&[
+        "personal",
+        "--debug",
+        "--log-file",
+        log,
+        "--bn-log-file",
+        log,
+    ]);
+    assert!(!output.status.success(), "{output:?}");
+    let settings = fs::read_to_string(fixture.path().join("cfg/personal/settings.json")).unwrap();
+    assert_eq!(settings, "{}\n");
+}